@@ -0,0 +1,30 @@
+//! The device class of the major browsers, which should take no more than a
+//! few microseconds. Compare against a previous run with
+//! `cargo bench --bench device_class -- --baseline <name>`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use rust_device_detector::device_detector::DeviceDetector;
+
+const TRAFFIC: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+    "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0",
+];
+
+fn bench_device_class(c: &mut Criterion) {
+    let detector = DeviceDetector::new();
+
+    c.bench_function("device class, fast path", |b| {
+        b.iter(|| {
+            for ua in TRAFFIC {
+                black_box(detector.classify_device_class(ua, None).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_device_class);
+criterion_main!(benches);
@@ -0,0 +1,47 @@
+//! A batch of repeating user agents through the shared detector's concurrent
+//! cache, and through a [`LocalDeviceDetector`]'s plain one. Compare against
+//! a previous run with
+//! `cargo bench --features cache --bench local_detector -- --baseline <name>`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::local::LocalDeviceDetector;
+
+const TRAFFIC: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+    "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+];
+
+fn bench_local_detector(c: &mut Criterion) {
+    let shared = DeviceDetector::new_with_cache(1000);
+    let mut local = LocalDeviceDetector::from(shared.clone()).with_cache(1000);
+
+    // compile the regexes and fill both caches
+    for ua in TRAFFIC {
+        shared.parse(ua, None).unwrap();
+        local.parse(ua, None).unwrap();
+    }
+
+    c.bench_function("shared detector, cached", |b| {
+        b.iter(|| {
+            for ua in TRAFFIC {
+                black_box(shared.parse(ua, None).unwrap());
+            }
+        })
+    });
+
+    c.bench_function("local detector, cached", |b| {
+        b.iter(|| {
+            for ua in TRAFFIC {
+                black_box(local.parse(ua, None).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_local_detector);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+//! The os stage on Chromium traffic, which sends `Sec-CH-UA-Platform` with
+//! every request. Compare against a previous run with
+//! `cargo bench --bench os_hints -- --baseline <name>`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use rust_device_detector::client_hints::ClientHint;
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::{DeviceDetector, ParseContext};
+use rust_device_detector::parsers::oss;
+
+const TRAFFIC: &[(&str, &str)] = &[
+    (
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        r#""Windows""#,
+    ),
+    (
+        "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+        r#""Android""#,
+    ),
+    (
+        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        r#""macOS""#,
+    ),
+    (
+        "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        r#""Linux""#,
+    ),
+    (
+        "Mozilla/5.0 (X11; CrOS x86_64 14541.0.0) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        r#""Chrome OS""#,
+    ),
+];
+
+fn hints(platform: &str) -> ClientHint {
+    ClientHint::from_headers(vec![
+        (
+            "sec-ch-ua".to_owned(),
+            r#""Not_A Brand";v="8", "Chromium";v="120", "Google Chrome";v="120""#.to_owned(),
+        ),
+        ("sec-ch-ua-mobile".to_owned(), "?0".to_owned()),
+        ("sec-ch-ua-platform".to_owned(), platform.to_owned()),
+    ])
+    .expect("valid hints")
+}
+
+fn bench_os_hints(c: &mut Criterion) {
+    let traffic = TRAFFIC
+        .iter()
+        .map(|(ua, platform)| (*ua, hints(platform)))
+        .collect::<Vec<_>>();
+
+    let db = Database::embedded();
+    let mut ctx = ParseContext::new();
+
+    // warm up the lazily compiled regexes
+    for (ua, hints) in &traffic {
+        oss::lookup(ua, Some(hints), &db, &mut ctx).unwrap();
+    }
+
+    c.bench_function("os stage with platform hint", |b| {
+        b.iter(|| {
+            for (ua, hints) in &traffic {
+                black_box(oss::lookup(ua, Some(hints), &db, &mut ctx).unwrap());
+            }
+        })
+    });
+
+    let detector = DeviceDetector::new();
+    c.bench_function("parse with platform hint", |b| {
+        b.iter(|| {
+            for (ua, hints) in &traffic {
+                black_box(
+                    detector
+                        .parse_client_hints_with_context(ua, Some(hints.clone()), &mut ctx)
+                        .unwrap(),
+                );
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_os_hints);
+criterion_main!(benches);
@@ -1,28 +1,92 @@
 #[cfg(feature = "ffi")]
 extern crate cbindgen;
-#[cfg(feature = "ffi")]
+#[cfg(any(feature = "ffi", feature = "binary-database"))]
 use std::env;
 
-// TODO parse and optimize yaml files into serde binary for faster
-// startup time.
+#[cfg(feature = "binary-database")]
+#[path = "src/binary_database/encode.rs"]
+mod encode;
 
 fn main() {
-    // let mut build_dir = get_cargo_target_dir().unwrap();
-    // build_dir.push("regexes");
-
-    // let _ = std::fs::create_dir(build_dir);
-
-    // let contents = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/regexes/bots.yml"));
-    // let value: serde_yaml::Value = serde_yaml::from_str(contents).unwrap();
     #[cfg(feature = "ffi")]
     build_cpp_header();
+
+    #[cfg(feature = "binary-database")]
+    build_binary_database();
+
+    #[cfg(feature = "grpc")]
+    build_grpc();
+
+    #[cfg(feature = "node")]
+    napi_build::setup();
+}
+
+#[cfg(feature = "grpc")]
+fn build_grpc() {
+    // so that building doesn't need protoc installed
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc");
+    std::env::set_var("PROTOC", protoc);
+
+    tonic_build::compile_protos("proto/device_detector.proto")
+        .expect("compiling proto/device_detector.proto");
+}
+
+#[cfg(feature = "binary-database")]
+fn build_binary_database() {
+    // the data crate's features aren't those of the build dependency, so it
+    // has both databases and every device list, and the ones embedded are
+    // picked here.
+    let mini = env::var_os("CARGO_FEATURE_MINI_DATABASE").is_some();
+    let enabled = |feature: &str| {
+        let var = format!("CARGO_FEATURE_{}", feature.to_uppercase().replace('-', "_"));
+        env::var_os(var).is_some()
+    };
+    let files = device_detector_data::FILES.iter();
+    let files = files.filter(|path| device_detector_data::feature(path).is_none_or(enabled));
+    let files = files.map(|path| {
+        let contents = if mini {
+            device_detector_data::mini_file(path)
+        } else {
+            device_detector_data::file(path)
+        };
+        (*path, contents.expect("embedded database file"))
+    });
+
+    let bytes = encode::encode(files).expect("compiling the embedded database");
+    let out = std::path::Path::new(&env::var("OUT_DIR").unwrap()).join("database.bin");
+    std::fs::write(out, bytes).expect("writing the compiled database");
 }
 
 #[cfg(feature = "ffi")]
 fn build_cpp_header() {
     let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    // the grpc build below lists the files it depends on, which would
+    // otherwise stop cargo from rerunning this when the source changes.
+    println!("cargo:rerun-if-changed=src");
+
+    // public items of the rust api that cbindgen would otherwise put in the
+    // header, although they're no part of the ffi. associated constants go by
+    // their own name, which is why `DEFAULT` and `MATOMO` of `OutputStyle` are
+    // listed too.
+    const NOT_FFI: &[&str] = &[
+        "MAX_PASSES",
+        "MAX_LEN",
+        "OutputStyle",
+        "DEFAULT",
+        "MATOMO",
+        "PREFIX_LEN",
+        "TOP_PREFIXES",
+        "DEFAULT_CAPACITY",
+        "SCHEMA_VERSION",
+    ];
 
-    let res = cbindgen::Builder::new().with_crate(crate_dir).generate();
+    let res = NOT_FFI
+        .iter()
+        .fold(
+            cbindgen::Builder::new().with_crate(crate_dir),
+            |builder, x| builder.exclude_item(x),
+        )
+        .generate();
 
     match res {
         Ok(res) => {
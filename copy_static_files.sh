@@ -6,10 +6,16 @@
 
 ORIGIN_DIR="$1"
 
-cp $ORIGIN_DIR/regexes/*.yml regexes/
-cp $ORIGIN_DIR/regexes/device/*.yml regexes/device/
-cp $ORIGIN_DIR/regexes/client/*.yml regexes/client/
-cp $ORIGIN_DIR/regexes/client/hints/*.yml regexes/client/hints/
+cp $ORIGIN_DIR/regexes/*.yml data/regexes/
+cp $ORIGIN_DIR/regexes/device/*.yml data/regexes/device/
+cp $ORIGIN_DIR/regexes/client/*.yml data/regexes/client/
+cp $ORIGIN_DIR/regexes/client/hints/*.yml data/regexes/client/hints/
+
+# the miniature database is a subset of the full one
+python3 data/regexes-mini/generate.py
+
+# remember to bump the version of device-detector-data in data/Cargo.toml and
+# the dependency on it in Cargo.toml, and UPSTREAM_COMMIT in data/src/lib.rs
 
 cp $ORIGIN_DIR/Tests/fixtures/*.yml tests/data/fixtures/
 cp $ORIGIN_DIR/Tests/Parser/fixtures/*.yml tests/data/fixtures/parser/
@@ -0,0 +1,144 @@
+#!/usr/bin/env python3
+"""Regenerates the miniature database from ../regexes.
+
+Keeps the entries for the most common bots, oss, clients and device brands,
+in the order they appear in the full database so that the regexes which are
+kept still take precedence over each other the same way. The small files are
+copied as they are.
+
+Usage: python3 data/regexes-mini/generate.py
+"""
+
+import os
+import re
+import shutil
+
+ROOT = os.path.dirname(os.path.abspath(__file__))
+FULL = os.path.join(ROOT, "..", "regexes")
+
+BOTS = {
+    "Googlebot", "Googlebot News", "Google Favicon", "BingBot", "Yandex Bot",
+    "Baidu Spider", "DuckDuckBot", "Applebot", "Facebook Crawler", "FacebookBot",
+    "Twitterbot", "LinkedIn Bot", "Slackbot", "TelegramBot", "Pinterest",
+    "aHrefs Bot", "SemrushBot", "MJ12 Bot", "DotBot", "Petal Bot", "Bytespider",
+    "Amazon Bot", "Yahoo! Slurp", "Sogou Spider", "Seznam Bot", "Qwantbot",
+    "MojeekBot", "archive.org bot", "ccBot crawler", "UptimeRobot", "Pingdom Bot",
+    "Lighthouse", "GTmetrix", "Datadog Agent", "StatusCake", "Generic Bot",
+}
+
+OSS = {
+    "Windows", "Windows Phone", "Windows Mobile", "Windows RT", "Mac", "iOS",
+    "iPadOS", "watchOS", "tvOS", "Android", "Android TV", "Fire OS", "GNU/Linux",
+    "Ubuntu", "Debian", "Fedora", "Chrome OS", "HarmonyOS", "Tizen", "KaiOS",
+    "webOS", "FreeBSD",
+}
+
+BROWSERS = {
+    "Chrome", "Chrome Mobile", "Chrome Mobile iOS", "Chrome Webview", "Chromium",
+    "Firefox", "Firefox Mobile", "Firefox Mobile iOS", "Firefox Focus", "Safari",
+    "Mobile Safari", "Microsoft Edge", "Opera", "Opera Mobile", "Opera Mini",
+    "Opera GX", "Samsung Browser", "UC Browser", "Yandex Browser", "Brave",
+    "Vivaldi", "DuckDuckGo Privacy Browser", "Internet Explorer", "IE Mobile",
+    "Android Browser", "Huawei Browser", "Mi Browser",
+    "Whale Browser", "QQ Browser", "Sogou Explorer",
+}
+
+MOBILE_APPS = {
+    "Facebook", "Facebook Messenger", "Instagram", "WhatsApp", "Spotify",
+    "TikTok", "Pinterest", "Snapchat", "Twitter", "LinkedIn", "Google Search App",
+    "YouTube",
+}
+
+LIBRARIES = {
+    "curl", "Wget", "Python Requests", "Python urllib", "Go-http-client",
+    "Java", "OkHttp", "Axios", "Node Fetch", "Guzzle (PHP HTTP Client)",
+}
+
+FEED_READERS = {"NetNewsWire", "NewsBlur", "Reeder"}
+MEDIA_PLAYERS = {"VLC", "iTunes", "Windows Media Player"}
+PIM = {"Outlook Express", "Microsoft Outlook", "Thunderbird"}
+
+BRANDS = {
+    "Apple", "Samsung", "Xiaomi", "Huawei", "Google", "OnePlus", "OPPO", "Vivo",
+    "Motorola", "LG", "Sony", "Nokia", "Realme", "Lenovo", "Amazon", "Microsoft",
+}
+
+TV_BRANDS = {
+    "Samsung", "LG", "Sony", "Amazon", "Hisense", "TCL", "Philips", "Xiaomi",
+    "Panasonic",
+}
+
+COPIED = [
+    "vendorfragments.yml",
+    "client/browser_engine.yml",
+    "client/hints/apps.yml",
+    "client/hints/browsers.yml",
+    "device/cameras.yml",
+    "device/car_browsers.yml",
+    "device/consoles.yml",
+    "device/notebooks.yml",
+    "device/portable_media_player.yml",
+    "device/shell_tv.yml",
+]
+
+HEADER = "# Generated by regexes-mini/generate.py from regexes/{}, do not edit.\n\n"
+
+
+def blocks(path, start):
+    """Splits a yaml file into top level entries, dropping comments."""
+    res = []
+    for line in open(path, encoding="utf-8"):
+        if line.startswith("#") or not line.strip():
+            continue
+        if start.match(line):
+            res.append([])
+        if res:
+            res[-1].append(line)
+    return ["".join(x) for x in res]
+
+
+def name(block):
+    m = re.search(r"^  name: ['\"]?(.*?)['\"]?$", block, re.M)
+    return m.group(1) if m else None
+
+
+def brand(block):
+    return block.split(":", 1)[0].strip("'\"")
+
+
+def write(path, entries, found=None, wanted=None):
+    out = os.path.join(ROOT, path)
+    os.makedirs(os.path.dirname(out), exist_ok=True)
+    with open(out, "w", encoding="utf-8") as f:
+        f.write(HEADER.format(path))
+        f.write("\n".join(entries) if entries else "[]\n")
+    if wanted is not None:
+        for missing in sorted(wanted - found):
+            print("{}: no entry for {}".format(path, missing))
+
+
+def filter_list(path, wanted):
+    entries = [x for x in blocks(os.path.join(FULL, path), re.compile(r"^- ")) if name(x) in wanted]
+    write(path, entries, {name(x) for x in entries}, wanted)
+
+
+def filter_brands(path, wanted):
+    entries = [x for x in blocks(os.path.join(FULL, path), re.compile(r"^\S")) if brand(x) in wanted]
+    write(path, entries, {brand(x) for x in entries}, wanted)
+
+
+filter_list("bots.yml", BOTS)
+filter_list("oss.yml", OSS)
+filter_list("client/browsers.yml", BROWSERS)
+filter_list("client/mobile_apps.yml", MOBILE_APPS)
+filter_list("client/libraries.yml", LIBRARIES)
+filter_list("client/feed_readers.yml", FEED_READERS)
+filter_list("client/mediaplayers.yml", MEDIA_PLAYERS)
+filter_list("client/pim.yml", PIM)
+filter_brands("device/mobiles.yml", BRANDS)
+filter_brands("device/televisions.yml", TV_BRANDS)
+
+for path in COPIED:
+    out = os.path.join(ROOT, path)
+    os.makedirs(os.path.dirname(out), exist_ok=True)
+    shutil.copyfile(os.path.join(FULL, path), out)
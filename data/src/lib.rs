@@ -0,0 +1,87 @@
+//! The matomo device detector regex database, embedded.
+//!
+//! This is released separately from the engine, so that database updates can
+//! ship, and be pinned, on their own. With the `mini` feature, the much smaller
+//! subset in `regexes-mini/` is embedded instead. The device lists other than
+//! `device/mobiles.yml` each have a feature, all of them in `all-devices`,
+//! and aren't embedded without it.
+
+/// The version of this crate, that of the database embedded.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The upstream commit `regexes/` was copied from.
+pub const UPSTREAM_COMMIT: &str = "20278b17366dac03707c176e6ef05a8f45526e4c";
+
+// the miniature database keeps only the most common entries, for tests and
+// constrained targets. See regexes-mini/generate.py.
+#[cfg(not(feature = "mini"))]
+macro_rules! embedded_dir {
+    () => {
+        "/regexes/"
+    };
+}
+
+#[cfg(feature = "mini")]
+macro_rules! embedded_dir {
+    () => {
+        "/regexes-mini/"
+    };
+}
+
+macro_rules! embedded_files {
+    ($($path:literal $(if $feature:literal)?),* $(,)?) => {
+        /// Every file the database is made of, relative to the database
+        /// directory, including those whose feature is disabled.
+        pub const FILES: &[&str] = &[$($path),*];
+
+        /// The contents of one of [`FILES`], if it's embedded.
+        pub fn file(path: &str) -> Option<&'static str> {
+            match path {
+                $($(#[cfg(feature = $feature)])? $path => Some(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), embedded_dir!(), $path))),)*
+                _ => None,
+            }
+        }
+
+        /// The contents of one of [`FILES`] in `regexes-mini/`, whichever
+        /// database is embedded.
+        #[cfg(feature = "mini-files")]
+        pub fn mini_file(path: &str) -> Option<&'static str> {
+            match path {
+                $($(#[cfg(feature = $feature)])? $path => Some(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/regexes-mini/", $path))),)*
+                _ => None,
+            }
+        }
+
+        /// The feature embedding one of [`FILES`], for those which can be
+        /// left out.
+        pub fn feature(path: &str) -> Option<&'static str> {
+            $($(if path == $path {
+                return Some($feature);
+            })?)*
+            None
+        }
+    };
+}
+
+embedded_files!(
+    "bots.yml",
+    "oss.yml",
+    "vendorfragments.yml",
+    "client/browsers.yml",
+    "client/browser_engine.yml",
+    "client/feed_readers.yml",
+    "client/libraries.yml",
+    "client/mediaplayers.yml",
+    "client/mobile_apps.yml",
+    "client/pim.yml",
+    "client/hints/apps.yml",
+    "client/hints/browsers.yml",
+    "device/cameras.yml" if "cameras",
+    "device/car_browsers.yml" if "car-browsers",
+    "device/consoles.yml" if "consoles",
+    "device/mobiles.yml",
+    "device/notebooks.yml" if "notebooks",
+    "device/portable_media_player.yml" if "portable-media-players",
+    "device/shell_tv.yml" if "shell-tvs",
+    "device/televisions.yml" if "televisions",
+);
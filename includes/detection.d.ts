@@ -0,0 +1,145 @@
+// Generated by rust-device-detector, the json of a detection.
+// Regenerate with `rust-device-detector typescript`.
+
+export type Detection = KnownDetection | BotDetection;
+
+export interface KnownDetection {
+  client: Client | null;
+  device: Device | null;
+  os: Os | null;
+  stage_errors?: StageError[];
+  snapshot?: string;
+  hints_used?: string[];
+  user_agent_decoded?: boolean;
+  approximate?: boolean;
+  is: DeviceFlags;
+}
+
+export interface BotDetection {
+  bot: Bot;
+}
+
+export interface Client {
+  name: string;
+  version: string | null;
+  type: ClientType;
+  short_code: string | null;
+  engine: string | null;
+  engine_version: string | null;
+}
+
+export type ClientType = "browser" | "feed reader" | "mobile app" | "pim" | "library" | "mediaplayer" | "download manager";
+
+export interface Device {
+  type: DeviceType | null;
+  brand: string | null;
+  model: string | null;
+  mobile_client_hint?: boolean;
+  touch_enabled?: boolean;
+  confidence?: DeviceConfidence;
+  spec?: DeviceSpec;
+  hbbtv?: HbbTv;
+}
+
+export type DeviceType = "desktop" | "smartphone" | "feature phone" | "tablet" | "phablet" | "console" | "portable media player" | "car browser" | "television" | "smart display" | "smart speaker" | "camera" | "notebook" | "wearable" | "peripheral";
+
+export interface DeviceConfidence {
+  type: Confidence | null;
+  brand: Confidence | null;
+  model: Confidence | null;
+}
+
+export type Confidence = "exact" | "inferred" | "fallback";
+
+export interface DeviceSpec {
+  screen_size: number | null;
+  ram_mb: number | null;
+  release_year: number | null;
+}
+
+export interface HbbTv {
+  version: string;
+  capabilities: string[];
+  vendor: string | null;
+  model: string | null;
+  software_version: string | null;
+  hardware_version: string | null;
+  family: string | null;
+  operator: TvOperator | null;
+}
+
+export interface TvOperator {
+  name: string;
+  country: string;
+}
+
+export interface Os {
+  name: string;
+  version: string | null;
+  platform: string | null;
+  family: string | null;
+}
+
+export interface StageError {
+  stage: Stage;
+  message: string;
+}
+
+export type Stage = "bot" | "os" | "client" | "device";
+
+export interface DeviceFlags {
+  touch_enabled: boolean;
+  mobile: boolean;
+  browser: boolean;
+  feed_reader: boolean;
+  library: boolean;
+  media_player: boolean;
+  mobile_app: boolean;
+  pim: boolean;
+  desktop: boolean;
+  notebook: boolean;
+  smart_phone: boolean;
+  feature_phone: boolean;
+  camera: boolean;
+  car_browser: boolean;
+  console: boolean;
+  portable_media_player: boolean;
+  television: boolean;
+  smart_display: boolean;
+  tablet: boolean;
+  smart_speaker: boolean;
+  peripheral: boolean;
+  wearable: boolean;
+  phablet: boolean;
+  robot: boolean;
+}
+
+export interface Bot {
+  name: string;
+  category: string | null;
+  url: string | null;
+  producer: BotProducer | null;
+  bot_spoof_suspected?: boolean;
+  spoof_signals?: SpoofSignal[];
+  claimed?: BotClaims;
+  snapshot?: string;
+  user_agent_decoded?: boolean;
+  approximate?: boolean;
+}
+
+export interface BotClaims {
+  client: Client | null;
+  device: Device | null;
+  os: Os | null;
+  stage_errors?: StageError[];
+  hints_used?: string[];
+  user_agent_decoded?: boolean;
+  approximate?: boolean;
+}
+
+export interface BotProducer {
+  name: string | null;
+  url: string | null;
+}
+
+export type SpoofSignal = "BrowserBrands" | "DeviceModel" | "FormFactors";
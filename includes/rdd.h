@@ -137,4 +137,19 @@ void rdd_free_detection(RDDDetection *rdd);
 
 void rdd_free_string(char *rdd);
 
+/// Creates a detector, to be freed with `device_detector_free`.
+RDDDeviceDetector *device_detector_new();
+
+/// Detects `ua`, with `headers` as a json array of the other headers of the
+/// request, as name and value pairs in the order they were received, such as
+/// `[["sec-ch-ua-model", "\"Pixel 7\""]]`, or null.
+///
+/// Returns the json of the detection, as the cli prints it, or
+/// `{"error": "..."}`. The string is freed with `device_detector_free_string`.
+char *device_detector_parse_json(const RDDDeviceDetector *dd, const char *ua, const char *headers);
+
+void device_detector_free(RDDDeviceDetector *dd);
+
+void device_detector_free_string(char *s);
+
 } // extern "C"
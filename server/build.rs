@@ -0,0 +1,19 @@
+fn main() {
+    #[cfg(feature = "ext-proc")]
+    build_ext_proc();
+}
+
+#[cfg(feature = "ext-proc")]
+fn build_ext_proc() {
+    // so that building doesn't need protoc installed
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc");
+    std::env::set_var("PROTOC", protoc);
+
+    tonic_build::configure()
+        .build_client(false)
+        .compile(
+            &["proto/envoy/service/ext_proc/v3/external_processor.proto"],
+            &["proto"],
+        )
+        .expect("compiling the envoy ext_proc protos");
+}
@@ -0,0 +1,216 @@
+//! An envoy external processor which annotates requests with what they were
+//! detected as, for enriching requests at the mesh level rather than in each
+//! service.
+//!
+//! Envoy is configured with an `ext_proc` http filter whose grpc service
+//! points at this, sending only the request headers:
+//!
+//! ```yaml
+//! - name: envoy.filters.http.ext_proc
+//!   typed_config:
+//!     "@type": type.googleapis.com/envoy.extensions.filters.http.ext_proc.v3.ExternalProcessor
+//!     grpc_service:
+//!       envoy_grpc:
+//!         cluster_name: device_detector
+//!     processing_mode:
+//!       request_header_mode: SEND
+//!       response_header_mode: SKIP
+//! ```
+//!
+//! The `device_detector` cluster must use http2. Requests are passed on with
+//! the `x-detected-*` headers set, any the client sent itself being replaced
+//! or removed.
+
+use anyhow::{bail, Result};
+
+use clap::Parser;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::edge;
+
+mod proto;
+use proto::envoy::config::core::v3::header_value_option::HeaderAppendAction;
+use proto::envoy::config::core::v3::{HeaderValue, HeaderValueOption};
+use proto::envoy::service::ext_proc::v3::external_processor_server::{
+    ExternalProcessor, ExternalProcessorServer,
+};
+use proto::envoy::service::ext_proc::v3::{
+    processing_request, processing_response, BodyResponse, CommonResponse, HeaderMutation,
+    HeadersResponse, HttpHeaders, ProcessingRequest, ProcessingResponse, TrailersResponse,
+};
+
+#[derive(Parser, Debug)]
+/// An envoy ext_proc service adding x-detected-* headers to requests
+#[command(version)]
+struct Args {
+    /// Address to listen on.
+    #[arg(
+        short = 'l',
+        long = "listen",
+        value_name = "ADDRESS",
+        default_value = "127.0.0.1"
+    )]
+    ip: String,
+
+    /// Port to listen on.
+    #[arg(short = 'p', long = "port", default_value = "50051")]
+    port: u16,
+
+    #[cfg(feature = "cache")]
+    /// If set, how many entries to cache in an lru cache.
+    #[arg(short = 'c', long = "cache", default_value = None, value_name = "ENTRIES")]
+    cache: Option<u64>,
+}
+
+/// The name and value pairs of `headers`. Newer envoys only send
+/// `raw_value`.
+fn header_pairs(headers: HttpHeaders) -> Vec<(String, String)> {
+    headers
+        .headers
+        .map(|x| x.headers)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|header| {
+            let value = if header.raw_value.is_empty() {
+                header.value
+            } else {
+                String::from_utf8_lossy(&header.raw_value).into_owned()
+            };
+            (header.key, value)
+        })
+        .collect()
+}
+
+/// Sets the `x-detected-*` headers of the detection of `headers`, and
+/// removes those without a value.
+async fn annotate(detector: &DeviceDetector, headers: HttpHeaders) -> Result<HeadersResponse> {
+    let headers = header_pairs(headers);
+    let (ua, headers) = edge::request_headers(headers.as_slice());
+    let detection = detector.parse_async(&ua, Some(headers)).await?;
+
+    let mut mutation = HeaderMutation::default();
+    for (key, value) in edge::detection_headers(&detection) {
+        if value.is_empty() {
+            mutation.remove_headers.push(key.to_owned());
+            continue;
+        }
+        mutation.set_headers.push(HeaderValueOption {
+            header: Some(HeaderValue {
+                key: key.to_owned(),
+                raw_value: value.clone().into_bytes(),
+                value,
+            }),
+            append_action: HeaderAppendAction::OverwriteIfExistsOrAdd.into(),
+            keep_empty_value: false,
+        });
+    }
+
+    Ok(HeadersResponse {
+        response: Some(CommonResponse {
+            header_mutation: Some(mutation),
+        }),
+    })
+}
+
+/// Answers `request`, letting every phase but the request headers through
+/// unchanged.
+async fn respond(
+    detector: &DeviceDetector,
+    request: ProcessingRequest,
+) -> Result<ProcessingResponse> {
+    use processing_request::Request as Phase;
+    use processing_response::Response as Reply;
+
+    let response = match request.request {
+        Some(Phase::RequestHeaders(headers)) => {
+            Reply::RequestHeaders(annotate(detector, headers).await?)
+        }
+        Some(Phase::ResponseHeaders(_)) => Reply::ResponseHeaders(HeadersResponse::default()),
+        Some(Phase::RequestBody(_)) => Reply::RequestBody(BodyResponse::default()),
+        Some(Phase::ResponseBody(_)) => Reply::ResponseBody(BodyResponse::default()),
+        Some(Phase::RequestTrailers(_)) => Reply::RequestTrailers(TrailersResponse::default()),
+        Some(Phase::ResponseTrailers(_)) => Reply::ResponseTrailers(TrailersResponse::default()),
+        None => bail!("processing request without a phase"),
+    };
+
+    Ok(ProcessingResponse {
+        response: Some(response),
+    })
+}
+
+struct Processor {
+    detector: Arc<DeviceDetector>,
+}
+
+#[tonic::async_trait]
+impl ExternalProcessor for Processor {
+    type ProcessStream = ReceiverStream<Result<ProcessingResponse, Status>>;
+
+    /// Answers each message of the stream as it arrives, until envoy closes
+    /// it.
+    async fn process(
+        &self,
+        request: Request<Streaming<ProcessingRequest>>,
+    ) -> Result<Response<Self::ProcessStream>, Status> {
+        let mut requests = request.into_inner();
+        let detector = self.detector.clone();
+        let (sender, receiver) = mpsc::channel(4);
+
+        tokio::spawn(async move {
+            loop {
+                let response = match requests.message().await {
+                    Ok(Some(request)) => respond(&detector, request).await.map_err(|err| {
+                        eprintln!("ext_proc stream failed: {:#}", err);
+                        Status::internal(format!("{:#}", err))
+                    }),
+                    Ok(None) => break,
+                    Err(status) => Err(status),
+                };
+                let failed = response.is_err();
+                if sender.send(response).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    #[cfg(not(feature = "cache"))]
+    let detector = DeviceDetector::new();
+
+    #[cfg(feature = "cache")]
+    let detector = match args.cache {
+        Some(entries) => DeviceDetector::new_with_cache(entries),
+        None => DeviceDetector::new(),
+    };
+
+    let detector = Arc::new(detector);
+
+    let ip: IpAddr = args.ip.parse().expect("valid ip address (ipv4 or ipv6)");
+    let listen_address = SocketAddr::new(ip, args.port);
+    eprintln!("Listening on {}", listen_address);
+
+    let server = tonic::transport::Server::builder()
+        .add_service(ExternalProcessorServer::new(Processor { detector }))
+        .serve_with_shutdown(listen_address, async {
+            tokio::signal::ctrl_c()
+                .await
+                .expect("failed to install CTRL+C signal handler");
+        });
+
+    if let Err(e) = server.await {
+        eprintln!("server error: {}", e);
+    }
+}
@@ -0,0 +1,23 @@
+//! The messages and server generated from the envoy protos in `proto/`, in
+//! modules following their packages, as the generated code refers to the
+//! messages of other packages by their relative paths. The oneof variants
+//! are named after envoy's fields, hence the lint allowed below.
+
+pub mod envoy {
+    pub mod config {
+        pub mod core {
+            pub mod v3 {
+                tonic::include_proto!("envoy.config.core.v3");
+            }
+        }
+    }
+
+    pub mod service {
+        pub mod ext_proc {
+            #[allow(clippy::enum_variant_names)]
+            pub mod v3 {
+                tonic::include_proto!("envoy.service.ext_proc.v3");
+            }
+        }
+    }
+}
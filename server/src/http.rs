@@ -0,0 +1,22 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use device_detector_server::http_server;
+use rust_device_detector::device_detector::DeviceDetector;
+
+async fn shutdown_signal() {
+    tokio::signal::ctrl_c()
+        .await
+        .expect("failed to install CTRL+C signal handler");
+}
+
+pub async fn server(listen_address: SocketAddr, device_detector: DeviceDetector) {
+    eprintln!("Listening on {}", listen_address);
+
+    let device_detector = Arc::new(device_detector);
+
+    // Run this server for... forever!
+    if let Err(e) = http_server::serve(listen_address, device_detector, shutdown_signal()).await {
+        eprintln!("server error: {}", e);
+    };
+}
@@ -0,0 +1,144 @@
+//! A small http server for running the detector as a sidecar of services
+//! which aren't written in rust:
+//!
+//! ```ignore
+//! let detector = Arc::new(DeviceDetector::new());
+//! http_server::serve(([127, 0, 0, 1], 8080).into(), detector, shutdown).await?;
+//! ```
+//!
+//! `GET /detect` detects from its own request headers, so a service passes on
+//! the headers of the request it's handling, the user agent and client hints
+//! among them, and gets back the json of [`Detection::to_value`]:
+//!
+//! ```sh
+//! curl -H 'User-Agent: Mozilla/5.0 ...' -H 'Sec-CH-UA-Mobile: ?1' localhost:8080/detect
+//! ```
+//!
+//! `POST /detect` detects the user agent sent as the body, without headers,
+//! refusing bodies over [`MAX_BODY_BYTES`], `GET /health` is a heartbeat, and
+//! `GET /metrics` serves [`rust_device_detector::metrics`] to Prometheus.
+//! Detections run on tokio's blocking pool, see
+//! [`rust_device_detector::async_parse`].
+//!
+//! [`Detection::to_value`]: rust_device_detector::device_detector::Detection::to_value
+
+use anyhow::{anyhow, Result};
+
+use http_body::{LengthLimitError, Limited};
+use hyper::http::StatusCode;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, HeaderMap, Method, Request, Response, Server};
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::edge;
+
+/// The longest body `POST /detect` takes. User agents are a few hundred
+/// bytes, so this only turns away abuse.
+pub const MAX_BODY_BYTES: usize = 16 * 1024;
+
+const ROUTES: &str = "valid routes:
+  GET  /detect with the headers of the request to detect
+  POST /detect with a body containing the user agent
+  GET  /health for heartbeat
+  GET  /metrics for prometheus
+";
+
+fn header(headers: &HeaderMap, name: &str) -> Option<String> {
+    let value = headers.get(name)?.to_str().ok()?;
+    Some(value.to_owned())
+}
+
+async fn detect(
+    detector: &DeviceDetector,
+    ua: &str,
+    headers: Option<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    let response = match detector.parse_async(ua, headers).await {
+        Ok(detection) => Response::builder()
+            .header("content-type", "application/json")
+            .body(Body::from(detection.to_value().to_string()))?,
+        Err(err) => Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::from(format!("{:#}\n", err)))?,
+    };
+    Ok(response)
+}
+
+/// The response of the server to `req`.
+pub async fn handle(req: Request<Body>, detector: &DeviceDetector) -> Result<Response<Body>> {
+    match (req.method(), req.uri().path()) {
+        (&Method::GET, "/detect") => {
+            let (ua, headers) = edge::request_headers(&|name: &str| header(req.headers(), name));
+            detect(detector, &ua, Some(headers)).await
+        }
+
+        (&Method::POST, "/detect") => {
+            let body = Limited::new(req.into_body(), MAX_BODY_BYTES);
+            let body = match hyper::body::to_bytes(body).await {
+                Ok(body) => body,
+                Err(err) if err.is::<LengthLimitError>() => {
+                    return Ok(Response::builder()
+                        .status(StatusCode::PAYLOAD_TOO_LARGE)
+                        .body(Body::from(format!(
+                            "the user agent must be at most {} bytes\n",
+                            MAX_BODY_BYTES
+                        )))?);
+                }
+                Err(err) => return Err(anyhow!(err)),
+            };
+            let body = String::from_utf8(body.to_vec())?;
+            detect(detector, &body, None).await
+        }
+
+        (&Method::GET, "/health") => Ok(Response::new("OK\n".into())),
+
+        (&Method::GET, "/metrics") => Ok(Response::builder()
+            .header("content-type", "text/plain; version=0.0.4")
+            .body(Body::from(detector.metrics().to_prometheus()))?),
+
+        _route => Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from(ROUTES))?),
+    }
+}
+
+/// Serves detections on `listen_address` until `shutdown` completes.
+pub async fn serve(
+    listen_address: SocketAddr,
+    detector: Arc<DeviceDetector>,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    let listener = TcpListener::bind(listen_address)?;
+    serve_listener(listener, detector, shutdown).await
+}
+
+/// Same as [`serve`], on a listener which is already bound, such as one bound
+/// to port 0 to have the os pick a free port.
+pub async fn serve_listener(
+    listener: TcpListener,
+    detector: Arc<DeviceDetector>,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    let make_svc = make_service_fn(|_conn| {
+        let detector = detector.clone();
+
+        let service = service_fn(move |req| {
+            let detector = detector.clone();
+            async move { handle(req, &detector).await }
+        });
+
+        async move { Ok::<_, Infallible>(service) }
+    });
+
+    listener.set_nonblocking(true)?;
+    Server::from_tcp(listener)?
+        .serve(make_svc)
+        .with_graceful_shutdown(shutdown)
+        .await?;
+
+    Ok(())
+}
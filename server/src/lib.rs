@@ -0,0 +1,6 @@
+//! The http server of the `rust-device-detector` binary, for running it from
+//! your own binary as well.
+
+pub mod http_server;
+#[cfg(feature = "test-util")]
+pub mod test_util;
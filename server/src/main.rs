@@ -0,0 +1,623 @@
+// use tracking_allocator::AllocationRegistry;
+// use tracking_allocator::Allocator;
+
+// #[global_allocator]
+// static GLOBAL: Allocator<System> = Allocator::system();
+
+// use std::env;
+use std::process::ExitCode;
+
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+
+mod http;
+mod output;
+mod stdin;
+use http::server;
+use output::{Case, Column, Format, Layout, Table, DEFAULT_COLUMNS};
+
+#[cfg(feature = "binary-database")]
+use rust_device_detector::binary_database;
+use rust_device_detector::database::{diff_user_agents, Database};
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::json_schema;
+use rust_device_detector::replay;
+use rust_device_detector::typescript;
+#[cfg(feature = "upstream-sync")]
+use rust_device_detector::upstream;
+use rust_device_detector::waf::WafRules;
+
+#[derive(Parser, Debug)]
+/// A commandline user agent detection tool
+///
+/// This is a long explanation
+#[command(version, subcommand_negates_reqs = true)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Run in interactive mode.
+    ///
+    /// In interactive mode, each stdin line will be parsed
+    /// as a user agent, and we will return on stout, one single
+    /// line of json as a result.
+    ///
+    /// Lines may also be json objects with the user agent and the
+    /// headers of the request, such as
+    /// {"user_agent": "...", "headers": {"sec-ch-ua-mobile": "?1"}}.
+    /// Lines which can't be detected give {"error": "..."}.
+    #[arg(short = 'i', long = "interactive")]
+    interactive: bool,
+
+    /// Run as an http server.
+    ///
+    /// This will run the command as an http server, listening on the
+    /// specified port or 8080 by default. You submit one line of user
+    /// agent and you will get back a response in json.
+    #[arg(short = 's', long = "server")]
+    server: bool,
+
+    /// Address to listen on, when in http server mode.
+    #[arg(
+        short = 'l',
+        long = "listen",
+        value_name = "ADDRESS",
+        default_value = "127.0.0.1"
+    )]
+    ip: String,
+
+    /// Port to run on, when in http server mode.
+    #[arg(short = 'p', long = "port", default_value = "8080")]
+    port: u16,
+
+    #[cfg(feature = "cache")]
+    /// If set, how many entries to cache in an lru cache.
+    ///
+    /// Each cache entry requires about 100 bytes for the value and as many bytes as
+    /// the user agent and headers (if supplied) take up for the key. That might be
+    /// about 200 bytes per entry.
+    #[arg(short = 'c', long = "cache", default_value = None, value_name = "ENTRIES")]
+    cache: Option<u64>,
+
+    /// When in cli mode (the default) this is the user agent to parse.
+    ///
+    /// Always remember escape shell arguments!
+    #[arg(required_unless_present_any(["interactive", "server"]))]
+    useragent: Option<String>,
+
+    // TODO we need to be able to just pass in a big block of headers as a single parameter
+    // so to make it easier to use without having to know headers ahead of time.
+    // we can even still cache if we filter out irrelevant headers and sort them.
+    /// Additional individual headers to pass to the detector. The user agent will NOT
+    /// be detected by passing it in this option. Furthermore, the cache will not be
+    /// bypassed when using this option because headers change all the time.
+    #[arg(short = 'H', long = "header", action = ArgAction::Append, value_name = "HEADER")]
+    headers: Option<Vec<String>>,
+
+    /// Generate a basic test cases instead of the normal output.
+    ///
+    /// This is purely to make adding new test cases easier, and the output
+    /// should not be relied upon for anything else. Manual tweaking may be
+    /// required, and you should ensure that all tests should work with the
+    /// php version of the detector.
+    #[arg(long = "gen-test-case", default_value = "false")]
+    gen_test_case: bool,
+
+    /// Format of detections, in cli and interactive mode.
+    ///
+    /// csv and tsv have a row per user agent with the fields of --columns,
+    /// after a header row.
+    #[arg(long = "format", value_enum, default_value = "json")]
+    format: Format,
+
+    /// Comma separated columns of csv and tsv output.
+    ///
+    /// browser, version, engine, os, device_type, brand, model and is_bot
+    /// by default.
+    #[arg(
+        long = "columns",
+        value_enum,
+        value_delimiter = ',',
+        value_name = "COLUMNS"
+    )]
+    columns: Option<Vec<Column>>,
+
+    /// Leave out the header row of csv and tsv output.
+    #[arg(long = "no-header")]
+    no_header: bool,
+
+    /// Percent-decode user agents which look encoded, as some logs have
+    /// them, before detecting them.
+    #[arg(long = "decode")]
+    decode: bool,
+
+    /// Detect Macs which are iPads asking for desktop sites as iPads, from
+    /// the tokens of iOS webviews and the form factors hint.
+    #[arg(long = "desktop-class-ipads")]
+    desktop_class_ipads: bool,
+
+    /// In server mode, keep the share of unknown browsers and devices over
+    /// this many of the last detections in the metrics.
+    #[arg(long = "unknowns-window", value_name = "DETECTIONS")]
+    unknowns_window: Option<usize>,
+
+    /// Fields of json output, those of this crate or of the php device
+    /// detector's getInfoFromUserAgent.
+    #[arg(long = "layout", value_enum, default_value = "detection")]
+    layout: Layout,
+
+    /// Case of the field names of json output.
+    #[arg(long = "case", value_enum, default_value = "snake")]
+    case: Case,
+
+    /// Merge the nested objects of json output into their parent, such as
+    /// os_version for the version of the os.
+    #[arg(long = "flatten")]
+    flatten: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Replay a corpus of test fixtures, reporting detections which differ.
+    ///
+    /// Exits with a non zero status if there are any mismatches that aren't
+    /// in the baseline, so that it can be used with `git bisect run` to find
+    /// the regex database change responsible.
+    Replay {
+        /// Yaml file of fixtures, in the same format as the matomo tests.
+        corpus: PathBuf,
+
+        /// Mismatches from a previous run, which will not be reported again.
+        #[arg(long = "baseline", value_name = "FILE")]
+        baseline: Option<PathBuf>,
+
+        /// Save this run's mismatches, for use as a future baseline.
+        #[arg(long = "write-baseline", value_name = "FILE")]
+        write_baseline: Option<PathBuf>,
+    },
+
+    /// Show why two user agents are detected differently: the fields whose
+    /// values differ, and the regex each of them matched in every list.
+    ///
+    /// Regexes both matched are prefixed with a space, those only the first
+    /// or second matched with `-` or `+`, like a diff.
+    Diff {
+        first: String,
+
+        second: String,
+
+        /// Print the differences as json.
+        #[arg(long = "json")]
+        json: bool,
+    },
+
+    /// Print perimeter rules matching the bots, and optionally the http
+    /// libraries, the detector recognizes.
+    ///
+    /// Definitions the rules don't fully cover are listed on stderr.
+    Waf {
+        #[arg(long = "format", value_enum, default_value = "nginx-map")]
+        format: WafFormat,
+
+        /// Bot category to include, such as "Crawler". May be repeated, all
+        /// bots are included if none are given.
+        #[arg(long = "category", action = ArgAction::Append, value_name = "CATEGORY")]
+        categories: Vec<String>,
+
+        /// Include http libraries such as curl.
+        #[arg(long = "libraries")]
+        libraries: bool,
+
+        /// Shortest literal token to match on.
+        #[arg(long = "min-len", default_value = "4")]
+        min_len: usize,
+
+        /// What the nginx `if` or ModSecurity rules do on a match.
+        #[arg(long = "action", value_name = "ACTION")]
+        action: Option<String>,
+    },
+
+    /// Print TypeScript definitions of the json detections are printed and
+    /// served as.
+    Typescript,
+
+    /// Print a JSON Schema of the json detections are printed and served as.
+    JsonSchema,
+
+    /// Print an OpenAPI document of the http server.
+    Openapi,
+
+    /// Print the version of the regex database and how many browsers,
+    /// engines, oses, devices and bots it has, as json.
+    DatabaseInfo,
+
+    /// Compile the regex database to a binary format, which loads several
+    /// times faster than its yaml, for `Database::from_binary`.
+    #[cfg(feature = "binary-database")]
+    CompileDatabase {
+        /// File to write the compiled database to.
+        output: PathBuf,
+
+        /// Directory of the database to compile, laid out like data/regexes,
+        /// rather than the embedded one.
+        #[arg(long = "dir", value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+
+    /// Download the regexes of the upstream matomo device detector, check
+    /// that every one compiles and the files load, and write them if so.
+    ///
+    /// Exits with a non zero status if upstream has anything the detector
+    /// can't use, which is reported on stderr.
+    #[cfg(feature = "upstream-sync")]
+    SyncUpstream {
+        /// Directory to write the regexes to, laid out like data/regexes.
+        #[arg(long = "dir", default_value = "data/regexes")]
+        dir: PathBuf,
+
+        /// Branch, tag or commit of upstream to sync with.
+        #[arg(long = "ref", default_value = "master")]
+        git_ref: String,
+
+        /// Only report, without writing the regexes.
+        #[arg(long = "check")]
+        check: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum WafFormat {
+    NginxMap,
+    NginxIf,
+    Modsecurity,
+}
+
+fn run_waf(
+    format: WafFormat,
+    categories: Vec<String>,
+    libraries: bool,
+    min_len: usize,
+    action: Option<String>,
+) {
+    let db = Database::embedded();
+    let categories = categories.iter().map(|x| x.as_str()).collect::<Vec<_>>();
+
+    let mut rules = WafRules::new(min_len).with_bots(&db, &categories);
+    if libraries {
+        rules = rules.with_libraries(&db);
+    }
+
+    for name in rules.incomplete() {
+        eprintln!("not fully covered: {}", name);
+    }
+
+    let res = match format {
+        WafFormat::NginxMap => rules.nginx_map("$bot_category"),
+        WafFormat::NginxIf => rules.nginx_if(action.as_deref().unwrap_or("return 403;")),
+        WafFormat::Modsecurity => {
+            rules.modsecurity(1000, action.as_deref().unwrap_or("deny,status:403,log"))
+        }
+    };
+    print!("{}", res);
+}
+
+fn run_replay(
+    detector: &DeviceDetector,
+    corpus: PathBuf,
+    baseline: Option<PathBuf>,
+    write_baseline: Option<PathBuf>,
+) -> anyhow::Result<bool> {
+    let cases = replay::load_corpus(&corpus)?;
+    let mismatches = replay::replay(detector, &cases)?;
+
+    if let Some(path) = write_baseline {
+        replay::save_baseline(path, &mismatches)?;
+    }
+
+    let baseline = match baseline {
+        Some(path) => replay::load_baseline(path)?,
+        None => Vec::new(),
+    };
+
+    let new = replay::new_mismatches(&mismatches, &baseline);
+
+    for mismatch in new.iter() {
+        println!("{}", serde_json::to_string(mismatch)?);
+    }
+
+    eprintln!(
+        "{} fixtures, {} mismatches, {} new",
+        cases.len(),
+        mismatches.len(),
+        new.len()
+    );
+
+    Ok(new.is_empty())
+}
+
+#[cfg(feature = "upstream-sync")]
+fn run_sync_upstream(git_ref: &str, dir: &std::path::Path, write: bool) -> anyhow::Result<bool> {
+    let agent = ureq::AgentBuilder::new()
+        .user_agent(concat!("rust-device-detector/", env!("CARGO_PKG_VERSION")))
+        .build();
+    let report = upstream::sync(git_ref, dir, write, |url| {
+        eprintln!("fetching {}", url);
+        Ok(agent.get(url).call()?.into_string()?)
+    })?;
+
+    for (file, errors) in &report.errors {
+        for error in errors {
+            eprintln!("error: {}: {}", file, error);
+        }
+    }
+    for (file, warnings) in &report.warnings {
+        for warning in warnings {
+            eprintln!("warning: {}: {}", file, warning);
+        }
+    }
+    for (file, keys) in &report.unknown_keys {
+        for key in keys {
+            eprintln!(
+                "warning: {}: unknown key '{}' in {} entries",
+                file, key.key, key.count
+            );
+        }
+    }
+    for file in &report.new_files {
+        eprintln!("warning: {} is not part of the database", file);
+    }
+    if let Some(err) = &report.load_error {
+        eprintln!("error: the database fails to load: {}", err);
+    }
+
+    println!("upstream commit {}", report.commit);
+    if report.changed.is_empty() {
+        println!("{} is up to date", dir.display());
+    } else if report.written {
+        println!("updated {}:", dir.display());
+    } else {
+        println!("changed since {}:", dir.display());
+    }
+    for file in &report.changed {
+        println!("  {}", file);
+    }
+    if report.written && !report.changed.is_empty() {
+        println!(
+            "set UPSTREAM_COMMIT in data/src/lib.rs to {}, bump the version of device-detector-data and run data/regexes-mini/generate.py",
+            report.commit
+        );
+    }
+
+    Ok(report.is_compatible())
+}
+
+#[cfg(feature = "binary-database")]
+fn run_compile_database(
+    output: &std::path::Path,
+    dir: Option<&std::path::Path>,
+) -> anyhow::Result<()> {
+    let bytes = match dir {
+        Some(dir) => binary_database::compile_dir(dir)?,
+        None => binary_database::compile_embedded()?,
+    };
+    std::fs::write(output, &bytes)?;
+    println!("wrote {} ({} bytes)", output.display(), bytes.len());
+    Ok(())
+}
+
+fn run_diff(
+    detector: &DeviceDetector,
+    first: &str,
+    second: &str,
+    json: bool,
+) -> anyhow::Result<()> {
+    let diff = diff_user_agents(detector, first, second)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+
+    if diff.fields.is_empty() {
+        println!("detected the same");
+    }
+    for field in diff.fields.iter() {
+        println!("{}: {:?} -> {:?}", field.field, field.first, field.second);
+    }
+
+    println!();
+    for regex in diff.regexes.iter() {
+        if regex.is_same() {
+            println!(
+                "  {}: {}",
+                regex.list,
+                regex.first.as_deref().unwrap_or_default()
+            );
+            continue;
+        }
+        if let Some(first) = &regex.first {
+            println!("- {}: {}", regex.list, first);
+        }
+        if let Some(second) = &regex.second {
+            println!("+ {}: {}", regex.list, second);
+        }
+    }
+
+    Ok(())
+}
+
+// use stats_alloc::{Region, StatsAlloc, INSTRUMENTED_SYSTEM};
+
+// #[global_allocator]
+// static ALLOC: dhat::Alloc = dhat::Alloc;
+// static GLOBAL: &StatsAlloc<System> = &INSTRUMENTED_SYSTEM;
+
+#[tokio::main]
+async fn main() -> Result<(), ExitCode> {
+    // let _profiler = dhat::Profiler::new_heap();
+    //    sc_core::setup::binary_setup();
+
+    // let reg = stats_alloc::Region::new(&INSTRUMENTED_SYSTEM);
+
+    let args = Args::parse();
+
+    #[cfg(feature = "upstream-sync")]
+    if let Some(Command::SyncUpstream {
+        dir,
+        git_ref,
+        check,
+    }) = &args.command
+    {
+        match run_sync_upstream(git_ref, dir, !check) {
+            Ok(true) => return Ok(()),
+            Ok(false) => std::process::exit(1),
+            Err(err) => {
+                eprintln!("sync failed: {:#}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    #[cfg(feature = "binary-database")]
+    if let Some(Command::CompileDatabase { output, dir }) = &args.command {
+        if let Err(err) = run_compile_database(output, dir.as_deref()) {
+            eprintln!("compiling failed: {:#}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    let style = output::style(args.layout, args.case, args.flatten);
+    let table = Table::new(
+        args.format,
+        args.columns
+            .clone()
+            .unwrap_or_else(|| DEFAULT_COLUMNS.to_vec()),
+    );
+    if let Some(table) = table.as_ref().filter(|_| {
+        !args.no_header && !args.gen_test_case && (args.interactive || args.useragent.is_some())
+    }) {
+        println!("{}", table.header());
+    }
+    #[cfg(not(feature = "cache"))]
+    let detector = DeviceDetector::new();
+
+    #[cfg(feature = "cache")]
+    let detector = if let Some(entries) = args.cache {
+        eprintln!("Cache enabled ({} entries)", entries);
+        DeviceDetector::new_with_cache(entries)
+    } else {
+        DeviceDetector::new()
+    };
+    let detector = detector
+        .with_user_agent_decoding(args.decode)
+        .with_desktop_class_ipads(args.desktop_class_ipads);
+    let detector = match args.unknowns_window {
+        Some(parses) => detector.with_unknowns_window(parses),
+        None => detector,
+    };
+
+    if let Some(Command::Replay {
+        corpus,
+        baseline,
+        write_baseline,
+    }) = args.command
+    {
+        match run_replay(&detector, corpus, baseline, write_baseline) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(err) => {
+                eprintln!("replay failed: {}", err);
+                // tells git bisect this revision can't be tested
+                std::process::exit(125);
+            }
+        }
+    } else if let Some(Command::Diff {
+        first,
+        second,
+        json,
+    }) = args.command
+    {
+        if let Err(err) = run_diff(&detector, &first, &second, json) {
+            eprintln!("diff failed: {}", err);
+            std::process::exit(1);
+        }
+    } else if let Some(Command::Waf {
+        format,
+        categories,
+        libraries,
+        min_len,
+        action,
+    }) = args.command
+    {
+        run_waf(format, categories, libraries, min_len, action);
+    } else if let Some(Command::Typescript) = args.command {
+        print!("{}", typescript::definitions());
+    } else if let Some(Command::JsonSchema) = args.command {
+        println!("{:#}", json_schema::json_schema());
+    } else if let Some(Command::Openapi) = args.command {
+        println!("{:#}", json_schema::openapi());
+    } else if let Some(Command::DatabaseInfo) = args.command {
+        println!("{:#}", serde_json::json!(detector.database_info()));
+    } else if args.interactive {
+        eprintln!("Starting interactive mode");
+        if let Err(err) = stdin::run(&detector, args.gen_test_case, table.as_ref(), &style) {
+            eprintln!("reading stdin failed: {}", err);
+            std::process::exit(1);
+        }
+    } else if args.server {
+        eprintln!("Starting server mode");
+        let ip: IpAddr = args.ip.parse().expect("valid ip address (ipv4 or ipv6)");
+        let sock = SocketAddr::new(ip, args.port);
+
+        server(sock, detector).await;
+    } else {
+        match args.useragent {
+            None => {
+                eprintln!("No user agent specified");
+                return Ok(());
+            }
+
+            Some(ua) => {
+                // eprintln!("ua: {}", ua);
+                // eprintln!("headers: {:?}", args.headers);
+                let headers: Option<Vec<(String, String)>> = match args.headers {
+                    Some(headers) => {
+                        headers
+                            .into_iter()
+                            .map(|x| {
+                                let mut split = x.split(":");
+                                // TODO FIXME handle all of these panics more cleanly.
+                                let name = split
+                                    .next()
+                                    .unwrap_or_else(|| panic!("Unable to parse header {}", x));
+                                let val = split
+                                    .next()
+                                    .unwrap_or_else(|| panic!("Unable to parse header {}", x));
+                                Some((name.to_owned(), val.trim_start().to_owned()))
+                            })
+                            .collect()
+                    }
+
+                    None => None,
+                };
+
+                let detection = detector
+                    .parse(&ua, headers)
+                    .unwrap_or_else(|err| panic!("parse failed {} for '{}'", err, &ua));
+
+                if args.gen_test_case {
+                    println!("{}", detection.to_test_case(&ua));
+                } else if let Some(table) = table {
+                    println!("{}", table.row(&detection, &ua));
+                } else {
+                    println!("{}", detection.to_styled_value(&ua, &style));
+                }
+            }
+        }
+    }
+
+    // let ch = reg.change();
+    // println!("allocations over entire run: {:#?} remaining {}", ch, ch.bytes_allocated - ch.bytes_deallocated);
+    Ok(())
+}
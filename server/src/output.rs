@@ -0,0 +1,164 @@
+use clap::ValueEnum;
+
+use rust_device_detector::device_detector::Detection;
+use rust_device_detector::output_style::{self, FieldCase, OutputStyle};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Json,
+    Csv,
+    Tsv,
+}
+
+/// The fields of json output, see [`output_style::Layout`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Layout {
+    Detection,
+    Matomo,
+}
+
+/// The case of field names of json output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Case {
+    Snake,
+    Camel,
+}
+
+pub fn style(layout: Layout, case: Case, flatten: bool) -> OutputStyle {
+    OutputStyle {
+        layout: match layout {
+            Layout::Detection => output_style::Layout::Detection,
+            Layout::Matomo => output_style::Layout::Matomo,
+        },
+        case: match case {
+            Case::Snake => FieldCase::Snake,
+            Case::Camel => FieldCase::Camel,
+        },
+        flatten,
+    }
+}
+
+/// A column of csv and tsv output. Bots only have `is_bot` and `bot`, and
+/// the other columns are empty for them, as are unknown fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "snake_case")]
+pub enum Column {
+    UserAgent,
+    /// The name of the client, which may be an app or library rather than a
+    /// browser.
+    Browser,
+    Version,
+    Engine,
+    Os,
+    OsVersion,
+    DeviceType,
+    Brand,
+    Model,
+    IsBot,
+    /// The name of the bot.
+    Bot,
+}
+
+pub const DEFAULT_COLUMNS: [Column; 8] = [
+    Column::Browser,
+    Column::Version,
+    Column::Engine,
+    Column::Os,
+    Column::DeviceType,
+    Column::Brand,
+    Column::Model,
+    Column::IsBot,
+];
+
+impl Column {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::UserAgent => "user_agent",
+            Self::Browser => "browser",
+            Self::Version => "version",
+            Self::Engine => "engine",
+            Self::Os => "os",
+            Self::OsVersion => "os_version",
+            Self::DeviceType => "device_type",
+            Self::Brand => "brand",
+            Self::Model => "model",
+            Self::IsBot => "is_bot",
+            Self::Bot => "bot",
+        }
+    }
+
+    fn value<'a>(&self, detection: &'a Detection, ua: &'a str) -> &'a str {
+        let known = detection.get_known_device();
+        let client = known.and_then(|x| x.client.as_ref());
+        let os = known.and_then(|x| x.os.as_ref());
+        let device = known.and_then(|x| x.device.as_ref());
+
+        let value = match self {
+            Self::UserAgent => Some(ua),
+            Self::Browser => client.map(|x| x.name.as_str()),
+            Self::Version => client.and_then(|x| x.version.as_deref()),
+            Self::Engine => client.and_then(|x| x.engine.as_deref()),
+            Self::Os => os.map(|x| x.name.as_str()),
+            Self::OsVersion => os.and_then(|x| x.version.as_deref()),
+            Self::DeviceType => device
+                .and_then(|x| x.device_type.as_ref())
+                .map(|x| x.as_str()),
+            Self::Brand => device.and_then(|x| x.brand.as_deref()),
+            Self::Model => device.and_then(|x| x.model.as_deref()),
+            Self::IsBot => Some(if detection.is_bot() { "true" } else { "false" }),
+            Self::Bot => detection.get_bot().map(|x| x.name.as_str()),
+        };
+        value.unwrap_or("")
+    }
+}
+
+/// Writes rows of the columns of detections, as csv or tsv.
+#[derive(Clone, Debug)]
+pub struct Table {
+    separator: char,
+    columns: Vec<Column>,
+}
+
+impl Table {
+    /// `None` for json output.
+    pub fn new(format: Format, columns: Vec<Column>) -> Option<Self> {
+        let separator = match format {
+            Format::Json => return None,
+            Format::Csv => ',',
+            Format::Tsv => '\t',
+        };
+        Some(Self { separator, columns })
+    }
+
+    /// Quotes fields as RFC 4180 csv does, while tsv can't quote and has tabs
+    /// and line breaks replaced with spaces.
+    fn field(&self, value: &str) -> String {
+        if self.separator == '\t' {
+            return value.replace(['\t', '\r', '\n'], " ");
+        }
+        if value.contains([',', '"', '\r', '\n']) {
+            return format!("\"{}\"", value.replace('"', "\"\""));
+        }
+        value.to_owned()
+    }
+
+    fn join<'a>(&self, values: impl Iterator<Item = &'a str>) -> String {
+        values
+            .map(|x| self.field(x))
+            .collect::<Vec<_>>()
+            .join(&self.separator.to_string())
+    }
+
+    pub fn header(&self) -> String {
+        self.join(self.columns.iter().map(Column::name))
+    }
+
+    pub fn row(&self, detection: &Detection, ua: &str) -> String {
+        self.join(self.columns.iter().map(|x| x.value(detection, ua)))
+    }
+
+    /// A row of empty fields, for input which couldn't be detected.
+    pub fn empty_row(&self) -> String {
+        self.join(self.columns.iter().map(|_| ""))
+    }
+}
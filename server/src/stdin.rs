@@ -0,0 +1,87 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+use std::io::{BufRead, Write};
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::output_style::OutputStyle;
+
+use crate::output::Table;
+
+type Headers = Vec<(String, String)>;
+
+/// A line of input, either a bare user agent or a json object such as
+/// `{"user_agent": "...", "headers": {"sec-ch-ua-mobile": "?1"}}`.
+fn parse_line(line: &str) -> Result<(String, Option<Headers>)> {
+    if !line.starts_with('{') {
+        return Ok((line.to_owned(), None));
+    }
+
+    let value: Value = serde_json::from_str(line)?;
+    let ua = value
+        .get("user_agent")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("missing user_agent"))?
+        .to_owned();
+
+    let headers = match value.get("headers") {
+        None | Some(Value::Null) => None,
+        Some(Value::Object(headers)) => Some(
+            headers
+                .iter()
+                .map(|(name, value)| {
+                    let value = value
+                        .as_str()
+                        .ok_or_else(|| anyhow!("header {} is not a string", name))?;
+                    Ok((name.clone(), value.to_owned()))
+                })
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        Some(_) => return Err(anyhow!("headers is not an object")),
+    };
+
+    Ok((ua, headers))
+}
+
+/// Detects each line of stdin, writing one line of json per line to stdout.
+///
+/// A line which can't be read or detected gets `{"error": "..."}` rather than
+/// ending the run, so that output lines always match input lines. With a
+/// table, it gets a row of empty fields and the error goes to stderr.
+pub fn run(
+    detector: &DeviceDetector,
+    gen_test_case: bool,
+    table: Option<&Table>,
+    style: &OutputStyle,
+) -> Result<()> {
+    let stdin = std::io::stdin().lock();
+    // line buffered, as callers may wait for each result before the next line
+    let mut stdout = std::io::stdout().lock();
+
+    for line in stdin.lines() {
+        let line = line?;
+        let line = line.trim_end();
+
+        let res = parse_line(line).and_then(|(ua, headers)| {
+            let detection = detector.parse(&ua, headers)?;
+            Ok(if gen_test_case {
+                detection.to_test_case(&ua)
+            } else if let Some(table) = table {
+                table.row(&detection, &ua)
+            } else {
+                detection.to_styled_value(&ua, style).to_string()
+            })
+        });
+
+        match (res, table) {
+            (Ok(res), _) => writeln!(stdout, "{}", res)?,
+            (Err(err), Some(table)) => {
+                eprintln!("{}: {:#}", line, err);
+                writeln!(stdout, "{}", table.empty_row())?;
+            }
+            (Err(err), None) => writeln!(stdout, "{}", json!({ "error": format!("{:#}", err) }))?,
+        }
+    }
+
+    Ok(())
+}
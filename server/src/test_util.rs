@@ -0,0 +1,97 @@
+//! A running instance of the [`crate::http_server`], for services calling it
+//! to write contract tests against in their own CI:
+//!
+//! ```ignore
+//! let server = device_detector_server::test_util::test_server()?;
+//!
+//! let res = reqwest::blocking::Client::new()
+//!     .get(server.url("/detect"))
+//!     .header("User-Agent", "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)")
+//!     .send()?;
+//! assert_eq!(res.json::<serde_json::Value>()?["bot"]["name"], "Googlebot");
+//! ```
+//!
+//! The server listens on a free port of `127.0.0.1`, on a thread of its own,
+//! so tests can use it from sync and async code alike, and stops when the
+//! [`TestServer`] is dropped. [`test_server`] detects with the miniature
+//! database of `regexes-mini/`, which starts far faster than the full one and
+//! detects the common browsers, oses, devices and bots, but not the long
+//! tail. Tests which need more pass a detector of their own to
+//! [`test_server_with`].
+
+use anyhow::Result;
+use tokio::sync::oneshot;
+
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::DeviceDetector;
+
+use crate::http_server;
+
+/// An http server running until dropped, see [`crate::test_util`].
+pub struct TestServer {
+    addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<Result<()>>>,
+}
+
+impl TestServer {
+    /// The address the server listens on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The url of `path` on the server, such as `http://127.0.0.1:34567/detect`.
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A server detecting with the miniature database.
+pub fn test_server() -> Result<TestServer> {
+    test_server_with(DeviceDetector::new_with_database(Arc::new(
+        Database::mini()?
+    )))
+}
+
+/// A server detecting with `detector`.
+pub fn test_server_with(detector: DeviceDetector) -> Result<TestServer> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let (shutdown, stop) = oneshot::channel::<()>();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let thread = std::thread::Builder::new()
+        .name("rdd-test-server".to_owned())
+        .spawn(move || {
+            runtime.block_on(http_server::serve_listener(
+                listener,
+                Arc::new(detector),
+                async {
+                    let _ = stop.await;
+                },
+            ))
+        })?;
+
+    Ok(TestServer {
+        addr,
+        shutdown: Some(shutdown),
+        thread: Some(thread),
+    })
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde_json::Value;
+
+use device_detector_server::http_server::{self, MAX_BODY_BYTES};
+use rust_device_detector::device_detector::DeviceDetector;
+
+const PIXEL: &str = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+
+fn respond(req: Request<Body>) -> Result<(StatusCode, String)> {
+    let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+    let detector = DeviceDetector::new();
+
+    runtime.block_on(async {
+        let res: Response<Body> = http_server::handle(req, &detector).await?;
+        let status = res.status();
+        let body = hyper::body::to_bytes(res.into_body()).await?;
+        Ok((status, String::from_utf8(body.to_vec())?))
+    })
+}
+
+#[test]
+fn test_get_detect() -> Result<()> {
+    let req = Request::builder()
+        .method(Method::GET)
+        .uri("/detect")
+        .header("User-Agent", PIXEL)
+        .header("Sec-CH-UA-Mobile", "?1")
+        .header("Sec-CH-UA-Model", r#""Pixel 7""#)
+        .header("Accept", "text/html")
+        .body(Body::empty())?;
+
+    let (status, body) = respond(req)?;
+    assert_eq!(status, StatusCode::OK);
+
+    let json: Value = serde_json::from_str(&body)?;
+    let expected = DeviceDetector::new()
+        .parse(
+            PIXEL,
+            Some(vec![
+                ("sec-ch-ua-mobile".to_owned(), "?1".to_owned()),
+                ("sec-ch-ua-model".to_owned(), r#""Pixel 7""#.to_owned()),
+            ]),
+        )?
+        .to_value();
+    assert_eq!(json, expected);
+    assert_eq!(json["device"]["model"], "Pixel 7");
+
+    Ok(())
+}
+
+#[test]
+fn test_post_detect() -> Result<()> {
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri("/detect")
+        .body(Body::from("Googlebot-News"))?;
+
+    let (status, body) = respond(req)?;
+    assert_eq!(status, StatusCode::OK);
+
+    let json: Value = serde_json::from_str(&body)?;
+    assert_eq!(json["bot"]["name"], "Googlebot News");
+
+    Ok(())
+}
+
+#[test]
+fn test_post_detect_too_large() -> Result<()> {
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri("/detect")
+        .body(Body::from("a".repeat(MAX_BODY_BYTES + 1)))?;
+
+    let (status, _) = respond(req)?;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    Ok(())
+}
+
+#[test]
+fn test_routes() -> Result<()> {
+    let req = Request::builder().uri("/health").body(Body::empty())?;
+    assert_eq!(respond(req)?, (StatusCode::OK, "OK\n".to_owned()));
+
+    let req = Request::builder().uri("/nowhere").body(Body::empty())?;
+    let (status, body) = respond(req)?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.contains("GET  /detect"), "{}", body);
+
+    Ok(())
+}
+
+#[test]
+fn test_metrics_route() -> Result<()> {
+    let req = Request::builder().uri("/metrics").body(Body::empty())?;
+    let (status, body) = respond(req)?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        body.contains("# TYPE device_detector_detections_total counter"),
+        "{}",
+        body
+    );
+
+    Ok(())
+}
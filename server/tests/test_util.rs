@@ -0,0 +1,74 @@
+#![cfg(feature = "test-util")]
+
+use anyhow::Result;
+use serde_json::Value;
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+use device_detector_server::test_util::{test_server, test_server_with};
+use rust_device_detector::device_detector::DeviceDetector;
+
+const PIXEL: &str = "Mozilla/5.0 (Linux; Android 13; Pixel 7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+
+/// The status line and body of a `GET` of `path` with `headers`.
+fn get(
+    addr: std::net::SocketAddr,
+    path: &str,
+    headers: &[(&str, &str)],
+) -> Result<(String, String)> {
+    let mut stream = TcpStream::connect(addr)?;
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n",
+        path, addr
+    )?;
+    for (name, value) in headers {
+        write!(stream, "{}: {}\r\n", name, value)?;
+    }
+    write!(stream, "\r\n")?;
+
+    let mut res = String::new();
+    stream.read_to_string(&mut res)?;
+    let (head, body) = res.split_once("\r\n\r\n").expect("http response");
+    Ok((head.lines().next().unwrap().to_owned(), body.to_owned()))
+}
+
+#[test]
+fn test_test_server() -> Result<()> {
+    let server = test_server()?;
+    assert_eq!(
+        server.url("/detect"),
+        format!("http://{}/detect", server.addr())
+    );
+
+    let (status, body) = get(server.addr(), "/health", &[])?;
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert_eq!(body, "OK\n");
+
+    let (status, body) = get(server.addr(), "/detect", &[("User-Agent", PIXEL)])?;
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    let json: Value = serde_json::from_str(&body)?;
+    assert_eq!(json["client"]["name"], "Chrome Mobile");
+    assert_eq!(json["os"]["name"], "Android");
+
+    let addr = server.addr();
+    drop(server);
+    assert!(TcpStream::connect(addr).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_test_server_with() -> Result<()> {
+    let first = test_server_with(DeviceDetector::new())?;
+    let second = test_server()?;
+    assert_ne!(first.addr(), second.addr());
+
+    let googlebot = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+    let (_, body) = get(first.addr(), "/detect", &[("User-Agent", googlebot)])?;
+    let json: Value = serde_json::from_str(&body)?;
+    assert_eq!(json["bot"]["name"], "Googlebot");
+
+    Ok(())
+}
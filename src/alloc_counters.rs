@@ -0,0 +1,124 @@
+//! Allocation counting, for tracking allocation regressions across versions.
+//!
+//! Counts are only collected when [`CountingAllocator`] is installed as the
+//! global allocator of the binary, and are kept per thread so that parses on
+//! other threads don't affect the numbers.
+//!
+//! ```ignore
+//! use rust_device_detector::alloc_counters::CountingAllocator;
+//! use std::alloc::System;
+//!
+//! #[global_allocator]
+//! static GLOBAL: CountingAllocator<System> = CountingAllocator::new(System);
+//!
+//! let detector = DeviceDetector::new();
+//! let (_detection, stats) = detector.parse_with_stats(ua, None)?;
+//! println!("{} allocations", stats.allocations);
+//! ```
+//!
+//! Any allocator can be wrapped, so if you use mimalloc or jemalloc in
+//! production, wrap that one instead of [`System`](std::alloc::System) to
+//! measure what you actually run.
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::cell::Cell;
+
+thread_local! {
+    static STATS: Cell<AllocationStats> = const { Cell::new(AllocationStats::new()) };
+}
+
+/// Allocation counts for the current thread.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocationStats {
+    pub allocations: u64,
+    pub deallocations: u64,
+    pub reallocations: u64,
+    pub bytes_allocated: u64,
+}
+
+impl AllocationStats {
+    const fn new() -> Self {
+        Self {
+            allocations: 0,
+            deallocations: 0,
+            reallocations: 0,
+            bytes_allocated: 0,
+        }
+    }
+
+    /// The difference between two snapshots taken with [`thread_stats`].
+    pub fn since(&self, earlier: &AllocationStats) -> AllocationStats {
+        AllocationStats {
+            allocations: self.allocations - earlier.allocations,
+            deallocations: self.deallocations - earlier.deallocations,
+            reallocations: self.reallocations - earlier.reallocations,
+            bytes_allocated: self.bytes_allocated - earlier.bytes_allocated,
+        }
+    }
+}
+
+/// Wraps another allocator, counting allocations made through it.
+pub struct CountingAllocator<A> {
+    inner: A,
+}
+
+impl<A> CountingAllocator<A> {
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+fn record(f: impl FnOnce(&mut AllocationStats)) {
+    // the thread local may already be gone while a thread is shutting down,
+    // allocations at that point just aren't counted.
+    let _ = STATS.try_with(|stats| {
+        let mut x = stats.get();
+        f(&mut x);
+        stats.set(x);
+    });
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(|x| {
+            x.allocations += 1;
+            x.bytes_allocated += layout.size() as u64;
+        });
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(|x| {
+            x.allocations += 1;
+            x.bytes_allocated += layout.size() as u64;
+        });
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record(|x| x.deallocations += 1);
+        self.inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(|x| {
+            x.reallocations += 1;
+            x.bytes_allocated += new_size.saturating_sub(layout.size()) as u64;
+        });
+        self.inner.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Totals for the current thread since it started. These stay at zero unless
+/// [`CountingAllocator`] is the global allocator.
+pub fn thread_stats() -> AllocationStats {
+    STATS.with(|x| x.get())
+}
+
+/// Runs `f`, returning what it allocated on the current thread.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, AllocationStats) {
+    let before = thread_stats();
+    let res = f();
+    let stats = thread_stats().since(&before);
+    (res, stats)
+}
@@ -0,0 +1,126 @@
+//! Approximate detection, for deployments such as CDN edges where tail
+//! latency matters more than perfect accuracy.
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().with_approximation(20_000, 64);
+//! ```
+//!
+//! Every detection is remembered by a hash of the first `prefix_len` bytes of
+//! its user agent and its client hints. Past `qps` requests per second, a
+//! request whose prefix and hints were already seen is answered with that
+//! earlier detection rather than parsed. Requests with novel prefixes are
+//! always parsed.
+//!
+//! Detections are kept in a fixed number of slots, a prefix replacing whichever
+//! one was in its slot before, so memory use is bounded however varied the
+//! traffic is. Approximate results are flagged with
+//! [`crate::device_detector::Detection::approximate`], are never stored in the
+//! detection caches, which are keyed by the whole user agent, and are not
+//! reported to [`crate::device_detector::DeviceDetector::on_unknown`].
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::client_hints::ClientHint;
+use crate::device_detector::Detection;
+use crate::rate::RateWindow;
+
+/// How many detections are remembered.
+const SLOTS: usize = 1 << 16;
+
+struct Slot {
+    prefix: String,
+    client_hints: Option<ClientHint>,
+    detection: Detection,
+}
+
+pub(crate) struct Approximation {
+    qps: u32,
+    prefix_len: usize,
+    rate: RateWindow,
+    slots: Box<[RwLock<Option<Box<Slot>>>]>,
+    served: AtomicU64,
+}
+
+impl Approximation {
+    pub(crate) fn new(qps: u32, prefix_len: usize) -> Arc<Self> {
+        Arc::new(Self {
+            qps,
+            prefix_len,
+            rate: RateWindow::new(Duration::from_secs(1)),
+            slots: (0..SLOTS).map(|_| RwLock::new(None)).collect(),
+            served: AtomicU64::new(0),
+        })
+    }
+
+    fn prefix<'a>(&self, ua: &'a str) -> &'a str {
+        let mut end = self.prefix_len.min(ua.len());
+        while !ua.is_char_boundary(end) {
+            end -= 1;
+        }
+        &ua[..end]
+    }
+
+    fn slot(&self, prefix: &str, client_hints: Option<&ClientHint>) -> &RwLock<Option<Box<Slot>>> {
+        let mut hasher = DefaultHasher::new();
+        prefix.hash(&mut hasher);
+        client_hints.hash(&mut hasher);
+        &self.slots[(hasher.finish() % self.slots.len() as u64) as usize]
+    }
+
+    /// The detection remembered for the prefix of `ua` and `client_hints`,
+    /// flagged as approximate, if past the rate.
+    pub(crate) fn get(&self, ua: &str, client_hints: Option<&ClientHint>) -> Option<Detection> {
+        if self.rate.hit() <= self.qps {
+            return None;
+        }
+
+        let prefix = self.prefix(ua);
+        let slot = self
+            .slot(prefix, client_hints)
+            .read()
+            .expect("approximation lock");
+        // slots are shared by whatever hashes to them
+        let res = slot
+            .as_ref()
+            .filter(|x| x.prefix == prefix && x.client_hints.as_ref() == client_hints)
+            .map(|x| x.detection.clone().with_approximate());
+
+        if res.is_some() {
+            self.served.fetch_add(1, Ordering::Relaxed);
+        }
+        res
+    }
+
+    pub(crate) fn insert(
+        &self,
+        ua: &str,
+        client_hints: Option<&ClientHint>,
+        detection: &Detection,
+    ) {
+        let prefix = self.prefix(ua);
+        *self
+            .slot(prefix, client_hints)
+            .write()
+            .expect("approximation lock") = Some(Box::new(Slot {
+            prefix: prefix.to_owned(),
+            client_hints: client_hints.cloned(),
+            detection: detection.clone(),
+        }));
+    }
+
+    /// Forgets the remembered detections.
+    pub(crate) fn clear(&self) {
+        for slot in self.slots.iter() {
+            *slot.write().expect("approximation lock") = None;
+        }
+    }
+
+    /// Number of requests answered approximately so far.
+    pub(crate) fn served(&self) -> u64 {
+        self.served.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,33 @@
+//! Detecting from async services without blocking their executor.
+//!
+//! A detection is cheap for most user agents, but worst case ones go through
+//! long chains of regexes, and compiling those the first time they are used
+//! takes longer still, which stalls every other task on the executor thread.
+//! [`DeviceDetector::parse_async`] runs the detection on tokio's blocking
+//! pool instead:
+//!
+//! ```ignore
+//! let detection = detector.parse_async(&ua, Some(headers)).await?;
+//! ```
+//!
+//! The detector is cloned into the blocking task, which only clones handles
+//! to its database and caches, so detections share those with the detector
+//! as they would on the calling thread.
+
+use anyhow::{anyhow, Result};
+
+use crate::device_detector::{Detection, DeviceDetector};
+
+pub(crate) async fn parse_async(
+    detector: &DeviceDetector,
+    ua: &str,
+    headers: Option<Vec<(String, String)>>,
+) -> Result<Detection> {
+    let detector = detector.clone();
+    let ua = ua.to_owned();
+    match tokio::task::spawn_blocking(move || detector.parse(&ua, headers)).await {
+        Ok(res) => res,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(err) => Err(anyhow!("detection cancelled: {}", err)),
+    }
+}
@@ -0,0 +1,141 @@
+//! Parsing many user agents at once, such as enriching a log file, in a way
+//! which can be stopped part way, such as when the service running the job
+//! shuts down.
+//!
+//! ```ignore
+//! let cancel = CancellationToken::new();
+//! shutdown_hooks.push({
+//!     let cancel = cancel.clone();
+//!     move || cancel.cancel()
+//! });
+//!
+//! let batch = detector.parse_batch_with_cancellation(&uas, &cancel);
+//! if batch.cancelled {
+//!     log::info!("stopped after {} of {}", batch.detections.len(), uas.len());
+//! }
+//! ```
+//!
+//! The token is checked before each user agent, so a batch stops within one
+//! parse of being cancelled, returning the detections of the user agents
+//! before it in input order. [`DeviceDetector::detect_stream`] does the same
+//! lazily, for inputs which don't fit in memory.
+//!
+//! Batches without a token, [`DeviceDetector::parse_batch`], reuse one
+//! [`ParseContext`] for the whole batch rather than allocating one per user
+//! agent. With the `parallel` feature, [`DeviceDetector::par_parse_batch`]
+//! spreads a batch over rayon's thread pool, with a context per thread,
+//! still returning detections in input order.
+
+use anyhow::Result;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::device_detector::{Detection, DeviceDetector, ParseContext};
+
+/// Cancels the batches and streams it is passed to, from any thread.
+/// Clones cancel the same batches.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Cancelled when the flag is set, for services which already have a
+/// shutdown flag of their own.
+impl From<Arc<AtomicBool>> for CancellationToken {
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        Self(flag)
+    }
+}
+
+#[derive(Debug)]
+pub struct Batch {
+    /// The detections of the user agents parsed, in input order. Shorter than
+    /// the input when the batch was cancelled.
+    pub detections: Vec<Result<Detection>>,
+    pub cancelled: bool,
+}
+
+pub(crate) fn parse_batch_with_cancellation(
+    detector: &DeviceDetector,
+    uas: &[&str],
+    cancel: &CancellationToken,
+) -> Batch {
+    let mut stream = detector.detect_stream(uas, cancel.clone());
+    let detections = stream.by_ref().collect();
+    Batch {
+        detections,
+        cancelled: stream.cancelled(),
+    }
+}
+
+#[cfg(feature = "parallel")]
+pub(crate) fn par_parse_batch(detector: &DeviceDetector, uas: &[&str]) -> Vec<Result<Detection>> {
+    use rayon::prelude::*;
+
+    uas.par_iter()
+        .map_init(ParseContext::new, |ctx, ua| {
+            detector.parse_with_context(ua, None, ctx)
+        })
+        .collect()
+}
+
+/// The detections of a sequence of user agents, parsed as they are iterated
+/// over. See [`DeviceDetector::detect_stream`].
+pub struct DetectStream<'a, I> {
+    detector: &'a DeviceDetector,
+    uas: I,
+    cancel: CancellationToken,
+    ctx: ParseContext,
+    cancelled: bool,
+}
+
+impl<'a, I> DetectStream<'a, I> {
+    pub(crate) fn new(detector: &'a DeviceDetector, uas: I, cancel: CancellationToken) -> Self {
+        Self {
+            detector,
+            uas,
+            cancel,
+            ctx: ParseContext::new(),
+            cancelled: false,
+        }
+    }
+
+    /// Whether the stream ended because it was cancelled, rather than because
+    /// the user agents ran out.
+    pub fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+impl<I> Iterator for DetectStream<'_, I>
+where
+    I: Iterator,
+    I::Item: AsRef<str>,
+{
+    type Item = Result<Detection>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cancelled || self.cancel.is_cancelled() {
+            self.cancelled = true;
+            return None;
+        }
+        let ua = self.uas.next()?;
+        Some(
+            self.detector
+                .parse_with_context(ua.as_ref(), None, &mut self.ctx),
+        )
+    }
+}
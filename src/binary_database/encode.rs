@@ -0,0 +1,53 @@
+// compiling yaml database files to the binary format. build.rs compiles the
+// embedded database with this same file, so it only uses what both have.
+
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+
+pub(crate) const MAGIC: &[u8; 4] = b"RDDB";
+
+/// Bumped whenever the layout changes, so that a file compiled by another
+/// version is rejected rather than misread.
+pub(crate) const FORMAT_VERSION: u32 = 1;
+
+/// Every field of the lists is a string, which serde_yaml reads from any
+/// scalar, so numbers and booleans are kept as the strings they'd be read as.
+fn strings(value: Value) -> Value {
+    match value {
+        Value::Bool(x) => Value::String(x.to_string()),
+        Value::Number(x) => Value::String(x.to_string()),
+        Value::Sequence(x) => Value::Sequence(x.into_iter().map(strings).collect()),
+        Value::Mapping(x) => Value::Mapping(
+            x.into_iter()
+                .map(|(k, v)| (strings(k), strings(v)))
+                .collect(),
+        ),
+        Value::Tagged(x) => strings(x.value),
+        x => x,
+    }
+}
+
+/// The messagepack of a yaml file.
+fn encode_file(yaml: &str) -> Result<Vec<u8>> {
+    let value: Value = serde_yaml::from_str(yaml)?;
+    Ok(rmp_serde::to_vec(&strings(value))?)
+}
+
+/// `files`, by their path, in the binary format: [`MAGIC`] and
+/// [`FORMAT_VERSION`], then the length and bytes of the path and of the
+/// messagepack of each file, lengths being little endian u32s.
+pub(crate) fn encode<'a>(files: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<Vec<u8>> {
+    let mut res = MAGIC.to_vec();
+    res.extend(FORMAT_VERSION.to_le_bytes());
+
+    for (path, yaml) in files {
+        let file = encode_file(yaml).with_context(|| format!("compiling {}", path))?;
+        for part in [path.as_bytes(), &file] {
+            let len = u32::try_from(part.len()).context("file too large")?;
+            res.extend(len.to_le_bytes());
+            res.extend(part);
+        }
+    }
+
+    Ok(res)
+}
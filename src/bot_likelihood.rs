@@ -0,0 +1,174 @@
+//! A score of how likely a request is to come from a bot, for requests which
+//! don't identify themselves as one.
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().with_header_fingerprint(HeaderFingerprint::new());
+//! let (detection, likelihood) = detector.parse_with_bot_likelihood(ua, &headers)?;
+//! if likelihood.score >= 0.8 { ... }
+//! ```
+//!
+//! The user agent alone scores bots as 1, http libraries highly and user
+//! agents without a known client somewhere in between. Scripts often send a
+//! browser's user agent though, but not the rest of the headers a browser
+//! would. With a [`HeaderFingerprint`] configured, which is off by default,
+//! the full header map is also checked for such anomalies, each adding its
+//! weight to the score. Header names are matched case insensitively, with or
+//! without the `HTTP_` prefix of cgi variables.
+
+use serde::{Deserialize, Serialize};
+
+use crate::device_detector::Detection;
+use crate::parsers::client::ClientType;
+
+/// Something about the headers of a request which browsers don't do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum HeaderAnomaly {
+    MissingAccept,
+    MissingAcceptEncoding,
+    MissingAcceptLanguage,
+    /// `Accept-Encoding` sent before `Accept`, which browsers never do but
+    /// libraries such as python's requests do.
+    AcceptEncodingBeforeAccept,
+}
+
+impl HeaderAnomaly {
+    pub const ALL: [HeaderAnomaly; 4] = [
+        HeaderAnomaly::MissingAccept,
+        HeaderAnomaly::MissingAcceptEncoding,
+        HeaderAnomaly::MissingAcceptLanguage,
+        HeaderAnomaly::AcceptEncodingBeforeAccept,
+    ];
+
+    fn default_weight(self) -> f32 {
+        match self {
+            HeaderAnomaly::MissingAccept => 0.3,
+            HeaderAnomaly::MissingAcceptEncoding => 0.3,
+            HeaderAnomaly::MissingAcceptLanguage => 0.2,
+            HeaderAnomaly::AcceptEncodingBeforeAccept => 0.3,
+        }
+    }
+}
+
+/// Which header anomalies are checked for, and how much each adds to the
+/// score.
+#[derive(Clone, Debug)]
+pub struct HeaderFingerprint {
+    weights: Vec<(HeaderAnomaly, f32)>,
+}
+
+impl Default for HeaderFingerprint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeaderFingerprint {
+    /// Checks for every anomaly, with default weights.
+    pub fn new() -> Self {
+        Self {
+            weights: HeaderAnomaly::ALL
+                .iter()
+                .map(|x| (*x, x.default_weight()))
+                .collect(),
+        }
+    }
+
+    /// Checks for `anomaly` with `weight`. A weight of 0 disables the check.
+    pub fn with_weight(mut self, anomaly: HeaderAnomaly, weight: f32) -> Self {
+        self.weights.retain(|(x, _)| *x != anomaly);
+        if weight > 0.0 {
+            self.weights.push((anomaly, weight));
+        }
+        self
+    }
+
+    pub fn weight(&self, anomaly: HeaderAnomaly) -> f32 {
+        self.weights
+            .iter()
+            .find(|(x, _)| *x == anomaly)
+            .map(|(_, weight)| *weight)
+            .unwrap_or(0.0)
+    }
+
+    /// The anomalies enabled checks find in `headers`, in the order of
+    /// [`HeaderAnomaly::ALL`].
+    pub fn check(&self, headers: &[(String, String)]) -> Vec<HeaderAnomaly> {
+        let names: Vec<String> = headers
+            .iter()
+            .map(|(name, _)| {
+                let name = name.trim().replace('_', "-").to_lowercase();
+                match name.strip_prefix("http-") {
+                    Some(name) => name.to_owned(),
+                    None => name,
+                }
+            })
+            .collect();
+        let position = |name: &str| names.iter().position(|x| x == name);
+
+        let accept = position("accept");
+        let accept_encoding = position("accept-encoding");
+
+        HeaderAnomaly::ALL
+            .iter()
+            .copied()
+            .filter(|anomaly| self.weight(*anomaly) > 0.0)
+            .filter(|anomaly| match anomaly {
+                HeaderAnomaly::MissingAccept => accept.is_none(),
+                HeaderAnomaly::MissingAcceptEncoding => accept_encoding.is_none(),
+                HeaderAnomaly::MissingAcceptLanguage => position("accept-language").is_none(),
+                HeaderAnomaly::AcceptEncodingBeforeAccept => {
+                    matches!((accept, accept_encoding), (Some(a), Some(e)) if e < a)
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BotLikelihood {
+    /// From 0, nothing suspicious, to 1, certainly a bot.
+    pub score: f32,
+    /// What the score from the user agent was, before header anomalies.
+    pub user_agent_score: f32,
+    pub anomalies: Vec<HeaderAnomaly>,
+}
+
+/// The score of a detection, before header anomalies.
+fn user_agent_score(detection: &Detection) -> f32 {
+    match detection {
+        Detection::Bot(_) => 1.0,
+        Detection::Known(known) => match &known.client {
+            Some(client)
+                if matches!(
+                    client.r#type,
+                    ClientType::Library | ClientType::DownloadManager
+                ) =>
+            {
+                0.8
+            }
+            Some(_) => 0.0,
+            None => 0.4,
+        },
+    }
+}
+
+pub(crate) fn bot_likelihood(
+    detection: &Detection,
+    headers: &[(String, String)],
+    fingerprint: Option<&HeaderFingerprint>,
+) -> BotLikelihood {
+    let user_agent_score = user_agent_score(detection);
+
+    let anomalies = fingerprint.map(|x| x.check(headers)).unwrap_or_default();
+    let score = anomalies
+        .iter()
+        .map(|x| fingerprint.map(|f| f.weight(*x)).unwrap_or(0.0))
+        .fold(user_agent_score, |acc, x| acc + x)
+        .min(1.0);
+
+    BotLikelihood {
+        score,
+        user_agent_score,
+        anomalies,
+    }
+}
@@ -0,0 +1,128 @@
+//! Flagging requests which claim to be a major search engine's crawler, but
+//! send client hints which only browsers send.
+//!
+//! Crawlers such as Googlebot don't send `Sec-CH-UA` brands, so a user agent
+//! claiming to be one alongside a full set of browser brands is most likely a
+//! browser, or a script replaying a browser's headers, hiding behind a user
+//! agent sites let through. Such bots are still detected as the bot they
+//! claim to be, with [`Bot::bot_spoof_suspected`] set and the contradictions
+//! found in [`Bot::spoof_signals`]:
+//!
+//! ```ignore
+//! let detection = detector.parse(ua, Some(headers))?;
+//! if detection.get_bot().is_some_and(|x| x.bot_spoof_suspected) { ... }
+//! ```
+//!
+//! This only looks at what the request says about itself. Telling whether a
+//! request really comes from a search engine takes checking its ip address,
+//! which is left to the caller.
+
+use serde::{Deserialize, Serialize};
+
+use crate::client_hints::ClientHint;
+use crate::parsers::bot::Bot;
+
+/// The bots checked by default, by their name in the database.
+pub const SEARCH_ENGINE_BOTS: [&str; 11] = [
+    "Googlebot",
+    "Googlebot News",
+    "BingBot",
+    "Yandex Bot",
+    "Baidu Spider",
+    "DuckDuckBot",
+    "Applebot",
+    "Yahoo! Slurp",
+    "Sogou Spider",
+    "Yeti/Naverbot",
+    "Petal Bot",
+];
+
+/// A client hint which contradicts a crawler's user agent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SpoofSignal {
+    /// `Sec-CH-UA` or `Sec-CH-UA-Full-Version-List` lists a browser brand,
+    /// rather than only the made up brands browsers mix in.
+    BrowserBrands,
+    /// `Sec-CH-UA-Model` names a device.
+    DeviceModel,
+    /// `Sec-CH-UA-Form-Factors` was sent.
+    FormFactors,
+}
+
+/// Which bots are checked for contradicting client hints.
+#[derive(Clone, Debug)]
+pub struct BotSpoofRules {
+    bots: Vec<String>,
+}
+
+impl Default for BotSpoofRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BotSpoofRules {
+    /// Checks the bots of [`SEARCH_ENGINE_BOTS`].
+    pub fn new() -> Self {
+        Self {
+            bots: SEARCH_ENGINE_BOTS.iter().map(|x| x.to_string()).collect(),
+        }
+    }
+
+    /// Checks no bots, to start from when only some are of interest.
+    pub fn none() -> Self {
+        Self { bots: Vec::new() }
+    }
+
+    /// Also checks the bot named `name` in the database.
+    pub fn with_bot(mut self, name: &str) -> Self {
+        if !self.bots.iter().any(|x| x == name) {
+            self.bots.push(name.to_owned());
+        }
+        self
+    }
+
+    pub fn bots(&self) -> impl Iterator<Item = &str> {
+        self.bots.iter().map(|x| x.as_str())
+    }
+
+    /// The client hints in `hints` contradicting that the request comes from
+    /// `bot`, in the order of [`SpoofSignal`].
+    pub fn check(&self, bot: &Bot, hints: Option<&ClientHint>) -> Vec<SpoofSignal> {
+        let Some(hints) = hints else {
+            return Vec::new();
+        };
+        if !self.bots.contains(&bot.name) {
+            return Vec::new();
+        }
+
+        let mut res = Vec::new();
+        if hints
+            .brand_versions()
+            .iter()
+            .any(|(brand, _)| !is_grease_brand(brand))
+        {
+            res.push(SpoofSignal::BrowserBrands);
+        }
+        if hints.model.as_deref().is_some_and(|x| !x.trim().is_empty()) {
+            res.push(SpoofSignal::DeviceModel);
+        }
+        if !hints.form_factors.is_empty() {
+            res.push(SpoofSignal::FormFactors);
+        }
+        res
+    }
+
+    /// Flags `bot` if `hints` contradict it.
+    pub(crate) fn apply(&self, bot: &mut Bot, hints: Option<&ClientHint>) {
+        bot.spoof_signals = self.check(bot, hints);
+        bot.bot_spoof_suspected = !bot.spoof_signals.is_empty();
+    }
+}
+
+/// Whether `brand` is one of the made up brands, such as `Not A(Brand`,
+/// which browsers add to their brands so that servers don't rely on them.
+fn is_grease_brand(brand: &str) -> bool {
+    let brand = brand.trim().to_lowercase();
+    brand.is_empty() || (brand.contains("not") && brand.contains("brand"))
+}
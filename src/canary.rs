@@ -0,0 +1,105 @@
+//! Canarying a database update on live traffic, by running a detector backed
+//! by the new database alongside the one in use.
+//!
+//! ```ignore
+//! let candidate = DeviceDetector::new().with_database(Arc::new(Database::from_dir("regexes-next")?));
+//! let canary = Canary::new(DeviceDetector::new(), candidate, 0.01);
+//!
+//! // in the request handler
+//! let detection = canary.parse(ua, headers)?;
+//!
+//! // from time to time
+//! log::info!("{}", serde_json::to_string(&canary.divergences())?);
+//! ```
+//!
+//! Every request is answered by the primary detector. A sampled fraction of
+//! them is also detected by the candidate, the fields which differ being
+//! counted the same way [`crate::database::diff_detectors`] does for a corpus.
+//! A candidate which fails to parse is counted, but never fails the request.
+
+use anyhow::Result;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::database::DatabaseDiff;
+use crate::device_detector::{Detection, DeviceDetector};
+
+pub struct Canary {
+    primary: DeviceDetector,
+    candidate: DeviceDetector,
+    fraction: f64,
+    requests: AtomicU64,
+    errors: AtomicU64,
+    divergences: Mutex<DatabaseDiff>,
+}
+
+impl Canary {
+    /// Answers with `primary`, comparing `fraction` of requests, from 0 to
+    /// 1, with `candidate`.
+    pub fn new(primary: DeviceDetector, candidate: DeviceDetector, fraction: f64) -> Self {
+        Self {
+            primary,
+            candidate,
+            fraction: fraction.clamp(0.0, 1.0),
+            requests: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            divergences: Mutex::new(DatabaseDiff::default()),
+        }
+    }
+
+    pub fn primary(&self) -> &DeviceDetector {
+        &self.primary
+    }
+
+    pub fn candidate(&self) -> &DeviceDetector {
+        &self.candidate
+    }
+
+    /// Whether the `n`th request is sampled. Samples are spread evenly rather
+    /// than randomly, so that exactly `fraction` of requests are compared.
+    fn sampled(&self, n: u64) -> bool {
+        (((n + 1) as f64) * self.fraction).floor() > ((n as f64) * self.fraction).floor()
+    }
+
+    /// Parses with the primary detector, as [`DeviceDetector::parse`] does,
+    /// also comparing with the candidate if the request is sampled.
+    pub fn parse(&self, ua: &str, headers: Option<Vec<(String, String)>>) -> Result<Detection> {
+        let n = self.requests.fetch_add(1, Ordering::Relaxed);
+        if !self.sampled(n) {
+            return self.primary.parse(ua, headers);
+        }
+
+        let detection = self.primary.parse(ua, headers.clone())?;
+        match self.candidate.parse(ua, headers.clone()) {
+            Ok(candidate) => self.divergences.lock().expect("canary lock").record(
+                ua,
+                headers.as_deref(),
+                &detection,
+                &candidate,
+            ),
+            Err(_) => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        Ok(detection)
+    }
+
+    /// The divergences of the candidate from the primary detector on the
+    /// requests sampled so far.
+    pub fn divergences(&self) -> DatabaseDiff {
+        self.divergences.lock().expect("canary lock").clone()
+    }
+
+    /// Number of sampled requests the candidate failed to parse.
+    pub fn candidate_errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    /// Clears the divergences and errors, for example after reporting them.
+    pub fn reset(&self) {
+        *self.divergences.lock().expect("canary lock") = DatabaseDiff::default();
+        self.errors.store(0, Ordering::Relaxed);
+    }
+}
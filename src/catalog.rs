@@ -0,0 +1,51 @@
+//! Enriching detected devices with specs from the user's own device catalog,
+//! such as their screen size, memory and release year, in the same pass as
+//! the detection.
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().with_device_catalog(|brand: &str, model: &str| {
+//!     specs.get(&(brand.to_owned(), model.to_owned())).cloned()
+//! });
+//!
+//! let detection = detector.parse(ua, headers)?;
+//! let year = detection.get_known_device().and_then(|x| x.device.as_ref()?.spec.as_ref()?.release_year);
+//! ```
+//!
+//! The catalog is looked up with the brand and model as detected, for every
+//! detection with both, after the detection caches, so that a catalog whose
+//! contents change is always looked up afresh. See
+//! [`crate::parsers::device::model_parts`] for keys to catalogs which split
+//! model names.
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceSpec {
+    /// The diagonal of the screen, in inches.
+    pub screen_size: Option<f32>,
+    /// Memory in megabytes.
+    pub ram_mb: Option<u32>,
+    pub release_year: Option<u16>,
+}
+
+/// The specs of devices, by their brand and model as detected.
+pub trait DeviceCatalog: Send + Sync {
+    fn lookup(&self, brand: &str, model: &str) -> Option<DeviceSpec>;
+}
+
+impl<F> DeviceCatalog for F
+where
+    F: Fn(&str, &str) -> Option<DeviceSpec> + Send + Sync,
+{
+    fn lookup(&self, brand: &str, model: &str) -> Option<DeviceSpec> {
+        self(brand, model)
+    }
+}
+
+impl DeviceCatalog for HashMap<(String, String), DeviceSpec> {
+    fn lookup(&self, brand: &str, model: &str) -> Option<DeviceSpec> {
+        self.get(&(brand.to_owned(), model.to_owned())).cloned()
+    }
+}
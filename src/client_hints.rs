@@ -1,7 +1,12 @@
 use anyhow::Result;
 
+use std::borrow::Cow;
+
 use once_cell::sync::Lazy;
 
+use version_compare::Cmp;
+
+use crate::parsers::client::browsers::AVAILABLE_BROWSERS;
 use crate::parsers::utils::SafeRegex as Regex;
 
 pub struct ClientHintMapping {
@@ -36,18 +41,161 @@ impl ClientHintMapping {
 }
 
 // TODO options?
-#[derive(Debug)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct ClientHint {
     pub architecture: Option<String>,
     pub bitness: Option<String>,
     pub mobile: bool,
+    /// Whether `Sec-CH-UA-Mobile` was sent at all, [`Self::mobile`] being
+    /// false either way when it wasn't.
+    pub mobile_sent: bool,
     pub model: Option<String>,
     pub ua_full_version: Option<String>,
     pub platform: Option<String>,
     pub platform_version: Option<String>,
+    /// The brands of `Sec-CH-UA`, with their major versions only. Browsers
+    /// send it by default, the full version list only when asked for it, see
+    /// [`Self::brand_versions`].
+    pub brands: Vec<(String, String)>,
     pub full_version_list: Vec<(String, String)>,
     pub app: Option<String>,
     pub form_factors: Vec<String>,
+    /// The user agent of the device itself, from headers such as
+    /// `X-OperaMini-Phone-UA` which proxy browsers send along with their own.
+    pub device_user_agent: Option<String>,
+}
+
+/// Headers proxy browsers and transcoders pass the device's own user agent
+/// in, most reliable first.
+pub(crate) const DEVICE_USER_AGENT_HEADERS: [&str; 7] = [
+    "x-operamini-phone-ua",
+    "x-ucbrowser-device-ua",
+    "device-stock-ua",
+    "x-device-user-agent",
+    "x-original-user-agent",
+    "x-skyfire-phone",
+    "x-bolt-phone-ua",
+];
+
+/// Whether `ua` is one of the frozen user agents chromium sends since user
+/// agent reduction, where only the major version and a handful of platforms
+/// vary and the client hints carry the rest.
+pub fn is_reduced_user_agent(ua: &str) -> bool {
+    static REDUCED_REGEX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(concat!(
+            r"^Mozilla/5\.0 \((?:Windows NT 10\.0; Win64; x64|Macintosh; Intel Mac OS X 10_15_7",
+            r"|X11; Linux x86_64|X11; CrOS \S+ 14541\.0\.0|Linux; Android 10; K)\) ",
+            r"AppleWebKit/537\.36 \(KHTML, like Gecko\) Chrome/\d+\.0\.0\.0 (?:Mobile )?Safari/537\.36$"
+        ))
+        .unwrap()
+    });
+    REDUCED_REGEX.is_match(ua).unwrap_or(false)
+}
+
+/// The first version of each browser which sends client hints by default, or
+/// `None` for browsers which never do, such as those built on Gecko or WebKit.
+pub const SUPPORT_MATRIX: [(&str, Option<&str>); 16] = [
+    ("Chrome", Some("89")),
+    ("Chrome Mobile", Some("89")),
+    ("Chromium", Some("89")),
+    ("Microsoft Edge", Some("89")),
+    ("Opera", Some("75")),
+    ("Opera Mobile", Some("63")),
+    ("Samsung Browser", Some("15.0")),
+    ("Yandex Browser", Some("21.3")),
+    ("Vivaldi", Some("3.7")),
+    ("Brave", Some("1.22")),
+    ("Chrome Mobile iOS", None),
+    ("Firefox", None),
+    ("Firefox Mobile", None),
+    ("Safari", None),
+    ("Mobile Safari", None),
+    ("Internet Explorer", None),
+];
+
+/// Whether `version` of `browser`, by its name as detected, sends client
+/// hints, for telling requests without hints from an old browser from those
+/// whose hints were stripped on the way, such as by a proxy. `None` when the
+/// browser isn't in [`SUPPORT_MATRIX`] and its family doesn't tell either.
+pub fn is_supported(browser: &str, version: &str) -> Option<bool> {
+    if let Some((_, since)) = SUPPORT_MATRIX
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(browser))
+    {
+        let Some(since) = since else {
+            return Some(false);
+        };
+        return Some(matches!(
+            version_compare::compare(version, since),
+            Ok(Cmp::Gt) | Ok(Cmp::Eq)
+        ));
+    }
+
+    // forks of browsers which never send hints don't either
+    let family = AVAILABLE_BROWSERS
+        .search_by_name(browser)?
+        .family
+        .as_deref()?;
+    match family {
+        "Firefox" | "Safari" | "Internet Explorer" => Some(false),
+        _ => None,
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// `brands` as `Sec-CH-UA` and `Sec-CH-UA-Full-Version-List` list them.
+fn brand_list(brands: &[(String, String)]) -> String {
+    brands
+        .iter()
+        .map(|(brand, version)| format!("{};v={}", quote(brand), quote(version)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `form_factors`, stored lower case, capitalized as browsers send them.
+fn form_factor_list(form_factors: &[String]) -> String {
+    form_factors
+        .iter()
+        .map(|x| match x.as_str() {
+            "xr" => "XR",
+            "eink" => "EInk",
+            "desktop" => "Desktop",
+            "automotive" => "Automotive",
+            "mobile" => "Mobile",
+            "tablet" => "Tablet",
+            "watch" => "Watch",
+            x => x,
+        })
+        .map(quote)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `value` without the escapes [`ClientHint::to_headers`] puts in quoted
+/// strings, as in `"Pixel \"7\""`.
+fn unescape(value: &str) -> String {
+    let mut res = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => res.extend(chars.next()),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+/// `value` without its quotes. Quoted strings which have escapes are
+/// unescaped, anything else only trimmed of quotes, which some senders leave
+/// out or double.
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|x| x.strip_suffix('"')) {
+        Some(inner) if inner.contains('\\') => unescape(inner),
+        _ => value.trim_matches('"').to_owned(),
+    }
 }
 
 impl ClientHint {
@@ -55,77 +203,83 @@ impl ClientHint {
         let mut architecture = None;
         let mut bitness = None;
         let mut mobile = false;
+        let mut mobile_sent = false;
         let mut model = None;
         let mut ua_full_version = None;
         let mut platform = None;
         let mut platform_version = None;
         let mut app = None;
         let mut form_factors: Vec<String> = Vec::new();
+        let mut device_user_agent: Option<(usize, String)> = None;
 
         let mut full_version_list: Vec<(String, String)> = Vec::new();
+        let mut brands: Vec<(String, String)> = Vec::new();
 
         static BRAND_REGEX: Lazy<Regex> =
-            Lazy::new(|| Regex::new(r#""([^"]+)"; ?v="([^"]+)"?"#).unwrap());
+            Lazy::new(|| Regex::new(r#""((?:[^"\\]|\\.)+)"; ?v="((?:[^"\\]|\\.)+)"?"#).unwrap());
 
         // println!("headers: {:?}", headers);
         for (header, value) in headers {
             let header = header.replace('_', "-").to_lowercase();
+            if header.trim() == "sec-ch-ua-mobile" {
+                mobile_sent = true;
+            }
+
             match header.trim() {
                 "sec-ch-ua-arch" => {
-                    architecture = Some(value.trim_matches('"').to_owned());
+                    architecture = Some(unquote(&value));
                 }
 
                 "sec-ch-ua-bitness" => {
-                    bitness = Some(value.trim_matches('"').to_owned());
+                    bitness = Some(unquote(&value));
                 }
 
-                "sec-ch-ua-mobile" => {
-                    // the php version interspersed actual headers and mock variable
-                    // names in its code, so I don't know which of these values actually
-                    // could come up in real user agents.
-                    if value == "1" || value == "true" || value == "yes" || value == "?1" {
-                        mobile = true;
-                    }
+                // the php version interspersed actual headers and mock variable
+                // names in its code, so I don't know which of these values actually
+                // could come up in real user agents.
+                "sec-ch-ua-mobile"
+                    if value == "1" || value == "true" || value == "yes" || value == "?1" =>
+                {
+                    mobile = true;
                 }
 
                 "sec-ch-ua-model" => {
-                    let value = value.trim_matches('"');
+                    let value = unquote(&value);
                     if !value.is_empty() {
-                        model = Some(value.to_owned());
+                        model = Some(value);
                     }
                 }
 
                 "sec-ch-ua-platform" => {
-                    platform = Some(value.trim_matches('"').to_owned());
+                    platform = Some(unquote(&value));
                 }
 
                 "sec-ch-ua-platform-version" => {
-                    platform_version = Some(value.trim_matches('"').to_owned());
+                    platform_version = Some(unquote(&value));
                     // TODO remove blanks from other values and see if tests pass.
                     if platform_version.as_deref() == Some("") {
                         platform_version = None;
                     }
                 }
 
-                "x-requested-with" | "http-x-requested-with" => {
-                    if value != "xmlhttprequest" {
-                        app = Some(value.to_owned());
-                    }
+                "x-requested-with" | "http-x-requested-with" if value != "xmlhttprequest" => {
+                    app = Some(value.to_owned());
                 }
 
                 "sec-ch-ua-full-version" => {
-                    ua_full_version = Some(value.trim_matches('"').to_owned());
+                    ua_full_version = Some(unquote(&value));
                 }
 
+                // low entropy, major versions only. Browsers send this by default, the
+                // full version list only when the server asks for it.
                 "sec-ch-ua" => {
-                    if full_version_list.is_empty() {
-                        for x in BRAND_REGEX.captures_iter(&value) {
-                            let res = x?;
-                            let brand = res.get(1).map(|x| x.as_str()).unwrap_or_else(|| "");
-                            let brand_version =
-                                res.get(2).map(|x| x.as_str()).unwrap_or_else(|| "");
-                            full_version_list.push((brand.to_owned(), brand_version.to_owned()));
-                        }
+                    brands.clear();
+
+                    for x in BRAND_REGEX.captures_iter(&value) {
+                        let res = x?;
+                        let brand = res.get(1).map(|x| x.as_str()).unwrap_or_else(|| "");
+                        let brand_version = res.get(2).map(|x| x.as_str()).unwrap_or_else(|| "");
+                        brands.push((unescape(brand), unescape(brand_version)));
                     }
                 }
 
@@ -136,7 +290,21 @@ impl ClientHint {
                         let res = x?;
                         let brand = res.get(1).map(|x| x.as_str()).unwrap_or_else(|| "");
                         let brand_version = res.get(2).map(|x| x.as_str()).unwrap_or_else(|| "");
-                        full_version_list.push((brand.to_owned(), brand_version.to_owned()));
+                        full_version_list.push((unescape(brand), unescape(brand_version)));
+                    }
+                }
+
+                header
+                    if DEVICE_USER_AGENT_HEADERS.contains(&header.trim_start_matches("http-"))
+                        && !value.trim().is_empty() =>
+                {
+                    let header = header.trim_start_matches("http-");
+                    let rank = DEVICE_USER_AGENT_HEADERS
+                        .iter()
+                        .position(|x| *x == header)
+                        .unwrap_or_default();
+                    if device_user_agent.as_ref().is_none_or(|x| rank < x.0) {
+                        device_user_agent = Some((rank, value.trim().to_owned()));
                     }
                 }
 
@@ -144,7 +312,7 @@ impl ClientHint {
                     // Parse FormFactors header - can be array or quoted string format
                     static FORM_FACTOR_REGEX: Lazy<Regex> =
                         Lazy::new(|| Regex::new(r#""([a-z]+)""#).unwrap());
-                    
+
                     form_factors.clear();
                     for cap in FORM_FACTOR_REGEX.captures_iter(&value.to_lowercase()) {
                         let cap = cap?;
@@ -161,17 +329,120 @@ impl ClientHint {
             architecture,
             bitness,
             mobile,
+            mobile_sent,
             model,
             ua_full_version,
             platform,
             platform_version,
+            brands,
             full_version_list,
             app,
             form_factors,
+            device_user_agent: device_user_agent.map(|x| x.1),
         };
 
         // println!("client hints: {:?}", res);
 
         Ok(res)
     }
+
+    /// The brands of the full version list, or of `Sec-CH-UA` with their
+    /// major versions only when the full version list wasn't sent.
+    pub fn brand_versions(&self) -> &[(String, String)] {
+        if self.full_version_list.is_empty() {
+            &self.brands
+        } else {
+            &self.full_version_list
+        }
+    }
+
+    /// The value of the client hint header `name`, lower case, if it was
+    /// sent. Single values are as parsed, without their quotes, lists are
+    /// formatted as [`Self::to_headers`] sends them.
+    pub(crate) fn header_value(&self, name: &str) -> Option<Cow<'_, str>> {
+        let list = |x: String| (!x.is_empty()).then_some(Cow::Owned(x));
+        match name {
+            "sec-ch-ua" => list(brand_list(&self.brands)),
+            "sec-ch-ua-mobile" if self.mobile_sent => {
+                Some(Cow::Borrowed(if self.mobile { "?1" } else { "?0" }))
+            }
+            "sec-ch-ua-platform" => self.platform.as_deref().map(Cow::Borrowed),
+            "sec-ch-ua-platform-version" => self.platform_version.as_deref().map(Cow::Borrowed),
+            "sec-ch-ua-arch" => self.architecture.as_deref().map(Cow::Borrowed),
+            "sec-ch-ua-bitness" => self.bitness.as_deref().map(Cow::Borrowed),
+            "sec-ch-ua-model" => self.model.as_deref().map(Cow::Borrowed),
+            "sec-ch-ua-full-version" => self.ua_full_version.as_deref().map(Cow::Borrowed),
+            "sec-ch-ua-full-version-list" => list(brand_list(&self.full_version_list)),
+            "sec-ch-ua-form-factors" => list(form_factor_list(&self.form_factors)),
+            "x-requested-with" => self.app.as_deref().map(Cow::Borrowed),
+            _ => None,
+        }
+    }
+
+    /// The inverse of [`Self::from_headers`], producing the standard Sec-CH-UA
+    /// headers for whatever is populated, with values quoted and formatted the
+    /// way browsers send them. Unset hints are left out, the mobile flag too
+    /// unless [`Self::mobile_sent`], as is [`Self::device_user_agent`], which
+    /// isn't a client hint.
+    pub fn to_headers(&self) -> Vec<(String, String)> {
+        let mut headers = Vec::new();
+
+        if !self.brands.is_empty() {
+            headers.push(("Sec-CH-UA".to_owned(), brand_list(&self.brands)));
+        }
+
+        if self.mobile_sent {
+            headers.push((
+                "Sec-CH-UA-Mobile".to_owned(),
+                if self.mobile { "?1" } else { "?0" }.to_owned(),
+            ));
+        }
+
+        if let Some(platform) = &self.platform {
+            headers.push(("Sec-CH-UA-Platform".to_owned(), quote(platform)));
+        }
+
+        if let Some(platform_version) = &self.platform_version {
+            headers.push((
+                "Sec-CH-UA-Platform-Version".to_owned(),
+                quote(platform_version),
+            ));
+        }
+
+        if let Some(architecture) = &self.architecture {
+            headers.push(("Sec-CH-UA-Arch".to_owned(), quote(architecture)));
+        }
+
+        if let Some(bitness) = &self.bitness {
+            headers.push(("Sec-CH-UA-Bitness".to_owned(), quote(bitness)));
+        }
+
+        if let Some(model) = &self.model {
+            headers.push(("Sec-CH-UA-Model".to_owned(), quote(model)));
+        }
+
+        if let Some(ua_full_version) = &self.ua_full_version {
+            headers.push(("Sec-CH-UA-Full-Version".to_owned(), quote(ua_full_version)));
+        }
+
+        if !self.full_version_list.is_empty() {
+            headers.push((
+                "Sec-CH-UA-Full-Version-List".to_owned(),
+                brand_list(&self.full_version_list),
+            ));
+        }
+
+        if !self.form_factors.is_empty() {
+            headers.push((
+                "Sec-CH-UA-Form-Factors".to_owned(),
+                form_factor_list(&self.form_factors),
+            ));
+        }
+
+        if let Some(app) = &self.app {
+            headers.push(("X-Requested-With".to_owned(), app.to_owned()));
+        }
+
+        headers
+    }
 }
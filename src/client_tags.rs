@@ -0,0 +1,118 @@
+//! Tagging clients with the groups brand-safety checks care about, such as
+//! privacy browsers, patched forks of mainstream browsers, download managers
+//! and scrapers, which the client type alone doesn't tell apart.
+//!
+//! ```ignore
+//! let client = detection.get_known_device().and_then(|x| x.client.as_ref());
+//! if client.is_some_and(|x| x.tags().contains(&ClientTag::Scraper)) { ... }
+//! ```
+//!
+//! The groups are maintained in `src/client_tags/tags.yml`, by client name
+//! and type. Tags are only worked out when asked for, so detections which
+//! don't need them pay nothing. Services keeping their own groups can load
+//! them with [`ClientTagMapping::from_yaml`] in the same format.
+
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+use crate::parsers::client::{Client, ClientType};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientTag {
+    PrivacyBrowser,
+    ModifiedClient,
+    DownloadManager,
+    Scraper,
+}
+
+const CLIENT_TYPES: [ClientType; 7] = [
+    ClientType::Browser,
+    ClientType::FeedReader,
+    ClientType::MobileApp,
+    ClientType::Pim,
+    ClientType::Library,
+    ClientType::MediaPlayer,
+    ClientType::DownloadManager,
+];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GroupEntry {
+    #[serde(default)]
+    clients: Vec<String>,
+    #[serde(default)]
+    types: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+struct Group {
+    tag: ClientTag,
+    clients: Vec<String>,
+    types: Vec<ClientType>,
+}
+
+/// Which clients each tag applies to.
+#[derive(Clone, Debug)]
+pub struct ClientTagMapping {
+    groups: Vec<Group>,
+}
+
+static EMBEDDED: Lazy<ClientTagMapping> = Lazy::new(|| {
+    ClientTagMapping::from_yaml(include_str!("client_tags/tags.yml")).expect("valid client tags")
+});
+
+impl ClientTagMapping {
+    /// The groups maintained with the crate.
+    pub fn embedded() -> &'static Self {
+        &EMBEDDED
+    }
+
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let entries: BTreeMap<ClientTag, GroupEntry> =
+            serde_yaml::from_str(yaml).context("invalid client tags")?;
+
+        let groups = entries
+            .into_iter()
+            .map(|(tag, entry)| {
+                let types = entry
+                    .types
+                    .iter()
+                    .map(|name| {
+                        CLIENT_TYPES
+                            .iter()
+                            .find(|x| x.as_str() == name)
+                            .cloned()
+                            .ok_or_else(|| anyhow!("unknown client type {}", name))
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(Group {
+                    tag,
+                    clients: entry.clients,
+                    types,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { groups })
+    }
+
+    /// The tags of `client`, in the order of [`ClientTag`].
+    pub fn tags(&self, client: &Client) -> Vec<ClientTag> {
+        self.groups
+            .iter()
+            .filter(|x| x.types.contains(&client.r#type) || x.clients.contains(&client.name))
+            .map(|x| x.tag)
+            .collect()
+    }
+}
+
+impl Client {
+    /// The tags of the client in the groups maintained with the crate, see
+    /// [`crate::client_tags`].
+    pub fn tags(&self) -> Vec<ClientTag> {
+        ClientTagMapping::embedded().tags(self)
+    }
+}
@@ -0,0 +1,642 @@
+//! The regex database the detector matches against.
+//!
+//! The snapshot in the `device-detector-data` crate is compiled in and used by
+//! default, or its much smaller subset with the `mini-database` feature.
+//! A database can also be loaded from a directory with the same layout, for
+//! example to try out a newer snapshot before rolling it out, see
+//! [`diff_databases`], or from a single file bundling all of it, see
+//! [`bundle_dir`]. With the `binary-database` feature, it can also be
+//! compiled to a binary format which loads faster, see
+//! [`crate::binary_database`].
+
+use anyhow::{Context, Result};
+
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_yaml::Value;
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::device_detector::{Detection, DeviceDetector};
+use crate::fixture_recorder::fixture;
+use crate::memory::RegexMemory;
+use crate::overlay::Overlay;
+use crate::parsers::bot::{BotDefinition, BotList};
+use crate::parsers::client::browsers::engines::BrowserEngineList;
+use crate::parsers::client::browsers::BrowserClientList;
+use crate::parsers::client::hints::HintList;
+use crate::parsers::client::ClientList;
+use crate::parsers::device::DeviceList;
+use crate::parsers::oss::OSList;
+use crate::parsers::utils::LazyRegex;
+use crate::parsers::vendor_fragments::VendorFragmentList;
+use crate::replay;
+#[cfg(feature = "signed-database")]
+use crate::signing::BundleKey;
+use crate::validation::{unknown_keys, RegexFileKind, UnknownKey};
+
+/// Every file the database is made of, relative to the database directory.
+pub use device_detector_data::FILES;
+
+/// Whether the list in `path`, one of [`FILES`], is part of this build. The
+/// device lists other than mobiles are left out without their feature, and
+/// are then neither embedded nor loaded from elsewhere.
+pub(crate) fn compiled_in(path: &str) -> bool {
+    device_detector_data::file(path).is_some()
+}
+
+pub(crate) fn embedded_file(path: &str) -> Result<&'static str> {
+    device_detector_data::file(path)
+        .ok_or_else(|| anyhow::anyhow!("{} is not part of the database", path))
+}
+
+static EMBEDDED: Lazy<Arc<Database>> = Lazy::new(|| {
+    // compiled by build.rs, so that no yaml is parsed at startup
+    #[cfg(feature = "binary-database")]
+    let db = Database::from_binary(include_bytes!(concat!(env!("OUT_DIR"), "/database.bin")));
+    #[cfg(not(feature = "binary-database"))]
+    let db = Database::load(|path| embedded_file(path).map(|x| x.into()));
+
+    let mut db = db.expect("loading embedded database");
+    db.embedded = true;
+    Arc::new(db)
+});
+
+/// The contents of one of [`FILES`], as yaml or as the messagepack of the
+/// binary format, see [`crate::binary_database`].
+pub(crate) enum Source<'a> {
+    Yaml(Cow<'a, str>),
+    #[cfg(feature = "binary-database")]
+    Binary(&'a [u8]),
+}
+
+impl Source<'_> {
+    pub(crate) fn parse<T: DeserializeOwned>(&self) -> Result<T> {
+        match self {
+            Source::Yaml(x) => Ok(serde_yaml::from_str(x)?),
+            #[cfg(feature = "binary-database")]
+            Source::Binary(x) => Ok(rmp_serde::from_slice(x)?),
+        }
+    }
+}
+
+pub struct Database {
+    pub(crate) bots: BotList,
+    pub(crate) oss: OSList,
+    pub(crate) vendor_fragments: VendorFragmentList,
+    pub(crate) browsers: BrowserClientList,
+    pub(crate) browser_engines: BrowserEngineList,
+    pub(crate) feed_readers: ClientList,
+    pub(crate) libraries: ClientList,
+    pub(crate) media_players: ClientList,
+    pub(crate) mobile_apps: ClientList,
+    pub(crate) pim: ClientList,
+    pub(crate) hint_apps: HintList,
+    pub(crate) hint_browsers: HintList,
+    pub(crate) cameras: DeviceList,
+    pub(crate) car_browsers: DeviceList,
+    pub(crate) consoles: DeviceList,
+    pub(crate) mobiles: DeviceList,
+    pub(crate) notebooks: DeviceList,
+    pub(crate) portable_media_players: DeviceList,
+    pub(crate) shell_tvs: DeviceList,
+    pub(crate) televisions: DeviceList,
+    unknown_keys: BTreeMap<&'static str, Vec<UnknownKey>>,
+    pub(crate) regex_memory: Arc<RegexMemory>,
+    /// Whether this is [`Database::embedded`], as it was compiled in.
+    embedded: bool,
+}
+
+/// Which ruleset a database is and how many entries it has, for deployments
+/// to report, see [`Database::info`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DatabaseInfo {
+    /// The version of the `device-detector-data` crate, for the embedded
+    /// database only, as others may have been loaded from anywhere.
+    pub version: Option<&'static str>,
+    /// The upstream commit the database was copied from, for the embedded
+    /// database only.
+    pub upstream_commit: Option<&'static str>,
+    pub browsers: usize,
+    pub browser_engines: usize,
+    /// Feed readers, libraries, media players, mobile apps and pim clients.
+    pub other_clients: usize,
+    pub oss: usize,
+    pub device_brands: usize,
+    pub device_models: usize,
+    pub bots: usize,
+}
+
+impl Database {
+    /// The database compiled into the crate. Loaded once and shared.
+    pub fn embedded() -> Arc<Database> {
+        EMBEDDED.clone()
+    }
+
+    /// The miniature database of `regexes-mini/`, with only the most common
+    /// entries, which loads and compiles far faster than the full one. For
+    /// tests, such as those of the test server of the server crate.
+    #[cfg(feature = "test-util")]
+    pub fn mini() -> Result<Database> {
+        Self::load(|path| {
+            device_detector_data::mini_file(path)
+                .map(|x| x.into())
+                .ok_or_else(|| anyhow::anyhow!("{} is not part of the database", path))
+        })
+    }
+
+    /// Loads a database from a directory laid out like `regexes/`.
+    ///
+    /// Keys the detector doesn't know about, such as attributes upstream has
+    /// added since, are ignored rather than failing the load. Each is printed
+    /// to stderr, and they are listed by [`Self::unknown_keys`].
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Database> {
+        let dir = dir.as_ref();
+        Self::from_files(|path: &str| {
+            let path = dir.join(path);
+            std::fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))
+        })
+    }
+
+    /// The embedded database with `overlay` merged in. See
+    /// [`crate::overlay`].
+    pub fn embedded_with_overlay(overlay: &Overlay) -> Result<Database> {
+        Self::from_files(|path: &str| overlay.apply(path, embedded_file(path)?.to_owned()))
+    }
+
+    /// Same as [`Self::from_dir`], with `overlay` merged in.
+    pub fn from_dir_with_overlay<P: AsRef<Path>>(dir: P, overlay: &Overlay) -> Result<Database> {
+        let dir = dir.as_ref();
+        Self::from_files(|path: &str| {
+            let file = dir.join(path);
+            let contents = std::fs::read_to_string(&file)
+                .with_context(|| format!("reading {}", file.display()))?;
+            overlay.apply(path, contents)
+        })
+    }
+
+    /// Loads a database from a bundle made by [`bundle_dir`], such as one
+    /// downloaded at startup. Unknown keys are handled as by
+    /// [`Self::from_dir`].
+    pub fn from_bundle(bundle: &[u8]) -> Result<Database> {
+        let files: BTreeMap<String, String> =
+            serde_json::from_slice(bundle).context("invalid database bundle")?;
+        Self::from_files(|path: &str| {
+            files
+                .get(path)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("{} is missing from the bundle", path))
+        })
+    }
+
+    /// Loads a database compiled by [`crate::binary_database::compile_dir`].
+    #[cfg(feature = "binary-database")]
+    pub fn from_binary(bytes: &[u8]) -> Result<Database> {
+        let files = crate::binary_database::decode(bytes)?;
+        Self::load_sources(|path: &str| {
+            files
+                .get(path)
+                .map(|x| Source::Binary(x))
+                .ok_or_else(|| anyhow::anyhow!("{} is missing from the binary database", path))
+        })
+    }
+
+    /// Loads a bundle made by [`bundle_dir`], once `signature` is checked to
+    /// be `key`'s signature of it. See [`crate::signing`].
+    #[cfg(feature = "signed-database")]
+    pub fn from_signed_bundle(
+        bundle: &[u8],
+        signature: &[u8],
+        key: &BundleKey,
+    ) -> Result<Database> {
+        key.verify(bundle, signature)?;
+        Self::from_bundle(bundle)
+    }
+
+    /// Loads the database in `dir`, laid out like `regexes/`, once
+    /// `signature` is checked to be `key`'s signature of its bundle, see
+    /// [`bundle_dir`].
+    #[cfg(feature = "signed-database")]
+    pub fn from_signed_dir<P: AsRef<Path>>(
+        dir: P,
+        signature: &[u8],
+        key: &BundleKey,
+    ) -> Result<Database> {
+        // the files which are checked are the ones loaded, rather than read
+        // again from disk
+        Self::from_signed_bundle(&bundle_dir(dir)?, signature, key)
+    }
+
+    fn from_files<F>(read: F) -> Result<Database>
+    where
+        F: Fn(&str) -> Result<String>,
+    {
+        let mut res = Self::load(|path| read(path).map(|x| x.into()))?;
+
+        for file in FILES.iter().filter(|x| compiled_in(x)) {
+            let Some(kind) = RegexFileKind::for_path(file) else {
+                continue;
+            };
+
+            let unknown = unknown_keys(&read(file)?, kind);
+            for key in &unknown {
+                eprintln!(
+                    "warning: {}: ignoring unknown key '{}' in {} entries, first on line {}",
+                    file,
+                    key.key,
+                    key.count,
+                    key.line
+                        .map(|x| x.to_string())
+                        .unwrap_or_else(|| "?".to_owned())
+                );
+            }
+
+            if !unknown.is_empty() {
+                res.unknown_keys.insert(file, unknown);
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// The keys ignored when loading the database, by file. Always empty for
+    /// the embedded database, which is checked when it is updated.
+    pub fn unknown_keys(&self) -> &BTreeMap<&'static str, Vec<UnknownKey>> {
+        &self.unknown_keys
+    }
+
+    /// The version and entry counts of the database.
+    pub fn info(&self) -> DatabaseInfo {
+        let clients = [
+            &self.feed_readers,
+            &self.libraries,
+            &self.media_players,
+            &self.mobile_apps,
+            &self.pim,
+        ];
+        let devices = [
+            &self.cameras,
+            &self.car_browsers,
+            &self.consoles,
+            &self.mobiles,
+            &self.notebooks,
+            &self.portable_media_players,
+            &self.shell_tvs,
+            &self.televisions,
+        ];
+
+        DatabaseInfo {
+            version: self.embedded.then_some(device_detector_data::VERSION),
+            upstream_commit: self
+                .embedded
+                .then_some(device_detector_data::UPSTREAM_COMMIT),
+            browsers: self.browsers.regexes().count(),
+            browser_engines: self.browser_engines.regexes().count(),
+            other_clients: clients.iter().map(|x| x.regexes().count()).sum(),
+            oss: self.oss.len(),
+            device_brands: devices.iter().map(|x| x.brand_regexes().count()).sum(),
+            device_models: devices.iter().map(|x| x.models()).sum(),
+            bots: self.bots.iter().count(),
+        }
+    }
+
+    /// Every bot in the database, in the order they are matched, for example
+    /// to export them to other tooling.
+    pub fn bot_definitions(&self) -> impl Iterator<Item = BotDefinition<'_>> {
+        self.bots.iter()
+    }
+
+    /// The first regex of each list which matches `ua`, with the name of the
+    /// list, such as `device/mobiles.yml`. For device lists this is the
+    /// brand's regex. Lists are matched in turn by the detector, so not
+    /// every match here is used in the detection.
+    pub fn matching_regexes(&self, ua: &str) -> Result<Vec<(&'static str, &str)>> {
+        let mut res = Vec::new();
+        for (list, regexes) in self.regex_lists() {
+            // a model regex only applies once its brand's matched
+            let regexes = match self.device_list(list) {
+                Some(devices) => devices.brand_regexes().collect(),
+                None => regexes,
+            };
+            for regex in regexes {
+                if regex.is_match(ua)? {
+                    res.push((list, regex.user_agent_source()));
+                    break;
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    fn device_list(&self, list: &str) -> Option<&DeviceList> {
+        match list {
+            "device/cameras.yml" => Some(&self.cameras),
+            "device/car_browsers.yml" => Some(&self.car_browsers),
+            "device/consoles.yml" => Some(&self.consoles),
+            "device/mobiles.yml" => Some(&self.mobiles),
+            "device/notebooks.yml" => Some(&self.notebooks),
+            "device/portable_media_player.yml" => Some(&self.portable_media_players),
+            "device/shell_tv.yml" => Some(&self.shell_tvs),
+            "device/televisions.yml" => Some(&self.televisions),
+            _ => None,
+        }
+    }
+
+    /// The regexes of each list, named after the file it was loaded from.
+    pub(crate) fn regex_lists(&self) -> Vec<(&'static str, Vec<&LazyRegex>)> {
+        vec![
+            ("bots.yml", self.bots.regexes().collect()),
+            ("oss.yml", self.oss.regexes().collect()),
+            (
+                "vendorfragments.yml",
+                self.vendor_fragments.regexes().collect(),
+            ),
+            ("client/browsers.yml", self.browsers.regexes().collect()),
+            (
+                "client/browser_engine.yml",
+                self.browser_engines.regexes().collect(),
+            ),
+            (
+                "client/feed_readers.yml",
+                self.feed_readers.regexes().collect(),
+            ),
+            ("client/libraries.yml", self.libraries.regexes().collect()),
+            (
+                "client/mediaplayers.yml",
+                self.media_players.regexes().collect(),
+            ),
+            (
+                "client/mobile_apps.yml",
+                self.mobile_apps.regexes().collect(),
+            ),
+            ("client/pim.yml", self.pim.regexes().collect()),
+            ("device/cameras.yml", self.cameras.regexes().collect()),
+            (
+                "device/car_browsers.yml",
+                self.car_browsers.regexes().collect(),
+            ),
+            ("device/consoles.yml", self.consoles.regexes().collect()),
+            ("device/mobiles.yml", self.mobiles.regexes().collect()),
+            ("device/notebooks.yml", self.notebooks.regexes().collect()),
+            (
+                "device/portable_media_player.yml",
+                self.portable_media_players.regexes().collect(),
+            ),
+            ("device/shell_tv.yml", self.shell_tvs.regexes().collect()),
+            (
+                "device/televisions.yml",
+                self.televisions.regexes().collect(),
+            ),
+        ]
+    }
+
+    pub(crate) fn load<'a, F>(read: F) -> Result<Database>
+    where
+        F: Fn(&str) -> Result<Cow<'a, str>>,
+    {
+        Self::load_sources(|path| read(path).map(Source::Yaml))
+    }
+
+    fn load_sources<'a, F>(read: F) -> Result<Database>
+    where
+        F: Fn(&str) -> Result<Source<'a>>,
+    {
+        let file = |path: &str| read(path).with_context(|| format!("loading {}", path));
+        let devices = |path: &str| {
+            if !compiled_in(path) {
+                return Ok(DeviceList::default());
+            }
+            DeviceList::from_source(&file(path)?)
+        };
+
+        let db = Database {
+            bots: BotList::from_source(&file("bots.yml")?)?,
+            oss: OSList::from_source(&file("oss.yml")?)?,
+            vendor_fragments: VendorFragmentList::from_source(&file("vendorfragments.yml")?)?,
+            browsers: BrowserClientList::from_source(&file("client/browsers.yml")?)?,
+            browser_engines: BrowserEngineList::from_source(&file("client/browser_engine.yml")?)?,
+            feed_readers: ClientList::from_source(&file("client/feed_readers.yml")?)?,
+            libraries: ClientList::from_source(&file("client/libraries.yml")?)?,
+            media_players: ClientList::from_source(&file("client/mediaplayers.yml")?)?,
+            mobile_apps: ClientList::from_source(&file("client/mobile_apps.yml")?)?,
+            pim: ClientList::from_source(&file("client/pim.yml")?)?,
+            hint_apps: HintList::from_source(&file("client/hints/apps.yml")?)?,
+            hint_browsers: HintList::from_source(&file("client/hints/browsers.yml")?)?,
+            cameras: devices("device/cameras.yml")?,
+            car_browsers: devices("device/car_browsers.yml")?,
+            consoles: devices("device/consoles.yml")?,
+            mobiles: DeviceList::from_source(&file("device/mobiles.yml")?)?,
+            notebooks: devices("device/notebooks.yml")?,
+            portable_media_players: devices("device/portable_media_player.yml")?,
+            shell_tvs: devices("device/shell_tv.yml")?,
+            televisions: devices("device/televisions.yml")?,
+            unknown_keys: BTreeMap::new(),
+            regex_memory: Arc::default(),
+            embedded: false,
+        };
+
+        for (_, regexes) in db.regex_lists() {
+            for regex in regexes {
+                regex.track(&db.regex_memory);
+            }
+        }
+
+        Ok(db)
+    }
+}
+
+/// Packs the database in `dir`, laid out like `regexes/`, into a single
+/// file for [`Database::from_bundle`]: a json object of each of [`FILES`]
+/// by its path.
+pub fn bundle_dir<P: AsRef<Path>>(dir: P) -> Result<Vec<u8>> {
+    let dir = dir.as_ref();
+    let mut files = BTreeMap::new();
+    for file in FILES {
+        let path = dir.join(file);
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        files.insert(*file, contents);
+    }
+    Ok(serde_json::to_vec(&files)?)
+}
+
+/// How often a field changed between two databases, with a few of the user
+/// agents it changed for.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct FieldChange {
+    pub count: usize,
+    pub examples: Vec<String>,
+}
+
+/// The classification changes a database update causes over a corpus.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DatabaseDiff {
+    /// Number of fixtures detected.
+    pub total: usize,
+    /// Number of fixtures where at least one field changed.
+    pub changed: usize,
+    /// Changes keyed by field path, such as `device.model`.
+    pub fields: BTreeMap<String, FieldChange>,
+}
+
+/// How many example user agents are kept per field.
+const MAX_EXAMPLES: usize = 5;
+
+impl DatabaseDiff {
+    /// Counts the fields of `new` which differ from those of `old`, both
+    /// being detections of the same request.
+    pub(crate) fn record(
+        &mut self,
+        ua: &str,
+        headers: Option<&[(String, String)]>,
+        old: &Detection,
+        new: &Detection,
+    ) {
+        // the old detection is the expectation the new one is compared against
+        let mut mismatches = Vec::new();
+        replay::compare(
+            ua,
+            "",
+            &fixture(ua, headers, old),
+            &fixture(ua, headers, new),
+            &mut mismatches,
+        );
+
+        self.total += 1;
+        if !mismatches.is_empty() {
+            self.changed += 1;
+        }
+
+        for mismatch in mismatches {
+            let change = self.fields.entry(mismatch.field).or_default();
+            change.count += 1;
+            if change.examples.len() < MAX_EXAMPLES {
+                change.examples.push(mismatch.user_agent);
+            }
+        }
+    }
+}
+
+/// A field whose value differs between the detections of two user agents.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FieldDiff {
+    /// Path to the field, such as `device.model`.
+    pub field: String,
+    pub first: String,
+    pub second: String,
+}
+
+/// The regex of a list matched by either of two user agents, see
+/// [`Database::matching_regexes`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RegexDiff {
+    pub list: &'static str,
+    pub first: Option<String>,
+    pub second: Option<String>,
+}
+
+impl RegexDiff {
+    pub fn is_same(&self) -> bool {
+        self.first == self.second
+    }
+}
+
+/// Why two user agents were detected differently.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct UserAgentDiff {
+    /// Fields which differ, in the order of the fixture format.
+    pub fields: Vec<FieldDiff>,
+    /// Every list either user agent matched, whether with the same regex or
+    /// not.
+    pub regexes: Vec<RegexDiff>,
+}
+
+/// Detects `first` and `second`, comparing their detections field by field
+/// and the regexes each of them matched.
+pub fn diff_user_agents(
+    detector: &DeviceDetector,
+    first: &str,
+    second: &str,
+) -> Result<UserAgentDiff> {
+    let mut first_fixture = fixture(first, None, &detector.parse(first, None)?);
+    let mut second_fixture = fixture(second, None, &detector.parse(second, None)?);
+    for fixture in [&mut first_fixture, &mut second_fixture] {
+        if let Some(fixture) = fixture.as_mapping_mut() {
+            fixture.remove("user_agent");
+        }
+    }
+
+    let mut mismatches = Vec::new();
+    replay::compare("", "", &first_fixture, &second_fixture, &mut mismatches);
+    let fields = mismatches
+        .into_iter()
+        .map(|x| FieldDiff {
+            field: x.field,
+            first: x.expected,
+            second: x.actual,
+        })
+        .collect();
+
+    let db = detector.database();
+    let first_regexes = db.matching_regexes(first)?;
+    let second_regexes = db.matching_regexes(second)?;
+
+    let regexes = db
+        .regex_lists()
+        .into_iter()
+        .filter_map(|(list, _)| {
+            let find = |regexes: &[(&str, &str)]| {
+                regexes
+                    .iter()
+                    .find(|(x, _)| *x == list)
+                    .map(|(_, regex)| regex.to_string())
+            };
+            let diff = RegexDiff {
+                list,
+                first: find(&first_regexes),
+                second: find(&second_regexes),
+            };
+            (diff.first.is_some() || diff.second.is_some()).then_some(diff)
+        })
+        .collect();
+
+    Ok(UserAgentDiff { fields, regexes })
+}
+
+/// Detects every fixture in `corpus` with the databases in `old_dir` and
+/// `new_dir`, counting the fields whose detected values differ. The
+/// fixtures' own expected values are ignored, only the user agent and
+/// headers are used.
+pub fn diff_databases<P: AsRef<Path>, Q: AsRef<Path>>(
+    old_dir: P,
+    new_dir: Q,
+    corpus: &[Value],
+) -> Result<DatabaseDiff> {
+    let old = DeviceDetector::new().with_database(Arc::new(Database::from_dir(old_dir)?));
+    let new = DeviceDetector::new().with_database(Arc::new(Database::from_dir(new_dir)?));
+    diff_detectors(&old, &new, corpus)
+}
+
+/// Same as [`diff_databases`] for detectors which have already been set up.
+pub fn diff_detectors(
+    old: &DeviceDetector,
+    new: &DeviceDetector,
+    corpus: &[Value],
+) -> Result<DatabaseDiff> {
+    let mut res = DatabaseDiff::default();
+
+    for case in corpus {
+        let (ua, headers) = replay::fixture_request(case)?;
+
+        let old_detection = old.parse(&ua, headers.clone())?;
+        let new_detection = new.parse(&ua, headers.clone())?;
+
+        res.record(&ua, headers.as_deref(), &old_detection, &new_detection);
+    }
+
+    Ok(res)
+}
@@ -0,0 +1,94 @@
+//! Decoding of user agents which were percent-encoded on their way into logs,
+//! such as `Mozilla%2F5.0+%28Windows+NT+10.0%3B+Win64%3B+x64%29`, before they
+//! are detected. Off by default, as the database has entries for user agents
+//! which really do contain escapes:
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().with_user_agent_decoding(true);
+//! let detection = detector.parse("Mozilla%2F5.0%20(compatible%3B%20Googlebot%2F2.1)", None)?;
+//! assert!(detection.user_agent_decoded());
+//! ```
+//!
+//! A user agent is only decoded when it has no spaces, as encoding replaces
+//! them with `%20` or `+`, and has an escape of one of the characters user
+//! agents are made of, such as `%2F` for `/`. It is decoded at most
+//! [`MAX_PASSES`] times, for those escaped twice such as `Mozilla%252F5.0`, and
+//! left as it is if it is longer than [`MAX_LEN`], or if decoding gives
+//! something other than printable utf-8.
+
+use std::borrow::Cow;
+
+/// Number of times a user agent is decoded at most.
+pub const MAX_PASSES: usize = 2;
+
+/// Length in bytes above which a user agent isn't decoded.
+pub const MAX_LEN: usize = 4096;
+
+/// Escapes of characters user agents are made of, which tell an encoded
+/// user agent, with or without the `%` escaped again as `%25`.
+const TELLTALE: [&str; 5] = ["20", "28", "29", "2F", "3B"];
+
+fn is_encoded(ua: &str) -> bool {
+    !ua.contains(' ')
+        && ua.split('%').skip(1).any(|x| {
+            let x = x.strip_prefix("25").unwrap_or(x);
+            x.get(..2)
+                .is_some_and(|x| TELLTALE.iter().any(|y| x.eq_ignore_ascii_case(y)))
+        })
+}
+
+fn hex(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// A single pass of percent decoding, with `+` as a space. Malformed
+/// escapes are kept as they are.
+fn decode_once(ua: &str) -> Option<String> {
+    let bytes = ua.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                (Some(high), Some(low)) => {
+                    res.push(high << 4 | low);
+                    i += 3;
+                    continue;
+                }
+                _ => res.push(b'%'),
+            },
+            b'+' => res.push(b' '),
+            byte => res.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8(res)
+        .ok()
+        .filter(|x| !x.chars().any(char::is_control))
+}
+
+/// `ua` decoded, if it looks percent-encoded, see [`crate::decode`].
+pub fn decode_user_agent(ua: &str) -> Cow<'_, str> {
+    if ua.len() > MAX_LEN || !is_encoded(ua) {
+        return Cow::Borrowed(ua);
+    }
+
+    let mut res = ua.to_owned();
+    for _ in 0..MAX_PASSES {
+        match decode_once(&res) {
+            Some(decoded) => res = decoded,
+            None => return Cow::Borrowed(ua),
+        }
+        if !is_encoded(&res) {
+            break;
+        }
+    }
+    Cow::Owned(res)
+}
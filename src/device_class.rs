@@ -0,0 +1,243 @@
+//! A coarse device class, for routing decisions such as which variant of a
+//! page to serve, which most requests get without running any regex:
+//!
+//! ```ignore
+//! match detector.classify_device_class(ua, client_hints)? {
+//!     DeviceClass::Mobile => "m.example.com",
+//!     _ => "www.example.com",
+//! }
+//! ```
+//!
+//! [`fast_path`] recognizes the user agents of the major browsers as they are
+//! sent today, such as the reduced user agent of Chrome, by the shape of the
+//! whole user agent rather than by tokens in it, and gives up on anything
+//! else. Only shapes which the database classifies the same way for every
+//! user agent of the test fixtures are recognized, so it agrees with the
+//! full detection whenever it answers. Otherwise
+//! [`DeviceDetector::classify_device_class`] falls back to the full
+//! detection, as it always does for detectors with rules or a device type
+//! mapping, which may classify differently.
+//!
+//! [`DeviceDetector::classify_device_class`]: crate::device_detector::DeviceDetector::classify_device_class
+
+use serde::{Deserialize, Serialize};
+
+use crate::client_hints::ClientHint;
+use crate::device_detector::Detection;
+use crate::parsers::device::DeviceType;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceClass {
+    /// Smartphones, feature phones and phablets.
+    Mobile,
+    /// Desktops and notebooks.
+    Desktop,
+    Tablet,
+    Tv,
+    Bot,
+    /// Every other device type, and devices whose type is unknown.
+    Other,
+}
+
+impl DeviceClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mobile => "mobile",
+            Self::Desktop => "desktop",
+            Self::Tablet => "tablet",
+            Self::Tv => "tv",
+            Self::Bot => "bot",
+            Self::Other => "other",
+        }
+    }
+
+    /// The class of a full detection.
+    pub fn of(detection: &Detection) -> Self {
+        let device_type = match detection {
+            Detection::Bot(_) => return Self::Bot,
+            Detection::Known(known) => known.device.as_ref().and_then(|x| x.device_type.as_ref()),
+        };
+        match device_type {
+            Some(DeviceType::SmartPhone | DeviceType::FeaturePhone | DeviceType::Phablet) => {
+                Self::Mobile
+            }
+            Some(DeviceType::Desktop | DeviceType::Notebook) => Self::Desktop,
+            Some(DeviceType::Tablet) => Self::Tablet,
+            Some(DeviceType::Television) => Self::Tv,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// The tokens after the parenthesized platform which browsers send, by name.
+const BROWSER_TOKENS: [&str; 12] = [
+    "AppleWebKit",
+    "Chrome",
+    "CriOS",
+    "Edg",
+    "EdgA",
+    "EdgiOS",
+    "Firefox",
+    "FxiOS",
+    "Gecko",
+    "Mobile",
+    "Safari",
+    "Version",
+];
+
+/// Whether a platform token is a version, such as `10_15_7` or `17.1`.
+fn is_version(version: &str) -> bool {
+    !version.is_empty()
+        && version
+            .bytes()
+            .all(|x| x.is_ascii_digit() || x == b'.' || x == b'_')
+}
+
+/// Whether `tail`, the user agent after the platform, has only the tokens of
+/// browsers, and if so whether it has a bare `Mobile` token.
+fn browser_tail(tail: &str) -> Option<bool> {
+    let mut mobile = false;
+    for token in tail.split(' ') {
+        match token {
+            "(KHTML," | "like" | "Gecko)" => continue,
+            "Mobile" => {
+                mobile = true;
+                continue;
+            }
+            _ => {}
+        }
+        let (name, version) = token.split_once('/')?;
+        let version_ok = !version.is_empty()
+            && version
+                .bytes()
+                .all(|x| x.is_ascii_alphanumeric() || x == b'.');
+        if !BROWSER_TOKENS.contains(&name) || !version_ok {
+            return None;
+        }
+    }
+    Some(mobile)
+}
+
+/// The class of an android platform, only known for reduced user agents and
+/// those of firefox.
+fn android_class(platform: &str, mobile: bool) -> Option<DeviceClass> {
+    let mut android = false;
+    let mut class = None;
+    for part in platform.split("; ") {
+        match part {
+            "Linux" => {}
+            _ if part.starts_with("rv:") => {}
+            _ if !android && part.strip_prefix("Android ").is_some_and(is_version) => {
+                android = true;
+            }
+            // the model of reduced user agents
+            "K" if class.is_none() => {
+                class = Some(if mobile {
+                    DeviceClass::Mobile
+                } else {
+                    DeviceClass::Tablet
+                });
+            }
+            // firefox names the form factor rather than the model
+            "Mobile" if class.is_none() => class = Some(DeviceClass::Mobile),
+            "Tablet" if class.is_none() => class = Some(DeviceClass::Tablet),
+            _ => return None,
+        }
+    }
+    class.filter(|_| android)
+}
+
+/// The class of the platform, the part of the user agent in parentheses,
+/// and its `Sec-CH-UA-Platform`.
+fn platform_class(platform: &str, mobile: bool) -> Option<(DeviceClass, &'static str)> {
+    let mut parts = platform.split("; ");
+    let first = parts.next()?;
+    let rest = parts.filter(|x| !x.starts_with("rv:"));
+
+    let class = match first {
+        _ if first.strip_prefix("Windows NT ").is_some_and(is_version) => {
+            for part in rest {
+                if !["Win64", "x64", "WOW64"].contains(&part) {
+                    return None;
+                }
+            }
+            (DeviceClass::Desktop, "Windows")
+        }
+        "Macintosh" => {
+            for part in rest {
+                let version = part.strip_prefix("Intel Mac OS X ")?;
+                if !is_version(version) {
+                    return None;
+                }
+            }
+            (DeviceClass::Desktop, "macOS")
+        }
+        "X11" => {
+            for part in rest {
+                if !["Linux x86_64", "Linux i686", "Ubuntu"].contains(&part) {
+                    return None;
+                }
+            }
+            (DeviceClass::Desktop, "Linux")
+        }
+        "Linux" => (android_class(platform, mobile)?, "Android"),
+        _ if first.starts_with("Android ") => (android_class(platform, mobile)?, "Android"),
+        "iPhone" => {
+            for part in rest {
+                let version = part
+                    .strip_prefix("CPU iPhone OS ")?
+                    .strip_suffix(" like Mac OS X")?;
+                if !is_version(version) {
+                    return None;
+                }
+            }
+            (DeviceClass::Mobile, "iOS")
+        }
+        "iPad" => {
+            for part in rest {
+                let version = part
+                    .strip_prefix("CPU OS ")?
+                    .strip_suffix(" like Mac OS X")?;
+                if !is_version(version) {
+                    return None;
+                }
+            }
+            (DeviceClass::Tablet, "iOS")
+        }
+        _ => return None,
+    };
+
+    // desktop browsers never say they are mobile
+    if class.0 == DeviceClass::Desktop && mobile {
+        return None;
+    }
+    Some(class)
+}
+
+/// The class of a request from the shape of its user agent alone, `None`
+/// when it takes the full detection to tell.
+pub fn fast_path(ua: &str, client_hints: Option<&ClientHint>) -> Option<DeviceClass> {
+    // hints which can change the device type of a user agent
+    if let Some(hints) = client_hints {
+        if hints.mobile
+            || hints.model.is_some()
+            || hints.app.is_some()
+            || hints.device_user_agent.is_some()
+            || !hints.form_factors.is_empty()
+        {
+            return None;
+        }
+    }
+
+    let (platform, tail) = ua.strip_prefix("Mozilla/5.0 (")?.split_once(") ")?;
+    let mobile = browser_tail(tail)?;
+    let (class, platform) = platform_class(platform, mobile)?;
+
+    // a platform hint which contradicts the user agent takes precedence
+    let platform_hint = client_hints.and_then(|x| x.platform.as_deref());
+    if platform_hint.is_some_and(|x| !x.eq_ignore_ascii_case(platform)) {
+        return None;
+    }
+    Some(class)
+}
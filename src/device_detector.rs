@@ -1,30 +1,86 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use arc_swap::ArcSwap;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
+use crate::approximate::Approximation;
+use crate::batch::{self, Batch, CancellationToken, DetectStream};
+use crate::bot_likelihood::{self, BotLikelihood, HeaderFingerprint};
+use crate::bot_spoof::BotSpoofRules;
+use crate::catalog::DeviceCatalog;
 use crate::client_hints::ClientHint;
+use crate::database::{Database, DatabaseInfo};
+use crate::decode;
+use crate::device_class::{self, DeviceClass};
+use crate::device_type_mapping::DeviceTypeMapping;
+use crate::hints_used::{Hint, HintSet};
+use crate::ipad;
+use crate::media_formats::{FormatSupport, MediaFormat};
+use crate::memory::{MemoryBudget, MemoryUsage};
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::normalize::{self, NormalizedRequest};
+use crate::output_style::OutputStyle;
+use crate::parsers::client::hints::HintMappings;
 use crate::parsers::client::ClientType;
+use crate::parsers::device::DeviceMatchStrategy;
 use crate::parsers::device::DeviceType;
+use crate::parsers::device::MobileHintFallback;
 use crate::parsers::{bot, client, device, oss};
-
-#[cfg(feature = "cache")]
-use moka::sync::Cache;
+use crate::profiler::{RegexProfile, RegexProfiler};
+use crate::quirks::{Quirk, QuirkSet};
+use crate::resilience::{Recovery, Stage, StageError};
+use crate::result_cache::{CacheKey, ResultCache};
+use crate::rules::{Rules, QUIRK_RULES};
+use crate::unknown::UnknownReporter;
+use crate::validation::TemplateExpansion;
+use crate::warm_up::{self, WarmUpProgress};
+
+use fancy_regex::Captures;
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
 pub use bot::Bot;
 
 // TODO we should Box KnownDevice as it is much larger than Bot
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Detection {
     Known(KnownDevice),
     Bot(Bot),
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct KnownDevice {
     pub client: Option<client::Client>,
     pub device: Option<device::Device>,
     pub os: Option<oss::OS>,
+    /// The stages which failed, only ever set with
+    /// [`DeviceDetector::with_resilience`], see [`crate::resilience`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stage_errors: Vec<StageError>,
+    /// The database snapshot detected with, only ever set with
+    /// [`DeviceDetector::with_snapshot`], see [`crate::snapshots`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
+    /// The client hints the detection was made with, see
+    /// [`crate::hints_used`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hints_used: Vec<Hint>,
+    /// Whether the user agent was percent-decoded before detection, only
+    /// ever set with [`DeviceDetector::with_user_agent_decoding`], see
+    /// [`crate::decode`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub user_agent_decoded: bool,
+    /// Whether this is the detection of an earlier user agent with the same
+    /// prefix, only ever set with [`DeviceDetector::with_approximation`], see
+    /// [`crate::approximate`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub approximate: bool,
 }
 
 impl Detection {
@@ -46,6 +102,137 @@ impl Detection {
     pub fn is_bot(&self) -> bool {
         matches!(self, Self::Bot(_))
     }
+
+    /// The detection in the stable v1 schema, see [`crate::v1`].
+    pub fn to_v1(&self) -> crate::v1::Detection {
+        self.into()
+    }
+
+    /// The detection as OpenTelemetry `user_agent.*`, `browser.*`,
+    /// `device.*` and `os.*` attributes, see [`crate::otel`].
+    pub fn to_otel_attributes(&self) -> Vec<crate::otel::Attribute> {
+        crate::otel::attributes(self)
+    }
+
+    /// The client hints which would make the detection more precise, were
+    /// they delegated, see [`crate::missing_hints`].
+    pub fn missing_hints(&self) -> Vec<crate::missing_hints::MissingHint> {
+        crate::missing_hints::missing_hints(self)
+    }
+
+    /// Whether the client decodes AVIF images, see [`crate::media_formats`].
+    pub fn supports_avif(&self) -> bool {
+        self.supports(MediaFormat::Avif)
+    }
+
+    /// Whether the client decodes WebP images, see [`crate::media_formats`].
+    pub fn supports_webp(&self) -> bool {
+        self.supports(MediaFormat::Webp)
+    }
+
+    /// Whether the client decodes HEVC video, see [`crate::media_formats`].
+    pub fn supports_hevc(&self) -> bool {
+        self.supports(MediaFormat::Hevc)
+    }
+
+    /// Whether the client decodes `format`, by the matrix maintained with
+    /// the crate, see [`crate::media_formats`].
+    pub fn supports(&self, format: MediaFormat) -> bool {
+        FormatSupport::embedded().supports(self, format)
+    }
+
+    /// The detection of `ua` in the JSON of Matomo's php device detector,
+    /// see [`crate::matomo`].
+    pub fn to_matomo_json(&self, ua: &str) -> String {
+        crate::matomo::to_json(self, ua)
+    }
+
+    /// The detection of `ua` as json in `style`, with the naming and nesting
+    /// of either [`Self::to_value`] or Matomo, see [`crate::output_style`].
+    pub fn to_styled_value(&self, ua: &str, style: &OutputStyle) -> serde_json::Value {
+        style.render(self, ua)
+    }
+
+    /// The same detection, flagged as made from a decoded user agent.
+    pub(crate) fn with_user_agent_decoded(mut self) -> Self {
+        match &mut self {
+            Self::Known(known) => known.user_agent_decoded = true,
+            Self::Bot(bot) => {
+                bot.user_agent_decoded = true;
+                if let Some(claimed) = &mut bot.claimed {
+                    claimed.user_agent_decoded = true;
+                }
+            }
+        }
+        self
+    }
+
+    /// The same detection, flagged as approximate.
+    pub(crate) fn with_approximate(mut self) -> Self {
+        match &mut self {
+            Self::Known(known) => known.approximate = true,
+            Self::Bot(bot) => {
+                bot.approximate = true;
+                if let Some(claimed) = &mut bot.claimed {
+                    claimed.approximate = true;
+                }
+            }
+        }
+        self
+    }
+
+    /// Whether the detection may be cached by its user agent, which neither
+    /// those with failed stages nor approximate ones may.
+    pub(crate) fn is_cacheable(&self) -> bool {
+        self.stage_errors().is_empty() && !self.approximate()
+    }
+
+    /// The stages which failed, see [`crate::resilience`]. Always empty for
+    /// bots, unless [`DeviceDetector::with_bot_claims`] detects their claims.
+    pub fn stage_errors(&self) -> &[StageError] {
+        match self {
+            Self::Known(known) => &known.stage_errors,
+            Self::Bot(bot) => bot.claimed.as_ref().map_or(&[], |x| &x.stage_errors),
+        }
+    }
+
+    /// The client hints which influenced the detection, see
+    /// [`crate::hints_used`]. Always empty for bots, unless
+    /// [`DeviceDetector::with_bot_claims`] detects their claims.
+    pub fn hints_used(&self) -> &[Hint] {
+        match self {
+            Self::Known(known) => &known.hints_used,
+            Self::Bot(bot) => bot.claimed.as_ref().map_or(&[], |x| &x.hints_used),
+        }
+    }
+
+    /// Whether the user agent was percent-decoded before detection, see
+    /// [`crate::decode`].
+    pub fn user_agent_decoded(&self) -> bool {
+        match self {
+            Self::Known(known) => known.user_agent_decoded,
+            Self::Bot(bot) => bot.user_agent_decoded,
+        }
+    }
+
+    /// Whether the detection is that of an earlier user agent with the same
+    /// prefix, see [`crate::approximate`].
+    pub fn approximate(&self) -> bool {
+        match self {
+            Self::Known(known) => known.approximate,
+            Self::Bot(bot) => bot.approximate,
+        }
+    }
+
+    /// The id of the database snapshot the detection was made with, if the
+    /// detector was pinned to one, see [`crate::snapshots`].
+    pub fn snapshot(&self) -> Option<&str> {
+        match self {
+            Self::Known(known) => known.snapshot.as_deref(),
+            Self::Bot(bot) => bot.snapshot.as_deref(),
+        }
+    }
+
     /// This is purely to aid in generating test cases, you should not rely on this for
     /// actual production usage. Only useful for normal stuff, not bots, etc.
     pub fn to_test_case(self, ua: &str) -> String {
@@ -342,6 +529,13 @@ impl KnownDevice {
             .unwrap_or(false)
     }
 
+    pub fn is_download_manager(&self) -> bool {
+        self.client
+            .as_ref()
+            .map(|x| x.r#type == ClientType::DownloadManager)
+            .unwrap_or(false)
+    }
+
     pub fn is_desktop(&self) -> bool {
         // the php library duplicates logic but as far as I can
         // tell it should be equivalent to this.
@@ -539,46 +733,625 @@ impl KnownDevice {
 
 // use std::alloc::System;
 
-#[cfg(feature = "cache")]
-type DetectionCache = Cache<String, Detection>;
+/// Settings which are passed down to the individual parsers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParseOptions {
+    pub template_expansion: TemplateExpansion,
+    pub device_match: DeviceMatchStrategy,
+    pub mobile_hint_fallback: MobileHintFallback,
+    pub disabled_quirks: QuirkSet,
+    /// Whether download managers and offline browsers are detected as such,
+    /// see [`crate::parsers::client::download_managers`].
+    pub download_managers: bool,
+    /// Whether Macs which are iPads asking for desktop sites are detected
+    /// as iPads, see [`crate::ipad`].
+    pub desktop_class_ipads: bool,
+}
+
+impl ParseOptions {
+    /// Whether `quirk` applies, see [`crate::quirks`].
+    pub fn quirk(&self, quirk: Quirk) -> bool {
+        !self.disabled_quirks.contains(quirk)
+    }
+}
+
+/// Scratch space used while parsing. Parsing allocates a fresh one each time,
+/// so services parsing at high rates can instead keep one per thread and pass
+/// it to [`DeviceDetector::parse_with_context`] to reuse its buffers.
+#[derive(Debug, Default)]
+pub struct ParseContext {
+    pub(crate) options: ParseOptions,
+    pub(crate) hint_mappings: Option<Arc<HintMappings>>,
+    pub(crate) hints_used: HintSet,
+    buf: String,
+}
+
+impl ParseContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `hint` influenced the detection, see [`crate::hints_used`].
+    pub(crate) fn use_hint(&mut self, hint: Hint) {
+        self.hints_used.insert(hint);
+    }
+
+    /// Expands `template` with `captures` into the scratch buffer.
+    pub(crate) fn expand(&mut self, template: &str, captures: &Captures<'_>) -> &str {
+        self.buf.clear();
+        captures.expand(template, &mut self.buf);
+        &self.buf
+    }
+
+    /// Same as [`Self::expand`], but treating `$1` followed by digits the way
+    /// php does, see [`crate::parsers::utils::expand`].
+    pub(crate) fn expand_php(&mut self, template: &str, captures: &Captures<'_>) -> &str {
+        self.buf.clear();
+        crate::parsers::utils::expand(template, &mut self.buf, captures);
+        &self.buf
+    }
+}
 
 #[derive(Clone)]
 pub struct DeviceDetector {
-    #[cfg(feature = "cache")]
-    caching: bool,
-    #[cfg(feature = "cache")]
-    cache: DetectionCache,
+    /// Shared by clones, so that [`Self::reload`] swaps it for all of them.
+    db: Arc<ArcSwap<Database>>,
+    options: ParseOptions,
+    on_unknown: Option<Arc<UnknownReporter>>,
+    approximation: Option<Arc<Approximation>>,
+    hint_mappings: Option<Arc<HintMappings>>,
+    header_fingerprint: Option<HeaderFingerprint>,
+    bot_spoof: Arc<BotSpoofRules>,
+    catalog: Option<Arc<dyn DeviceCatalog>>,
+    device_type_mapping: Option<Arc<DeviceTypeMapping>>,
+    rules: Option<Arc<Rules>>,
+    memory_budget: Option<Arc<MemoryBudget>>,
+    profiler: Option<Arc<RegexProfiler>>,
+    result_cache: Option<Arc<dyn ResultCache>>,
+    resilient: bool,
+    bot_claims: bool,
+    snapshot: Option<String>,
+    decode_user_agents: bool,
+    #[cfg(feature = "metrics")]
+    metrics: Arc<Metrics>,
 }
 
 impl DeviceDetector {
-    #[cfg(not(feature = "cache"))]
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self {}
+        Self::new_with_database(Database::embedded())
     }
 
-    #[cfg(feature = "cache")]
-    #[allow(clippy::new_without_default)]
-    pub fn new() -> Self {
+    /// Detects with `db` rather than the database compiled into the crate,
+    /// which is never loaded. Unlike [`Self::with_database`], this lets the
+    /// linker leave the embedded database out of binaries which don't use it
+    /// elsewhere, see [`crate::database::Database::from_bundle`].
+    pub fn new_with_database(db: Arc<Database>) -> Self {
         Self {
-            caching: false,
-            cache: Cache::new(0),
+            db: Arc::new(ArcSwap::new(db)),
+            options: ParseOptions::default(),
+            on_unknown: None,
+            approximation: None,
+            hint_mappings: None,
+            header_fingerprint: None,
+            bot_spoof: Arc::new(BotSpoofRules::new()),
+            catalog: None,
+            device_type_mapping: None,
+            rules: None,
+            memory_budget: None,
+            profiler: None,
+            result_cache: None,
+            resilient: false,
+            bot_claims: false,
+            snapshot: None,
+            decode_user_agents: false,
+            #[cfg(feature = "metrics")]
+            metrics: Arc::new(Metrics::default()),
         }
     }
 
+    /// Detects with the database in `dir`, laid out like `regexes/`, read
+    /// at runtime so that its rules can be updated without recompiling. Like
+    /// [`Self::new_with_database`], the embedded database is never loaded.
+    /// See [`Database::from_dir`].
+    pub fn with_database_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        Ok(Self::new_with_database(Arc::new(Database::from_dir(dir)?)))
+    }
+
+    /// A detector with a cache of up to `entries` detections, see
+    /// [`Self::with_result_cache`].
     #[cfg(feature = "cache")]
     pub fn new_with_cache(entries: u64) -> Self {
-        Self {
-            caching: true,
-            cache: Cache::new(entries),
+        Self::new().with_result_cache(entries)
+    }
+
+    /// Keeps the estimated memory of compiled regexes and cached detections
+    /// under `bytes`, freeing the least recently used regexes when it goes
+    /// over. See [`crate::memory`].
+    pub fn with_memory_budget(mut self, bytes: usize) -> Self {
+        let budget = MemoryBudget::new(bytes);
+        #[cfg(feature = "cache")]
+        let entries = budget.cache_entries();
+        self.memory_budget = Some(Arc::new(budget));
+
+        #[cfg(feature = "cache")]
+        if self
+            .result_cache
+            .as_ref()
+            .and_then(|x| x.capacity())
+            .is_some_and(|x| x > entries)
+        {
+            return self.with_result_cache(entries);
+        }
+
+        self
+    }
+
+    /// Caches up to `entries` detections, keyed by the user agent and client
+    /// hints, in a moka cache. Capped by a memory budget set before it. See
+    /// [`crate::result_cache`].
+    #[cfg(feature = "cache")]
+    pub fn with_result_cache(self, entries: u64) -> Self {
+        let entries = match &self.memory_budget {
+            Some(budget) => entries.min(budget.cache_entries()),
+            None => entries,
+        };
+        self.with_custom_result_cache(moka::sync::Cache::<CacheKey, Detection>::new(entries))
+    }
+
+    /// Caches detections in `cache` rather than in moka's.
+    pub fn with_custom_result_cache(mut self, cache: impl ResultCache + 'static) -> Self {
+        self.result_cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Turns the cache of [`Self::with_result_cache`] or
+    /// [`Self::new_with_cache`] back off.
+    pub fn without_result_cache(mut self) -> Self {
+        self.result_cache = None;
+        self
+    }
+
+    /// Estimates of the memory taken by compiled regexes and cached
+    /// detections. See [`crate::memory`].
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let cached_detections = self.result_cache.as_ref().map_or(0, |x| x.entry_count());
+
+        MemoryUsage::new(&self.db.load(), cached_detections)
+    }
+
+    /// Carries on past a stage of detection which fails, recording its error
+    /// on the detection, rather than failing the parse. Off by default, see
+    /// [`crate::resilience`].
+    pub fn with_resilience(mut self, resilient: bool) -> Self {
+        self.resilient = resilient;
+        self
+    }
+
+    /// Percent-decodes user agents which look encoded, such as those of some
+    /// logs, before detecting them. Off by default, see [`crate::decode`].
+    pub fn with_user_agent_decoding(mut self, decode: bool) -> Self {
+        self.decode_user_agents = decode;
+        self
+    }
+
+    /// `ua`, decoded if [`Self::with_user_agent_decoding`] is on and it looks
+    /// encoded.
+    pub(crate) fn decode<'a>(&self, ua: &'a str) -> Cow<'a, str> {
+        match self.decode_user_agents {
+            true => decode::decode_user_agent(ua),
+            false => Cow::Borrowed(ua),
+        }
+    }
+
+    /// Tags detections with `id`, that of the database snapshot detected
+    /// with, see [`crate::snapshots`].
+    pub fn with_snapshot(mut self, id: impl Into<String>) -> Self {
+        self.snapshot = Some(id.into());
+        self
+    }
+
+    /// The id of the database snapshot this detects with, if pinned to one.
+    pub fn snapshot(&self) -> Option<&str> {
+        self.snapshot.as_deref()
+    }
+
+    /// Goes on to detect the client, os and device of requests from bots,
+    /// which Matomo doesn't, and keeps them in [`Bot::claimed`], for
+    /// analyzing what bots pretend to be. Off by default.
+    pub fn with_bot_claims(mut self, bot_claims: bool) -> Self {
+        self.bot_claims = bot_claims;
+        self
+    }
+
+    /// Times the regexes matched in one in `sample_rate` detections, for
+    /// [`Self::regex_profile`]. See [`crate::profiler`].
+    pub fn with_regex_profiling(mut self, sample_rate: u64) -> Self {
+        self.profiler = Some(Arc::new(RegexProfiler::new(sample_rate)));
+        self
+    }
+
+    /// The time spent matching each regex so far, slowest first, if
+    /// profiling with [`Self::with_regex_profiling`].
+    pub fn regex_profile(&self) -> Option<RegexProfile> {
+        self.profiler.as_ref().map(|x| x.profile(&self.db.load()))
+    }
+
+    /// Keeps the share of unknown browsers and devices over the last
+    /// `parses` parses in the metrics, see [`crate::metrics`]. The metrics of
+    /// the detector start afresh.
+    #[cfg(feature = "metrics")]
+    pub fn with_unknowns_window(mut self, parses: usize) -> Self {
+        self.metrics = Arc::new(Metrics::with_unknowns_window(parses));
+        self
+    }
+
+    /// The version and entry counts of the database this detects with, see
+    /// [`Database::info`].
+    pub fn database_info(&self) -> DatabaseInfo {
+        self.db.load().info()
+    }
+
+    /// The detections of this detector and its clones so far, see
+    /// [`crate::metrics`].
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Counts a lookup in a detection cache, see [`crate::metrics`].
+    pub(crate) fn record_cache_lookup(&self, _hit: bool) {
+        #[cfg(feature = "metrics")]
+        self.metrics.record_cache_lookup(_hit);
+    }
+
+    /// Starts the regex profile afresh, such as after it was exported.
+    pub fn reset_regex_profile(&self) {
+        if let Some(profiler) = &self.profiler {
+            profiler.reset(&self.db.load());
+        }
+    }
+
+    /// Detects with `db` rather than the database compiled into the crate.
+    pub fn with_database(mut self, db: Arc<Database>) -> Self {
+        self.db = Arc::new(ArcSwap::new(db));
+        self
+    }
+
+    /// The database detections are made with.
+    pub fn database(&self) -> Arc<Database> {
+        self.db.load_full()
+    }
+
+    /// Swaps the database of this detector, and of its clones, for `db`,
+    /// without stopping detections. Those already under way finish with the
+    /// previous database, and the cached detections made with it are
+    /// dropped. Unlike [`Self::with_database`], this doesn't need `&mut`, so
+    /// that servers sharing a detector between threads can reload it.
+    pub fn reload(&self, db: Arc<Database>) {
+        self.db.store(db);
+
+        if let Some(result_cache) = &self.result_cache {
+            result_cache.clear();
         }
+        if let Some(approximation) = &self.approximation {
+            approximation.clear();
+        }
+        self.reset_regex_profile();
+    }
+
+    /// Reloads the database from `dir`, laid out like `regexes/`, such as
+    /// after its files were updated. A database which fails to load is an
+    /// error, and the previous one is kept. See [`Self::reload`].
+    pub fn reload_from_dir<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        self.reload(Arc::new(Database::from_dir(dir)?));
+        Ok(())
+    }
+
+    /// How to handle templates in the regex database which refer to capture
+    /// groups that don't exist. Lenient by default, see [`TemplateExpansion`].
+    pub fn with_template_expansion(mut self, mode: TemplateExpansion) -> Self {
+        self.options.template_expansion = mode;
+        self
+    }
+
+    /// Whether device lists use the first or the most specific matching brand.
+    /// First by default, see [`DeviceMatchStrategy`].
+    pub fn with_device_match(mut self, strategy: DeviceMatchStrategy) -> Self {
+        self.options.device_match = strategy;
+        self
     }
+
+    /// The device type to fall back to from `Sec-CH-UA-Mobile` when nothing
+    /// else determines one. Off by default, see [`MobileHintFallback`].
+    pub fn with_mobile_hint_fallback(mut self, fallback: MobileHintFallback) -> Self {
+        self.options.mobile_hint_fallback = fallback;
+        self
+    }
+
+    /// Detects download managers and offline browsers, such as wget or
+    /// HTTrack, as their own client type rather than as libraries, or not at
+    /// all. Off by default, as Matomo doesn't.
+    pub fn with_download_managers(mut self, detect: bool) -> Self {
+        self.options.download_managers = detect;
+        self
+    }
+
+    /// Detects Macs which are iPads asking for desktop sites as iPads, from
+    /// the tokens of iOS webviews and the form factors hint. Off by default,
+    /// as Matomo doesn't, see [`crate::ipad`].
+    pub fn with_desktop_class_ipads(mut self, detect: bool) -> Self {
+        self.options.desktop_class_ipads = detect;
+        self
+    }
+
+    /// Detects requests whose `X-Requested-With` header is `package` as the
+    /// mobile app `name`, taking precedence over `client/hints/apps.yml`.
+    pub fn with_hint_app(mut self, package: &str, name: &str) -> Self {
+        let mappings = Arc::make_mut(self.hint_mappings.get_or_insert_with(Default::default));
+        mappings.apps.insert(package.to_owned(), name.to_owned());
+        self
+    }
+
+    /// Detects requests whose `X-Requested-With` header is `package` as the
+    /// browser `name`, taking precedence over `client/hints/browsers.yml`.
+    pub fn with_hint_browser(mut self, package: &str, name: &str) -> Self {
+        let mappings = Arc::make_mut(self.hint_mappings.get_or_insert_with(Default::default));
+        mappings
+            .browsers
+            .insert(package.to_owned(), name.to_owned());
+        self
+    }
+
+    /// Applies `rules` to every detection, after the detector's own. See
+    /// [`crate::rules`].
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = Some(Arc::new(rules));
+        self
+    }
+
+    /// Turns off one of the hardcoded special cases, see [`crate::quirks`].
+    pub fn without_quirk(mut self, quirk: Quirk) -> Self {
+        self.options.disabled_quirks.insert(quirk);
+        self
+    }
+
+    /// Calls `callback` with detections missing their client, os or device
+    /// type, at most `limit` times per `interval`. See [`crate::unknown`].
+    pub fn on_unknown<F>(mut self, limit: u32, interval: Duration, callback: F) -> Self
+    where
+        F: Fn(&str, Option<&ClientHint>, &KnownDevice) + Send + Sync + 'static,
+    {
+        self.on_unknown = Some(UnknownReporter::new(limit, interval, Box::new(callback)));
+        self
+    }
+
+    /// Number of incomplete detections not passed to the [`Self::on_unknown`]
+    /// callback because of its rate limit.
+    pub fn unknowns_dropped(&self) -> u64 {
+        self.on_unknown.as_ref().map(|x| x.dropped()).unwrap_or(0)
+    }
+
+    /// Past `qps` requests per second, answers requests whose user agent
+    /// starts with the same `prefix_len` bytes, and has the same client hints,
+    /// as an earlier one with that earlier detection. See [`crate::approximate`].
+    pub fn with_approximation(mut self, qps: u32, prefix_len: usize) -> Self {
+        self.approximation = Some(Approximation::new(qps, prefix_len));
+        self
+    }
+
+    /// Number of requests answered approximately, see [`Self::with_approximation`].
+    pub fn approximations_served(&self) -> u64 {
+        self.approximation.as_ref().map(|x| x.served()).unwrap_or(0)
+    }
+
+    /// Also checks the full header map passed to
+    /// [`Self::parse_with_bot_likelihood`] for anomalies typical of scripts,
+    /// see [`crate::bot_likelihood`]. Off by default.
+    pub fn with_header_fingerprint(mut self, fingerprint: HeaderFingerprint) -> Self {
+        self.header_fingerprint = Some(fingerprint);
+        self
+    }
+
+    /// Which crawlers are flagged when they send client hints only browsers
+    /// send, see [`crate::bot_spoof`]. Defaults to the major search engines,
+    /// [`BotSpoofRules::none`] turns the check off.
+    pub fn with_bot_spoof_rules(mut self, rules: BotSpoofRules) -> Self {
+        self.bot_spoof = Arc::new(rules);
+        self
+    }
+
+    /// Looks up the specs of every detected device with a brand and model in
+    /// `catalog`, see [`crate::catalog`].
+    pub fn with_device_catalog(mut self, catalog: impl DeviceCatalog + 'static) -> Self {
+        self.catalog = Some(Arc::new(catalog));
+        self
+    }
+
+    /// Returns devices with their types mapped by `mapping`, such as
+    /// phablets as smartphones. See [`crate::device_type_mapping`].
+    pub fn with_device_type_mapping(mut self, mapping: DeviceTypeMapping) -> Self {
+        self.device_type_mapping = Some(Arc::new(mapping)).filter(|x| !x.is_empty());
+        self
+    }
+
+    fn enrich(&self, known: &mut KnownDevice) {
+        let Some(device) = &mut known.device else {
+            return;
+        };
+        if let Some(mapping) = &self.device_type_mapping {
+            if let Some(device_type) = &device.device_type {
+                device.device_type = Some(mapping.map(device_type).clone());
+            }
+        }
+        if let (Some(catalog), Some(brand), Some(model)) =
+            (&self.catalog, &device.brand, &device.model)
+        {
+            device.spec = catalog.lookup(brand, model);
+        }
+    }
+
+    /// Compiles every regex in the database now, rather than as requests
+    /// need them. See [`crate::warm_up`].
+    pub fn warm_up(&self) -> Result<()> {
+        self.warm_up_with_progress(|_| {})
+    }
+
+    /// Same as [`Self::warm_up`], calling `progress` as each list is
+    /// compiled, from whichever thread is compiling it.
+    pub fn warm_up_with_progress<F>(&self, progress: F) -> Result<()>
+    where
+        F: Fn(&WarmUpProgress) + Sync,
+    {
+        warm_up::warm_up(&self.db.load(), progress)
+    }
+
+    /// Parses the user agents in the file at `path`, one per line, so that
+    /// the regexes they need are compiled and, with a cache, their detections
+    /// cached before serving traffic. Blank lines and lines starting with `#`
+    /// are skipped. Returns how many user agents were parsed.
+    pub fn prime_from_file(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let path = path.as_ref();
+        let contents =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+
+        let uas: Vec<&str> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|x| !x.is_empty() && !x.starts_with('#'))
+            .collect();
+        warm_up::prime(self, &uas)?;
+
+        Ok(uas.len())
+    }
+
+    /// Parses each of `uas`, returning their detections in input order. See
+    /// [`crate::batch`].
+    pub fn parse_batch(&self, uas: &[&str]) -> Vec<Result<Detection>> {
+        self.parse_batch_with_cancellation(uas, &CancellationToken::new())
+            .detections
+    }
+
+    /// Same as [`Self::parse_batch`], parsing on rayon's thread pool.
+    #[cfg(feature = "parallel")]
+    pub fn par_parse_batch(&self, uas: &[&str]) -> Vec<Result<Detection>> {
+        batch::par_parse_batch(self, uas)
+    }
+
+    /// Parses `uas` in order until they run out or `cancel` is cancelled,
+    /// returning the detections parsed so far. See [`crate::batch`].
+    pub fn parse_batch_with_cancellation(&self, uas: &[&str], cancel: &CancellationToken) -> Batch {
+        batch::parse_batch_with_cancellation(self, uas, cancel)
+    }
+
+    /// Parses each user agent of `uas` as the returned iterator is advanced,
+    /// ending early once `cancel` is cancelled.
+    pub fn detect_stream<I>(
+        &self,
+        uas: I,
+        cancel: CancellationToken,
+    ) -> DetectStream<'_, I::IntoIter>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        DetectStream::new(self, uas.into_iter(), cancel)
+    }
+
+    /// Same as [`Self::parse`], on tokio's blocking pool rather than on the
+    /// executor. See [`crate::async_parse`].
+    #[cfg(feature = "tokio")]
+    pub async fn parse_async(
+        &self,
+        ua: &str,
+        headers: Option<Vec<(String, String)>>,
+    ) -> Result<Detection> {
+        crate::async_parse::parse_async(self, ua, headers).await
+    }
+
     pub fn parse(&self, ua: &str, headers: Option<Vec<(String, String)>>) -> Result<Detection> {
+        self.parse_with_context(ua, headers, &mut ParseContext::new())
+    }
+
+    /// Parses a request without a user agent from its client hints alone,
+    /// such as one whose user agent was stripped by a privacy proxy. The os
+    /// comes from `Sec-CH-UA-Platform`, the client from `Sec-CH-UA-Full-Version-List`,
+    /// `Sec-CH-UA` or `X-Requested-With`, and the device from `Sec-CH-UA-Model`
+    /// and `Sec-CH-UA-Form-Factors`. A bot is never detected. The same as
+    /// [`Self::parse`] with an empty user agent.
+    pub fn parse_hints_only(&self, headers: Vec<(String, String)>) -> Result<Detection> {
+        self.parse("", Some(headers))
+    }
+
+    /// Parses as [`Self::parse`] does, `headers` being the full header map of
+    /// the request, also scoring how likely it is to come from a bot. See
+    /// [`crate::bot_likelihood`].
+    pub fn parse_with_bot_likelihood(
+        &self,
+        ua: &str,
+        headers: &[(String, String)],
+    ) -> Result<(Detection, BotLikelihood)> {
+        let detection = self.parse(ua, Some(headers.to_vec()))?;
+        let likelihood =
+            bot_likelihood::bot_likelihood(&detection, headers, self.header_fingerprint.as_ref());
+        Ok((detection, likelihood))
+    }
+
+    /// Same as [`Self::parse`], reusing the buffers in `ctx` rather than
+    /// allocating new ones.
+    pub fn parse_with_context(
+        &self,
+        ua: &str,
+        headers: Option<Vec<(String, String)>>,
+        ctx: &mut ParseContext,
+    ) -> Result<Detection> {
         let client_hints = match headers {
             Some(headers) => Some(ClientHint::from_headers(headers)?),
             None => None,
         };
-        self.parse_client_hints(ua, client_hints)
+        self.parse_client_hints_with_context(ua, client_hints, ctx)
+    }
+
+    /// Parses as [`Self::parse`] does, also returning the allocations made
+    /// doing so. See [`crate::alloc_counters`] for the allocator this requires.
+    #[cfg(feature = "alloc-counters")]
+    pub fn parse_with_stats(
+        &self,
+        ua: &str,
+        headers: Option<Vec<(String, String)>>,
+    ) -> Result<(Detection, crate::alloc_counters::AllocationStats)> {
+        let (res, stats) = crate::alloc_counters::measure(|| self.parse(ua, headers));
+        Ok((res?, stats))
+    }
+
+    /// Parses a request and rewrites its user agent and hints into a reduced
+    /// form which detects the same way at a coarse level, see [`crate::normalize`].
+    pub fn normalize(
+        &self,
+        ua: &str,
+        headers: Option<Vec<(String, String)>>,
+    ) -> Result<NormalizedRequest> {
+        let client_hints = match headers {
+            Some(headers) => Some(ClientHint::from_headers(headers)?),
+            None => None,
+        };
+        let detection = self.parse_client_hints(ua, client_hints.clone())?;
+        Ok(normalize::normalize(&detection, ua, client_hints.as_ref()))
+    }
+
+    /// The coarse class of a request, without a full detection for most
+    /// requests from the major browsers, see [`crate::device_class`].
+    pub fn classify_device_class(
+        &self,
+        ua: &str,
+        client_hints: Option<ClientHint>,
+    ) -> Result<DeviceClass> {
+        if self.rules.is_none() && self.device_type_mapping.is_none() {
+            if let Some(class) = device_class::fast_path(ua, client_hints.as_ref()) {
+                return Ok(class);
+            }
+        }
+        Ok(DeviceClass::of(&self.parse_client_hints(ua, client_hints)?))
     }
 
     pub fn parse_client_hints(
@@ -586,40 +1359,252 @@ impl DeviceDetector {
         ua: &str,
         client_hints: Option<ClientHint>,
     ) -> Result<Detection> {
-        let parse = || {
-            if let Some(bot) = bot::lookup_bot(ua)? {
-                return Ok(Detection::Bot(bot));
-            }
+        self.parse_client_hints_with_context(ua, client_hints, &mut ParseContext::new())
+    }
+
+    fn drops_bot(&self, ua: &str, hints: Option<&ClientHint>, bot: &Detection) -> Result<bool> {
+        match &self.rules {
+            Some(rules) => rules.drops_bot(ua, hints, bot, &self.options),
+            None => Ok(false),
+        }
+    }
+
+    fn apply_rules(
+        &self,
+        ua: &str,
+        hints: Option<&ClientHint>,
+        detection: &mut Detection,
+    ) -> Result<()> {
+        QUIRK_RULES.apply(ua, hints, detection, &self.options)?;
+        if let Some(rules) = &self.rules {
+            rules.apply(ua, hints, detection, &self.options)?;
+        }
+        Ok(())
+    }
+
+    pub fn parse_client_hints_with_context(
+        &self,
+        ua: &str,
+        client_hints: Option<ClientHint>,
+        ctx: &mut ParseContext,
+    ) -> Result<Detection> {
+        self.measured(ua, || {
+            let decoded = self.decode(ua);
+            let res = self.detect_with_context(&decoded, client_hints.as_ref(), ctx);
+            let res = self.finish(res, client_hints.as_ref())?;
+            Ok(match decoded {
+                Cow::Owned(_) => res.with_user_agent_decoded(),
+                Cow::Borrowed(_) => res,
+            })
+        })
+    }
 
-            let os = oss::lookup(ua, client_hints.as_ref())?;
+    /// The detection `detect` makes, counted in the metrics, see
+    /// [`crate::metrics`].
+    pub(crate) fn measured(
+        &self,
+        _ua: &str,
+        detect: impl FnOnce() -> Result<Detection>,
+    ) -> Result<Detection> {
+        #[cfg(feature = "metrics")]
+        {
+            let start = std::time::Instant::now();
+            let res = detect();
+            self.metrics.record(_ua, &res, start.elapsed());
+            res
+        }
 
-            let client = client::lookup(ua, client_hints.as_ref())?;
+        #[cfg(not(feature = "metrics"))]
+        detect()
+    }
 
-            let device = device::lookup(ua, client.as_ref(), client_hints.as_ref(), os.as_ref())?;
+    /// Flags and enriches a detection, which may have come from a cache
+    /// filled by the same user agent with other hints.
+    pub(crate) fn finish(
+        &self,
+        mut res: Result<Detection>,
+        client_hints: Option<&ClientHint>,
+    ) -> Result<Detection> {
+        match &mut res {
+            Ok(Detection::Bot(bot)) => {
+                self.bot_spoof.apply(bot, client_hints);
+                if let Some(claimed) = &mut bot.claimed {
+                    self.enrich(claimed);
+                }
+                bot.snapshot.clone_from(&self.snapshot);
+            }
+            Ok(Detection::Known(known)) => {
+                self.enrich(known);
+                known.snapshot.clone_from(&self.snapshot);
+            }
+            Err(_) => {}
+        }
+        if let Some(budget) = &self.memory_budget {
+            budget.enforce(&self.db.load(), self.memory_usage());
+        }
+        res
+    }
 
-            let known = Detection::Known(KnownDevice { client, device, os });
+    /// The same detector without its detection caches.
+    pub(crate) fn uncached(self) -> Self {
+        Self {
+            result_cache: None,
+            ..self
+        }
+    }
 
-            Ok::<_, anyhow::Error>(known)
+    pub(crate) fn detect_with_context(
+        &self,
+        ua: &str,
+        client_hints: Option<&ClientHint>,
+        ctx: &mut ParseContext,
+    ) -> Result<Detection> {
+        let Some(result_cache) = &self.result_cache else {
+            return self.lookup_with_context(ua, client_hints, ctx);
         };
 
-        #[cfg(feature = "cache")]
-        {
-            if !self.caching {
-                return Ok(parse()?);
+        let key = CacheKey::new(ua, client_hints);
+        let cached = result_cache.get(&key);
+        self.record_cache_lookup(cached.is_some());
+        if let Some(res) = cached {
+            return Ok(res);
+        }
+
+        let res = self.lookup_with_context(ua, client_hints, ctx)?;
+        if res.is_cacheable() {
+            result_cache.insert(key, res.clone());
+        }
+        Ok(res)
+    }
+
+    fn lookup_with_context(
+        &self,
+        ua: &str,
+        client_hints: Option<&ClientHint>,
+        ctx: &mut ParseContext,
+    ) -> Result<Detection> {
+        ctx.options = self.options;
+        ctx.hint_mappings.clone_from(&self.hint_mappings);
+        ctx.hints_used = HintSet::default();
+        // kept for the whole detection, even if the database is reloaded
+        let db = self.db.load_full();
+        let db = &*db;
+
+        // without a user agent only the client hints are left to go on, which
+        // never identify a bot.
+        let hints_only = ua.trim().is_empty();
+
+        let mut lookup = || {
+            let mut recovery = Recovery::new(self.resilient);
+
+            let mut bot = None;
+            if !hints_only {
+                if let Some(found) = recovery.recover(Stage::Bot, bot::lookup_bot(ua, db))? {
+                    let mut res = Detection::Bot(found);
+                    if !self.drops_bot(ua, client_hints, &res)? {
+                        if !self.bot_claims {
+                            self.apply_rules(ua, client_hints, &mut res)?;
+                            return Ok(res);
+                        }
+                        bot = res.get_bot().cloned();
+                    }
+                }
             }
 
-            if let Some(res) = self.cache.get(ua) {
-                return Ok(res);
+            let mut os = recovery.recover(Stage::Os, oss::lookup(ua, client_hints, db, ctx))?;
+
+            let mut client =
+                recovery.recover(Stage::Client, client::lookup(ua, client_hints, db, ctx))?;
+
+            // proxy browsers such as opera mini may pass along the user agent of
+            // the device they run on, which says more about the os and device
+            // than their own does. The client is still the proxy browser.
+            let device_ua = client_hints
+                .as_ref()
+                .and_then(|x| x.device_user_agent.as_deref())
+                .filter(|x| *x != ua);
+
+            let mut device = None;
+            if let Some(device_ua) = device_ua {
+                let hints_used = ctx.hints_used;
+                let device_os = oss::lookup(device_ua, client_hints, db, ctx);
+                if let Some(device_os) = recovery.recover(Stage::Os, device_os)? {
+                    os = Some(device_os);
+                }
+
+                let device_ua_device = device::lookup(
+                    device_ua,
+                    client.as_ref(),
+                    client_hints,
+                    os.as_ref(),
+                    db,
+                    ctx,
+                );
+                device = recovery
+                    .recover(Stage::Device, device_ua_device)?
+                    .filter(|x| x.brand.is_some() || x.model.is_some());
+                if device.is_none() {
+                    ctx.hints_used = hints_used;
+                }
+            }
+
+            if device.is_none() {
+                let ua_device =
+                    device::lookup(ua, client.as_ref(), client_hints, os.as_ref(), db, ctx);
+                device = recovery.recover(Stage::Device, ua_device)?;
+            }
+
+            if self.options.desktop_class_ipads {
+                ipad::reclassify(ua, client_hints, &mut os, &mut client, &mut device, ctx)?;
+            }
+
+            let known = KnownDevice {
+                client,
+                device,
+                os,
+                stage_errors: recovery.into_errors(),
+                snapshot: None,
+                hints_used: ctx.hints_used.to_vec(),
+                user_agent_decoded: false,
+                approximate: false,
             };
+            let mut res = match bot {
+                Some(bot) => Detection::Bot(Bot {
+                    claimed: Some(Box::new(known)),
+                    ..bot
+                }),
+                None => Detection::Known(known),
+            };
+            self.apply_rules(ua, client_hints, &mut res)?;
+
+            // a stage which failed isn't a gap in the database
+            if let (Some(on_unknown), Detection::Known(known)) = (&self.on_unknown, &res) {
+                if known.stage_errors.is_empty() {
+                    on_unknown.report(ua, client_hints, known);
+                }
+            }
 
-            let known = parse()?;
+            Ok::<_, anyhow::Error>(res)
+        };
 
-            self.cache.insert(ua.to_owned(), known.clone());
+        let mut detect = || match &self.profiler {
+            Some(profiler) => profiler.run(&mut lookup),
+            None => lookup(),
+        };
 
-            Ok(known)
-        }
+        match &self.approximation {
+            Some(approximation) => {
+                if let Some(res) = approximation.get(ua, client_hints) {
+                    return Ok(res);
+                }
 
-        #[cfg(not(feature = "cache"))]
-        parse()
+                let res = detect()?;
+                if res.stage_errors().is_empty() {
+                    approximation.insert(ua, client_hints, &res);
+                }
+                Ok(res)
+            }
+            None => detect(),
+        }
     }
 }
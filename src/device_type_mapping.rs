@@ -0,0 +1,69 @@
+//! Collapsing the fine grained device types into the buckets a service
+//! actually reports on, once for every detection rather than in each of its
+//! consumers.
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().with_device_type_mapping(
+//!     DeviceTypeMapping::new()
+//!         .collapse(DeviceType::Phablet, DeviceType::SmartPhone)
+//!         .collapse(DeviceType::SmartDisplay, DeviceType::Television),
+//! );
+//! ```
+//!
+//! Mappings only change the type detections are returned with. Detection
+//! itself, such as telling a tablet from a phablet by its screen, and the
+//! detection caches, still use the database's types, so that a detector
+//! with a mapping and one without can share a database and agree on
+//! everything else. Types map once, so collapsing phablets into smartphones
+//! and smartphones into phones doesn't make phablets phones.
+
+use anyhow::{anyhow, Result};
+
+use crate::parsers::device::DeviceType;
+
+#[derive(Clone, Debug, Default)]
+pub struct DeviceTypeMapping {
+    mappings: Vec<(DeviceType, DeviceType)>,
+}
+
+impl DeviceTypeMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `from` as `to`, replacing any mapping of `from` before.
+    pub fn collapse(mut self, from: DeviceType, to: DeviceType) -> Self {
+        self.mappings.retain(|(x, _)| *x != from);
+        if from != to {
+            self.mappings.push((from, to));
+        }
+        self
+    }
+
+    /// The mapping of pairs of type names, as [`DeviceType::parse`] takes
+    /// them, such as `("phablet", "smartphone")`, for mappings read from
+    /// configuration.
+    pub fn from_names<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<Self> {
+        let parse = |name: &str| {
+            DeviceType::parse(name).ok_or_else(|| anyhow!("unknown device type {}", name))
+        };
+
+        let mut res = Self::new();
+        for (from, to) in pairs {
+            res = res.collapse(parse(from)?, parse(to)?);
+        }
+        Ok(res)
+    }
+
+    /// What `device_type` is returned as.
+    pub fn map<'a>(&'a self, device_type: &'a DeviceType) -> &'a DeviceType {
+        self.mappings
+            .iter()
+            .find(|(from, _)| from == device_type)
+            .map_or(device_type, |(_, to)| to)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+}
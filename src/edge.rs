@@ -0,0 +1,152 @@
+//! Adapters for running the detector in an edge function, such as a
+//! Cloudflare Worker or a Fastly Compute service, which passes the request on
+//! with `x-detected-*` headers for the origin to use.
+//!
+//! The platform only has to look up a request header by name. On Cloudflare
+//! Workers, with the `worker` crate:
+//!
+//! ```ignore
+//! static DETECTOR: Lazy<DeviceDetector> = Lazy::new(DeviceDetector::new);
+//!
+//! #[event(fetch)]
+//! async fn fetch(req: Request, env: Env, _ctx: Context) -> Result<Response> {
+//!     let headers = edge::annotate(&DETECTOR, |name| req.headers().get(name).ok().flatten())
+//!         .map_err(|e| Error::RustError(e.to_string()))?;
+//!
+//!     let mut init = RequestInit::new();
+//!     let mut forwarded = req.headers().clone();
+//!     for (name, value) in headers {
+//!         forwarded.set(name, &value)?;
+//!     }
+//!     init.with_headers(forwarded);
+//!     Fetch::Request(Request::new_with_init(req.url()?.as_str(), &init)?).send().await
+//! }
+//! ```
+//!
+//! On Fastly Compute, with the `fastly` crate:
+//!
+//! ```ignore
+//! #[fastly::main]
+//! fn main(mut req: Request) -> Result<Response, Error> {
+//!     let headers = edge::annotate(&DETECTOR, |name| req.get_header_str(name).map(str::to_owned))?;
+//!     for (name, value) in headers {
+//!         req.set_header(name, value);
+//!     }
+//!     Ok(req.send("origin")?)
+//! }
+//! ```
+//!
+//! Every header in [`DETECTION_HEADERS`] is returned, with an empty value when
+//! it wasn't detected, so that setting them all replaces any a client sent
+//! itself.
+
+use anyhow::Result;
+
+use crate::client_hints::DEVICE_USER_AGENT_HEADERS;
+use crate::device_detector::{Detection, DeviceDetector};
+
+/// The request headers the detector reads, besides `user-agent`.
+pub const HINT_HEADERS: [&str; 10] = [
+    "sec-ch-ua",
+    "sec-ch-ua-arch",
+    "sec-ch-ua-bitness",
+    "sec-ch-ua-form-factors",
+    "sec-ch-ua-full-version",
+    "sec-ch-ua-full-version-list",
+    "sec-ch-ua-mobile",
+    "sec-ch-ua-model",
+    "sec-ch-ua-platform",
+    "sec-ch-ua-platform-version",
+];
+
+/// The headers returned by [`detection_headers`].
+pub const DETECTION_HEADERS: [&str; 10] = [
+    "x-detected-bot",
+    "x-detected-bot-name",
+    "x-detected-client-type",
+    "x-detected-client",
+    "x-detected-client-version",
+    "x-detected-os",
+    "x-detected-os-version",
+    "x-detected-device-type",
+    "x-detected-device-brand",
+    "x-detected-device-model",
+];
+
+/// A request as seen by an edge function.
+pub trait EdgeRequest {
+    /// The value of the header `name`, given in lowercase.
+    fn header(&self, name: &str) -> Option<String>;
+}
+
+impl<F> EdgeRequest for F
+where
+    F: Fn(&str) -> Option<String>,
+{
+    fn header(&self, name: &str) -> Option<String> {
+        self(name)
+    }
+}
+
+impl EdgeRequest for [(String, String)] {
+    fn header(&self, name: &str) -> Option<String> {
+        self.iter()
+            .find(|(x, _)| x.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    }
+}
+
+/// The user agent and the headers of `request` the detector reads, as
+/// [`DeviceDetector::parse`] takes them.
+pub fn request_headers(request: &(impl EdgeRequest + ?Sized)) -> (String, Vec<(String, String)>) {
+    let ua = request.header("user-agent").unwrap_or_default();
+
+    let headers = HINT_HEADERS
+        .iter()
+        .chain(["x-requested-with"].iter())
+        .chain(DEVICE_USER_AGENT_HEADERS.iter())
+        .filter_map(|name| Some((name.to_string(), request.header(name)?)))
+        .collect();
+
+    (ua, headers)
+}
+
+/// The `x-detected-*` headers for `detection`, in the order of
+/// [`DETECTION_HEADERS`].
+pub fn detection_headers(detection: &Detection) -> Vec<(&'static str, String)> {
+    let detection = detection.to_v1();
+
+    let client = detection.client.as_ref();
+    let os = detection.os.as_ref();
+    let device = detection.device.as_ref();
+
+    let values = [
+        Some(detection.is_bot.to_string()),
+        detection.bot.map(|x| x.name),
+        client.map(|x| x.client_type.clone()),
+        client.map(|x| x.name.clone()),
+        client.and_then(|x| x.version.clone()),
+        os.map(|x| x.name.clone()),
+        os.and_then(|x| x.version.clone()),
+        device.and_then(|x| x.device_type.clone()),
+        device.and_then(|x| x.brand.clone()),
+        device.and_then(|x| x.model.clone()),
+    ];
+
+    DETECTION_HEADERS
+        .into_iter()
+        .zip(values)
+        .map(|(name, value)| (name, value.unwrap_or_default()))
+        .collect()
+}
+
+/// Detects `request`, returning the headers to set on it before passing it
+/// on.
+pub fn annotate(
+    detector: &DeviceDetector,
+    request: &(impl EdgeRequest + ?Sized),
+) -> Result<Vec<(&'static str, String)>> {
+    let (ua, headers) = request_headers(request);
+    let detection = detector.parse(&ua, Some(headers))?;
+    Ok(detection_headers(&detection))
+}
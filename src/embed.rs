@@ -0,0 +1,124 @@
+//! Embedding an overlay of your own into the binary at compile time, for
+//! fully static deployments which don't read anything from disk at startup:
+//!
+//! ```ignore
+//! use rust_device_detector::include_device_db;
+//! use rust_device_detector::embed::EmbeddedOverlay;
+//!
+//! static DB: EmbeddedOverlay = include_device_db!("../regexes/overlay.yml");
+//!
+//! let detector = DeviceDetector::new_with_database(DB.database()?);
+//! ```
+//!
+//! The path is resolved like that of `include_str!`, relative to the file the
+//! macro is in, and the file is an overlay as described in
+//! [`crate::overlay`]. Each file it changes is checked with
+//! [`crate::validation`] once merged, the same way the built-in lists are,
+//! which tolerates templates referring to groups their regex doesn't have,
+//! as upstream relies on those. To fail the build rather than the first
+//! load, call [`check_overlay_file`] from the build script as well:
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     rust_device_detector::embed::check_overlay_file("regexes/overlay.yml").unwrap();
+//! }
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::OnceCell;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::database::Database;
+use crate::overlay::Overlay;
+use crate::validation::{validate_regex_str, RegexFileKind, ValidationErrorKind};
+
+/// Embeds the overlay at `path` into the binary, as an [`EmbeddedOverlay`].
+/// See [`crate::embed`].
+#[macro_export]
+macro_rules! include_device_db {
+    ($path:literal) => {
+        $crate::embed::EmbeddedOverlay::new($path, include_str!($path))
+    };
+}
+
+/// An overlay embedded by [`include_device_db`], loaded on first use.
+pub struct EmbeddedOverlay {
+    path: &'static str,
+    yaml: &'static str,
+    database: OnceCell<Arc<Database>>,
+}
+
+impl EmbeddedOverlay {
+    #[doc(hidden)]
+    pub const fn new(path: &'static str, yaml: &'static str) -> Self {
+        Self {
+            path,
+            yaml,
+            database: OnceCell::new(),
+        }
+    }
+
+    /// The path the overlay was embedded from.
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// The overlay, once validated.
+    pub fn overlay(&self) -> Result<Overlay> {
+        check_overlay(self.yaml).with_context(|| format!("loading {}", self.path))
+    }
+
+    /// The embedded database with the overlay merged in. Loaded once and
+    /// shared.
+    pub fn database(&self) -> Result<Arc<Database>> {
+        self.database
+            .get_or_try_init(|| {
+                let db = Database::embedded_with_overlay(&self.overlay()?)
+                    .with_context(|| format!("loading {}", self.path))?;
+                Ok(Arc::new(db))
+            })
+            .cloned()
+    }
+}
+
+/// Checks the overlay at `path` as [`include_device_db`] does when it is
+/// loaded, for build scripts. Also has cargo rerun the build script when the
+/// file changes.
+pub fn check_overlay_file<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    println!("cargo:rerun-if-changed={}", path.display());
+    let yaml =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    check_overlay(&yaml).with_context(|| format!("loading {}", path.display()))?;
+    Ok(())
+}
+
+fn check_overlay(yaml: &str) -> Result<Overlay> {
+    let overlay = Overlay::from_yaml(yaml)?;
+
+    let mut problems = Vec::new();
+    for file in overlay.files() {
+        let Some(kind) = RegexFileKind::for_path(file) else {
+            continue;
+        };
+        let base = device_detector_data::file(file)
+            .ok_or_else(|| anyhow!("{} is not part of the database", file))?;
+        let merged = overlay.apply(file, base.to_owned())?;
+
+        // as for the built-in lists, see tests/php_tests/validation.rs
+        problems.extend(
+            validate_regex_str(&merged, kind)
+                .into_iter()
+                .filter(|x| !matches!(x.kind, ValidationErrorKind::CaptureGroupOutOfRange { .. }))
+                .map(|x| format!("{}: {}", file, x.kind)),
+        );
+    }
+
+    if !problems.is_empty() {
+        return Err(anyhow!("invalid overlay:\n{}", problems.join("\n")));
+    }
+    Ok(overlay)
+}
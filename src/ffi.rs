@@ -1,6 +1,9 @@
 #![allow(clippy::missing_safety_doc)]
 
+use anyhow::{anyhow, Result};
 use libc::c_char;
+use serde_json::{json, Value};
+
 use std::ffi::{CStr, CString};
 use std::ptr::{null, null_mut};
 
@@ -596,3 +599,69 @@ pub unsafe extern "C" fn rdd_free_string(rdd: *mut c_char) {
     let cstr = unsafe { CString::from_raw(rdd) };
     drop(cstr);
 }
+
+// A json api, for languages where walking the structs above is more trouble
+// than parsing a string, such as go through cgo.
+
+/// Creates a detector, to be freed with `device_detector_free`.
+#[no_mangle]
+pub extern "C" fn device_detector_new() -> *mut RDDDeviceDetector {
+    Box::into_raw(Box::new(RDDDeviceDetector {
+        dd: DeviceDetector::new(),
+    }))
+}
+
+fn parse_json(dd: &DeviceDetector, ua: &CStr, headers: Option<&CStr>) -> Result<Value> {
+    let ua = ua.to_str()?;
+    let headers = match headers {
+        None => None,
+        Some(headers) => {
+            let headers: Vec<(String, String)> =
+                serde_json::from_str(headers.to_str()?).map_err(|err| {
+                    anyhow!("headers is not an array of name and value pairs: {}", err)
+                })?;
+            Some(headers)
+        }
+    };
+
+    Ok(dd.parse(ua, headers)?.to_value())
+}
+
+/// Detects `ua`, with `headers` as a json array of the other headers of the
+/// request, as name and value pairs in the order they were received, such as
+/// `[["sec-ch-ua-model", "\"Pixel 7\""]]`, or null.
+///
+/// Returns the json of the detection, as the cli prints it, or
+/// `{"error": "..."}`. The string is freed with `device_detector_free_string`.
+#[no_mangle]
+pub unsafe extern "C" fn device_detector_parse_json(
+    dd: *const RDDDeviceDetector,
+    ua: *const c_char,
+    headers: *const c_char,
+) -> *mut c_char {
+    let dd = unsafe { &*dd };
+    let ua = unsafe { CStr::from_ptr(ua) };
+    let headers = (!headers.is_null()).then(|| unsafe { CStr::from_ptr(headers) });
+
+    let res = match parse_json(&dd.dd, ua, headers) {
+        Ok(res) => res,
+        Err(err) => json!({ "error": format!("{:#}", err) }),
+    };
+
+    // json escapes control characters, so there's never a nul in it.
+    CString::new(res.to_string()).unwrap().into_raw()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn device_detector_free(dd: *mut RDDDeviceDetector) {
+    unsafe {
+        drop(Box::from_raw(dd));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn device_detector_free_string(s: *mut c_char) {
+    unsafe {
+        drop(CString::from_raw(s));
+    }
+}
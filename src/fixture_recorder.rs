@@ -0,0 +1,232 @@
+//! Samples production traffic into the same fixture yaml format the matomo
+//! tests use, so that user agents detected incorrectly can be corrected by hand
+//! and contributed as regression tests.
+//!
+//! Recorded user agents have anything that looks like personal data replaced,
+//! and only client hint headers are kept. Redaction is best effort, recorded
+//! fixtures should still be reviewed before being shared.
+
+use anyhow::Result;
+
+use once_cell::sync::Lazy;
+
+use serde_yaml::{Mapping, Value};
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::device_detector::{Detection, KnownDevice};
+use crate::parsers::utils::SafeRegex as Regex;
+
+pub struct FixtureRecorder {
+    sample_every: u64,
+    limit: usize,
+    seen: AtomicU64,
+    fixtures: Mutex<Vec<Value>>,
+}
+
+impl FixtureRecorder {
+    /// Records one in every `sample_every` detections passed to [`Self::record`].
+    pub fn new(sample_every: u64) -> Self {
+        Self {
+            sample_every: sample_every.max(1),
+            limit: usize::MAX,
+            seen: AtomicU64::new(0),
+            fixtures: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Stop recording once this many fixtures have been kept.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Offers a detection to the recorder, returning whether it was sampled.
+    pub fn record(
+        &self,
+        ua: &str,
+        headers: Option<&[(String, String)]>,
+        detection: &Detection,
+    ) -> bool {
+        let seen = self.seen.fetch_add(1, Ordering::Relaxed);
+        if !seen.is_multiple_of(self.sample_every) {
+            return false;
+        }
+
+        let mut fixtures = self.fixtures.lock().expect("fixture recorder lock");
+        if fixtures.len() >= self.limit {
+            return false;
+        }
+
+        fixtures.push(fixture(ua, headers, detection));
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.fixtures.lock().expect("fixture recorder lock").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The recorded fixtures as a yaml document, clearing them from the recorder.
+    pub fn drain_yaml(&self) -> Result<String> {
+        let fixtures = std::mem::take(&mut *self.fixtures.lock().expect("fixture recorder lock"));
+        let res = serde_yaml::to_string(&Value::Sequence(fixtures))?;
+        Ok(res)
+    }
+
+    /// Writes the recorded fixtures to a file, clearing them from the recorder.
+    pub fn drain_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, self.drain_yaml()?)?;
+        Ok(())
+    }
+}
+
+/// Replaces emails, uuids, long hex tokens and url query strings, none of which
+/// the detector relies upon, with placeholders.
+pub fn redact_user_agent(ua: &str) -> String {
+    static EMAIL: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}").unwrap()
+    });
+    static UUID: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b").unwrap()
+    });
+    static TOKEN: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\b[0-9a-f]{24,}\b").unwrap());
+    static QUERY: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?i)(https?://[^\s?;)]*)\?[^\s;)]*").unwrap());
+
+    let ua = EMAIL.replace_all(ua, "user@example.com");
+    let ua = UUID.replace_all(&ua, "00000000-0000-0000-0000-000000000000");
+    let ua = TOKEN.replace_all(&ua, "0000000000000000");
+    let ua = QUERY.replace_all(&ua, "$1");
+
+    ua.into_owned()
+}
+
+fn is_client_hint_header(name: &str) -> bool {
+    let name = name.replace('_', "-").to_lowercase();
+    let name = name.trim_start_matches("http-");
+    name.starts_with("sec-ch-ua") || name == "x-requested-with"
+}
+
+fn string(value: Option<&str>) -> Value {
+    Value::String(value.unwrap_or("").to_owned())
+}
+
+fn mapping<const N: usize>(entries: [(&str, Value); N]) -> Value {
+    let mut res = Mapping::new();
+    for (k, v) in entries {
+        res.insert(Value::String(k.to_owned()), v);
+    }
+    Value::Mapping(res)
+}
+
+fn known_fixture(known: &KnownDevice) -> [(&'static str, Value); 5] {
+    // php renders an empty os as an empty array, and a missing client as null
+    let os = match &known.os {
+        Some(os) => mapping([
+            ("name", string(Some(&os.name))),
+            ("version", string(os.version.as_deref())),
+            ("platform", string(os.platform.as_deref())),
+        ]),
+        None => Value::Sequence(Vec::new()),
+    };
+
+    let client = match &known.client {
+        Some(client) => mapping([
+            ("type", string(Some(client.r#type.as_str()))),
+            ("name", string(Some(&client.name))),
+            ("version", string(client.version.as_deref())),
+            ("engine", string(client.engine.as_deref())),
+            ("engine_version", string(client.engine_version.as_deref())),
+        ]),
+        None => Value::Null,
+    };
+
+    let device = known.device.as_ref();
+    let device = mapping([
+        (
+            "type",
+            string(
+                device
+                    .and_then(|x| x.device_type.as_ref())
+                    .map(|x| x.as_str()),
+            ),
+        ),
+        ("brand", string(device.and_then(|x| x.brand.as_deref()))),
+        ("model", string(device.and_then(|x| x.model.as_deref()))),
+    ]);
+
+    let os_family = known
+        .os
+        .as_ref()
+        .and_then(|x| x.family.as_deref())
+        .unwrap_or("Unknown");
+
+    let browser_family = known
+        .client
+        .as_ref()
+        .and_then(|x| x.browser.as_ref())
+        .and_then(|x| x.family.as_deref())
+        .unwrap_or("Unknown");
+
+    [
+        ("os", os),
+        ("client", client),
+        ("device", device),
+        ("os_family", string(Some(os_family))),
+        ("browser_family", string(Some(browser_family))),
+    ]
+}
+
+/// A single fixture entry for a detection.
+pub fn fixture(ua: &str, headers: Option<&[(String, String)]>, detection: &Detection) -> Value {
+    let mut res = Mapping::new();
+    res.insert("user_agent".into(), Value::String(redact_user_agent(ua)));
+
+    let headers = headers
+        .unwrap_or_default()
+        .iter()
+        .filter(|(name, _)| is_client_hint_header(name))
+        .map(|(name, value)| {
+            (
+                Value::String(name.to_owned()),
+                Value::String(value.to_owned()),
+            )
+        })
+        .collect::<Mapping>();
+
+    if !headers.is_empty() {
+        res.insert("headers".into(), Value::Mapping(headers));
+    }
+
+    match detection {
+        Detection::Bot(bot) => {
+            let producer = bot.producer.as_ref();
+            let bot = mapping([
+                ("name", string(Some(&bot.name))),
+                ("category", string(bot.category.as_deref())),
+                ("url", string(bot.url.as_deref())),
+                (
+                    "producer",
+                    mapping([
+                        ("name", string(producer.and_then(|x| x.name.as_deref()))),
+                        ("url", string(producer.and_then(|x| x.url.as_deref()))),
+                    ]),
+                ),
+            ]);
+            res.insert("bot".into(), bot);
+        }
+        Detection::Known(known) => {
+            for (k, v) in known_fixture(known) {
+                res.insert(k.into(), v);
+            }
+        }
+    }
+
+    Value::Mapping(res)
+}
@@ -0,0 +1,229 @@
+//! A grpc service of detections, for service meshes where the overhead of
+//! http and json matters, defined by `proto/device_detector.proto`:
+//!
+//! ```ignore
+//! let detector = Arc::new(DeviceDetector::new());
+//! grpc::serve(([127, 0, 0, 1], 50051).into(), detector, shutdown).await?;
+//! ```
+//!
+//! `Detect` takes the user agent and the other headers of a request, as
+//! `GET /detect` of the http server does, and answers with the whole
+//! detection. Its messages mirror the json of [`Detection::to_value`], with
+//! missing values unset rather than null. Detections run on tokio's blocking
+//! pool, see [`crate::async_parse`].
+//!
+//! The generated server and client are in [`proto`], for those which rather
+//! add the service to a server of their own:
+//!
+//! ```ignore
+//! Server::builder()
+//!     .add_service(grpc::service(detector))
+//!     .add_service(other)
+//!     .serve(addr)
+//!     .await?;
+//! ```
+//!
+//! [`Detection::to_value`]: crate::device_detector::Detection::to_value
+
+use anyhow::Result;
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use crate::device_detector::{Detection, DeviceDetector, KnownDevice};
+use crate::parsers::{client, device, oss};
+use crate::resilience::StageError;
+
+/// The messages, server and client generated from
+/// `proto/device_detector.proto`.
+pub mod proto {
+    tonic::include_proto!("device_detector.v1");
+}
+
+use proto::detect_response;
+use proto::device_detector_server::DeviceDetectorServer;
+
+fn client(client: client::Client) -> proto::Client {
+    proto::Client {
+        name: client.name,
+        version: client.version,
+        r#type: client.r#type.as_str().to_owned(),
+        short_code: client.short_code,
+        engine: client.engine,
+        engine_version: client.engine_version,
+    }
+}
+
+fn device(device: device::Device) -> proto::Device {
+    proto::Device {
+        r#type: device.device_type.map(|x| x.as_str().to_owned()),
+        brand: device.brand,
+        model: device.model,
+        mobile_client_hint: device.mobile_client_hint,
+        touch_enabled: device.touch_enabled,
+        spec: device.spec.map(|x| proto::DeviceSpec {
+            screen_size: x.screen_size.map(f64::from),
+            ram_mb: x.ram_mb,
+            release_year: x.release_year.map(u32::from),
+        }),
+        hbbtv: device.hbbtv.map(|x| proto::HbbTv {
+            version: x.version,
+            capabilities: x.capabilities,
+            vendor: x.vendor,
+            model: x.model,
+            software_version: x.software_version,
+            hardware_version: x.hardware_version,
+            family: x.family,
+            operator: x.operator.map(|x| proto::TvOperator {
+                name: x.name.to_owned(),
+                country: x.country.to_owned(),
+            }),
+        }),
+    }
+}
+
+fn os(os: oss::OS) -> proto::Os {
+    proto::Os {
+        name: os.name,
+        version: os.version,
+        platform: os.platform,
+        family: os.family,
+    }
+}
+
+fn stage_errors(errors: Vec<StageError>) -> Vec<proto::StageError> {
+    errors
+        .into_iter()
+        .map(|x| proto::StageError {
+            stage: x.stage.as_str().to_owned(),
+            message: x.message,
+        })
+        .collect()
+}
+
+fn flags(known: &KnownDevice) -> proto::DeviceFlags {
+    proto::DeviceFlags {
+        touch_enabled: known.is_touch_enabled(),
+        mobile: known.is_mobile(),
+        browser: known.is_browser(),
+        feed_reader: known.is_feed_reader(),
+        library: known.is_library(),
+        media_player: known.is_media_player(),
+        mobile_app: known.is_mobile_app(),
+        pim: known.is_pim(),
+        desktop: known.is_desktop(),
+        notebook: known.is_notebook(),
+        smart_phone: known.is_smart_phone(),
+        feature_phone: known.is_feature_phone(),
+        camera: known.is_camera(),
+        car_browser: known.is_car_browser(),
+        console: known.is_console(),
+        portable_media_player: known.is_portable_media_player(),
+        television: known.is_television(),
+        smart_display: known.is_smart_display(),
+        tablet: known.is_tablet(),
+        smart_speaker: known.is_smart_speaker(),
+        peripheral: known.is_peripheral(),
+        wearable: known.is_wearable(),
+        phablet: known.is_phablet(),
+    }
+}
+
+impl From<Detection> for proto::DetectResponse {
+    fn from(detection: Detection) -> Self {
+        let detection = match detection {
+            Detection::Known(known) => detect_response::Detection::Known(proto::KnownDevice {
+                is: Some(flags(&known)),
+                client: known.client.map(client),
+                device: known.device.map(device),
+                os: known.os.map(os),
+                stage_errors: stage_errors(known.stage_errors),
+                snapshot: known.snapshot,
+            }),
+            Detection::Bot(bot) => detect_response::Detection::Bot(proto::Bot {
+                name: bot.name,
+                category: bot.category,
+                url: bot.url,
+                producer: bot.producer.map(|x| proto::BotProducer {
+                    name: x.name,
+                    url: x.url,
+                }),
+                bot_spoof_suspected: bot.bot_spoof_suspected,
+                spoof_signals: bot
+                    .spoof_signals
+                    .iter()
+                    .map(|x| format!("{:?}", x))
+                    .collect(),
+                claimed: bot.claimed.map(|x| proto::BotClaims {
+                    client: x.client.map(client),
+                    device: x.device.map(device),
+                    os: x.os.map(os),
+                    stage_errors: stage_errors(x.stage_errors),
+                }),
+                snapshot: bot.snapshot,
+            }),
+        };
+
+        Self {
+            detection: Some(detection),
+        }
+    }
+}
+
+/// The `DeviceDetector` grpc service.
+#[derive(Clone)]
+pub struct Service {
+    detector: Arc<DeviceDetector>,
+}
+
+impl Service {
+    pub fn new(detector: Arc<DeviceDetector>) -> Self {
+        Self { detector }
+    }
+}
+
+#[tonic::async_trait]
+impl proto::device_detector_server::DeviceDetector for Service {
+    async fn detect(
+        &self,
+        request: Request<proto::DetectRequest>,
+    ) -> Result<Response<proto::DetectResponse>, Status> {
+        let request = request.into_inner();
+        let headers = request
+            .headers
+            .into_iter()
+            .map(|x| (x.name, x.value))
+            .collect();
+
+        match self
+            .detector
+            .parse_async(&request.user_agent, Some(headers))
+            .await
+        {
+            Ok(detection) => Ok(Response::new(detection.into())),
+            Err(err) => Err(Status::internal(format!("{:#}", err))),
+        }
+    }
+}
+
+/// The service detecting with `detector`, for adding to a server.
+pub fn service(detector: Arc<DeviceDetector>) -> DeviceDetectorServer<Service> {
+    DeviceDetectorServer::new(Service::new(detector))
+}
+
+/// Serves detections on `listen_address` until `shutdown` completes.
+pub async fn serve(
+    listen_address: SocketAddr,
+    detector: Arc<DeviceDetector>,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    tonic::transport::Server::builder()
+        .add_service(service(detector))
+        .serve_with_shutdown(listen_address, shutdown)
+        .await?;
+
+    Ok(())
+}
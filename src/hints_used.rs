@@ -0,0 +1,96 @@
+//! Which client hints a detection was actually made with, for delegating only
+//! the hints which matter with `Accept-CH`, and for telling when a middlebox
+//! strips some of them on the way:
+//!
+//! ```ignore
+//! for hint in detection.hints_used() {
+//!     metrics.increment(hint.header());
+//! }
+//! ```
+//!
+//! A hint is used when the parsers adopt its value into the detection, such as
+//! the model of `Sec-CH-UA-Model` when the user agent has none, or the
+//! architecture of `Sec-CH-UA-Arch`. Hints which were sent but lost to the
+//! user agent aren't listed. The brands of `Sec-CH-UA` count as
+//! `Sec-CH-UA-Full-Version-List`, which they stand in for.
+
+use serde::{Deserialize, Serialize};
+
+/// A client hint header, serialized as `Accept-CH` lists it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Hint {
+    #[serde(rename = "Sec-CH-UA-Arch")]
+    Arch,
+    #[serde(rename = "Sec-CH-UA-Bitness")]
+    Bitness,
+    #[serde(rename = "Sec-CH-UA-Mobile")]
+    Mobile,
+    #[serde(rename = "Sec-CH-UA-Model")]
+    Model,
+    #[serde(rename = "Sec-CH-UA-Platform")]
+    Platform,
+    #[serde(rename = "Sec-CH-UA-Platform-Version")]
+    PlatformVersion,
+    #[serde(rename = "Sec-CH-UA-Full-Version")]
+    FullVersion,
+    #[serde(rename = "Sec-CH-UA-Full-Version-List")]
+    FullVersionList,
+    #[serde(rename = "Sec-CH-UA-Form-Factors")]
+    FormFactors,
+    #[serde(rename = "X-Requested-With")]
+    RequestedWith,
+}
+
+impl Hint {
+    pub const ALL: [Hint; 10] = [
+        Hint::Arch,
+        Hint::Bitness,
+        Hint::Mobile,
+        Hint::Model,
+        Hint::Platform,
+        Hint::PlatformVersion,
+        Hint::FullVersion,
+        Hint::FullVersionList,
+        Hint::FormFactors,
+        Hint::RequestedWith,
+    ];
+
+    /// The header as `Accept-CH` lists it, such as `Sec-CH-UA-Model`.
+    pub fn header(&self) -> &'static str {
+        match self {
+            Hint::Arch => "Sec-CH-UA-Arch",
+            Hint::Bitness => "Sec-CH-UA-Bitness",
+            Hint::Mobile => "Sec-CH-UA-Mobile",
+            Hint::Model => "Sec-CH-UA-Model",
+            Hint::Platform => "Sec-CH-UA-Platform",
+            Hint::PlatformVersion => "Sec-CH-UA-Platform-Version",
+            Hint::FullVersion => "Sec-CH-UA-Full-Version",
+            Hint::FullVersionList => "Sec-CH-UA-Full-Version-List",
+            Hint::FormFactors => "Sec-CH-UA-Form-Factors",
+            Hint::RequestedWith => "X-Requested-With",
+        }
+    }
+}
+
+impl std::fmt::Display for Hint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.header())
+    }
+}
+
+/// The hints used while parsing, kept in the [`crate::device_detector::ParseContext`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct HintSet(u16);
+
+impl HintSet {
+    pub(crate) fn insert(&mut self, hint: Hint) {
+        self.0 |= 1 << hint as u16;
+    }
+
+    pub(crate) fn to_vec(self) -> Vec<Hint> {
+        Hint::ALL
+            .into_iter()
+            .filter(|x| self.0 & (1 << *x as u16) != 0)
+            .collect()
+    }
+}
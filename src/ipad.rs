@@ -0,0 +1,94 @@
+//! Telling iPads apart from Macs. Since iPadOS 13, Safari on an iPad asks
+//! for desktop sites, with the user agent of Safari on a Mac, and so do the
+//! webviews of many apps. Such iPads are detected as Macs by the php library,
+//! and tablets undercounted, unless this is turned on:
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().with_desktop_class_ipads(true);
+//! ```
+//!
+//! A Mac user agent is then an iPad when one of these holds:
+//!
+//! - it has the `Mobile/15E148` token of iOS builds, which webviews keep in
+//!   desktop mode and no Mac browser sends,
+//! - `Sec-CH-UA-Form-Factors` says it is a tablet.
+//!
+//! Safari itself sends neither, so its desktop mode is only told apart when a
+//! proxy or the page adds the form factors. The device becomes an Apple iPad
+//! tablet and the os iPadOS, without a version, as the one of the user agent
+//! is frozen. Safari becomes Mobile Safari, as on any other iPad.
+
+use anyhow::Result;
+use fancy_regex::Regex;
+use once_cell::sync::Lazy;
+
+use crate::client_hints::ClientHint;
+use crate::device_detector::ParseContext;
+use crate::hints_used::Hint;
+use crate::parsers::client::browsers::AVAILABLE_BROWSERS;
+use crate::parsers::client::Client;
+use crate::parsers::device::{Confidence, Device, DeviceType};
+use crate::parsers::oss::OS;
+
+static IOS_BUILD: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bMobile/\d+[A-Z]\d+\b").unwrap());
+
+/// Whether `ua`, detected as a Mac, comes from an iPad, and the hint which
+/// told if any.
+fn is_ipad(ua: &str, hints: Option<&ClientHint>) -> Result<Option<Option<Hint>>> {
+    if !ua.contains("Macintosh") {
+        return Ok(None);
+    }
+    if IOS_BUILD.is_match(ua)? {
+        return Ok(Some(None));
+    }
+    let tablet = hints.is_some_and(|x| {
+        x.form_factors
+            .iter()
+            .any(|x| x.eq_ignore_ascii_case("tablet"))
+    });
+    Ok(tablet.then_some(Some(Hint::FormFactors)))
+}
+
+/// Detects a Mac as an iPad, see [`crate::ipad`].
+pub(crate) fn reclassify(
+    ua: &str,
+    hints: Option<&ClientHint>,
+    os: &mut Option<OS>,
+    client: &mut Option<Client>,
+    device: &mut Option<Device>,
+    ctx: &mut ParseContext,
+) -> Result<()> {
+    if os.as_ref().is_none_or(|x| x.name != "Mac") {
+        return Ok(());
+    }
+    let Some(hint) = is_ipad(ua, hints)? else {
+        return Ok(());
+    };
+    if let Some(hint) = hint {
+        ctx.use_hint(hint);
+    }
+
+    *os = Some(OS {
+        name: "iPadOS".to_owned(),
+        version: None,
+        platform: None,
+        family: Some("iOS".to_owned()),
+        desktop: false,
+    });
+
+    if let Some(client) = client.as_mut().filter(|x| x.name == "Safari") {
+        client.name = "Mobile Safari".to_owned();
+        client.browser = AVAILABLE_BROWSERS.search_by_name(&client.name).cloned();
+        client.short_code = client.browser.as_ref().map(|x| x.short_code.clone());
+    }
+
+    let device = device.get_or_insert_with(Device::default);
+    device.brand = Some("Apple".to_owned());
+    device.model = Some("iPad".to_owned());
+    device.device_type = Some(DeviceType::Tablet);
+    device.confidence.brand = Some(Confidence::Inferred);
+    device.confidence.model = Some(Confidence::Inferred);
+    device.confidence.device_type = Some(Confidence::Inferred);
+
+    Ok(())
+}
@@ -0,0 +1,183 @@
+//! A JSON Schema of the json detections are served as, and an OpenAPI
+//! document of the http server, for generating clients in other languages:
+//!
+//! ```sh
+//! rust-device-detector json-schema > detection.schema.json
+//! rust-device-detector openapi > openapi.json
+//! ```
+//!
+//! Both are built from [`crate::typescript::declarations`], the json of
+//! [`Detection::to_value`], rather than derived from the rust types, whose
+//! serde form is that of [`Detection`] itself. Objects don't allow properties
+//! other than those declared, so clients generated from them notice when
+//! fields are added.
+//!
+//! [`Detection`]: crate::device_detector::Detection
+//! [`Detection::to_value`]: crate::device_detector::Detection::to_value
+
+use serde_json::{json, Map, Value};
+
+use crate::typescript::{declarations, Declaration, TsType};
+
+/// The schema of `ty`, with references to declarations prefixed with `refs`.
+fn type_schema(ty: &TsType, refs: &str) -> Value {
+    match ty {
+        TsType::String => json!({ "type": "string" }),
+        TsType::Number => json!({ "type": "number" }),
+        TsType::Boolean => json!({ "type": "boolean" }),
+        TsType::Null => json!({ "type": "null" }),
+        TsType::Literal(x) => json!({ "const": x }),
+        TsType::Named(name) => json!({ "$ref": format!("{}{}", refs, name) }),
+        TsType::Array(x) => json!({ "type": "array", "items": type_schema(x, refs) }),
+        TsType::Union(types) => {
+            let literals: Option<Vec<_>> = types
+                .iter()
+                .map(|x| match x {
+                    TsType::Literal(x) => Some(x),
+                    _ => None,
+                })
+                .collect();
+            if let Some(literals) = literals {
+                return json!({ "type": "string", "enum": literals });
+            }
+
+            let any_of: Vec<_> = types.iter().map(|x| type_schema(x, refs)).collect();
+            json!({ "anyOf": any_of })
+        }
+    }
+}
+
+fn declaration_schema(declaration: &Declaration, refs: &str) -> Value {
+    match declaration {
+        Declaration::Alias { ty, .. } => type_schema(ty, refs),
+        Declaration::Interface { fields, .. } => {
+            let properties: Map<_, _> = fields
+                .iter()
+                .map(|x| (x.name.to_owned(), type_schema(&x.ty, refs)))
+                .collect();
+            let required: Vec<_> = fields
+                .iter()
+                .filter(|x| !x.optional)
+                .map(|x| x.name)
+                .collect();
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+                "additionalProperties": false,
+            })
+        }
+    }
+}
+
+fn schemas(refs: &str) -> Map<String, Value> {
+    declarations()
+        .iter()
+        .map(|x| (x.name().to_owned(), declaration_schema(x, refs)))
+        .collect()
+}
+
+/// A draft 7 JSON Schema of a detection, with the types it's made of in
+/// `definitions`.
+pub fn json_schema() -> Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Detection",
+        "$ref": "#/definitions/Detection",
+        "definitions": schemas("#/definitions/"),
+    })
+}
+
+/// An OpenAPI 3.1 document of the endpoints of the http server.
+pub fn openapi() -> Value {
+    let detection_responses = json!({
+        "200": {
+            "description": "The detection.",
+            "content": {
+                "application/json": {
+                    "schema": { "$ref": "#/components/schemas/Detection" },
+                },
+            },
+        },
+        "500": {
+            "description": "The detection failed.",
+            "content": {
+                "text/plain": {
+                    "schema": { "type": "string" },
+                },
+            },
+        },
+    });
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "rust-device-detector",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": {
+            "/detect": {
+                "get": {
+                    "operationId": "detectRequest",
+                    "summary": "Detect the client, os and device of a request from its headers.",
+                    "description": "Pass on the headers of the request to detect, its user agent and client hints such as `Sec-CH-UA-Model` among them.",
+                    "parameters": [{
+                        "name": "User-Agent",
+                        "in": "header",
+                        "required": false,
+                        "schema": { "type": "string" },
+                    }],
+                    "responses": detection_responses,
+                },
+                "post": {
+                    "operationId": "detect",
+                    "summary": "Detect the client, os and device of a user agent.",
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "text/plain": {
+                                "schema": { "type": "string" },
+                            },
+                        },
+                    },
+                    "responses": detection_responses,
+                },
+            },
+            "/metrics": {
+                "get": {
+                    "operationId": "metrics",
+                    "summary": "Prometheus metrics, with the metrics feature.",
+                    "responses": {
+                        "200": {
+                            "description": "The metrics in the Prometheus text format.",
+                            "content": {
+                                "text/plain": {
+                                    "schema": { "type": "string" },
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+            "/health": {
+                "get": {
+                    "operationId": "health",
+                    "summary": "Heartbeat.",
+                    "responses": {
+                        "200": {
+                            "description": "`OK`.",
+                            "content": {
+                                "text/plain": {
+                                    "schema": { "type": "string" },
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+        },
+        "components": {
+            "schemas": schemas("#/components/schemas/"),
+        },
+    })
+}
@@ -1,5 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
+use indexmap::IndexMap;
+
 use serde::Serialize;
 
 // These are the known browsers from matamo's device detector. While this
@@ -9,12 +11,18 @@ use serde::Serialize;
 pub struct AvailableBrowsers {
     // TODO this may not ever be needed.
     //    browsers_by_short: HashMap<String, AvailableBrowser>,
+    /// By lowercased name.
     browsers_by_name: HashMap<String, AvailableBrowser>,
+    /// By lowercased name without spaces. A few names only differ by their
+    /// spaces, such as `U Browser` and `UBrowser`, in which case the first in
+    /// the list is kept, as the php library would find it first.
+    browsers_by_compact_name: HashMap<String, AvailableBrowser>,
 }
 
 #[derive(Clone, Debug, Serialize)]
 pub struct AvailableBrowser {
     pub name: String,
+    pub short_code: String,
     pub family: Option<String>,
     #[serde(skip)]
     pub(crate) mobile_only: bool,
@@ -41,51 +49,58 @@ impl Default for AvailableBrowsers {
                 let mobile_only = mobile_only_browsers.contains(&short);
                 let family = browser_families.get(&short).cloned();
                 (
-                    short,
+                    short.clone(),
                     AvailableBrowser {
                         name: browser,
+                        short_code: short,
                         mobile_only,
                         family,
                     },
                 )
             })
-            .collect::<HashMap<String, AvailableBrowser>>();
+            .collect::<IndexMap<String, AvailableBrowser>>();
 
-        let browsers_by_name = browsers_by_short
-            .values()
-            .map(|browser| {
-                (
-                    browser.name.replace(' ', "").to_lowercase(),
-                    browser.clone(),
-                )
-            })
-            .collect::<HashMap<String, AvailableBrowser>>();
+        let mut browsers_by_name = HashMap::new();
+        let mut browsers_by_compact_name = HashMap::new();
+        for browser in browsers_by_short.values() {
+            browsers_by_name
+                .entry(browser.name.to_lowercase())
+                .or_insert_with(|| browser.clone());
+            browsers_by_compact_name
+                .entry(browser.name.replace(' ', "").to_lowercase())
+                .or_insert_with(|| browser.clone());
+        }
 
         Self {
             //browsers_by_short,
             browsers_by_name,
+            browsers_by_compact_name,
         }
     }
 }
 
 impl AvailableBrowsers {
     pub fn search_by_name(&self, name: &str) -> Option<&AvailableBrowser> {
+        if let Some(res) = self.browsers_by_name.get(&name.to_lowercase()) {
+            return Some(res);
+        }
+
         let name = name.to_lowercase().replace(' ', "");
-        self.browsers_by_name
+        self.browsers_by_compact_name
             .get(&name)
             .or_else(|| {
                 let mut name = name.clone();
                 name.push_str("browser");
-                self.browsers_by_name.get(&name)
+                self.browsers_by_compact_name.get(&name)
             })
             .or_else(|| {
-                self.browsers_by_name
+                self.browsers_by_compact_name
                     .get(name.to_lowercase().trim_end_matches("browser"))
             })
     }
 }
 
-fn available_browsers() -> HashMap<String, String> {
+fn available_browsers() -> IndexMap<String, String> {
     [
         ("V1", "Via"),
         ("1P", "Pure Mini Browser"),
@@ -737,10 +752,10 @@ fn available_browsers() -> HashMap<String, String> {
     ]
     .into_iter()
     .map(|(short, name)| (short.to_owned(), name.to_owned()))
-    .collect::<HashMap<String, String>>()
+    .collect::<IndexMap<String, String>>()
 }
 
-fn browser_families() -> HashMap<String, Vec<String>> {
+fn browser_families() -> IndexMap<String, Vec<String>> {
     [
         ("Android Browser", vec!["AN", "MU"]),
         ("BlackBerry Browser", vec!["BB"]),
@@ -749,25 +764,26 @@ fn browser_families() -> HashMap<String, Vec<String>> {
         (
             "Chrome",
             vec![
-                "CH", "2B", "7S", "A0", "AC", "A4", "AE", "AH", "AI", "AL", "AO", "AS", "BA", "BM", "BR",
-                "C2", "C3", "C5", "C4", "C6", "CC", "CD", "CE", "CF", "CG", "1B", "CI", "CL", "CM",
-                "CN", "CP", "CR", "CV", "CW", "DA", "DD", "DG", "DR", "EC", "EE", "EU", "EW", "FA",
-                "FS", "GB", "GI", "H2", "HA", "HE", "HH", "HS", "I3", "IR", "JB", "KN", "KW", "LF",
-                "LL", "LO", "M1", "MA", "MD", "MR", "MS", "MT", "MZ", "NM", "NR", "O0", "O2", "O3",
-                "OC", "PB", "PT", "QU", "QW", "RM", "S4", "S6", "S8", "S9", "SB", "SG", "SS", "SU",
-                "SV", "SW", "SY", "SZ", "T1", "T3", "TA", "TB", "TG", "TR", "TS", "TU", "TV", "UB", "UR",
-                "VE", "VG", "VI", "VM", "WP", "WH", "XV", "YJ", "YN", "FH", "B1", "BO", "HB", "PC",
-                "LA", "LT", "PD", "HR", "HU", "HP", "IO", "TP", "CJ", "HQ", "HI", "PN", "BW", "YO",
-                "DC", "G8", "DT", "AP", "AK", "UI", "SD", "VN", "4S", "2S", "RF", "LR", "SQ", "BV",
-                "L1", "F0", "KS", "V0", "C8", "AZ", "MM", "BT", "N0", "P0", "F3", "VS", "DU", "D0",
-                "P1", "O4", "8S", "H3", "TE", "WB", "K1", "P2", "XO", "U0", "B0", "VA", "X0", "NX",
-                "O5", "R1", "I1", "HO", "A5", "X1", "18", "B5", "B6", "TC", "A6", "2X", "F4", "YG",
-                "WR", "NA", "DM", "1M", "A7", "XN", "XT", "XB", "W1", "HT", "B8", "F5", "B9", "WA",
-                "T0", "HC", "O6", "P7", "LJ", "LC", "O7", "N2", "A8", "P8", "RB", "1W", "EV", "I9",
-                "V4", "H4", "1T", "M5", "0S", "0C", "ZR", "D6", "F6", "RC", "WD", "P3", "FT", "A9",
-                "X2", "N3", "GD", "O9", "Q3", "F7", "K2", "K5", "P5", "H5", "V3", "K3", "Q4", "G2", "R2",
-                "WX", "XP", "3I", "BG", "R0", "JO", "OL", "GN", "W4", "QI", "E1", "RI", "8B", "5B",
-                "K4", "WK", "8C", "3M", "X5", "PU", "5U", "6U", "7U", "TQ", "ZB", "W2", "RA", "3P",
+                "CH", "2B", "7S", "A0", "AC", "A4", "AE", "AH", "AI", "AL", "AO", "AS", "BA", "BM",
+                "BR", "C2", "C3", "C5", "C4", "C6", "CC", "CD", "CE", "CF", "CG", "1B", "CI", "CL",
+                "CM", "CN", "CP", "CR", "CV", "CW", "DA", "DD", "DG", "DR", "EC", "EE", "EU", "EW",
+                "FA", "FS", "GB", "GI", "H2", "HA", "HE", "HH", "HS", "I3", "IR", "JB", "KN", "KW",
+                "LF", "LL", "LO", "M1", "MA", "MD", "MR", "MS", "MT", "MZ", "NM", "NR", "O0", "O2",
+                "O3", "OC", "PB", "PT", "QU", "QW", "RM", "S4", "S6", "S8", "S9", "SB", "SG", "SS",
+                "SU", "SV", "SW", "SY", "SZ", "T1", "T3", "TA", "TB", "TG", "TR", "TS", "TU", "TV",
+                "UB", "UR", "VE", "VG", "VI", "VM", "WP", "WH", "XV", "YJ", "YN", "FH", "B1", "BO",
+                "HB", "PC", "LA", "LT", "PD", "HR", "HU", "HP", "IO", "TP", "CJ", "HQ", "HI", "PN",
+                "BW", "YO", "DC", "G8", "DT", "AP", "AK", "UI", "SD", "VN", "4S", "2S", "RF", "LR",
+                "SQ", "BV", "L1", "F0", "KS", "V0", "C8", "AZ", "MM", "BT", "N0", "P0", "F3", "VS",
+                "DU", "D0", "P1", "O4", "8S", "H3", "TE", "WB", "K1", "P2", "XO", "U0", "B0", "VA",
+                "X0", "NX", "O5", "R1", "I1", "HO", "A5", "X1", "18", "B5", "B6", "TC", "A6", "2X",
+                "F4", "YG", "WR", "NA", "DM", "1M", "A7", "XN", "XT", "XB", "W1", "HT", "B8", "F5",
+                "B9", "WA", "T0", "HC", "O6", "P7", "LJ", "LC", "O7", "N2", "A8", "P8", "RB", "1W",
+                "EV", "I9", "V4", "H4", "1T", "M5", "0S", "0C", "ZR", "D6", "F6", "RC", "WD", "P3",
+                "FT", "A9", "X2", "N3", "GD", "O9", "Q3", "F7", "K2", "K5", "P5", "H5", "V3", "K3",
+                "Q4", "G2", "R2", "WX", "XP", "3I", "BG", "R0", "JO", "OL", "GN", "W4", "QI", "E1",
+                "RI", "8B", "5B", "K4", "WK", "8C", "3M", "X5", "PU", "5U", "6U", "7U", "TQ", "ZB",
+                "W2", "RA", "3P",
             ],
         ),
         (
@@ -806,7 +822,7 @@ fn browser_families() -> HashMap<String, Vec<String>> {
                 .collect::<Vec<String>>(),
         )
     })
-    .collect::<HashMap<String, Vec<String>>>()
+    .collect::<IndexMap<String, Vec<String>>>()
 }
 
 pub fn mobile_only_browsers() -> HashSet<String> {
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 
+use indexmap::IndexMap;
+
 pub struct AvailableOSs {
     // TODO this may not ever be needed.
     #[allow(dead_code)]
@@ -49,12 +51,16 @@ impl Default for AvailableOSs {
                     },
                 )
             })
-            .collect::<HashMap<String, AvailableOS>>();
+            .collect::<IndexMap<String, AvailableOS>>();
 
-        let oss_by_name = oss_by_short
-            .values()
-            .map(|os| (os.name.replace(' ', "").to_lowercase(), os.clone()))
-            .collect::<HashMap<String, AvailableOS>>();
+        // the first of any names differing only by their spaces, as the php
+        // library would find it first
+        let mut oss_by_name = HashMap::new();
+        for os in oss_by_short.values() {
+            oss_by_name
+                .entry(os.name.replace(' ', "").to_lowercase())
+                .or_insert_with(|| os.clone());
+        }
 
         Self { oss_by_name }
     }
@@ -67,7 +73,7 @@ impl AvailableOSs {
     }
 }
 
-fn available_operating_systems() -> HashMap<String, String> {
+fn available_operating_systems() -> IndexMap<String, String> {
     [
         ("AIX", "AIX"),
         ("AND", "Android"),
@@ -257,16 +263,17 @@ fn available_operating_systems() -> HashMap<String, String> {
     ]
     .into_iter()
     .map(|(short, name)| (short.to_owned(), name.to_owned()))
-    .collect::<HashMap<String, String>>()
+    .collect::<IndexMap<String, String>>()
 }
 
-fn os_families() -> HashMap<String, Vec<String>> {
+fn os_families() -> IndexMap<String, Vec<String>> {
     [
         (
             "Android",
             vec![
                 "AND", "CYN", "FIR", "REM", "RZD", "MLD", "MCD", "YNS", "GRI", "HAR", "ADR", "CLR",
-                "BOS", "REV", "LEN", "SIR", "RRS", "WER", "PIC", "ARM", "HEL", "BYI", "RIS", "PUF", "MET", "LEA",
+                "BOS", "REV", "LEN", "SIR", "RRS", "WER", "PIC", "ARM", "HEL", "BYI", "RIS", "PUF",
+                "MET", "LEA",
             ],
         ),
         ("AmigaOS", vec!["AMG", "MOR", "ARO"]),
@@ -289,13 +296,17 @@ fn os_families() -> HashMap<String, Vec<String>> {
                 "PUR", "PLA", "FUC", "PAR", "FOR", "MON", "KAN", "ZEN", "LND", "LNS", "CHN", "AMZ",
                 "TEN", "CST", "NOV", "ROU", "ZOR", "RED", "KAL", "ORA", "VID", "TIV", "BSN", "RAS",
                 "UOS", "PIO", "FRI", "LIR", "WEB", "SER", "ASP", "AOS", "LOO", "EUL", "SCI", "ALP",
-                "CLO", "ROC", "OVZ", "PVE", "RST", "EZX", "GNS", "JOL", "TUR", "QTP", "WPO", "PAN", "VIZ", "AZU",
+                "CLO", "ROC", "OVZ", "PVE", "RST", "EZX", "GNS", "JOL", "TUR", "QTP", "WPO", "PAN",
+                "VIZ", "AZU",
             ],
         ),
         ("Mac", vec!["MAC"]),
         ("Mobile Gaming Console", vec!["PSP", "NDS", "XBX"]),
         ("OpenVMS", vec!["OVS"]),
-        ("Real-time OS", vec!["MTK", "TDX", "MRE", "JME", "REX", "RNX"]),
+        (
+            "Real-time OS",
+            vec!["MTK", "TDX", "MRE", "JME", "REX", "RNX"],
+        ),
         (
             "Other Mobile",
             vec!["WOS", "POS", "SBA", "TIZ", "SMG", "MAE", "LUN", "GEO"],
@@ -310,7 +321,10 @@ fn os_families() -> HashMap<String, Vec<String>> {
         ),
         ("WebTV", vec!["WTV"]),
         ("Windows", vec!["WIN"]),
-        ("Windows Mobile", vec!["WPH", "WMO", "WCE", "WRT", "WIO", "KIN"]),
+        (
+            "Windows Mobile",
+            vec!["WPH", "WMO", "WCE", "WRT", "WIO", "KIN"],
+        ),
         ("Other Smart TV", vec!["WHS"]),
     ]
     .into_iter()
@@ -323,7 +337,7 @@ fn os_families() -> HashMap<String, Vec<String>> {
                 .collect::<Vec<String>>(),
         )
     })
-    .collect::<HashMap<String, Vec<String>>>()
+    .collect::<IndexMap<String, Vec<String>>>()
 }
 
 pub fn desktop_oss() -> HashSet<String> {
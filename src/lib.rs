@@ -1,10 +1,77 @@
+#[cfg(feature = "alloc-counters")]
+pub mod alloc_counters;
+pub mod approximate;
+#[cfg(feature = "tokio")]
+pub mod async_parse;
+pub mod batch;
+#[cfg(feature = "binary-database")]
+pub mod binary_database;
+pub mod bot_likelihood;
+pub mod bot_spoof;
+pub mod canary;
+pub mod catalog;
 pub mod client_hints;
+pub mod client_tags;
+pub mod database;
+pub mod decode;
+pub mod device_class;
 pub mod device_detector;
-#[cfg(feature = "build-binary")]
-pub mod http;
+pub mod device_type_mapping;
+#[cfg(feature = "edge")]
+pub mod edge;
+pub mod embed;
+pub mod fixture_recorder;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod hints_used;
+pub mod ipad;
+#[cfg(feature = "json-schema")]
+pub mod json_schema;
 pub mod known_browsers;
 pub mod known_oss;
+pub mod local;
+pub mod matomo;
+pub mod media_formats;
+pub mod memory;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod missing_hints;
+#[cfg(feature = "node")]
+pub mod node;
+pub mod normalize;
+pub mod otel;
+pub mod output_style;
+pub mod overlay;
 pub mod parsers;
+pub mod profiler;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod quick;
+pub mod quirks;
+mod rate;
+#[cfg(feature = "remote-database")]
+pub mod remote;
+pub mod replay;
+pub mod resilience;
+pub mod result_cache;
+pub mod rules;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod session;
+#[cfg(feature = "signed-database")]
+pub mod signing;
+pub mod snapshots;
+pub mod typescript;
+#[cfg(feature = "ua-hash")]
+pub mod ua_hash;
+pub mod unknown;
+pub mod upstream;
+pub mod v1;
+pub mod validation;
+pub mod waf;
+pub mod warm_up;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 #[cfg(feature = "ffi")]
 pub mod ffi;
@@ -0,0 +1,138 @@
+//! A detector for single threaded batch jobs, such as going through a log
+//! file, which skips the synchronization the shared [`DeviceDetector`] needs
+//! to be used from many threads at once.
+//!
+//! ```ignore
+//! let mut detector = LocalDeviceDetector::from(DeviceDetector::new()).with_cache(100_000);
+//! for line in log.lines() {
+//!     let detection = detector.parse(line?, None)?;
+//! }
+//! ```
+//!
+//! Its cache is a plain map rather than the concurrent cache of
+//! [`DeviceDetector::new_with_cache`], and one [`ParseContext`] is reused for
+//! every parse. Converting a shared detector only clones its configuration
+//! and the handle to its database, whose compiled regexes stay shared. The
+//! cache is emptied once it is full, which is cheaper than tracking which
+//! entries were used last, and works as well for logs, where popular user
+//! agents come back quickly.
+
+use anyhow::Result;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::client_hints::ClientHint;
+use crate::device_detector::{Detection, DeviceDetector, ParseContext};
+
+pub struct LocalDeviceDetector {
+    detector: DeviceDetector,
+    ctx: ParseContext,
+    /// Detections by user agent, then by hints, of which a user agent rarely
+    /// comes with more than a few.
+    cache: HashMap<String, Vec<(Option<ClientHint>, Detection)>>,
+    cached: usize,
+    cache_entries: usize,
+}
+
+impl From<DeviceDetector> for LocalDeviceDetector {
+    /// Configured the same as `detector`, but without its caches, see
+    /// [`LocalDeviceDetector::with_cache`].
+    fn from(detector: DeviceDetector) -> Self {
+        Self {
+            detector: detector.uncached(),
+            ctx: ParseContext::new(),
+            cache: HashMap::new(),
+            cached: 0,
+            cache_entries: 0,
+        }
+    }
+}
+
+impl LocalDeviceDetector {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        DeviceDetector::new().into()
+    }
+
+    /// Caches up to `entries` detections.
+    pub fn with_cache(mut self, entries: usize) -> Self {
+        self.cache_entries = entries;
+        self
+    }
+
+    /// The detector this detects with, without caches.
+    pub fn detector(&self) -> &DeviceDetector {
+        &self.detector
+    }
+
+    /// Number of detections currently cached.
+    pub fn cached_detections(&self) -> usize {
+        self.cached
+    }
+
+    pub fn parse(&mut self, ua: &str, headers: Option<Vec<(String, String)>>) -> Result<Detection> {
+        let client_hints = match headers {
+            Some(headers) => Some(ClientHint::from_headers(headers)?),
+            None => None,
+        };
+        self.parse_client_hints(ua, client_hints)
+    }
+
+    pub fn parse_client_hints(
+        &mut self,
+        ua: &str,
+        client_hints: Option<ClientHint>,
+    ) -> Result<Detection> {
+        #[cfg(feature = "metrics")]
+        let start = std::time::Instant::now();
+
+        // the cache is keyed by the user agent as given
+        let decoded = self.detector.decode(ua);
+        let cached = self.cache.get(ua).and_then(|x| {
+            x.iter()
+                .find(|(hints, _)| *hints == client_hints)
+                .map(|(_, detection)| detection.clone())
+        });
+        if self.cache_entries > 0 {
+            self.detector.record_cache_lookup(cached.is_some());
+        }
+
+        let res = match cached {
+            Some(detection) => Ok(detection),
+            None => {
+                let detection = self.detector.detect_with_context(
+                    &decoded,
+                    client_hints.as_ref(),
+                    &mut self.ctx,
+                )?;
+                self.insert(ua, client_hints.clone(), &detection);
+                Ok(detection)
+            }
+        };
+        let res = self.detector.finish(res, client_hints.as_ref());
+        let res = match decoded {
+            Cow::Owned(_) => res.map(Detection::with_user_agent_decoded),
+            Cow::Borrowed(_) => res,
+        };
+
+        #[cfg(feature = "metrics")]
+        self.detector.metrics().record(ua, &res, start.elapsed());
+        res
+    }
+
+    fn insert(&mut self, ua: &str, client_hints: Option<ClientHint>, detection: &Detection) {
+        if self.cache_entries == 0 || detection.approximate() {
+            return;
+        }
+        if self.cached >= self.cache_entries {
+            self.cache.clear();
+            self.cached = 0;
+        }
+        self.cache
+            .entry(ua.to_owned())
+            .or_default()
+            .push((client_hints, detection.clone()));
+        self.cached += 1;
+    }
+}
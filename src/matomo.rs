@@ -0,0 +1,208 @@
+//! Detections as the JSON Matomo's php device detector gives for them, the
+//! result of `DeviceDetector::getInfoFromUserAgent` passed to `json_encode`,
+//! so that consumers migrating from it don't notice the difference:
+//!
+//! ```ignore
+//! let json = detector.parse(ua, headers)?.to_matomo_json(ua);
+//! ```
+//!
+//! It is the shape of the test fixtures: bots are only `user_agent` and
+//! `bot`, which has only the fields their database entry has, except that
+//! producers always have both a name and a url, empty if unknown. The one
+//! producer in the database without a url, that of `TactiScout`, gets an
+//! empty one where php leaves it out. Otherwise
+//! unknown fields are empty strings, an unknown os is an empty array, as php
+//! doesn't tell empty arrays from empty maps, and an unknown client is
+//! `null`. Strings are escaped the way `json_encode` does by default, with
+//! `/` and non ascii characters escaped.
+
+use serde::Serialize;
+use serde_json::ser::{Formatter, Serializer};
+
+use std::io;
+
+use crate::device_detector::{Detection, KnownDevice};
+use crate::parsers::bot::Bot;
+use crate::parsers::client::ClientType;
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Info<'a> {
+    Bot {
+        user_agent: &'a str,
+        bot: BotInfo<'a>,
+    },
+    Known {
+        user_agent: &'a str,
+        os: Os<'a>,
+        client: Option<Client<'a>>,
+        device: Device<'a>,
+        os_family: &'a str,
+        browser_family: &'a str,
+    },
+}
+
+#[derive(Serialize)]
+struct BotInfo<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    producer: Option<Producer<'a>>,
+}
+
+#[derive(Serialize)]
+struct Producer<'a> {
+    name: &'a str,
+    url: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Os<'a> {
+    Known {
+        name: &'a str,
+        version: &'a str,
+        platform: &'a str,
+    },
+    Unknown([(); 0]),
+}
+
+#[derive(Serialize)]
+struct Client<'a> {
+    r#type: &'a str,
+    name: &'a str,
+    version: &'a str,
+    /// Only browsers have an engine.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    engine: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    engine_version: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Device<'a> {
+    r#type: &'a str,
+    brand: &'a str,
+    model: &'a str,
+}
+
+fn bot_info(bot: &Bot) -> BotInfo<'_> {
+    BotInfo {
+        name: &bot.name,
+        category: bot.category.as_deref(),
+        url: bot.url.as_deref(),
+        producer: bot.producer.as_ref().map(|producer| Producer {
+            name: producer.name.as_deref().unwrap_or(""),
+            url: producer.url.as_deref().unwrap_or(""),
+        }),
+    }
+}
+
+fn known_info<'a>(known: &'a KnownDevice, ua: &'a str) -> Info<'a> {
+    let os = match &known.os {
+        Some(os) => Os::Known {
+            name: &os.name,
+            version: os.version.as_deref().unwrap_or(""),
+            platform: os.platform.as_deref().unwrap_or(""),
+        },
+        None => Os::Unknown([]),
+    };
+
+    let client = known.client.as_ref().map(|client| {
+        let browser = client.r#type == ClientType::Browser;
+        Client {
+            r#type: client.r#type.as_str(),
+            name: &client.name,
+            version: client.version.as_deref().unwrap_or(""),
+            engine: browser.then(|| client.engine.as_deref().unwrap_or("")),
+            engine_version: browser.then(|| client.engine_version.as_deref().unwrap_or("")),
+        }
+    });
+
+    let device = known.device.as_ref();
+    let device = Device {
+        r#type: device
+            .and_then(|x| x.device_type.as_ref())
+            .map_or("", |x| x.as_str()),
+        brand: device.and_then(|x| x.brand.as_deref()).unwrap_or(""),
+        model: device.and_then(|x| x.model.as_deref()).unwrap_or(""),
+    };
+
+    let browser_family = known
+        .client
+        .as_ref()
+        .filter(|x| x.r#type == ClientType::Browser)
+        .and_then(|x| x.browser.as_ref())
+        .and_then(|x| x.family.as_deref())
+        .unwrap_or("Unknown");
+
+    Info::Known {
+        user_agent: ua,
+        os,
+        client,
+        device,
+        os_family: known
+            .os
+            .as_ref()
+            .and_then(|x| x.family.as_deref())
+            .unwrap_or("Unknown"),
+        browser_family,
+    }
+}
+
+/// Escapes strings as php's `json_encode` does without flags. Control
+/// characters are already escaped the same way.
+struct PhpFormatter;
+
+impl Formatter for PhpFormatter {
+    fn write_string_fragment<W>(&mut self, writer: &mut W, fragment: &str) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        let mut start = 0;
+        for (i, c) in fragment.char_indices() {
+            if c != '/' && c.is_ascii() {
+                continue;
+            }
+            writer.write_all(&fragment.as_bytes()[start..i])?;
+            if c == '/' {
+                writer.write_all(b"\\/")?;
+            } else {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    write!(writer, "\\u{:04x}", unit)?;
+                }
+            }
+            start = i + c.len_utf8();
+        }
+        writer.write_all(&fragment.as_bytes()[start..])
+    }
+}
+
+fn info<'a>(detection: &'a Detection, ua: &'a str) -> Info<'a> {
+    match detection {
+        Detection::Bot(bot) => Info::Bot {
+            user_agent: ua,
+            bot: bot_info(bot),
+        },
+        Detection::Known(known) => known_info(known, ua),
+    }
+}
+
+/// The detection of `ua` as Matomo's `getInfoFromUserAgent` gives it, in
+/// the json `json_encode` makes of it.
+pub fn to_json(detection: &Detection, ua: &str) -> String {
+    let mut res = Vec::new();
+    info(detection, ua)
+        .serialize(&mut Serializer::with_formatter(&mut res, PhpFormatter))
+        .expect("serializing to a vec never fails");
+    String::from_utf8(res).expect("the formatter writes utf-8")
+}
+
+/// [`to_json`] as a json value, for [`crate::output_style`].
+pub(crate) fn to_value(detection: &Detection, ua: &str) -> serde_json::Value {
+    serde_json::to_value(info(detection, ua)).expect("serializing to a value never fails")
+}
@@ -0,0 +1,126 @@
+//! Which image and video formats a client decodes, for media servers picking
+//! the format to serve straight from the detection:
+//!
+//! ```ignore
+//! let image = if detection.supports_avif() {
+//!     "hero.avif"
+//! } else if detection.supports_webp() {
+//!     "hero.webp"
+//! } else {
+//!     "hero.jpg"
+//! };
+//! ```
+//!
+//! The matrix is maintained in `src/media_formats/formats.yml`, by client,
+//! engine and os, each with the lowest version decoding the format. Clients
+//! it doesn't cover, bots and undetected clients are taken not to decode any
+//! format, so that a fallback is served. Services keeping their own matrix
+//! can load it with [`FormatSupport::from_yaml`] in the same format. An
+//! `Accept` header listing the format is still the surer sign, where sent.
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use version_compare::Cmp;
+
+use std::collections::BTreeMap;
+
+use crate::device_detector::Detection;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaFormat {
+    Avif,
+    Webp,
+    Hevc,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Rule {
+    #[serde(default)]
+    clients: Vec<String>,
+    #[serde(default)]
+    engines: Vec<String>,
+    #[serde(default)]
+    oss: Vec<String>,
+    client_version: Option<String>,
+    engine_version: Option<String>,
+    os_version: Option<String>,
+}
+
+/// Whether `version` is at least `since`, an unknown version being too low.
+fn at_least(version: Option<&str>, since: Option<&str>) -> bool {
+    let Some(since) = since else {
+        return true;
+    };
+    version.is_some_and(|x| {
+        matches!(
+            version_compare::compare(x, since),
+            Ok(Cmp::Gt) | Ok(Cmp::Eq)
+        )
+    })
+}
+
+/// Whether `names` is empty or has `name`.
+fn any_of(names: &[String], name: Option<&str>) -> bool {
+    names.is_empty() || name.is_some_and(|x| names.iter().any(|y| y == x))
+}
+
+impl Rule {
+    fn matches(&self, detection: &Detection) -> bool {
+        let Some(known) = detection.get_known_device() else {
+            return false;
+        };
+        let client = known.client.as_ref();
+        let engine = client.and_then(|x| x.engine.as_deref());
+        let os = known.os.as_ref();
+
+        any_of(&self.clients, client.map(|x| x.name.as_str()))
+            && any_of(&self.engines, engine)
+            && (any_of(&self.oss, os.map(|x| x.name.as_str()))
+                || any_of(&self.oss, os.and_then(|x| x.family.as_deref())))
+            && at_least(
+                client.and_then(|x| x.version.as_deref()),
+                self.client_version.as_deref(),
+            )
+            && at_least(
+                client.and_then(|x| x.engine_version.as_deref()),
+                self.engine_version.as_deref(),
+            )
+            && at_least(
+                os.and_then(|x| x.version.as_deref()),
+                self.os_version.as_deref(),
+            )
+    }
+}
+
+/// The clients decoding each format.
+#[derive(Clone, Debug)]
+pub struct FormatSupport {
+    formats: BTreeMap<MediaFormat, Vec<Rule>>,
+}
+
+static EMBEDDED: Lazy<FormatSupport> = Lazy::new(|| {
+    FormatSupport::from_yaml(include_str!("media_formats/formats.yml"))
+        .expect("valid media formats")
+});
+
+impl FormatSupport {
+    /// The matrix maintained with the crate.
+    pub fn embedded() -> &'static Self {
+        &EMBEDDED
+    }
+
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let formats = serde_yaml::from_str(yaml).context("invalid media formats")?;
+        Ok(Self { formats })
+    }
+
+    /// Whether the client of `detection` decodes `format`.
+    pub fn supports(&self, detection: &Detection, format: MediaFormat) -> bool {
+        self.formats
+            .get(&format)
+            .is_some_and(|rules| rules.iter().any(|x| x.matches(detection)))
+    }
+}
@@ -0,0 +1,149 @@
+//! Keeping the memory the detector grows into under a budget, for sidecars
+//! and other memory constrained deployments.
+//!
+//! Regexes are compiled as requests first need them, and never freed, so a
+//! detector slowly grows towards having compiled most of the database, which
+//! takes around a gigabyte. With a budget, the regexes which haven't been
+//! used for the longest are freed once the estimate is over it, and compiled
+//! again if they are needed after all:
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new_with_cache(100_000).with_memory_budget(256 * 1024 * 1024);
+//!
+//! let usage = detector.memory_usage();
+//! log::info!("{} regexes compiled, {} bytes", usage.compiled_regexes, usage.total_bytes());
+//! ```
+//!
+//! The sizes are estimates, as neither compiled regexes nor cache entries
+//! report what they take, so leave some headroom. The result cache is
+//! limited to a quarter of the budget, and regexes get the rest. A budget
+//! smaller than what the usual traffic needs compiled makes parsing slower,
+//! as regexes are compiled again and again, but doesn't make it fail.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::database::Database;
+
+/// Estimated bytes of a compiled regex besides its pattern, measured on the
+/// embedded database after matching a user agent.
+const REGEX_BASE_BYTES: usize = 8 * 1024;
+/// Estimated bytes of a compiled regex per byte of its pattern.
+const REGEX_PATTERN_BYTES: usize = 384;
+/// Estimated bytes of a cached detection, with its key.
+const CACHE_ENTRY_BYTES: usize = 1024;
+
+pub(crate) fn regex_bytes(pattern: &str) -> usize {
+    REGEX_BASE_BYTES + REGEX_PATTERN_BYTES * pattern.len()
+}
+
+/// The compiled regexes of a database.
+#[derive(Debug, Default)]
+pub(crate) struct RegexMemory {
+    compiled: AtomicUsize,
+    bytes: AtomicUsize,
+    /// Advanced by each parse under a budget, to tell which regexes were used
+    /// least recently.
+    epoch: AtomicU64,
+}
+
+impl RegexMemory {
+    pub(crate) fn epoch(&self) -> u64 {
+        self.epoch.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn compiled(&self, pattern: &str) {
+        self.compiled.fetch_add(1, Ordering::Relaxed);
+        self.bytes
+            .fetch_add(regex_bytes(pattern), Ordering::Relaxed);
+    }
+
+    pub(crate) fn freed(&self, pattern: &str) {
+        self.compiled.fetch_sub(1, Ordering::Relaxed);
+        self.bytes
+            .fetch_sub(regex_bytes(pattern), Ordering::Relaxed);
+    }
+}
+
+/// Estimated memory taken by a detector, on top of its loaded database.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Regexes of the database currently compiled, by any detector using it.
+    pub compiled_regexes: usize,
+    pub regex_bytes: usize,
+    /// Entries of the result cache, see
+    /// [`crate::device_detector::DeviceDetector::new_with_cache`].
+    pub cached_detections: u64,
+    pub cache_bytes: usize,
+}
+
+impl MemoryUsage {
+    pub(crate) fn new(db: &Database, cached_detections: u64) -> Self {
+        let memory = &db.regex_memory;
+        Self {
+            compiled_regexes: memory.compiled.load(Ordering::Relaxed),
+            regex_bytes: memory.bytes.load(Ordering::Relaxed),
+            cached_detections,
+            cache_bytes: cached_detections as usize * CACHE_ENTRY_BYTES,
+        }
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.regex_bytes + self.cache_bytes
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct MemoryBudget {
+    pub(crate) bytes: usize,
+    evicting: Mutex<()>,
+}
+
+impl MemoryBudget {
+    pub(crate) fn new(bytes: usize) -> Self {
+        Self {
+            bytes,
+            evicting: Mutex::new(()),
+        }
+    }
+
+    /// How many detections the result cache may hold.
+    #[cfg(feature = "cache")]
+    pub(crate) fn cache_entries(&self) -> u64 {
+        (self.bytes / 4 / CACHE_ENTRY_BYTES) as u64
+    }
+
+    /// Frees the least recently used regexes of `db` if `usage` is over the
+    /// budget, down to three quarters of it so that this doesn't happen on
+    /// every parse.
+    pub(crate) fn enforce(&self, db: &Database, usage: MemoryUsage) {
+        db.regex_memory.epoch.fetch_add(1, Ordering::Relaxed);
+        if usage.total_bytes() <= self.bytes {
+            return;
+        }
+        // another thread is already on it
+        let Ok(_evicting) = self.evicting.try_lock() else {
+            return;
+        };
+
+        let target = (self.bytes / 4 * 3).saturating_sub(usage.cache_bytes);
+        let mut bytes = db.regex_memory.bytes.load(Ordering::Relaxed);
+
+        let mut compiled: Vec<_> = db
+            .regex_lists()
+            .into_iter()
+            .flat_map(|(_, regexes)| regexes)
+            .filter_map(|x| Some((x.last_used()?, x)))
+            .collect();
+        compiled.sort_unstable_by_key(|(last_used, _)| *last_used);
+
+        for (_, regex) in compiled {
+            if bytes <= target {
+                break;
+            }
+            if regex.evict() {
+                bytes = bytes.saturating_sub(regex_bytes(&regex.pattern));
+            }
+        }
+    }
+}
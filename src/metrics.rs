@@ -0,0 +1,372 @@
+//! Counters and a latency histogram of a detector's detections, in the
+//! Prometheus text format:
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new();
+//! // ... serve traffic
+//! let body = detector.metrics().to_prometheus();
+//! ```
+//!
+//! Every detector counts its detections when the `metrics` feature is on, as
+//! each only costs a few atomic increments, and clones of a detector count
+//! into the same metrics. The http server of the server crate serves them on
+//! `GET /metrics`.
+//!
+//! Cache lookups are those of the result cache, see
+//! [`DeviceDetector::with_custom_result_cache`].
+//!
+//! With [`DeviceDetector::with_unknowns_window`], the metrics also keep the
+//! share of unknown browsers and devices over the last parses, and the user
+//! agent prefixes most often unknown among them, as an early warning that the
+//! database is out of date for the traffic it sees:
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().with_unknowns_window(10_000);
+//! // ... serve traffic
+//! let unknowns = detector.metrics().snapshot().unknowns.unwrap();
+//! if unknowns.unknown_browser_rate() > Some(0.05) {
+//!     eprintln!("unknown browsers: {:?}", unknowns.top_prefixes);
+//! }
+//! ```
+//!
+//! Unlike the counters, the window takes a lock on every parse, which is why
+//! it is opt-in. A parse counts as an unknown browser when it isn't a bot and
+//! has no client, and as an unknown device when it isn't a bot and has no
+//! device type, brand nor model. The prefix of a user agent is its first
+//! [`PREFIX_LEN`] characters.
+//!
+//! [`DeviceDetector::with_result_cache`]: crate::device_detector::DeviceDetector::with_result_cache
+//! [`DeviceDetector::with_unknowns_window`]: crate::device_detector::DeviceDetector::with_unknowns_window
+
+use anyhow::Result;
+use serde::Serialize;
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::device_detector::Detection;
+
+/// The upper bounds of the buckets of [`MetricsSnapshot::latency_buckets`],
+/// in seconds.
+pub const LATENCY_BUCKETS: [f64; 10] = [
+    0.000_01, 0.000_025, 0.000_05, 0.000_1, 0.000_25, 0.000_5, 0.001, 0.005, 0.025, 0.1,
+];
+
+/// Number of characters of a user agent its prefix is made of.
+pub const PREFIX_LEN: usize = 40;
+
+/// Number of prefixes in [`UnknownsSnapshot::top_prefixes`].
+pub const TOP_PREFIXES: usize = 10;
+
+/// The metrics a detector counts into.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    detections: AtomicU64,
+    bots: AtomicU64,
+    errors: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    /// Detections no slower than each of [`LATENCY_BUCKETS`], but slower
+    /// than the one before, and those slower than all of them last.
+    latency: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    latency_nanos: AtomicU64,
+    unknowns: Option<Mutex<UnknownsWindow>>,
+}
+
+/// The last parses, for [`UnknownsSnapshot`].
+#[derive(Debug)]
+struct UnknownsWindow {
+    capacity: usize,
+    /// Whether each parse had an unknown browser and an unknown device, and
+    /// its prefix if either.
+    parses: VecDeque<(bool, bool, Option<String>)>,
+    unknown_browsers: u64,
+    unknown_devices: u64,
+    prefixes: HashMap<String, u64>,
+}
+
+impl UnknownsWindow {
+    fn push(&mut self, ua: &str, detection: &Detection) {
+        let (browser, device) = match detection {
+            Detection::Bot(_) => (false, false),
+            Detection::Known(known) => (
+                known.client.is_none(),
+                known.device.as_ref().is_none_or(|x| {
+                    x.device_type.is_none() && x.brand.is_none() && x.model.is_none()
+                }),
+            ),
+        };
+        let prefix = (browser || device).then(|| ua.chars().take(PREFIX_LEN).collect::<String>());
+
+        if self.parses.len() == self.capacity {
+            if let Some((browser, device, prefix)) = self.parses.pop_front() {
+                self.unknown_browsers -= browser as u64;
+                self.unknown_devices -= device as u64;
+                if let Some(prefix) = prefix {
+                    if let Some(count) = self.prefixes.get_mut(&prefix) {
+                        *count -= 1;
+                        if *count == 0 {
+                            self.prefixes.remove(&prefix);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.unknown_browsers += browser as u64;
+        self.unknown_devices += device as u64;
+        if let Some(prefix) = &prefix {
+            *self.prefixes.entry(prefix.clone()).or_default() += 1;
+        }
+        self.parses.push_back((browser, device, prefix));
+    }
+
+    fn snapshot(&self) -> UnknownsSnapshot {
+        let mut top_prefixes: Vec<_> = self
+            .prefixes
+            .iter()
+            .map(|(prefix, count)| (prefix.clone(), *count))
+            .collect();
+        top_prefixes.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_prefixes.truncate(TOP_PREFIXES);
+
+        UnknownsSnapshot {
+            window: self.capacity as u64,
+            parses: self.parses.len() as u64,
+            unknown_browsers: self.unknown_browsers,
+            unknown_devices: self.unknown_devices,
+            top_prefixes,
+        }
+    }
+}
+
+/// The metrics of a detector at one point.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct MetricsSnapshot {
+    /// Detections, failed ones included.
+    pub detections: u64,
+    pub bots: u64,
+    pub errors: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// Cumulative counts of detections no slower than each of
+    /// [`LATENCY_BUCKETS`], as Prometheus histograms have them.
+    pub latency_buckets: Vec<u64>,
+    /// The time spent on all detections.
+    pub latency_sum: Duration,
+    /// The unknowns of the last parses, only ever set with
+    /// [`DeviceDetector::with_unknowns_window`].
+    ///
+    /// [`DeviceDetector::with_unknowns_window`]: crate::device_detector::DeviceDetector::with_unknowns_window
+    pub unknowns: Option<UnknownsSnapshot>,
+}
+
+/// The unknowns of the last parses, see [`crate::metrics`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct UnknownsSnapshot {
+    /// Number of parses the window holds once full.
+    pub window: u64,
+    /// Parses in the window, fewer than `window` until it is full.
+    pub parses: u64,
+    pub unknown_browsers: u64,
+    pub unknown_devices: u64,
+    /// The prefixes of the user agents with an unknown browser or device,
+    /// with how many parses had them, most frequent first.
+    pub top_prefixes: Vec<(String, u64)>,
+}
+
+impl UnknownsSnapshot {
+    /// The share of parses in the window with an unknown browser, `None`
+    /// before the first.
+    pub fn unknown_browser_rate(&self) -> Option<f64> {
+        (self.parses > 0).then(|| self.unknown_browsers as f64 / self.parses as f64)
+    }
+
+    /// The share of parses in the window with an unknown device, `None`
+    /// before the first.
+    pub fn unknown_device_rate(&self) -> Option<f64> {
+        (self.parses > 0).then(|| self.unknown_devices as f64 / self.parses as f64)
+    }
+}
+
+impl Metrics {
+    /// Metrics which also keep the unknowns of the last `parses` parses.
+    pub fn with_unknowns_window(parses: usize) -> Self {
+        Self {
+            unknowns: (parses > 0).then(|| {
+                Mutex::new(UnknownsWindow {
+                    capacity: parses,
+                    parses: VecDeque::with_capacity(parses),
+                    unknown_browsers: 0,
+                    unknown_devices: 0,
+                    prefixes: HashMap::new(),
+                })
+            }),
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn record(&self, ua: &str, res: &Result<Detection>, elapsed: Duration) {
+        self.detections.fetch_add(1, Ordering::Relaxed);
+        match res {
+            Ok(Detection::Bot(_)) => {
+                self.bots.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(Detection::Known(_)) => {}
+            Err(_) => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        if let (Some(unknowns), Ok(detection)) = (&self.unknowns, res) {
+            unknowns.lock().expect("unknowns lock").push(ua, detection);
+        }
+
+        let seconds = elapsed.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|x| seconds <= *x)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.latency[bucket].fetch_add(1, Ordering::Relaxed);
+        self.latency_nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_cache_lookup(&self, hit: bool) {
+        let counter = if hit {
+            &self.cache_hits
+        } else {
+            &self.cache_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let mut cumulative = 0;
+        let latency_buckets = self.latency[..LATENCY_BUCKETS.len()]
+            .iter()
+            .map(|x| {
+                cumulative += x.load(Ordering::Relaxed);
+                cumulative
+            })
+            .collect();
+
+        MetricsSnapshot {
+            detections: self.detections.load(Ordering::Relaxed),
+            bots: self.bots.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+            latency_buckets,
+            latency_sum: Duration::from_nanos(self.latency_nanos.load(Ordering::Relaxed)),
+            unknowns: self
+                .unknowns
+                .as_ref()
+                .map(|x| x.lock().expect("unknowns lock").snapshot()),
+        }
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        self.snapshot().to_prometheus()
+    }
+}
+
+impl MetricsSnapshot {
+    /// The share of cache lookups which were hits, `None` before the first.
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let lookups = self.cache_hits + self.cache_misses;
+        (lookups > 0).then(|| self.cache_hits as f64 / lookups as f64)
+    }
+
+    pub fn to_prometheus(&self) -> String {
+        let mut res = String::new();
+
+        let counters = [
+            (
+                "device_detector_detections_total",
+                "Detections, failed ones included.",
+                self.detections,
+            ),
+            (
+                "device_detector_bot_detections_total",
+                "Detections of bots.",
+                self.bots,
+            ),
+            (
+                "device_detector_detection_errors_total",
+                "Detections which failed.",
+                self.errors,
+            ),
+            (
+                "device_detector_cache_hits_total",
+                "Lookups in detection caches which found the detection.",
+                self.cache_hits,
+            ),
+            (
+                "device_detector_cache_misses_total",
+                "Lookups in detection caches which didn't.",
+                self.cache_misses,
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(res, "# HELP {} {}", name, help);
+            let _ = writeln!(res, "# TYPE {} counter", name);
+            let _ = writeln!(res, "{} {}", name, value);
+        }
+
+        let name = "device_detector_detection_duration_seconds";
+        let _ = writeln!(res, "# HELP {} Time taken by detections.", name);
+        let _ = writeln!(res, "# TYPE {} histogram", name);
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(&self.latency_buckets) {
+            let _ = writeln!(res, "{}_bucket{{le=\"{}\"}} {}", name, bound, count);
+        }
+        let _ = writeln!(res, "{}_bucket{{le=\"+Inf\"}} {}", name, self.detections);
+        let _ = writeln!(res, "{}_sum {}", name, self.latency_sum.as_secs_f64());
+        let _ = writeln!(res, "{}_count {}", name, self.detections);
+
+        if let Some(unknowns) = &self.unknowns {
+            let gauges = [
+                (
+                    "device_detector_window_parses",
+                    "Parses in the window of unknowns.",
+                    unknowns.parses as f64,
+                ),
+                (
+                    "device_detector_unknown_browser_ratio",
+                    "Share of the parses in the window with an unknown browser.",
+                    unknowns.unknown_browser_rate().unwrap_or(0.0),
+                ),
+                (
+                    "device_detector_unknown_device_ratio",
+                    "Share of the parses in the window with an unknown device.",
+                    unknowns.unknown_device_rate().unwrap_or(0.0),
+                ),
+            ];
+            for (name, help, value) in gauges {
+                let _ = writeln!(res, "# HELP {} {}", name, help);
+                let _ = writeln!(res, "# TYPE {} gauge", name);
+                let _ = writeln!(res, "{} {}", name, value);
+            }
+
+            let name = "device_detector_unknown_prefix_parses";
+            let _ = writeln!(
+                res,
+                "# HELP {} Parses in the window with an unknown browser or device, by the most frequent user agent prefixes.",
+                name
+            );
+            let _ = writeln!(res, "# TYPE {} gauge", name);
+            for (prefix, count) in &unknowns.top_prefixes {
+                let prefix = prefix
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\n', "\\n");
+                let _ = writeln!(res, "{}{{prefix=\"{}\"}} {}", name, prefix, count);
+            }
+        }
+
+        res
+    }
+}
@@ -0,0 +1,131 @@
+//! Which client hints, if a site delegated them with `Accept-CH`, would make
+//! a detection more precise, for tuning which hints to ask for by what the
+//! traffic actually lacks:
+//!
+//! ```ignore
+//! for hint in detection.missing_hints() {
+//!     metrics.increment(hint.header, hint.improves.as_str());
+//! }
+//! ```
+//!
+//! Only Blink based browsers send client hints, so every other detection,
+//! bots included, has none missing. A detection misses a hint when the field
+//! the hint would fill is unknown, or has one of the frozen values of reduced
+//! user agents, such as Windows 10 for every version of Windows since, or a
+//! browser version of `120.0.0.0`. A hint can be listed even though it was
+//! sent, when it didn't help, see [`crate::hints_used`] for those which did.
+
+use serde::Serialize;
+
+use crate::device_detector::{Detection, KnownDevice};
+
+/// The part of a detection a hint improves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Improvement {
+    DeviceModel,
+    DeviceType,
+    OsVersion,
+    ClientVersion,
+    Architecture,
+}
+
+impl Improvement {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Improvement::DeviceModel => "device_model",
+            Improvement::DeviceType => "device_type",
+            Improvement::OsVersion => "os_version",
+            Improvement::ClientVersion => "client_version",
+            Improvement::Architecture => "architecture",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct MissingHint {
+    /// The header as `Accept-CH` lists it, such as `Sec-CH-UA-Model`.
+    pub header: &'static str,
+    pub improves: Improvement,
+}
+
+impl MissingHint {
+    const fn new(header: &'static str, improves: Improvement) -> Self {
+        Self { header, improves }
+    }
+}
+
+/// The versions reduced user agents freeze the os at, by os name.
+const FROZEN_OS_VERSIONS: [(&str, &str); 3] =
+    [("Windows", "10"), ("Mac", "10.15.7"), ("Android", "10")];
+
+pub(crate) fn missing_hints(detection: &Detection) -> Vec<MissingHint> {
+    match detection {
+        Detection::Known(known) if sends_hints(known) => known_missing_hints(known),
+        _ => Vec::new(),
+    }
+}
+
+fn sends_hints(known: &KnownDevice) -> bool {
+    known
+        .client
+        .as_ref()
+        .is_some_and(|x| x.engine.as_deref() == Some("Blink"))
+}
+
+fn known_missing_hints(known: &KnownDevice) -> Vec<MissingHint> {
+    let mut res = Vec::new();
+    let device = known.device.as_ref();
+    let os = known.os.as_ref();
+
+    let is_android = os.is_some_and(|x| x.family.as_deref() == Some("Android"));
+    if is_android && device.is_none_or(|x| x.model.is_none()) {
+        res.push(MissingHint::new(
+            "Sec-CH-UA-Model",
+            Improvement::DeviceModel,
+        ));
+    }
+
+    if device.is_none_or(|x| x.device_type.is_none()) {
+        res.push(MissingHint::new(
+            "Sec-CH-UA-Form-Factors",
+            Improvement::DeviceType,
+        ));
+    }
+
+    if let Some(os) = os {
+        let frozen = FROZEN_OS_VERSIONS
+            .iter()
+            .any(|(name, version)| os.name == *name && os.version.as_deref() == Some(*version));
+        if os.version.is_none() || frozen {
+            res.push(MissingHint::new(
+                "Sec-CH-UA-Platform-Version",
+                Improvement::OsVersion,
+            ));
+        }
+
+        if os.desktop && os.platform.is_none() {
+            res.push(MissingHint::new(
+                "Sec-CH-UA-Arch",
+                Improvement::Architecture,
+            ));
+            res.push(MissingHint::new(
+                "Sec-CH-UA-Bitness",
+                Improvement::Architecture,
+            ));
+        }
+    }
+
+    let reduced_version = known
+        .client
+        .as_ref()
+        .is_some_and(|x| x.version.as_deref().is_none_or(|x| x.ends_with(".0.0.0")));
+    if reduced_version {
+        res.push(MissingHint::new(
+            "Sec-CH-UA-Full-Version-List",
+            Improvement::ClientVersion,
+        ));
+    }
+
+    res
+}
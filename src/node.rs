@@ -0,0 +1,37 @@
+//! Node.js bindings with napi-rs, as a faster drop in for the javascript
+//! ports of the device detector, built with `napi build --release --features
+//! node`:
+//!
+//! ```js
+//! const { detect } = require("./rust-device-detector.node");
+//!
+//! app.use((req, res, next) => {
+//!   req.device = detect(req.get("user-agent") ?? "", req.headers);
+//!   next();
+//! });
+//! ```
+//!
+//! Detections are plain objects of the json the cli prints, see
+//! `includes/detection.d.ts`. Every call shares one detector, whose regexes
+//! are compiled as user agents need them.
+
+use napi::{Error, Result};
+use napi_derive::napi;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+
+use std::collections::HashMap;
+
+use crate::device_detector::DeviceDetector;
+
+static DETECTOR: Lazy<DeviceDetector> = Lazy::new(DeviceDetector::new);
+
+/// Detects `ua`, with the other headers of the request for client hints.
+#[napi]
+pub fn detect(ua: String, headers: Option<HashMap<String, String>>) -> Result<Value> {
+    let headers = headers.map(|x| x.into_iter().collect());
+    match DETECTOR.parse(&ua, headers) {
+        Ok(detection) => Ok(detection.to_value()),
+        Err(err) => Err(Error::from_reason(format!("{:#}", err))),
+    }
+}
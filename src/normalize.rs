@@ -0,0 +1,164 @@
+//! Rewrites a user agent and its client hints into a minimal canonical form
+//! which still detects as the same coarse classification: os, os major version,
+//! browser family, browser major version and whether it is a phone, tablet or
+//! desktop. Everything else, such as device models, exact versions and
+//! architectures, is dropped. Intended for privacy proxies which forward
+//! requests upstream.
+
+use crate::client_hints::ClientHint;
+use crate::device_detector::{Detection, KnownDevice};
+
+/// A reduced user agent, plus the reduced low entropy client hints if the
+/// original request had client hints.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NormalizedRequest {
+    pub user_agent: String,
+    pub headers: Vec<(String, String)>,
+}
+
+fn major(version: Option<&str>) -> Option<&str> {
+    version
+        .and_then(|x| x.split('.').next())
+        .filter(|x| !x.is_empty())
+}
+
+fn os_token(known: &KnownDevice) -> Option<String> {
+    let os = known.os.as_ref()?;
+    let family = os.family.as_deref().unwrap_or(&os.name);
+    let version = major(os.version.as_deref());
+
+    let res = match family {
+        "Android" => match version {
+            Some(v) => format!("Linux; Android {}; K", v),
+            None => "Linux; Android; K".to_owned(),
+        },
+        "Windows" => {
+            let nt = match os.version.as_deref() {
+                Some("XP") => "5.1",
+                Some("Vista") => "6.0",
+                Some("7") => "6.1",
+                Some("8") => "6.2",
+                Some("8.1") => "6.3",
+                _ => "10.0",
+            };
+            format!("Windows NT {}; Win64; x64", nt)
+        }
+        "Mac" => "Macintosh; Intel Mac OS X 10_15_7".to_owned(),
+        "iOS" => {
+            let v = version.unwrap_or("17");
+            if known.is_tablet() {
+                format!("iPad; CPU OS {}_0 like Mac OS X", v)
+            } else {
+                format!("iPhone; CPU iPhone OS {}_0 like Mac OS X", v)
+            }
+        }
+        "Chrome OS" => "X11; CrOS x86_64 14541.0.0".to_owned(),
+        "GNU/Linux" => "X11; Linux x86_64".to_owned(),
+        _ => return None,
+    };
+
+    Some(res)
+}
+
+/// The platform as browsers send it in `Sec-CH-UA-Platform`.
+fn platform_hint(known: &KnownDevice) -> Option<&'static str> {
+    let os = known.os.as_ref()?;
+    let res = match os.family.as_deref().unwrap_or(&os.name) {
+        "Android" => "Android",
+        "Windows" => "Windows",
+        "Mac" => "macOS",
+        "iOS" => "iOS",
+        "Chrome OS" => "Chrome OS",
+        "GNU/Linux" => "Linux",
+        _ => return None,
+    };
+    Some(res)
+}
+
+fn browser_family(known: &KnownDevice) -> Option<&str> {
+    let client = known.client.as_ref()?;
+    client.browser.as_ref().and_then(|x| x.family.as_deref())
+}
+
+fn normalize_known(known: &KnownDevice, had_hints: bool) -> NormalizedRequest {
+    let os = os_token(known);
+    let mobile = known.is_mobile() && !known.is_tablet();
+    let family = browser_family(known);
+    let version = major(known.client.as_ref().and_then(|x| x.version.as_deref()));
+
+    let user_agent = match (family, &os) {
+        (Some("Chrome"), Some(os)) => format!(
+            "Mozilla/5.0 ({}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{}.0.0.0 {}Safari/537.36",
+            os,
+            version.unwrap_or("0"),
+            if mobile { "Mobile " } else { "" }
+        ),
+        (Some("Firefox"), Some(os)) => {
+            let v = version.unwrap_or("0");
+            format!(
+                "Mozilla/5.0 ({}; {}rv:{}.0) Gecko/20100101 Firefox/{}.0",
+                os,
+                if mobile { "Mobile; " } else { "" },
+                v,
+                v
+            )
+        }
+        (Some("Safari"), Some(os)) => format!(
+            "Mozilla/5.0 ({}) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/{}.0 {}Safari/604.1",
+            os,
+            version.unwrap_or("0"),
+            if mobile { "Mobile/15E148 " } else { "" }
+        ),
+        (_, Some(os)) => format!("Mozilla/5.0 ({})", os),
+        (_, None) => "Mozilla/5.0".to_owned(),
+    };
+
+    let mut headers = Vec::new();
+
+    if had_hints {
+        let mut hints = ClientHint {
+            mobile,
+            mobile_sent: true,
+            platform: platform_hint(known).map(|x| x.to_owned()),
+            ..Default::default()
+        };
+
+        if family == Some("Chrome") {
+            let v = version.unwrap_or("0").to_owned();
+            hints.brands = vec![
+                ("Chromium".to_owned(), v),
+                ("Not_A Brand".to_owned(), "24".to_owned()),
+            ];
+        }
+
+        // only the low entropy hints which browsers send by default
+        headers = hints
+            .to_headers()
+            .into_iter()
+            .filter(|(header, _)| {
+                ["Sec-CH-UA", "Sec-CH-UA-Mobile", "Sec-CH-UA-Platform"].contains(&header.as_str())
+            })
+            .collect();
+    }
+
+    NormalizedRequest {
+        user_agent,
+        headers,
+    }
+}
+
+/// Normalizes a request given its detection. Bots are passed through as is,
+/// as there is no user to protect and reducing them would lose the bot.
+pub fn normalize(
+    detection: &Detection,
+    ua: &str,
+    client_hints: Option<&ClientHint>,
+) -> NormalizedRequest {
+    match detection {
+        Detection::Bot(_) => NormalizedRequest {
+            user_agent: ua.to_owned(),
+            headers: Vec::new(),
+        },
+        Detection::Known(known) => normalize_known(known, client_hints.is_some()),
+    }
+}
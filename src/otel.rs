@@ -0,0 +1,106 @@
+//! Detections as OpenTelemetry semantic convention attributes, to attach to
+//! spans without depending on a particular version of the opentelemetry crate.
+//!
+//! ```ignore
+//! for attribute in detection.to_otel_attributes() {
+//!     span.set_attribute(match attribute.value {
+//!         AttributeValue::String(x) => KeyValue::new(attribute.key, x),
+//!         AttributeValue::Bool(x) => KeyValue::new(attribute.key, x),
+//!     });
+//! }
+//! ```
+//!
+//! Only attributes with a known value are included. `user_agent.original`
+//! isn't, as a detection doesn't keep the user agent it was made from.
+
+use crate::device_detector::Detection;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttributeValue {
+    String(String),
+    Bool(bool),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attribute {
+    pub key: &'static str,
+    pub value: AttributeValue,
+}
+
+impl Attribute {
+    fn string(key: &'static str, value: &str) -> Self {
+        Self {
+            key,
+            value: AttributeValue::String(value.to_owned()),
+        }
+    }
+
+    fn bool(key: &'static str, value: bool) -> Self {
+        Self {
+            key,
+            value: AttributeValue::Bool(value),
+        }
+    }
+}
+
+/// The `os.type` of an os, for those the convention lists.
+fn os_type(name: &str, family: Option<&str>) -> Option<&'static str> {
+    let res = match (name, family.unwrap_or(name)) {
+        ("FreeBSD", _) => "freebsd",
+        ("NetBSD", _) => "netbsd",
+        ("OpenBSD", _) => "openbsd",
+        ("DragonFly", _) => "dragonflybsd",
+        ("HP-UX", _) => "hpux",
+        ("AIX", _) => "aix",
+        ("Solaris", _) => "solaris",
+        (_, "Windows" | "Windows Mobile") => "windows",
+        (_, "Mac" | "iOS") => "darwin",
+        (_, "GNU/Linux" | "Android" | "Chrome OS") => "linux",
+        _ => return None,
+    };
+    Some(res)
+}
+
+pub(crate) fn attributes(detection: &Detection) -> Vec<Attribute> {
+    let mut res = Vec::new();
+
+    let known = match detection {
+        Detection::Bot(bot) => {
+            res.push(Attribute::string("user_agent.name", &bot.name));
+            res.push(Attribute::string("user_agent.synthetic.type", "bot"));
+            return res;
+        }
+        Detection::Known(known) => known,
+    };
+
+    if let Some(client) = &known.client {
+        res.push(Attribute::string("user_agent.name", &client.name));
+        if let Some(version) = &client.version {
+            res.push(Attribute::string("user_agent.version", version));
+        }
+    }
+
+    if let Some(os) = &known.os {
+        res.push(Attribute::string("os.name", &os.name));
+        if let Some(version) = &os.version {
+            res.push(Attribute::string("os.version", version));
+        }
+        if let Some(os_type) = os_type(&os.name, os.family.as_deref()) {
+            res.push(Attribute::string("os.type", os_type));
+        }
+        res.push(Attribute::string("browser.platform", &os.name));
+    }
+
+    res.push(Attribute::bool("browser.mobile", known.is_mobile()));
+
+    if let Some(device) = &known.device {
+        if let Some(brand) = &device.brand {
+            res.push(Attribute::string("device.manufacturer", brand));
+        }
+        if let Some(model) = &device.model {
+            res.push(Attribute::string("device.model.name", model));
+        }
+    }
+
+    res
+}
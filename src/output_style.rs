@@ -0,0 +1,154 @@
+//! The naming and nesting of json detections, for services which settle on
+//! one format across languages rather than the one this crate happens to
+//! serialize:
+//!
+//! ```ignore
+//! let style = OutputStyle {
+//!     case: FieldCase::Camel,
+//!     flatten: true,
+//!     ..OutputStyle::DEFAULT
+//! };
+//! // {"clientName": "Chrome", "deviceType": "smartphone", "isMobile": true, ...}
+//! let json = detection.to_styled_value(ua, &style);
+//! ```
+//!
+//! A style starts from a [`Layout`], the fields and nesting of either
+//! [`Detection::to_value`] or the php device detector's
+//! `getInfoFromUserAgent`, see [`crate::matomo`]. Field names are then put in
+//! the [`FieldCase`] of the style, and with `flatten` nested objects are
+//! merged into their parent, their names prefixed with that of the object,
+//! such as `os_version` for the version of the os. Arrays, such as
+//! `stage_errors`, are kept as they are, and an unknown os, client or device
+//! stays a single field, `null` or the empty array of php, rather than one per
+//! field it would have had.
+//!
+//! [`Detection::to_value`]: crate::device_detector::Detection::to_value
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::device_detector::Detection;
+
+/// The fields, and how they nest, which a style starts from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Layout {
+    /// That of [`Detection::to_value`], the json the server answers with.
+    ///
+    /// [`Detection::to_value`]: crate::device_detector::Detection::to_value
+    #[default]
+    Detection,
+    /// That of Matomo's `getInfoFromUserAgent`, with empty strings for
+    /// unknown fields, see [`crate::matomo`].
+    Matomo,
+}
+
+/// How field names are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldCase {
+    /// `device_type`, as both layouts have them.
+    #[default]
+    Snake,
+    /// `deviceType`.
+    Camel,
+}
+
+impl FieldCase {
+    /// `name`, a snake case field name, in this case.
+    pub fn field(&self, name: &str) -> String {
+        match self {
+            FieldCase::Snake => name.to_owned(),
+            FieldCase::Camel => {
+                let mut res = String::with_capacity(name.len());
+                let mut upper = false;
+                for c in name.chars() {
+                    if c == '_' {
+                        upper = !res.is_empty();
+                    } else if upper {
+                        res.push(c.to_ascii_uppercase());
+                        upper = false;
+                    } else {
+                        res.push(c);
+                    }
+                }
+                res
+            }
+        }
+    }
+}
+
+/// The layout, case and nesting of json detections.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputStyle {
+    pub layout: Layout,
+    pub case: FieldCase,
+    /// Merges nested objects into their parent.
+    pub flatten: bool,
+}
+
+impl OutputStyle {
+    /// The json of [`Detection::to_value`].
+    ///
+    /// [`Detection::to_value`]: crate::device_detector::Detection::to_value
+    pub const DEFAULT: Self = Self {
+        layout: Layout::Detection,
+        case: FieldCase::Snake,
+        flatten: false,
+    };
+
+    /// The json of Matomo's `getInfoFromUserAgent`, as
+    /// [`crate::matomo::to_json`] gives it, but for escaping.
+    pub const MATOMO: Self = Self {
+        layout: Layout::Matomo,
+        case: FieldCase::Snake,
+        flatten: false,
+    };
+
+    /// The detection of `ua` in this style.
+    pub fn render(&self, detection: &Detection, ua: &str) -> Value {
+        let value = match self.layout {
+            Layout::Detection => detection.clone().to_value(),
+            Layout::Matomo => crate::matomo::to_value(detection, ua),
+        };
+        self.restyle(value)
+    }
+
+    /// `value`, in the snake case and nesting of a layout, in this style.
+    fn restyle(&self, value: Value) -> Value {
+        match value {
+            Value::Object(fields) => {
+                let mut res = Map::new();
+                self.insert_fields(&mut res, None, fields);
+                Value::Object(res)
+            }
+            Value::Array(values) => {
+                Value::Array(values.into_iter().map(|x| self.restyle(x)).collect())
+            }
+            value => value,
+        }
+    }
+
+    fn insert_fields(
+        &self,
+        res: &mut Map<String, Value>,
+        prefix: Option<&str>,
+        fields: Map<String, Value>,
+    ) {
+        for (name, value) in fields {
+            let name = match prefix {
+                Some(prefix) => format!("{}_{}", prefix, name),
+                None => name,
+            };
+            match value {
+                Value::Object(fields) if self.flatten => {
+                    self.insert_fields(res, Some(&name), fields)
+                }
+                value => {
+                    res.insert(self.case.field(&name), self.restyle(value));
+                }
+            }
+        }
+    }
+}
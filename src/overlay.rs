@@ -0,0 +1,523 @@
+//! Small additions and fixes on top of a database snapshot, so that an urgent
+//! fix, such as a new bot or a new flagship phone, can ship as a delta
+//! rather than waiting for the next full update.
+//!
+//! An overlay is a yaml file keyed by the database files it changes, each
+//! with entries in the same format as that file:
+//!
+//! ```yaml
+//! bots.yml:
+//!   - regex: 'ExampleBot'
+//!     name: 'ExampleBot'
+//!     category: 'Crawler'
+//! device/mobiles.yml:
+//!   Samsung:
+//!     models:
+//!       - regex: 'SM-S931'
+//!         model: 'Galaxy S25'
+//! ```
+//!
+//! ```ignore
+//! let overlay = Overlay::from_file("overlay.yml")?;
+//! let detector = DeviceDetector::new_with_database(Arc::new(Database::embedded_with_overlay(&overlay)?));
+//! ```
+//!
+//! Entries are merged by name, or by position when they have none:
+//!
+//! - In lists of entries such as `bots.yml`, an entry replaces the first
+//!   with the same `name`. Entries without a match are added before all the
+//!   others, so that they are matched first.
+//! - In files keyed by brand such as `device/mobiles.yml`, the fields of an
+//!   existing brand are replaced by those given, except `models`, which are
+//!   added before the brand's own. New brands are added before all the others.
+//! - In `vendorfragments.yml`, regexes are added before the brand's own, and
+//!   in the client hints files, ids are added or replaced.
+//!
+//! Rules for internal apps and proprietary devices can also be added from
+//! code, without writing yaml, and before or after the bundled entries:
+//!
+//! ```ignore
+//! let overlay = Overlay::default()
+//!     .with_browser(BrowserRule::new("AcmeBrowser/([\\d.]+)", "Acme Browser").engine("Blink"), Position::Before)
+//!     .with_device(DeviceRule::new("AcmeKiosk", "Acme", "Kiosk", DeviceType::SmartDisplay), Position::Before);
+//! ```
+//!
+//! Unlike the entries of an overlay file, rules never replace bundled
+//! entries of the same name. A device rule of a brand the database already
+//! has becomes one of its models, and its regex one of those of the brand.
+//!
+//! Lists of extra bots, such as those of partner crawlers, are merged with
+//! the bundled ones by name, with a [`Precedence`] for the bots in both:
+//!
+//! ```ignore
+//! let overlay = Overlay::default().with_bots_file("partner_bots.yml", Precedence::Extra)?;
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use serde_yaml::{Mapping, Value};
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::database::FILES;
+use crate::parsers::device::DeviceType;
+
+#[derive(Clone, Debug, Default)]
+pub struct Overlay {
+    files: BTreeMap<&'static str, Value>,
+    rules: BTreeMap<&'static str, Vec<(Position, Value)>>,
+    bots: Vec<(Precedence, Vec<Value>)>,
+}
+
+/// Which of an extra bot and a bundled bot of the same name is kept, when
+/// merging a list of bots with [`Overlay::with_bots`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Precedence {
+    /// The extra bots are matched before the bundled ones, which are dropped
+    /// when an extra bot has the same name.
+    #[default]
+    Extra,
+    /// The extra bots are matched after the bundled ones, and dropped when a
+    /// bundled bot has the same name.
+    Bundled,
+}
+
+/// Whether a rule is matched before or after the bundled entries.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Position {
+    #[default]
+    Before,
+    After,
+}
+
+/// A browser, as an entry of `client/browsers.yml`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BrowserRule {
+    pub regex: String,
+    pub name: String,
+    /// The version, with `$1` and so on for the groups of `regex`.
+    pub version: String,
+    pub engine: Option<String>,
+}
+
+impl BrowserRule {
+    /// A browser whose version is the first group of `regex`.
+    pub fn new(regex: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            regex: regex.into(),
+            name: name.into(),
+            version: "$1".to_owned(),
+            engine: None,
+        }
+    }
+
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    pub fn engine(mut self, engine: impl Into<String>) -> Self {
+        self.engine = Some(engine.into());
+        self
+    }
+
+    fn to_yaml(&self) -> Value {
+        let mut res = Mapping::new();
+        res.insert("regex".into(), self.regex.as_str().into());
+        res.insert("name".into(), self.name.as_str().into());
+        res.insert("version".into(), self.version.as_str().into());
+        if let Some(engine) = &self.engine {
+            let mut default = Mapping::new();
+            default.insert("default".into(), engine.as_str().into());
+            res.insert("engine".into(), default.into());
+        }
+        res.into()
+    }
+}
+
+/// A mobile app, as an entry of `client/mobile_apps.yml`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MobileAppRule {
+    pub regex: String,
+    pub name: String,
+    /// The version, with `$1` and so on for the groups of `regex`.
+    pub version: String,
+}
+
+impl MobileAppRule {
+    /// An app whose version is the first group of `regex`.
+    pub fn new(regex: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            regex: regex.into(),
+            name: name.into(),
+            version: "$1".to_owned(),
+        }
+    }
+
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    fn to_yaml(&self) -> Value {
+        let mut res = Mapping::new();
+        res.insert("regex".into(), self.regex.as_str().into());
+        res.insert("name".into(), self.name.as_str().into());
+        res.insert("version".into(), self.version.as_str().into());
+        res.into()
+    }
+}
+
+/// A bot, as an entry of `bots.yml`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BotRule {
+    pub regex: String,
+    pub name: String,
+    pub category: Option<String>,
+    pub url: Option<String>,
+}
+
+impl BotRule {
+    pub fn new(regex: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            regex: regex.into(),
+            name: name.into(),
+            category: None,
+            url: None,
+        }
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    fn to_yaml(&self) -> Value {
+        let mut res = Mapping::new();
+        res.insert("regex".into(), self.regex.as_str().into());
+        res.insert("name".into(), self.name.as_str().into());
+        if let Some(category) = &self.category {
+            res.insert("category".into(), category.as_str().into());
+        }
+        if let Some(url) = &self.url {
+            res.insert("url".into(), url.as_str().into());
+        }
+        res.into()
+    }
+}
+
+/// A device, as a brand of `device/mobiles.yml` with a single model.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceRule {
+    pub regex: String,
+    pub brand: String,
+    /// The model, with `$1` and so on for the groups of `regex`.
+    pub model: String,
+    pub device_type: DeviceType,
+}
+
+impl DeviceRule {
+    pub fn new(
+        regex: impl Into<String>,
+        brand: impl Into<String>,
+        model: impl Into<String>,
+        device_type: DeviceType,
+    ) -> Self {
+        Self {
+            regex: regex.into(),
+            brand: brand.into(),
+            model: model.into(),
+            device_type,
+        }
+    }
+
+    fn to_yaml(&self) -> Value {
+        let mut entry = Mapping::new();
+        entry.insert("regex".into(), self.regex.as_str().into());
+        entry.insert("device".into(), self.device_type.as_str().into());
+        entry.insert("model".into(), self.model.as_str().into());
+
+        let mut res = Mapping::new();
+        res.insert(self.brand.as_str().into(), entry.into());
+        res.into()
+    }
+}
+
+impl Overlay {
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let files: BTreeMap<String, Value> =
+            serde_yaml::from_str(yaml).context("invalid overlay")?;
+
+        let mut res = Self::default();
+        for (file, entries) in files {
+            let file = FILES
+                .iter()
+                .find(|x| **x == file)
+                .ok_or_else(|| anyhow!("{} is not part of the database", file))?;
+            res.files.insert(file, entries);
+        }
+        Ok(res)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let yaml =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::from_yaml(&yaml).with_context(|| format!("loading {}", path.display()))
+    }
+
+    /// Adds `rule`, matched before or after the bundled browsers.
+    pub fn with_browser(self, rule: BrowserRule, position: Position) -> Self {
+        self.with_rule("client/browsers.yml", position, rule.to_yaml())
+    }
+
+    /// Adds `rule`, matched before or after the bundled mobile apps.
+    pub fn with_mobile_app(self, rule: MobileAppRule, position: Position) -> Self {
+        self.with_rule("client/mobile_apps.yml", position, rule.to_yaml())
+    }
+
+    /// Adds `rule`, matched before or after the bundled bots.
+    pub fn with_bot(self, rule: BotRule, position: Position) -> Self {
+        self.with_rule("bots.yml", position, rule.to_yaml())
+    }
+
+    /// Adds `rule`, matched before or after the bundled brands, or the
+    /// models of its brand if the database has it.
+    pub fn with_device(self, rule: DeviceRule, position: Position) -> Self {
+        self.with_rule("device/mobiles.yml", position, rule.to_yaml())
+    }
+
+    /// Merges `bots` into the bundled bots, see [`Precedence`].
+    pub fn with_bots(
+        mut self,
+        bots: impl IntoIterator<Item = BotRule>,
+        precedence: Precedence,
+    ) -> Self {
+        let bots = bots.into_iter().map(|x| x.to_yaml()).collect();
+        self.bots.push((precedence, bots));
+        self
+    }
+
+    /// Merges the bots of `yaml`, in the format of `bots.yml`, into the
+    /// bundled bots, see [`Precedence`].
+    pub fn with_bots_yaml(mut self, yaml: &str, precedence: Precedence) -> Result<Self> {
+        let bots: Vec<Value> = serde_yaml::from_str(yaml).context("invalid bots")?;
+        for bot in &bots {
+            let valid = bot.get("regex").is_some_and(Value::is_string)
+                && bot.get("name").is_some_and(Value::is_string);
+            if !valid {
+                return Err(anyhow!("bot without a regex or name: {:?}", bot));
+            }
+        }
+        self.bots.push((precedence, bots));
+        Ok(self)
+    }
+
+    pub fn with_bots_file<P: AsRef<Path>>(self, path: P, precedence: Precedence) -> Result<Self> {
+        let path = path.as_ref();
+        let yaml =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        self.with_bots_yaml(&yaml, precedence)
+            .with_context(|| format!("loading {}", path.display()))
+    }
+
+    fn with_rule(mut self, file: &'static str, position: Position, rule: Value) -> Self {
+        self.rules.entry(file).or_default().push((position, rule));
+        self
+    }
+
+    /// The database files the overlay changes.
+    pub fn files(&self) -> impl Iterator<Item = &'static str> + '_ {
+        let mut files: Vec<_> = self
+            .files
+            .keys()
+            .chain(self.rules.keys())
+            .copied()
+            .collect();
+        if !self.bots.is_empty() {
+            files.push("bots.yml");
+        }
+        files.sort_unstable();
+        files.dedup();
+        files.into_iter()
+    }
+
+    /// `contents` of the database file `file`, with the overlay merged in.
+    pub(crate) fn apply(&self, file: &str, contents: String) -> Result<String> {
+        let overlay = self.files.get(file);
+        let rules = self.rules.get(file);
+        let bots = if file == "bots.yml" {
+            &self.bots[..]
+        } else {
+            &[]
+        };
+        if overlay.is_none() && rules.is_none() && bots.is_empty() {
+            return Ok(contents);
+        }
+
+        let mut base: Value = serde_yaml::from_str(&contents)?;
+        if let Some(overlay) = overlay {
+            merge(&mut base, overlay)
+                .with_context(|| format!("merging the overlay of {}", file))?;
+        }
+        if let Some(rules) = rules {
+            add_rules(&mut base, rules).with_context(|| format!("adding the rules of {}", file))?;
+        }
+        for (precedence, bots) in bots {
+            merge_bots(&mut base, bots, *precedence)
+                .with_context(|| format!("merging bots into {}", file))?;
+        }
+        Ok(serde_yaml::to_string(&base)?)
+    }
+}
+
+fn merge_bots(base: &mut Value, bots: &[Value], precedence: Precedence) -> Result<()> {
+    let Value::Sequence(base) = base else {
+        return Err(anyhow!("unexpected bots: {:?}", base));
+    };
+    let name = |x: &Value| x.get("name").cloned();
+
+    match precedence {
+        Precedence::Extra => {
+            base.retain(|x| !bots.iter().any(|y| name(x).is_some() && name(x) == name(y)));
+            base.splice(0..0, bots.iter().cloned());
+        }
+        Precedence::Bundled => {
+            let added: Vec<_> = bots
+                .iter()
+                .filter(|x| !base.iter().any(|y| name(y).is_some() && name(x) == name(y)))
+                .cloned()
+                .collect();
+            base.extend(added);
+        }
+    }
+    Ok(())
+}
+
+/// Adds `rules` to `base` as they are, rather than by name as [`merge`] does.
+fn add_rules(base: &mut Value, rules: &[(Position, Value)]) -> Result<()> {
+    let before = rules.iter().filter(|(x, _)| *x == Position::Before);
+    let after = rules.iter().filter(|(x, _)| *x == Position::After);
+
+    match base {
+        Value::Sequence(base) => {
+            base.splice(0..0, before.map(|(_, x)| x.clone()));
+            base.extend(after.map(|(_, x)| x.clone()));
+        }
+        Value::Mapping(base) => {
+            let mut added = Vec::new();
+            let mut appended = Mapping::new();
+            // those added first at the start end up after those added later
+            for (position, rule) in before.rev().chain(after) {
+                let Value::Mapping(rule) = rule else {
+                    return Err(anyhow!("unexpected rule: {:?}", rule));
+                };
+                for (brand, entry) in rule {
+                    match base.get_mut(brand) {
+                        Some(existing) => add_model(existing, entry, *position)?,
+                        None if *position == Position::Before => {
+                            added.push((brand.clone(), entry.clone()));
+                        }
+                        None => {
+                            appended.insert(brand.clone(), entry.clone());
+                        }
+                    }
+                }
+            }
+            let mut added: Mapping = added.into_iter().rev().collect();
+            added.extend(std::mem::take(base));
+            added.extend(appended);
+            *base = added;
+        }
+        base => return Err(anyhow!("unexpected entries: {:?}", base)),
+    }
+    Ok(())
+}
+
+/// Adds the device rule `entry` as a model of the brand `base`, whose regex
+/// then also matches that of the rule.
+fn add_model(base: &mut Value, entry: &Value, position: Position) -> Result<()> {
+    let regex = |x: &Value| {
+        x.get("regex")
+            .and_then(Value::as_str)
+            .map(str::to_owned)
+            .ok_or_else(|| anyhow!("entry without a regex: {:?}", x))
+    };
+    let brand_regex = format!("(?:{})|(?:{})", regex(base)?, regex(entry)?);
+
+    let Value::Mapping(base) = base else {
+        return Err(anyhow!("unexpected brand: {:?}", base));
+    };
+    base.insert("regex".into(), brand_regex.into());
+    let models = base
+        .entry("models".into())
+        .or_insert_with(|| Value::Sequence(Vec::new()));
+    let Value::Sequence(models) = models else {
+        return Err(anyhow!("unexpected models: {:?}", models));
+    };
+    match position {
+        Position::Before => models.insert(0, entry.clone()),
+        Position::After => models.push(entry.clone()),
+    }
+    Ok(())
+}
+
+fn merge(base: &mut Value, overlay: &Value) -> Result<()> {
+    match (base, overlay) {
+        (Value::Sequence(base), Value::Sequence(overlay)) => {
+            let mut added = Vec::new();
+            for entry in overlay {
+                let name = entry
+                    .get("name")
+                    .ok_or_else(|| anyhow!("entry without a name: {:?}", entry))?;
+                match base.iter_mut().find(|x| x.get("name") == Some(name)) {
+                    Some(existing) => *existing = entry.clone(),
+                    None => added.push(entry.clone()),
+                }
+            }
+            base.splice(0..0, added);
+        }
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            let mut added = Mapping::new();
+            for (key, entry) in overlay {
+                match base.get_mut(key) {
+                    Some(existing) => merge_entry(existing, entry),
+                    None => {
+                        added.insert(key.clone(), entry.clone());
+                    }
+                }
+            }
+            added.extend(std::mem::take(base));
+            *base = added;
+        }
+        (_, overlay) => return Err(anyhow!("unexpected overlay entries: {:?}", overlay)),
+    }
+    Ok(())
+}
+
+/// Merges `overlay` into the entry of an existing brand or id.
+fn merge_entry(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (field, value) in overlay {
+                match (base.get_mut(field), value) {
+                    (Some(Value::Sequence(models)), Value::Sequence(added)) => {
+                        models.splice(0..0, added.iter().cloned());
+                    }
+                    _ => {
+                        base.insert(field.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (Value::Sequence(base), Value::Sequence(overlay)) => {
+            base.splice(0..0, overlay.iter().cloned());
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
@@ -2,26 +2,46 @@ use anyhow::Result;
 
 use serde::Deserialize;
 
-use once_cell::sync::Lazy;
 use serde::Serialize;
 
+use crate::bot_spoof::SpoofSignal;
+use crate::database::{Database, Source};
+use crate::device_detector::KnownDevice;
 use crate::parsers::utils::{lazy_user_agent_match, LazyRegex};
 
-static BOT_LIST: Lazy<BotList> = Lazy::new(|| {
-    let contents = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/regexes/bots.yml"));
-    BotList::from_file(contents).unwrap_or_else(|_| panic!("loading bots.yml"))
-});
-
-pub fn lookup_bot(ua: &str) -> Result<Option<Bot>> {
-    BOT_LIST.lookup(ua)
+pub fn lookup_bot(ua: &str, db: &Database) -> Result<Option<Bot>> {
+    db.bots.lookup(ua)
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Bot {
     pub name: String,
     pub category: Option<String>,
     pub url: Option<String>,
     pub producer: Option<BotProducer>,
+    /// Whether the request contradicts being this bot, see
+    /// [`crate::bot_spoof`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bot_spoof_suspected: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spoof_signals: Vec<SpoofSignal>,
+    /// The client, os and device the request claims to be from, only
+    /// detected with [`DeviceDetector::with_bot_claims`].
+    ///
+    /// [`DeviceDetector::with_bot_claims`]: crate::device_detector::DeviceDetector::with_bot_claims
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claimed: Option<Box<KnownDevice>>,
+    /// The database snapshot detected with, see [`crate::snapshots`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
+    /// Whether the user agent was percent-decoded before detection, see
+    /// [`crate::decode`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub user_agent_decoded: bool,
+    /// Whether this is the detection of an earlier user agent with the same
+    /// prefix, see [`crate::approximate`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub approximate: bool,
 }
 
 #[derive(Debug, Deserialize, Clone, Serialize)]
@@ -30,9 +50,22 @@ pub struct BotProducer {
     pub url: Option<String>,
 }
 
+/// A bot as defined in the database, see [`Database::bot_definitions`].
+#[derive(Clone, Debug, Serialize)]
+pub struct BotDefinition<'a> {
+    pub name: &'a str,
+    pub category: Option<&'a str>,
+    pub url: Option<&'a str>,
+    pub producer: Option<&'a BotProducer>,
+    /// The regex as written in `bots.yml`. The detector matches it case
+    /// insensitively, and only where it isn't preceded by a letter or digit.
+    pub regex: &'a str,
+}
+
 #[derive(Debug)]
 struct BotEntry {
     regex: LazyRegex,
+    source: String,
     name: String,
     url: Option<String>,
     category: Option<String>,
@@ -46,6 +79,12 @@ impl From<&BotEntry> for Bot {
             category: entry.category.clone(),
             url: entry.url.clone(),
             producer: entry.producer.clone(),
+            bot_spoof_suspected: false,
+            spoof_signals: Vec::new(),
+            claimed: None,
+            snapshot: None,
+            user_agent_decoded: false,
+            approximate: false,
         }
     }
 }
@@ -57,6 +96,10 @@ pub struct BotList {
 
 impl BotList {
     pub fn from_file(contents: &str) -> Result<BotList> {
+        Self::from_source(&Source::Yaml(contents.into()))
+    }
+
+    pub(crate) fn from_source(source: &Source<'_>) -> Result<BotList> {
         #[derive(Debug, Deserialize)]
         struct YamlBotEntry {
             regex: String,
@@ -80,6 +123,7 @@ impl BotList {
 
                 BotEntry {
                     regex: lazy_user_agent_match(&self.regex),
+                    source: self.regex,
                     name: self.name,
                     url: self.url,
                     category: self.category,
@@ -103,10 +147,24 @@ impl BotList {
             }
         }
 
-        let res: YamlBotList = serde_yaml::from_str(contents)?;
+        let res: YamlBotList = source.parse()?;
         Ok(res.into())
     }
 
+    pub(crate) fn iter(&self) -> impl Iterator<Item = BotDefinition<'_>> {
+        self.bots.iter().map(|bot| BotDefinition {
+            name: &bot.name,
+            category: bot.category.as_deref(),
+            url: bot.url.as_deref(),
+            producer: bot.producer.as_ref(),
+            regex: &bot.source,
+        })
+    }
+
+    pub(crate) fn regexes(&self) -> impl Iterator<Item = &LazyRegex> {
+        self.bots.iter().map(|x| &x.regex)
+    }
+
     fn lookup(&self, ua: &str) -> Result<Option<Bot>> {
         for bot in self.bots.iter() {
             if bot.regex.is_match(ua)? {
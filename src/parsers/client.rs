@@ -23,10 +23,13 @@ use serde::{Deserialize, Serialize};
 
 use serde::de::Deserializer;
 
+use crate::database::{Database, Source};
+use crate::device_detector::ParseContext;
 use crate::known_browsers::AvailableBrowser;
-use crate::parsers::utils::{lazy_user_agent_match, LazyRegex};
+use crate::parsers::utils::{check_template, lazy_user_agent_match, LazyRegex};
 
 pub mod browsers;
+pub mod download_managers;
 pub mod feed_readers;
 pub mod hints;
 pub mod libraries;
@@ -37,7 +40,7 @@ pub mod pim;
 use crate::client_hints::ClientHint;
 
 #[repr(C)]
-#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClientType {
     #[serde(rename = "browser")]
     Browser,
@@ -51,6 +54,10 @@ pub enum ClientType {
     Library,
     #[serde(rename = "mediaplayer")]
     MediaPlayer,
+    /// Download managers and offline browsers. Only detected with
+    /// [`crate::device_detector::DeviceDetector::with_download_managers`].
+    #[serde(rename = "download manager")]
+    DownloadManager,
 }
 
 impl ClientType {
@@ -62,44 +69,132 @@ impl ClientType {
             ClientType::Pim => "pim",
             ClientType::Library => "library",
             ClientType::MediaPlayer => "mediaplayer",
+            ClientType::DownloadManager => "download manager",
         }
     }
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "SerializedClient")]
 pub struct Client {
     pub name: String,
     pub version: Option<String>,
     pub r#type: ClientType,
+    /// Matomo's two or three letter code for the browser, such as `CH` for
+    /// chrome. Only browsers have one.
+    pub short_code: Option<String>,
+    /// The rendering engine of a browser. Never an empty string, unknown
+    /// engines and clients which aren't browsers are `None`. See
+    /// [`browsers::engines::Engine`] for its vendor and history.
     pub engine: Option<String>,
+    /// Never an empty string, and always `None` when `engine` is.
     pub engine_version: Option<String>,
 
     #[serde(skip)]
     pub(crate) browser: Option<AvailableBrowser>,
 }
 
-pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Client>> {
-    if let Some(res) = feed_readers::lookup(ua)? {
+/// A [`Client`] as serialized, which the browser it came from is looked up
+/// again for by name.
+#[derive(Deserialize)]
+struct SerializedClient {
+    name: String,
+    version: Option<String>,
+    r#type: ClientType,
+    short_code: Option<String>,
+    engine: Option<String>,
+    engine_version: Option<String>,
+}
+
+impl From<SerializedClient> for Client {
+    fn from(client: SerializedClient) -> Self {
+        let browser = if client.r#type == ClientType::Browser {
+            browsers::AVAILABLE_BROWSERS
+                .search_by_name(&client.name)
+                .cloned()
+        } else {
+            None
+        };
+
+        Client {
+            name: client.name,
+            version: client.version,
+            r#type: client.r#type,
+            short_code: client.short_code,
+            engine: client.engine,
+            engine_version: client.engine_version,
+            browser,
+        }
+    }
+}
+
+impl Client {
+    /// Fills in the short code and enforces the engine invariants documented
+    /// on the fields, whichever parser the client came from.
+    fn finish(mut self) -> Self {
+        if self.r#type == ClientType::Browser {
+            self.short_code = browsers::AVAILABLE_BROWSERS
+                .search_by_name(&self.name)
+                .map(|x| x.short_code.clone());
+        } else {
+            self.short_code = None;
+        }
+
+        if self.engine.as_deref() == Some("") {
+            self.engine = None;
+        }
+
+        if self.engine.is_none() || self.engine_version.as_deref() == Some("") {
+            self.engine_version = None;
+        }
+
+        self
+    }
+}
+
+pub fn lookup(
+    ua: &str,
+    client_hints: Option<&ClientHint>,
+    db: &Database,
+    ctx: &mut ParseContext,
+) -> Result<Option<Client>> {
+    let res = find(ua, client_hints, db, ctx)?;
+    Ok(res.map(Client::finish))
+}
+
+fn find(
+    ua: &str,
+    client_hints: Option<&ClientHint>,
+    db: &Database,
+    ctx: &mut ParseContext,
+) -> Result<Option<Client>> {
+    if ctx.options.download_managers {
+        if let Some(res) = download_managers::lookup(ua, ctx)? {
+            return Ok(Some(res));
+        }
+    }
+
+    if let Some(res) = feed_readers::lookup(ua, db, ctx)? {
         return Ok(Some(res));
     }
 
-    if let Some(res) = mobile_apps::lookup(ua, client_hints)? {
+    if let Some(res) = mobile_apps::lookup(ua, client_hints, db, ctx)? {
         return Ok(Some(res));
     }
 
-    if let Some(res) = media_players::lookup(ua)? {
+    if let Some(res) = media_players::lookup(ua, db, ctx)? {
         return Ok(Some(res));
     }
 
-    if let Some(res) = pim::lookup(ua)? {
+    if let Some(res) = pim::lookup(ua, db, ctx)? {
         return Ok(Some(res));
     }
 
-    if let Some(res) = libraries::lookup(ua)? {
+    if let Some(res) = libraries::lookup(ua, db, ctx)? {
         return Ok(Some(res));
     }
 
-    if let Some(res) = browsers::lookup(ua, client_hints)? {
+    if let Some(res) = browsers::lookup(ua, client_hints, db, ctx)? {
         return Ok(Some(res));
     }
 
@@ -120,35 +215,55 @@ pub struct ClientList {
 }
 
 impl ClientList {
-    pub fn lookup(&self, ua: &str, r#type: ClientType) -> Result<Option<Client>> {
+    /// The name and pattern of each entry, in the order they are matched.
+    pub(crate) fn patterns(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.clients
+            .iter()
+            .map(|x| (x.name.as_str(), x.regex.user_agent_source()))
+    }
+
+    pub(crate) fn regexes(&self) -> impl Iterator<Item = &LazyRegex> {
+        self.clients.iter().map(|x| &x.regex)
+    }
+
+    pub fn lookup(
+        &self,
+        ua: &str,
+        r#type: ClientType,
+        ctx: &mut ParseContext,
+    ) -> Result<Option<Client>> {
         for client in self.clients.iter() {
             if client.regex.is_match(ua)? {
-                let mut version = "".to_owned();
-                let mut name = "".to_owned();
                 let caps = client.regex.captures(ua)?.expect("valid_regex");
 
-                // expands $1, $2 etc in names / versions to captures from regex
-                caps.expand(&client.version, &mut version);
+                let pattern = &client.regex.pattern;
+                check_template(
+                    &client.version,
+                    pattern,
+                    &caps,
+                    ctx.options.template_expansion,
+                )?;
+                check_template(&client.name, pattern, &caps, ctx.options.template_expansion)?;
 
-                // TODO I don't know if this is needed, but here it is.
-                let version = if version.ends_with(&['.', ' ']) {
-                    version.trim_end_matches(&['.', ' ']).to_owned()
-                } else {
-                    version
-                };
+                // expands $1, $2 etc in names / versions to captures from regex
+                // TODO I don't know if the trimming is needed, but here it is.
+                let version = ctx
+                    .expand(&client.version, &caps)
+                    .trim_end_matches(['.', ' ']);
 
                 let version = if !version.is_empty() {
-                    Some(version)
+                    Some(version.to_owned())
                 } else {
                     None
                 };
 
-                caps.expand(&client.name, &mut name);
+                let name = ctx.expand(&client.name, &caps).to_owned();
 
                 return Ok(Some(Client {
                     name,
                     version,
                     r#type,
+                    short_code: None,
                     browser: None,
                     engine: None,
                     engine_version: None,
@@ -160,6 +275,10 @@ impl ClientList {
     }
 
     pub fn from_file(contents: &str) -> Result<ClientList> {
+        Self::from_source(&Source::Yaml(contents.into()))
+    }
+
+    pub(crate) fn from_source(source: &Source<'_>) -> Result<ClientList> {
         #[derive(Debug, Deserialize)]
         #[serde(transparent)]
         pub struct YamlClientList {
@@ -175,7 +294,7 @@ impl ClientList {
             }
         }
 
-        let res: YamlClientList = serde_yaml::from_str(contents)?;
+        let res: YamlClientList = source.parse()?;
         Ok(res.into())
     }
 }
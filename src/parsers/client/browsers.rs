@@ -6,17 +6,19 @@ use serde::Deserialize;
 
 use version_compare::Cmp;
 
-use std::cmp::Ordering;
-
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 use fallible_iterator::{convert, FallibleIterator};
 
 use super::{Client, ClientType};
 use crate::client_hints::{ClientHint, ClientHintMapping};
+use crate::database::{Database, Source};
+use crate::device_detector::ParseContext;
+use crate::hints_used::Hint;
 use crate::known_browsers::AvailableBrowsers;
 
-use crate::parsers::utils::LazyRegex;
+use crate::parsers::utils::{check_template, LazyRegex};
+use crate::quirks::Quirk;
 
 pub mod engines;
 use engines::detect_engine_version;
@@ -29,13 +31,13 @@ fn extract_version_from_ua(ua: &str, app_hint: &str) -> Result<Option<String>> {
     let escaped_app = app_hint.replace(".", r"\.");
     let pattern = format!(r"{}/(\d+[\.\d]+)", escaped_app);
     let regex = Regex::new(&pattern)?;
-    
+
     if let Some(captures) = regex.captures(ua)? {
         if let Some(version_match) = captures.get(1) {
             return Ok(Some(version_match.as_str().to_owned()));
         }
     }
-    
+
     Ok(None)
 }
 
@@ -48,17 +50,14 @@ const BROWSERS_NEEDING_EARLY_VERSION_HANDLING: &[&str] = &[
 
 // Browsers that need user agent version after standard processing (final override)
 const BROWSERS_USING_UA_VERSION_FINAL: &[&str] = &[
-    "Aloha Browser", "JioSphere", "mCent", "Opera", "Opera Mini", "Opera Mobile"
+    "Aloha Browser",
+    "JioSphere",
+    "mCent",
+    "Opera",
+    "Opera Mini",
+    "Opera Mobile",
 ];
 
-static CLIENT_LIST: Lazy<BrowserClientList> = Lazy::new(|| {
-    let contents = include_str!(concat!(
-        env!("CARGO_MANIFEST_DIR"),
-        "/regexes/client/browsers.yml"
-    ));
-    BrowserClientList::from_file(contents).expect("loading browsers.yml")
-});
-
 static CLIENT_HINT_MAPPING: Lazy<ClientHintMapping> = Lazy::new(|| {
     ClientHintMapping::new(vec![
         ("Chrome".to_owned(), vec!["Google Chrome".to_owned()]),
@@ -84,15 +83,24 @@ static CLIENT_HINT_MAPPING: Lazy<ClientHintMapping> = Lazy::new(|| {
     ])
 });
 
-static AVAILABLE_BROWSERS: Lazy<AvailableBrowsers> = Lazy::new(AvailableBrowsers::default);
+pub(crate) static AVAILABLE_BROWSERS: Lazy<AvailableBrowsers> =
+    Lazy::new(AvailableBrowsers::default);
 
-pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Client>> {
-    let client_from_ua: Option<Client> = CLIENT_LIST.lookup(ua)?;
+// Each rule of precedence between client hints and the user agent below is
+// marked `// rule: <id>`, and has cases in tests/data/hints/browsers.yml
+pub fn lookup(
+    ua: &str,
+    client_hints: Option<&ClientHint>,
+    db: &Database,
+    ctx: &mut ParseContext,
+) -> Result<Option<Client>> {
+    let client_from_ua: Option<Client> = db.browsers.lookup(ua, db, ctx)?;
 
     let mut client_from_hints = if let Some(client_hints) = client_hints {
-        let client_hints_iter = convert(client_hints.full_version_list.iter().map(anyhow::Ok));
+        let client_hints_iter = convert(client_hints.brand_versions().iter().map(anyhow::Ok));
         let mut possible_results: Vec<_> = client_hints_iter
             .filter_map(|i| {
+                // rule: brand-mapping
                 let brand = CLIENT_HINT_MAPPING.apply(&i.0)?;
                 let res = AVAILABLE_BROWSERS
                     .search_by_name(brand.trim())
@@ -102,37 +110,54 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
             })
             .collect()?;
 
+        // rule: chromium-last
         // ensure chromium is the last result
         possible_results.sort_by_key(|x| x.0 == "Chromium" || x.0 == "Microsoft Edge");
 
         if let Some((brand_version, brand_result)) = possible_results.first().map(|x| (x.1, x.2)) {
+            // rule: ua-full-version
             let version = if let Some(ua_full_version) = &client_hints.ua_full_version {
                 Some(ua_full_version.to_owned())
             } else {
                 Some(brand_version.to_owned())
             };
-            
+
             // Determine engine based on browser
             let mut engine = None;
             let mut engine_version = None;
-            
+
+            // rule: blink-from-hints
             // Chrome, Chromium, Edge and Chrome-based browsers use Blink engine
-            if ["Chrome", "Chromium", "Microsoft Edge", "Edge", "CCleaner", "AVG Secure Browser", "Avast Secure Browser"].contains(&brand_result.name.as_str()) {
+            if [
+                "Chrome",
+                "Chromium",
+                "Microsoft Edge",
+                "Edge",
+                "CCleaner",
+                "AVG Secure Browser",
+                "Avast Secure Browser",
+            ]
+            .contains(&brand_result.name.as_str())
+            {
                 engine = Some("Blink".to_owned());
-                
+
                 // First get engine version from User Agent (like PHP does)
                 let ua_engine_version = detect_engine_version(ua, "Blink").unwrap_or(None);
-                
+
                 // Get client hints version for comparison
                 // PHP uses the browser version from client hints as engine version
                 let client_hints_version = version.clone();
-                
+
                 // Follow PHP logic: use client hints version only if it's more detailed than UA version
                 // and the browser is not "Iridium"
                 if brand_result.name != "Iridium" {
-                    if let (Some(ua_version), Some(ch_version)) = (&ua_engine_version, &client_hints_version) {
+                    if let (Some(ua_version), Some(ch_version)) =
+                        (&ua_engine_version, &client_hints_version)
+                    {
                         // Use client hints version if it's greater than UA version
-                        if version_compare::compare(ch_version, ua_version) == Ok(version_compare::Cmp::Gt) {
+                        if version_compare::compare(ch_version, ua_version)
+                            == Ok(version_compare::Cmp::Gt)
+                        {
                             engine_version = client_hints_version;
                         } else {
                             engine_version = ua_engine_version;
@@ -151,6 +176,7 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
                 name: brand_result.name.clone(),
                 version,
                 r#type: ClientType::Browser,
+                short_code: None,
                 engine,
                 engine_version: engine_version.clone(),
                 browser: Some(brand_result.to_owned()),
@@ -167,9 +193,10 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
         if let Some(client_hints_version) = &client_from_hints.version {
             // If the version reported from the client hints is YYYY or YYYY.MM (e.g., 2022 or 2022.04), then it's Iridium.
             // https://iridiumbrowser.de/news/
-            let iridium = ["2020", "2021", "2022", "2023", "2024"]
-                .iter()
-                .any(|year| client_hints_version.starts_with(year));
+            let iridium = ctx.options.quirk(Quirk::IridiumYear)
+                && ["2020", "2021", "2022", "2023", "2024"]
+                    .iter()
+                    .any(|year| client_hints_version.starts_with(year));
             if iridium {
                 client_from_hints.name = "Iridium".to_owned();
             }
@@ -179,6 +206,7 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
                 if let Some(ua_client_version) = &ua_client.version {
                     if client_hints_version.starts_with("15")
                         && ua_client_version.starts_with("114")
+                        && ctx.options.quirk(Quirk::SecureBrowser360)
                     {
                         client_from_hints.name = "360 Secure Browser".to_owned();
                         client_from_hints.engine = ua_client.engine.clone();
@@ -188,6 +216,7 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
             }
         }
 
+        // rule: early-ua-version
         // Some browsers need special version handling early in the process
         if BROWSERS_NEEDING_EARLY_VERSION_HANDLING.contains(&client_from_hints.name.as_str()) {
             client_from_hints.version = client_from_ua
@@ -196,10 +225,7 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
                 .unwrap_or_default();
         }
 
-        if client_from_hints.name == "DuckDuckGo Privacy Browser" {
-            client_from_hints.version = None;
-        }
-
+        // rule: vewd-engine
         if client_from_hints.name == "Vewd Browser" {
             client_from_hints.engine = client_from_ua
                 .as_ref()
@@ -211,6 +237,7 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
                 .unwrap_or_default();
         }
 
+        // rule: chromium-ua-name
         if client_from_hints.name == "Chromium" {
             if let Some(client) = &client_from_ua {
                 if client.name != "Chromium" {
@@ -221,6 +248,7 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
         }
 
         if let Some(client) = &client_from_ua {
+            // rule: mobile-suffix
             if client.name == format!("{} Mobile", client_from_hints.name) {
                 client_from_hints.name = client_from_ua
                     .as_ref()
@@ -230,6 +258,7 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
         }
 
         if let Some(client) = &client_from_ua {
+            // rule: family-engine
             #[allow(clippy::collapsible_if)]
             if client_from_hints.name != client.name {
                 if client_from_hints
@@ -242,9 +271,13 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
                 {
                     client_from_hints.engine = client.engine.clone();
                     // Only override engine version if client hints doesn't have one, or if UA version is more detailed
-                    if let (Some(ua_engine_version), Some(ch_engine_version)) = (&client.engine_version, &client_from_hints.engine_version) {
+                    if let (Some(ua_engine_version), Some(ch_engine_version)) =
+                        (&client.engine_version, &client_from_hints.engine_version)
+                    {
                         // Keep the more detailed version
-                        if version_compare::compare(ua_engine_version, ch_engine_version) == Ok(version_compare::Cmp::Gt) {
+                        if version_compare::compare(ua_engine_version, ch_engine_version)
+                            == Ok(version_compare::Cmp::Gt)
+                        {
                             client_from_hints.engine_version = client.engine_version.clone();
                         }
                         // Otherwise keep the client hints version
@@ -257,12 +290,17 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
         }
 
         if let Some(client) = &client_from_ua {
+            // rule: same-name-engine
             if client_from_hints.name == client.name {
                 client_from_hints.engine = client.engine.clone();
                 // Only override engine version if client hints doesn't have one, or if UA version is more detailed
-                if let (Some(ua_engine_version), Some(ch_engine_version)) = (&client.engine_version, &client_from_hints.engine_version) {
+                if let (Some(ua_engine_version), Some(ch_engine_version)) =
+                    (&client.engine_version, &client_from_hints.engine_version)
+                {
                     // Keep the more detailed version
-                    if version_compare::compare(ua_engine_version, ch_engine_version) == Ok(version_compare::Cmp::Gt) {
+                    if version_compare::compare(ua_engine_version, ch_engine_version)
+                        == Ok(version_compare::Cmp::Gt)
+                    {
                         client_from_hints.engine_version = client.engine_version.clone();
                     }
                     // Otherwise keep the client hints version
@@ -273,6 +311,7 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
             }
         }
 
+        // rule: detailed-ua-version
         // In case the user agent reports a more detailed version, we try to use this instead
         // This applies regardless of whether browser names match (e.g., "106.0.0.0" vs "106")
         if let Some(client) = &client_from_ua {
@@ -289,73 +328,50 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
             }
         }
 
+        // rule: final-ua-version
         // Additional browsers that need user agent version (handled after name resolution)
         if let Some(client) = &client_from_ua {
-            if !client.version.as_ref().unwrap_or(&String::new()).is_empty() {
-                if BROWSERS_USING_UA_VERSION_FINAL.contains(&client_from_hints.name.as_str()) {
-                    client_from_hints.version = client.version.clone();
-                }
+            if !client.version.as_ref().unwrap_or(&String::new()).is_empty()
+                && BROWSERS_USING_UA_VERSION_FINAL.contains(&client_from_hints.name.as_str())
+            {
+                client_from_hints.version = client.version.clone();
             }
         }
     };
 
-    let mut res = client_from_hints.or(client_from_ua);
-
-    // Special handling for Opera Mobile with WebView
-    // If Chrome WebView is detected but the UA contains OPR/, it's actually Opera Mobile
-    if let Some(client) = res.as_mut() {
-        if client.name == "Chrome Webview" && ua.contains(" OPR/") {
-            // Re-detect as Opera Mobile
-            static OPERA_MOBILE_REGEX: Lazy<Regex> = Lazy::new(|| {
-                Regex::new(r"Mobile.+OPR/(\d+[\.\d]+)").expect("valid opera mobile regex")
-            });
-            
-            if let Some(captures) = OPERA_MOBILE_REGEX.captures(ua)? {
-                if let Some(version_match) = captures.get(1) {
-                    client.name = "Opera Mobile".to_owned();
-                    client.version = Some(version_match.as_str().to_owned());
-                    client.engine = Some("Blink".to_owned());
-                    
-                    // Extract Chrome/Blink engine version
-                    static CHROME_VERSION_REGEX: Lazy<Regex> = Lazy::new(|| {
-                        Regex::new(r"Chrome/(\d+[\.\d]+)").expect("valid chrome version regex")
-                    });
-                    
-                    if let Some(chrome_captures) = CHROME_VERSION_REGEX.captures(ua)? {
-                        if let Some(chrome_version) = chrome_captures.get(1) {
-                            client.engine_version = Some(chrome_version.as_str().to_owned());
-                        }
-                    }
-                    
-                    if let Some(browser) = AVAILABLE_BROWSERS.search_by_name("Opera Mobile") {
-                        client.browser = Some(browser.to_owned());
-                    }
-                }
-            }
+    if client_from_hints.is_some() {
+        ctx.use_hint(Hint::FullVersionList);
+        if client_hints.is_some_and(|x| x.ua_full_version.is_some()) {
+            ctx.use_hint(Hint::FullVersion);
         }
     }
 
+    let mut res = client_from_hints.or(client_from_ua);
+
     if let Some(client) = res.as_mut() {
         if let Some(client_hints) = client_hints {
+            // rule: app-hint
             if let Some(app_hint) = &client_hints.app {
-                if let Some(app_name) = super::hints::browsers::get_hint(app_hint)? {
+                if let Some(app_name) = super::hints::browsers::get_hint(app_hint, db, ctx)? {
+                    let app_name = app_name.as_str();
                     if client.name != app_name {
+                        ctx.use_hint(Hint::RequestedWith);
                         client.name = app_name.to_owned();
-                        
+
                         // Try to extract version from user agent for the app-based browser
                         client.version = extract_version_from_ua(ua, app_hint)?;
 
                         if let Some(browser) = AVAILABLE_BROWSERS.search_by_name(app_name) {
+                            let mut client_browser = browser.clone();
+
                             static BLINK_REGEX: Lazy<Regex> = Lazy::new(|| {
                                 Regex::new(r"Chrome/.+ Safari/537.36").expect("valid blink regex")
                             });
 
+                            // rule: always-blink-apps
                             // Some app-based browsers are always Blink-based
-                            const ALWAYS_BLINK_APPS: &[&str] = &[
-                                "TV-Browser Internet",
-                                "XnBrowse",
-                                "Open Browser Lite",
-                            ];
+                            const ALWAYS_BLINK_APPS: &[&str] =
+                                &["TV-Browser Internet", "XnBrowse", "Open Browser Lite"];
 
                             if BLINK_REGEX.is_match(ua)? || ALWAYS_BLINK_APPS.contains(&app_name) {
                                 client.engine = Some("Blink".to_owned());
@@ -365,13 +381,12 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
                                         BrowserClientList::engine_version(ua, engine)?;
                                 }
 
-                                let mut client_browser = browser.clone();
                                 if client_browser.family.is_none() {
                                     client_browser.family = Some("Chrome".to_owned());
                                 }
-
-                                client.browser = Some(client_browser);
                             }
+
+                            client.browser = Some(client_browser);
                         }
                     }
                 }
@@ -381,10 +396,12 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<Clie
 
     if let Some(client) = &mut res {
         if let Some(engine) = &client.engine {
+            // rule: flow-browser
             if engine == "Blink" && client.name == "Flow Browser" {
                 client.engine_version = None;
             }
 
+            // rule: every-browser
             if client.name == "Every Browser" {
                 client.engine = Some("Blink".to_owned());
                 client.engine_version = None;
@@ -408,45 +425,58 @@ struct BrowserClientEntry {
 struct BrowserEngine {
     default: Option<String>,
     #[serde(default)]
-    versions: HashMap<String, String>,
+    versions: IndexMap<String, String>,
 }
 
 #[derive(Debug, Default, Deserialize)]
 #[serde(transparent)]
-struct BrowserClientList {
+pub(crate) struct BrowserClientList {
     clients: Vec<BrowserClientEntry>,
 }
 
 impl BrowserClientList {
-    pub fn lookup(&self, ua: &str) -> Result<Option<Client>> {
+    pub(crate) fn regexes(&self) -> impl Iterator<Item = &LazyRegex> {
+        self.clients.iter().map(|x| &x.regex)
+    }
+
+    pub fn lookup(
+        &self,
+        ua: &str,
+        db: &Database,
+        ctx: &mut ParseContext,
+    ) -> Result<Option<Client>> {
         for entry in self.clients.iter() {
             if entry.regex.is_match(ua)? {
-                let mut name = "".to_owned();
-                let mut version = "".to_owned();
-
                 let caps = entry.regex.captures(ua)?.expect("valid_regex");
 
-                caps.expand(&entry.version, &mut version);
-                let version = if version.ends_with(&['.', ' ']) {
-                    version.trim_end_matches(&['.', ' ']).to_owned()
-                } else {
-                    version
-                };
+                let pattern = &entry.regex.pattern;
+                check_template(
+                    &entry.version,
+                    pattern,
+                    &caps,
+                    ctx.options.template_expansion,
+                )?;
+                check_template(&entry.name, pattern, &caps, ctx.options.template_expansion)?;
 
-                caps.expand(&entry.name, &mut name);
+                let version = ctx
+                    .expand(&entry.version, &caps)
+                    .trim_end_matches(['.', ' '])
+                    .to_owned();
+
+                let name = ctx.expand(&entry.name, &caps).to_owned();
 
                 // browsers are always have engine versions even if they are empty strings
                 let mut engine = None;
                 let mut engine_version = None;
 
                 if let Some(entry_engine) = &entry.engine {
-                    if let Some(e) = Self::engine(ua, entry_engine, &version)? {
+                    if let Some(e) = Self::engine(ua, entry_engine, &version, db)? {
                         engine = Some(e);
                     }
                 }
 
                 if engine.is_none() {
-                    engine = self::engines::lookup(ua)?;
+                    engine = self::engines::lookup(ua, db)?;
                 }
 
                 if let Some(e) = &engine {
@@ -469,6 +499,7 @@ impl BrowserClientList {
                     name,
                     version,
                     r#type: ClientType::Browser,
+                    short_code: None,
                     engine,
                     engine_version,
                     browser,
@@ -504,6 +535,9 @@ impl BrowserClientList {
             token = "(?:Arachne\\/5\\.)";
         } else if engine == "LibWeb" {
             token = "(?:LibWeb\\+LibJs)";
+        } else if engine == "EkiohFlow" {
+            // older versions of flow only send `Ekioh/4.3.2`
+            token = "(?:Ekioh(?:Flow)?)";
         }
 
         use crate::parsers::utils::LimitedUserMatchRegex;
@@ -523,25 +557,32 @@ impl BrowserClientList {
             ));
         }
 
+        // goanna browsers not pretending to be firefox send a build date,
+        // `rv:3.4) Goanna/20180327`, with the goanna version in `rv:`.
+        if engine == "Goanna" {
+            static GOANNA_VERSION: Lazy<Regex> = Lazy::new(|| {
+                Regex::new(r#"(?i:rv:(\d+\.[.\d]*)\).*Goanna/\d{8})"#).expect("valid browser regex")
+            });
+
+            if let Some(r#match) = GOANNA_VERSION.captures(ua)? {
+                return Ok(r#match.get(1).map(|x| x.as_str().to_owned()));
+            }
+        }
+
         Ok(None)
     }
 
-    fn engine(ua: &str, entry_engine: &BrowserEngine, version: &str) -> Result<Option<String>> {
+    fn engine(
+        ua: &str,
+        entry_engine: &BrowserEngine,
+        version: &str,
+        db: &Database,
+    ) -> Result<Option<String>> {
         let mut engine = None;
-        let mut engine_versions = entry_engine.versions.iter().collect::<Vec<_>>();
-
-        // php code assumes the versions will be sorted as they are in
-        // the file, but yaml doesn't guarantee that, so we sort them
-        // lexographically, which is all we can do.
-
-        engine_versions.sort_by(|&(a, _), &(b, _)| {
-            version_compare::compare(a, b)
-                .unwrap_or(Cmp::Eq)
-                .ord()
-                .unwrap_or(Ordering::Equal)
-        });
 
-        for (engine_version, eng) in &engine_versions {
+        // in the order of the file, as in the php library, the last version
+        // the browser's is at least deciding the engine.
+        for (engine_version, eng) in &entry_engine.versions {
             match version_compare::compare(version, engine_version) {
                 Ok(Cmp::Eq) | Ok(Cmp::Gt) => {
                     engine = Some(eng.to_string());
@@ -553,14 +594,14 @@ impl BrowserClientList {
         engine = engine.or_else(|| entry_engine.default.clone());
 
         if engine.is_none() || engine.as_ref().unwrap() == "" {
-            engine = self::engines::lookup(ua)?;
+            engine = self::engines::lookup(ua, db)?;
         }
 
         Ok(engine)
     }
 
-    pub fn from_file(contents: &str) -> Result<Self> {
-        let res = serde_yaml::from_str(contents)?;
+    pub(crate) fn from_source(source: &Source<'_>) -> Result<Self> {
+        let res = source.parse()?;
         Ok(res)
     }
 }
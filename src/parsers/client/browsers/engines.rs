@@ -1,57 +1,164 @@
 use anyhow::Result;
-use serde::Deserialize;
 use fancy_regex::Regex;
+use serde::Deserialize;
 
+use crate::database::{Database, Source};
 use crate::parsers::utils::{lazy_user_agent_match, LazyRegex};
-use once_cell::sync::Lazy;
-
-static ENGINE_LIST: Lazy<BrowserEngineList> = Lazy::new(|| {
-    let contents = include_str!(concat!(
-        env!("CARGO_MANIFEST_DIR"),
-        "/regexes/client/browser_engine.yml"
-    ));
-    BrowserEngineList::from_file(contents).expect("loading browser_engine.yml")
-});
-
-static AVAILABLE_ENGINES: Lazy<Vec<String>> = Lazy::new(|| {
-    // hard coded list taken from matamoto device detector
-    let engines = [
-        "WebKit",
-        "Blink",
-        "Trident",
-        "Text-based",
-        "Dillo",
-        "iCab",
-        "Elektra",
-        "Presto",
-        "Clecko",
-        "Gecko",
-        "KHTML",
-        "NetFront",
-        "Edge",
-        "NetSurf",
-        "Servo",
-        "Goanna",
-        "EkiohFlow",
-        "Arachne",
-        "LibWeb",
+
+/// The rendering engines a browser may be detected with, from matomo's
+/// device detector, with a little of their history.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Engine {
+    WebKit,
+    Blink,
+    Trident,
+    TextBased,
+    Dillo,
+    ICab,
+    Elektra,
+    Presto,
+    Clecko,
+    Gecko,
+    Khtml,
+    NetFront,
+    /// EdgeHTML, the engine of the first versions of Microsoft Edge.
+    Edge,
+    NetSurf,
+    Servo,
+    Goanna,
+    EkiohFlow,
+    Arachne,
+    LibWeb,
+    Maple,
+}
+
+impl Engine {
+    pub const ALL: &'static [Engine] = &[
+        Engine::WebKit,
+        Engine::Blink,
+        Engine::Trident,
+        Engine::TextBased,
+        Engine::Dillo,
+        Engine::ICab,
+        Engine::Elektra,
+        Engine::Presto,
+        Engine::Clecko,
+        Engine::Gecko,
+        Engine::Khtml,
+        Engine::NetFront,
+        Engine::Edge,
+        Engine::NetSurf,
+        Engine::Servo,
+        Engine::Goanna,
+        Engine::EkiohFlow,
+        Engine::Arachne,
+        Engine::LibWeb,
+        Engine::Maple,
     ];
-    engines.into_iter().map(|x| x.to_owned()).collect()
-});
 
-pub fn lookup(name: &str) -> Result<Option<String>> {
-    // println!("browser engine lookup {}", name);
-    let res = match ENGINE_LIST.lookup(name)? {
-        None => AVAILABLE_ENGINES
+    /// The name as detected, in [`crate::parsers::client::Client::engine`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            Engine::WebKit => "WebKit",
+            Engine::Blink => "Blink",
+            Engine::Trident => "Trident",
+            Engine::TextBased => "Text-based",
+            Engine::Dillo => "Dillo",
+            Engine::ICab => "iCab",
+            Engine::Elektra => "Elektra",
+            Engine::Presto => "Presto",
+            Engine::Clecko => "Clecko",
+            Engine::Gecko => "Gecko",
+            Engine::Khtml => "KHTML",
+            Engine::NetFront => "NetFront",
+            Engine::Edge => "Edge",
+            Engine::NetSurf => "NetSurf",
+            Engine::Servo => "Servo",
+            Engine::Goanna => "Goanna",
+            Engine::EkiohFlow => "EkiohFlow",
+            Engine::Arachne => "Arachne",
+            Engine::LibWeb => "LibWeb",
+            Engine::Maple => "Maple",
+        }
+    }
+
+    /// Case insensitively, as the database isn't consistent.
+    pub fn from_name(name: &str) -> Option<Engine> {
+        Self::ALL
             .iter()
-            .find(|engine| engine.to_lowercase() == name.to_lowercase())
-            .map(|x| x.to_owned()),
+            .find(|x| x.name().eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    /// Who develops it, or did. `None` for the text based browsers, which
+    /// are many engines.
+    pub fn vendor(&self) -> Option<&'static str> {
+        match self {
+            Engine::WebKit => Some("Apple"),
+            Engine::Blink => Some("Google"),
+            Engine::Trident | Engine::Edge => Some("Microsoft"),
+            Engine::TextBased => None,
+            Engine::Dillo => Some("Dillo project"),
+            Engine::ICab => Some("Alexander Clauss"),
+            Engine::Elektra | Engine::Presto => Some("Opera Software"),
+            Engine::Clecko => Some("Cameron Kaiser"),
+            Engine::Gecko => Some("Mozilla"),
+            Engine::Khtml => Some("KDE"),
+            Engine::NetFront => Some("Access"),
+            Engine::NetSurf => Some("NetSurf project"),
+            Engine::Servo => Some("Linux Foundation"),
+            Engine::Goanna => Some("Moonchild Productions"),
+            Engine::EkiohFlow => Some("Ekioh"),
+            Engine::Arachne => Some("Arachne Labs"),
+            Engine::LibWeb => Some("Ladybird"),
+            Engine::Maple => Some("Samsung"),
+        }
+    }
+
+    /// The engine its vendor replaced it with, such as blink for presto when
+    /// opera moved to chromium.
+    pub fn successor(&self) -> Option<Engine> {
+        match self {
+            Engine::Elektra => Some(Engine::Presto),
+            Engine::Presto | Engine::Edge => Some(Engine::Blink),
+            Engine::Trident => Some(Engine::Edge),
+            _ => None,
+        }
+    }
+
+    /// The engine it was forked from, such as webkit for blink.
+    pub fn forked_from(&self) -> Option<Engine> {
+        match self {
+            Engine::WebKit => Some(Engine::Khtml),
+            Engine::Blink | Engine::Maple => Some(Engine::WebKit),
+            Engine::Clecko | Engine::Goanna => Some(Engine::Gecko),
+            _ => None,
+        }
+    }
+
+    /// The successors of the engine, in order, such as edge then blink for
+    /// trident.
+    pub fn successors(&self) -> impl Iterator<Item = Engine> {
+        std::iter::successors(self.successor(), Engine::successor)
+    }
+}
+
+impl std::fmt::Display for Engine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+pub fn lookup(name: &str, db: &Database) -> Result<Option<String>> {
+    // println!("browser engine lookup {}", name);
+    let res = match db.browser_engines.lookup(name)? {
+        None => Engine::from_name(name).map(|x| x.name().to_owned()),
         res => res,
     };
     Ok(res)
 }
 
-struct BrowserEngineList {
+pub(crate) struct BrowserEngineList {
     list: Vec<BrowserEngine>,
 }
 
@@ -62,6 +169,10 @@ struct BrowserEngine {
 }
 
 impl BrowserEngineList {
+    pub(crate) fn regexes(&self) -> impl Iterator<Item = &LazyRegex> {
+        self.list.iter().map(|x| &x.regex)
+    }
+
     fn lookup(&self, ua: &str) -> Result<Option<String>> {
         for engine in &self.list {
             // println!("engine {:?}", engine);
@@ -73,7 +184,7 @@ impl BrowserEngineList {
 
         Ok(None)
     }
-    fn from_file(contents: &str) -> Result<Self> {
+    pub(crate) fn from_source(source: &Source<'_>) -> Result<Self> {
         #[derive(Debug, Deserialize)]
         #[serde(transparent)]
         struct YamlBrowserEngineList {
@@ -106,7 +217,7 @@ impl BrowserEngineList {
             }
         }
 
-        let res: YamlBrowserEngineList = serde_yaml::from_str(contents)?;
+        let res: YamlBrowserEngineList = source.parse()?;
         Ok(res.into())
     }
 }
@@ -142,7 +253,7 @@ pub fn detect_engine_version(ua: &str, engine: &str) -> Result<Option<String>> {
     // The conditional regex (?(?=\d+\.\d)\d+[.\d]*|\d{1,7}(?=(?:\D|$))) is complex, let's simplify
     let pattern = format!(r"(?i)(?:{})\s*[/_]?\s*(\d+(?:\.\d+)*)", engine_token);
     let regex = Regex::new(&pattern)?;
-    
+
     if let Some(captures) = regex.captures(ua)? {
         if let Some(version_match) = captures.get(1) {
             return Ok(Some(version_match.as_str().to_owned()));
@@ -0,0 +1,20 @@
+//! Download managers and offline browsers, which fetch files or mirror whole
+//! sites rather than browse them. Matomo's database has a few of them among
+//! its libraries, and most not at all, so detecting them is opt in, see
+//! [`crate::device_detector::DeviceDetector::with_download_managers`]. The
+//! list is maintained in `src/parsers/client/download_managers.yml`.
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+
+use super::{Client, ClientList, ClientType};
+
+use crate::device_detector::ParseContext;
+
+static DOWNLOAD_MANAGERS: Lazy<ClientList> = Lazy::new(|| {
+    ClientList::from_file(include_str!("download_managers.yml")).expect("valid download managers")
+});
+
+pub fn lookup(ua: &str, ctx: &mut ParseContext) -> Result<Option<Client>> {
+    DOWNLOAD_MANAGERS.lookup(ua, ClientType::DownloadManager, ctx)
+}
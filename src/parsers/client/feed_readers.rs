@@ -1,16 +1,11 @@
 use anyhow::Result;
 
-use super::{Client, ClientList};
-use once_cell::sync::Lazy;
+use super::Client;
 
-static CLIENT_LIST: Lazy<ClientList> = Lazy::new(|| {
-    let contents = include_str!(concat!(
-        env!("CARGO_MANIFEST_DIR"),
-        "/regexes/client/feed_readers.yml"
-    ));
-    ClientList::from_file(contents).expect("loading feed_readers.yml")
-});
+use crate::database::Database;
+use crate::device_detector::ParseContext;
 
-pub fn lookup(ua: &str) -> Result<Option<Client>> {
-    CLIENT_LIST.lookup(ua, super::ClientType::FeedReader)
+pub fn lookup(ua: &str, db: &Database, ctx: &mut ParseContext) -> Result<Option<Client>> {
+    db.feed_readers
+        .lookup(ua, super::ClientType::FeedReader, ctx)
 }
@@ -2,6 +2,8 @@ use anyhow::Result;
 use serde::Deserialize;
 use std::collections::HashMap;
 
+use crate::database::Source;
+
 pub mod apps;
 pub mod browsers;
 
@@ -13,7 +15,11 @@ pub struct HintList {
 
 impl HintList {
     pub fn from_file(contents: &str) -> Result<HintList> {
-        let hints: HintList = serde_yaml::from_str(contents)?;
+        Self::from_source(&Source::Yaml(contents.into()))
+    }
+
+    pub(crate) fn from_source(source: &Source<'_>) -> Result<HintList> {
+        let hints: HintList = source.parse()?;
         Ok(hints)
     }
 
@@ -22,3 +28,12 @@ impl HintList {
         Ok(res.map(|s| s.as_ref()))
     }
 }
+
+/// `X-Requested-With` package names mapped to app and browser names at
+/// runtime, such as internal apps, consulted before `client/hints/apps.yml`
+/// and `client/hints/browsers.yml`.
+#[derive(Clone, Debug, Default)]
+pub struct HintMappings {
+    pub apps: HashMap<String, String>,
+    pub browsers: HashMap<String, String>,
+}
@@ -1,16 +1,11 @@
 use anyhow::Result;
 
-use super::HintList;
-use once_cell::sync::Lazy;
+use crate::database::Database;
+use crate::device_detector::ParseContext;
 
-static HINT_LIST: Lazy<HintList> = Lazy::new(|| {
-    let contents = include_str!(concat!(
-        env!("CARGO_MANIFEST_DIR"),
-        "/regexes/client/hints/apps.yml"
-    ));
-    HintList::from_file(contents).expect("loading hints/apps.yml")
-});
-
-pub fn get_hint(app: &str) -> Result<Option<&str>> {
-    HINT_LIST.get_hint(app)
+pub fn get_hint(app: &str, db: &Database, ctx: &ParseContext) -> Result<Option<String>> {
+    if let Some(res) = ctx.hint_mappings.as_ref().and_then(|x| x.apps.get(app)) {
+        return Ok(Some(res.clone()));
+    }
+    Ok(db.hint_apps.get_hint(app)?.map(|x| x.to_owned()))
 }
@@ -1,16 +1,11 @@
 use anyhow::Result;
 
-use super::{Client, ClientList};
-use once_cell::sync::Lazy;
+use super::Client;
 
-static CLIENT_LIST: Lazy<ClientList> = Lazy::new(|| {
-    let contents = include_str!(concat!(
-        env!("CARGO_MANIFEST_DIR"),
-        "/regexes/client/mediaplayers.yml"
-    ));
-    ClientList::from_file(contents).expect("loading mediaplayers.yml")
-});
+use crate::database::Database;
+use crate::device_detector::ParseContext;
 
-pub fn lookup(ua: &str) -> Result<Option<Client>> {
-    CLIENT_LIST.lookup(ua, super::ClientType::MediaPlayer)
+pub fn lookup(ua: &str, db: &Database, ctx: &mut ParseContext) -> Result<Option<Client>> {
+    db.media_players
+        .lookup(ua, super::ClientType::MediaPlayer, ctx)
 }
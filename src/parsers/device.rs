@@ -2,8 +2,6 @@ use anyhow::Result;
 
 use serde::{Deserialize, Serialize};
 
-use serde_yaml::Value;
-
 use once_cell::sync::Lazy;
 
 use version_compare::{self, Version};
@@ -12,24 +10,32 @@ use super::vendor_fragments;
 
 use std::borrow::Cow;
 
+use crate::catalog::DeviceSpec;
 use crate::client_hints::ClientHint;
+use crate::database::{Database, Source};
+use crate::device_detector::ParseContext;
+use crate::hints_used::Hint;
 use crate::parsers::client::{Client, ClientType};
 use crate::parsers::oss::OS;
 
 use crate::parsers::utils::{
-    lazy_user_agent_match, static_user_agent_match, LazyRegex, SafeRegex as Regex,
+    check_template, lazy_user_agent_match, static_user_agent_match, LazyRegex, SafeRegex as Regex,
 };
 
+use fancy_regex::Captures;
+
 pub mod cameras;
 pub mod car_browsers;
 pub mod consoles;
+pub mod hbbtv;
 pub mod mobiles;
+pub mod model_parts;
 pub mod notebooks;
 pub mod portable_media_players;
 pub mod shell_tvs;
 pub mod televisions;
 
-#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum DeviceType {
     #[serde(rename = "desktop")]
     Desktop,
@@ -86,8 +92,14 @@ impl DeviceType {
             Self::Desktop => "desktop",
         }
     }
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(name: &str) -> DeviceType {
-        match name {
+        Self::parse(name).unwrap_or_else(|| panic!("Unknown device type {}", name))
+    }
+
+    /// The device type named `name`, as [`Self::as_str`] names it.
+    pub fn parse(name: &str) -> Option<DeviceType> {
+        let res = match name {
             "desktop" => Self::Desktop,
             "smartphone" => Self::SmartPhone,
             "feature phone" => Self::FeaturePhone,
@@ -103,12 +115,13 @@ impl DeviceType {
             "notebook" => Self::Notebook,
             "wearable" => Self::Wearable,
             "peripheral" => Self::Peripheral,
-            _ => panic!("Unknown device type {}", name),
-        }
+            _ => return None,
+        };
+        Some(res)
     }
 }
 
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Device {
     #[serde(rename = "type")]
     pub device_type: Option<DeviceType>,
@@ -117,13 +130,111 @@ pub struct Device {
 
     // Can be gotten from headers, and can solely determine whether
     // this is a mobile device or not regardless of everything else.
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub(crate) mobile_client_hint: bool,
-    #[serde(skip)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub(crate) touch_enabled: bool,
+
+    /// How directly each of the fields above was detected.
+    #[serde(default, skip_serializing_if = "DeviceConfidence::is_empty")]
+    pub confidence: DeviceConfidence,
+
+    /// From the catalog configured with
+    /// [`crate::device_detector::DeviceDetector::with_device_catalog`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<DeviceSpec>,
+
+    /// The HbbTV capability string of TVs which send one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hbbtv: Option<hbbtv::HbbTv>,
 }
 
-#[derive(Debug)]
+/// How directly a device field was detected, for weighting it, such as in
+/// features of a model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    /// Named by the device database entry the user agent matched, or sent in
+    /// the client hints.
+    Exact,
+    /// Derived from parts of the request which point to it, such as a
+    /// `Tablet` token, the os, or the form factors hint.
+    Inferred,
+    /// A default for requests which say little about their device, such as
+    /// desktop for a desktop os.
+    Fallback,
+}
+
+/// The [`Confidence`] of each field of a [`Device`], `None` where the field
+/// wasn't detected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceConfidence {
+    #[serde(rename = "type")]
+    pub device_type: Option<Confidence>,
+    pub brand: Option<Confidence>,
+    pub model: Option<Confidence>,
+}
+
+impl DeviceConfidence {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Device {
+    /// The series, variant and such of the model, see [`model_parts`].
+    /// `None` without a model.
+    pub fn model_parts(&self) -> Result<Option<model_parts::ModelParts>> {
+        let Some(model) = &self.model else {
+            return Ok(None);
+        };
+        let brand = self.brand.as_deref().unwrap_or_default();
+        Ok(Some(model_parts::split(brand, model)?))
+    }
+
+    fn set_type(&mut self, device_type: DeviceType, confidence: Confidence) {
+        self.device_type = Some(device_type);
+        self.confidence.device_type = Some(confidence);
+    }
+}
+
+/// How a device list chooses between several brands whose regexes all match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeviceMatchStrategy {
+    /// Use the first brand that matches, in file order, as the php library does.
+    #[default]
+    First,
+    /// Try every brand and use the most specific match, being the longest
+    /// match and then the one with the most groups captured. This costs
+    /// running every brand regex against every user agent.
+    Best,
+}
+
+/// The device type assumed from `Sec-CH-UA-Mobile` when neither the user
+/// agent nor the other client hints determine one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MobileHintFallback {
+    /// No device type, as the php library does.
+    #[default]
+    Off,
+    /// `?1` is a smartphone and `?0` a desktop.
+    SmartPhoneOrDesktop,
+    /// `?1` is a smartphone, `?0` is left without a device type.
+    SmartPhone,
+}
+
+impl MobileHintFallback {
+    fn device_type(self, mobile: bool) -> Option<DeviceType> {
+        match (self, mobile) {
+            (Self::Off, _) => None,
+            (Self::SmartPhoneOrDesktop | Self::SmartPhone, true) => Some(DeviceType::SmartPhone),
+            (Self::SmartPhoneOrDesktop, false) => Some(DeviceType::Desktop),
+            (Self::SmartPhone, false) => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
 pub struct DeviceList {
     devices: Vec<(String, DeviceEntry)>,
 }
@@ -146,6 +257,8 @@ struct DeviceEntry {
 struct DeviceMatchResult {
     device: Option<DeviceType>,
     model: Option<ModelMatchResult>,
+    // length of the brand regex match and the number of groups it captured
+    specificity: (usize, usize),
 }
 
 #[derive(Debug)]
@@ -202,6 +315,8 @@ pub fn lookup(
     client: Option<&Client>,
     client_hints: Option<&ClientHint>,
     os_info: Option<&OS>,
+    db: &Database,
+    ctx: &mut ParseContext,
 ) -> Result<Option<Device>> {
     static ANDROID_10_MODEL: Lazy<Regex> =
         Lazy::new(|| Regex::new(r"(Android 10[.\d]*; K)(?: Build/|[;)])").unwrap());
@@ -216,6 +331,7 @@ pub fn lookup(
                         os_info.and_then(|os| os.version.as_deref()).unwrap_or("10");
                     let replacement = format!("Android {}; {}", os_version, model);
                     let res = ua.replace(&captures[1], &replacement);
+                    ctx.use_hint(Hint::Model);
 
                     break 'ua Cow::Owned(res);
                 }
@@ -226,33 +342,38 @@ pub fn lookup(
     };
 
     let mut device = 'dev: {
-        if let Some(res) = televisions::lookup(&ua)? {
+        // nothing to match against, the device can only come from client hints.
+        if ua.trim().is_empty() {
+            break 'dev Device::default();
+        }
+
+        if let Some(res) = televisions::lookup(&ua, db, ctx)? {
             break 'dev res;
         }
 
-        if let Some(res) = shell_tvs::lookup(&ua)? {
+        if let Some(res) = shell_tvs::lookup(&ua, db, ctx)? {
             break 'dev res;
         }
-        if let Some(res) = notebooks::lookup(&ua)? {
+        if let Some(res) = notebooks::lookup(&ua, db, ctx)? {
             break 'dev res;
         }
 
-        if let Some(res) = consoles::lookup(&ua)? {
+        if let Some(res) = consoles::lookup(&ua, db, ctx)? {
             break 'dev res;
         }
 
-        if let Some(res) = car_browsers::lookup(&ua)? {
+        if let Some(res) = car_browsers::lookup(&ua, db, ctx)? {
             break 'dev res;
         }
 
-        if let Some(res) = cameras::lookup(&ua)? {
+        if let Some(res) = cameras::lookup(&ua, db, ctx)? {
             break 'dev res;
         }
-        if let Some(res) = portable_media_players::lookup(&ua)? {
+        if let Some(res) = portable_media_players::lookup(&ua, db, ctx)? {
             break 'dev res;
         }
 
-        if let Some(res) = mobiles::lookup(&ua)? {
+        if let Some(res) = mobiles::lookup(&ua, db, ctx)? {
             break 'dev res;
         }
 
@@ -264,6 +385,13 @@ pub fn lookup(
         }
     };
 
+    // whatever the device lists matched is named by the database
+    device.confidence = DeviceConfidence {
+        device_type: device.device_type.as_ref().map(|_| Confidence::Exact),
+        brand: device.brand.as_ref().map(|_| Confidence::Exact),
+        model: device.model.as_ref().map(|_| Confidence::Exact),
+    };
+
     static TOUCH: Lazy<Regex> = static_user_agent_match!(r#"Touch"#);
 
     if TOUCH.is_match(&ua)? {
@@ -273,16 +401,20 @@ pub fn lookup(
     if let Some(client_hints) = client_hints {
         if device.model.is_none() && client_hints.model.is_some() {
             device.model = client_hints.model.clone();
-            
+            device.confidence.model = Some(Confidence::Exact);
+            ctx.use_hint(Hint::Model);
+
             // If we got a model from client hints, try to detect device type from the model
             if device.device_type.is_none() {
                 if let Some(model) = &device.model {
                     // Create a synthetic UA containing the model to trigger device detection
                     let model_ua = format!("Android; {}", model);
-                    if let Some(mobile_device) = mobiles::lookup(&model_ua)? {
+                    if let Some(mobile_device) = mobiles::lookup(&model_ua, db, ctx)? {
                         device.device_type = mobile_device.device_type;
+                        device.confidence.device_type = Some(Confidence::Inferred);
                         if device.brand.is_none() {
                             device.brand = mobile_device.brand;
+                            device.confidence.brand = Some(Confidence::Inferred);
                         }
                         // Update the model to the properly formatted name from the device database
                         device.model = mobile_device.model;
@@ -293,17 +425,24 @@ pub fn lookup(
 
         if client_hints.mobile {
             device.mobile_client_hint = true;
+            ctx.use_hint(Hint::Mobile);
         }
 
         // Check FormFactors for device type detection
         if device.device_type.is_none() && !client_hints.form_factors.is_empty() {
             device.device_type = get_device_type_from_form_factors(&client_hints.form_factors);
+            device.confidence.device_type =
+                device.device_type.as_ref().map(|_| Confidence::Inferred);
+            if device.device_type.is_some() {
+                ctx.use_hint(Hint::FormFactors);
+            }
         }
     }
 
     if device.brand.is_none() {
-        if let Some(brand) = vendor_fragments::lookup(&ua)? {
+        if let Some(brand) = vendor_fragments::lookup(&ua, db)? {
             device.brand = Some(brand.to_owned());
+            device.confidence.brand = Some(Confidence::Inferred);
         }
     }
 
@@ -313,13 +452,13 @@ pub fn lookup(
                 device.device_type = None;
                 device.brand = None;
                 device.model = None;
+                device.confidence = DeviceConfidence::default();
             }
         }
 
-        if device.brand.is_none() {
-            if APPLE_OS_NAMES.iter().any(|x| *x == os.name) {
-                device.brand = Some("Apple".to_owned());
-            }
+        if device.brand.is_none() && APPLE_OS_NAMES.iter().any(|x| *x == os.name) {
+            device.brand = Some("Apple".to_owned());
+            device.confidence.brand = Some(Confidence::Inferred);
         }
     }
 
@@ -333,7 +472,7 @@ pub fn lookup(
     static OPERA_TABLET: Lazy<Regex> = static_user_agent_match!(r#"Opera Tablet"#);
 
     if device.device_type.is_none() && ANDROID_VR.is_match(&ua)? {
-        device.device_type = Some(DeviceType::Wearable);
+        device.set_type(DeviceType::Wearable, Confidence::Inferred);
     }
 
     if let Some(os) = &os_info {
@@ -344,9 +483,9 @@ pub fn lookup(
             if let Some(family) = &os.family {
                 if family == "Android" && CHROME.is_match(&ua)? {
                     if SAFARI_PHONE.is_match(&ua)? {
-                        device.device_type = Some(DeviceType::SmartPhone);
+                        device.set_type(DeviceType::SmartPhone, Confidence::Inferred);
                     } else if SAFARI_TAB.is_match(&ua)? {
-                        device.device_type = Some(DeviceType::Tablet);
+                        device.set_type(DeviceType::Tablet, Confidence::Inferred);
                     }
                 }
             }
@@ -354,17 +493,17 @@ pub fn lookup(
     }
 
     if device.device_type == Some(DeviceType::SmartPhone) && APAD_TABLET.is_match(&ua)? {
-        device.device_type = Some(DeviceType::Tablet);
+        device.set_type(DeviceType::Tablet, Confidence::Inferred);
     }
 
     if device.device_type.is_none()
         && (ANDROID_TABLET.is_match(&ua)? || OPERA_TABLET.is_match(&ua)?)
     {
-        device.device_type = Some(DeviceType::Tablet);
+        device.set_type(DeviceType::Tablet, Confidence::Inferred);
     }
 
     if device.device_type.is_none() && ANDROID_MOBILE.is_match(&ua)? {
-        device.device_type = Some(DeviceType::SmartPhone);
+        device.set_type(DeviceType::SmartPhone, Confidence::Inferred);
     }
 
     if let Some(os) = &os_info {
@@ -377,9 +516,9 @@ pub fn lookup(
             if let Some(os_version) = os.version.as_ref() {
                 if let Some(os_version) = Version::from(os_version) {
                     if os_version < *V2 {
-                        device.device_type = Some(DeviceType::SmartPhone);
+                        device.set_type(DeviceType::SmartPhone, Confidence::Fallback);
                     } else if os_version >= *V3 && os_version < *V4 {
-                        device.device_type = Some(DeviceType::Tablet);
+                        device.set_type(DeviceType::Tablet, Confidence::Fallback);
                     }
                 }
             }
@@ -388,31 +527,31 @@ pub fn lookup(
             if *device_type == DeviceType::FeaturePhone {
                 if let Some(family) = &os.family {
                     if family == "Android" {
-                        device.device_type = Some(DeviceType::SmartPhone);
+                        device.set_type(DeviceType::SmartPhone, Confidence::Inferred);
                     }
                 }
             }
         }
 
         if device.device_type.is_none() && os.name == "Java ME" {
-            device.device_type = Some(DeviceType::FeaturePhone);
+            device.set_type(DeviceType::FeaturePhone, Confidence::Fallback);
         }
-        
+
         // KaiOS devices are always feature phones, even if detected as smartphones
         if os.name == "KaiOS" {
-            device.device_type = Some(DeviceType::FeaturePhone);
+            device.set_type(DeviceType::FeaturePhone, Confidence::Inferred);
         }
 
         if device.device_type.is_none() {
             if os.name == "Windows RT" {
-                device.device_type = Some(DeviceType::Tablet);
+                device.set_type(DeviceType::Tablet, Confidence::Inferred);
             }
             if let Some(os_version) = os.version.as_ref() {
                 if let Some(os_version) = Version::from(os_version) {
                     if os.name == "Windows RT"
                         || (os.name == "Windows" && os_version >= *V8 && is_touch(&ua)?)
                     {
-                        device.device_type = Some(DeviceType::Tablet);
+                        device.set_type(DeviceType::Tablet, Confidence::Inferred);
                     }
                 }
             }
@@ -421,18 +560,19 @@ pub fn lookup(
 
     // Puffin browser device type detection patterns
     static PUFFIN_DESKTOP: Lazy<Regex> = static_user_agent_match!(r#"Puffin/(?:\d+[.\d]+)[LMW]D"#);
-    static PUFFIN_SMARTPHONE: Lazy<Regex> = static_user_agent_match!(r#"Puffin/(?:\d+[.\d]+)[AIFLW]P"#);
+    static PUFFIN_SMARTPHONE: Lazy<Regex> =
+        static_user_agent_match!(r#"Puffin/(?:\d+[.\d]+)[AIFLW]P"#);
     static PUFFIN_TABLET: Lazy<Regex> = static_user_agent_match!(r#"Puffin/(?:\d+[.\d]+)[AILW]T"#);
 
     // Check for Puffin browser device type patterns first
     if device.device_type.is_none() && PUFFIN_DESKTOP.is_match(&ua)? {
-        device.device_type = Some(DeviceType::Desktop);
+        device.set_type(DeviceType::Desktop, Confidence::Inferred);
     }
     if device.device_type.is_none() && PUFFIN_SMARTPHONE.is_match(&ua)? {
-        device.device_type = Some(DeviceType::SmartPhone);
+        device.set_type(DeviceType::SmartPhone, Confidence::Inferred);
     }
     if device.device_type.is_none() && PUFFIN_TABLET.is_match(&ua)? {
-        device.device_type = Some(DeviceType::Tablet);
+        device.set_type(DeviceType::Tablet, Confidence::Inferred);
     }
 
     static OPERA: Lazy<Regex> = static_user_agent_match!(r#"Opera TV Store| OMI/"#);
@@ -442,20 +582,20 @@ pub fn lookup(
     static GENERIC_TV: Lazy<Regex> = static_user_agent_match!(r#"\(TV;"#);
 
     // Only set TV type if device is not already detected as TV or Peripheral
-    let should_check_tv = match &device.device_type {
-        Some(DeviceType::Television) | Some(DeviceType::Peripheral) => false,
-        _ => true,
-    };
-    
+    let should_check_tv = !matches!(
+        &device.device_type,
+        Some(DeviceType::Television) | Some(DeviceType::Peripheral)
+    );
+
     if should_check_tv {
         if OPERA.is_match(&ua)? {
-            device.device_type = Some(DeviceType::Television);
+            device.set_type(DeviceType::Television, Confidence::Inferred);
         }
         if ANDR0ID.is_match(&ua)? {
-            device.device_type = Some(DeviceType::Television);
+            device.set_type(DeviceType::Television, Confidence::Inferred);
         }
         if device.device_type.is_none() && TIZEN.is_match(&ua)? {
-            device.device_type = Some(DeviceType::Television);
+            device.set_type(DeviceType::Television, Confidence::Inferred);
         }
     }
 
@@ -479,12 +619,12 @@ pub fn lookup(
             .iter()
             .any(|x| *x == client.name)
             {
-                device.device_type = Some(DeviceType::Television);
+                device.set_type(DeviceType::Television, Confidence::Inferred);
             }
         }
 
         if device.device_type.is_none() && GENERIC_TV.is_match(&ua)? {
-            device.device_type = Some(DeviceType::Television);
+            device.set_type(DeviceType::Television, Confidence::Inferred);
         }
     }
 
@@ -496,21 +636,89 @@ pub fn lookup(
             && ua.contains("Desktop")
             && DESKTOP_FRAGMENT.is_match(&ua)?
         {
-            device.device_type = Some(DeviceType::Desktop);
+            device.set_type(DeviceType::Desktop, Confidence::Inferred);
         }
     }
     if device.device_type.is_none() && DESKTOP_FRAGMENT.is_match(&ua)? {
-        device.device_type = Some(DeviceType::Desktop);
+        device.set_type(DeviceType::Desktop, Confidence::Inferred);
     }
 
     if device.device_type.is_none() && is_desktop(os_info, client) {
-        device.device_type = Some(DeviceType::Desktop);
+        device.set_type(DeviceType::Desktop, Confidence::Fallback);
+    }
+
+    // Chrome on Chrome OS freezes its user agent to the one of Linux, and the
+    // android apps of Chromebooks send the one of a phone, but the platform
+    // hint still says Chrome OS, which only runs on laptops and desktops.
+    if let Some(client_hints) = client_hints.filter(|x| is_chrome_os_platform(x)) {
+        if device.brand.is_none() {
+            if let Some(brand) = chromebook_brand(&ua)? {
+                device.brand = Some(brand.to_owned());
+                device.confidence.brand = Some(Confidence::Inferred);
+            }
+        }
+
+        let form_factor = get_device_type_from_form_factors(&client_hints.form_factors)
+            .filter(|x| matches!(x, DeviceType::Tablet | DeviceType::Desktop));
+        if form_factor.is_some() {
+            ctx.use_hint(Hint::FormFactors);
+        }
+        device.set_type(
+            form_factor.unwrap_or(DeviceType::Desktop),
+            Confidence::Inferred,
+        );
+        ctx.use_hint(Hint::Platform);
     }
-    
+
     // If user agent contains KaiOS but device type is still not detected, it's a feature phone
     static KAIOS_UA: Lazy<Regex> = static_user_agent_match!(r#"KaiOS"#);
     if device.device_type.is_none() && KAIOS_UA.is_match(&ua)? {
-        device.device_type = Some(DeviceType::FeaturePhone);
+        device.set_type(DeviceType::FeaturePhone, Confidence::Inferred);
+    }
+
+    // headsets and watches often send a user agent copied from a phone or a
+    // desktop, so their form factors win over the generic types guessed from it.
+    if let Some(client_hints) = client_hints {
+        if get_device_type_from_form_factors(&client_hints.form_factors)
+            == Some(DeviceType::Wearable)
+            && matches!(
+                device.device_type,
+                None | Some(
+                    DeviceType::SmartPhone
+                        | DeviceType::Phablet
+                        | DeviceType::Tablet
+                        | DeviceType::FeaturePhone
+                        | DeviceType::Desktop
+                )
+            )
+        {
+            device.set_type(DeviceType::Wearable, Confidence::Inferred);
+            ctx.use_hint(Hint::FormFactors);
+        }
+    }
+
+    if device.device_type.is_none() {
+        if let Some(client_hints) = client_hints.filter(|x| x.mobile_sent) {
+            device.device_type = ctx
+                .options
+                .mobile_hint_fallback
+                .device_type(client_hints.mobile);
+            device.confidence.device_type =
+                device.device_type.as_ref().map(|_| Confidence::Fallback);
+            if device.device_type.is_some() {
+                ctx.use_hint(Hint::Mobile);
+            }
+        }
+    }
+
+    if device.device_type.is_none() {
+        device.confidence.device_type = None;
+    }
+    if device.brand.is_none() {
+        device.confidence.brand = None;
+    }
+    if device.model.is_none() {
+        device.confidence.model = None;
     }
 
     if device.device_type.is_none() && device.brand.is_none() && device.model.is_none() {
@@ -553,6 +761,32 @@ pub(crate) fn uses_mobile_browser(client: &Client) -> bool {
     false
 }
 
+fn is_chrome_os_platform(client_hints: &ClientHint) -> bool {
+    client_hints.platform.as_deref().is_some_and(|x| {
+        x.eq_ignore_ascii_case("Chrome OS") || x.eq_ignore_ascii_case("Chromium OS")
+    })
+}
+
+/// The brand of a Chromebook named in the user agent, as android apps and
+/// some webviews do, `HP Chromebook x360 14` for instance.
+fn chromebook_brand(ua: &str) -> Result<Option<&'static str>> {
+    static CHROMEBOOK: Lazy<Regex> = static_user_agent_match!(
+        r#"(Acer|Asus|Dell|Google|Haier|HP|Lenovo|LG|Medion|Positivo|Samsung|Toshiba)[ _]Chromebook"#
+    );
+    static BRANDS: [&str; 12] = [
+        "Acer", "Asus", "Dell", "Google", "Haier", "HP", "Lenovo", "LG", "Medion", "Positivo",
+        "Samsung", "Toshiba",
+    ];
+
+    let Some(captures) = CHROMEBOOK.captures(ua)? else {
+        return Ok(None);
+    };
+    Ok(captures
+        .get(1)
+        .and_then(|x| BRANDS.iter().find(|b| b.eq_ignore_ascii_case(x.as_str())))
+        .copied())
+}
+
 fn is_touch(ua: &str) -> Result<bool> {
     static TOUCH: Lazy<Regex> = static_user_agent_match!(r#"Touch"#);
 
@@ -561,64 +795,48 @@ fn is_touch(ua: &str) -> Result<bool> {
 }
 
 impl DeviceList {
-    fn lookup(&self, ua: &str, _type: &str) -> Result<Option<Device>> {
-        for (name, device) in self.devices.iter() {
-            if let Some(match_result) = device.lookup(ua)? {
-                static TD: Lazy<Regex> = Lazy::new(|| Regex::new(r#" [Tt][Dd]$"#).unwrap());
-
-                let mut model: Option<String> =
-                    match match_result.model.as_ref().map(|x| x.model.as_str()) {
-                        None => None,
-                        Some("") => None,
-                        Some(model) => Some(
-                            TD.replace_all(model.replace('_', " ").trim(), "")
-                                .into_owned(),
-                        ),
-                    };
-
-                if let Some(m) = &model {
-                    if m == "Build" {
-                        model = None;
-                    }
-                }
+    pub(crate) fn regexes(&self) -> impl Iterator<Item = &LazyRegex> {
+        self.devices.iter().flat_map(|(_, x)| {
+            std::iter::once(&x.regex).chain(x.models.iter().filter_map(|x| x.regex.as_ref()))
+        })
+    }
 
-                let device_type: Option<DeviceType> = match_result
-                    .model
-                    .as_ref()
-                    .and_then(|model| model.device.as_ref())
-                    .or(match_result.device.as_ref())
-                    .cloned();
-
-                let mut brand = match_result
-                    .model
-                    .as_ref()
-                    .and_then(|model| model.brand.as_ref())
-                    .or(Some(name))
-                    .cloned();
-
-                if let Some(b) = &brand {
-                    if b == "Unknown" {
-                        brand = None;
-                    }
-                }
+    /// Number of models of all brands.
+    pub(crate) fn models(&self) -> usize {
+        self.devices.iter().map(|(_, x)| x.models.len()).sum()
+    }
 
-                let dev = Device {
-                    device_type,
-                    model,
-                    brand,
-                    ..Default::default()
-                };
+    /// The regex of each brand, without those of its models.
+    pub(crate) fn brand_regexes(&self) -> impl Iterator<Item = &LazyRegex> {
+        self.devices.iter().map(|(_, x)| &x.regex)
+    }
 
-                return Ok(Some(dev));
+    fn lookup(&self, ua: &str, _type: &str, ctx: &mut ParseContext) -> Result<Option<Device>> {
+        let mut best: Option<(&String, DeviceMatchResult)> = None;
+
+        for (name, device) in self.devices.iter() {
+            if let Some(match_result) = device.lookup(ua, ctx)? {
+                match ctx.options.device_match {
+                    DeviceMatchStrategy::First => return Ok(Some(match_result.into_device(name))),
+                    DeviceMatchStrategy::Best => {
+                        let better = best
+                            .as_ref()
+                            .is_none_or(|(_, x)| match_result.specificity > x.specificity);
+
+                        if better {
+                            best = Some((name, match_result));
+                        }
+                    }
+                }
             }
         }
 
-        Ok(None)
+        Ok(best.map(|(name, match_result)| match_result.into_device(name)))
     }
 
-    fn from_file(file_contents: &str) -> Result<DeviceList> {
+    pub(crate) fn from_source(source: &Source<'_>) -> Result<DeviceList> {
         #[derive(Debug, Deserialize)]
-        #[serde(try_from = "Value")]
+        #[serde(from = "YamlModel")]
         struct YamlModelEntry {
             regex: Option<String>,
             device: Option<String>,
@@ -693,61 +911,124 @@ impl DeviceList {
             }
         }
 
-        impl TryFrom<Value> for YamlModelEntry {
-            type Error = anyhow::Error;
+        // a model is either its name or a mapping, deserialized straight
+        // rather than through a Value, which took most of the load time of
+        // device/mobiles.yml.
+        #[derive(Debug, Deserialize)]
+        #[serde(untagged)]
+        enum YamlModel {
+            Name(String),
+            Entry {
+                regex: Option<String>,
+                device: Option<String>,
+                model: String,
+                brand: Option<String>,
+            },
+        }
 
-            fn try_from(value: Value) -> Result<Self, Self::Error> {
-                let model = match value {
-                    Value::String(str) => YamlModelEntry {
+        impl From<YamlModel> for YamlModelEntry {
+            fn from(value: YamlModel) -> Self {
+                match value {
+                    YamlModel::Name(model) => YamlModelEntry {
                         regex: None,
                         device: None,
                         brand: None,
-                        model: str,
+                        model,
                     },
-                    Value::Mapping(mappings) => {
-                        let model = mappings["model"]
-                            .as_str()
-                            .ok_or(anyhow::anyhow!("invalid model"))?;
-
-                        let regex = mappings["regex"].as_str();
-                        let device = mappings.get("device").and_then(|x| x.as_str());
-                        let brand = mappings.get("brand").and_then(|x| x.as_str());
-
-                        YamlModelEntry {
-                            regex: regex.map(|x| x.to_owned()),
-                            device: device.map(|x| x.to_owned()),
-                            brand: brand.map(|x| x.to_owned()),
-                            model: model.to_owned(),
-                        }
-                    }
-                    err => Err(anyhow::anyhow!("Invalid model {:?}", err))?,
-                };
-                Ok(model)
+                    YamlModel::Entry {
+                        regex,
+                        device,
+                        model,
+                        brand,
+                    } => YamlModelEntry {
+                        regex,
+                        device,
+                        brand,
+                        model,
+                    },
+                }
             }
         }
 
-        let res: YamlDeviceList = serde_yaml::from_str(file_contents)?;
+        let res: YamlDeviceList = source.parse()?;
         Ok(res.into())
     }
 }
 
+impl DeviceMatchResult {
+    fn into_device(self, name: &str) -> Device {
+        static TD: Lazy<Regex> = Lazy::new(|| Regex::new(r#" [Tt][Dd]$"#).unwrap());
+
+        let mut model: Option<String> = match self.model.as_ref().map(|x| x.model.as_str()) {
+            None => None,
+            Some("") => None,
+            Some(model) => Some(
+                TD.replace_all(model.replace('_', " ").trim(), "")
+                    .into_owned(),
+            ),
+        };
+
+        if let Some(m) = &model {
+            if m == "Build" {
+                model = None;
+            }
+        }
+
+        let device_type: Option<DeviceType> = self
+            .model
+            .as_ref()
+            .and_then(|model| model.device.as_ref())
+            .or(self.device.as_ref())
+            .cloned();
+
+        let mut brand = self
+            .model
+            .as_ref()
+            .and_then(|model| model.brand.as_deref())
+            .or(Some(name))
+            .map(|x| x.to_owned());
+
+        if let Some(b) = &brand {
+            if b == "Unknown" {
+                brand = None;
+            }
+        }
+
+        Device {
+            device_type,
+            model,
+            brand,
+            ..Default::default()
+        }
+    }
+}
+
 impl DeviceEntry {
-    fn lookup(&self, ua: &str) -> Result<Option<DeviceMatchResult>> {
+    fn lookup(&self, ua: &str, ctx: &mut ParseContext) -> Result<Option<DeviceMatchResult>> {
         let res = if let Some(captures) = self.regex.captures(ua)? {
-            if let Some(mut model) = self.model_match(ua)? {
-                let mut m = "".to_owned();
-
+            let specificity = (
+                captures
+                    .get(0)
+                    .map(|x| x.as_str().len())
+                    .unwrap_or_default(),
+                captures.iter().skip(1).filter(|x| x.is_some()).count(),
+            );
+
+            if let Some(mut model) = self.model_match(ua, &captures, ctx)? {
                 // php will treat $1[0-9]+ as $1 and so that bug constantly
                 // creeps into the php code because tests don't catch it.
                 // The fix inevitably comes down when someone reports it, but
                 // we can just fix the most common case here to side step the
                 // issue 99.999% of the time.
                 if model.model.contains("$10") {
-                  model.model = model.model.replace("$1", "${1}");
+                    model.model = model.model.replace("$1", "${1}");
                 }
 
-                captures.expand(&model.model, &mut m);
-                model.model = m;
+                if model.model.contains('$') {
+                    let expanded = ctx.expand(&model.model, &captures);
+                    model.model.clear();
+                    model.model.push_str(expanded);
+                }
 
                 Some(DeviceMatchResult {
                     model: Some(model),
@@ -755,6 +1036,7 @@ impl DeviceEntry {
                         .device
                         .as_ref()
                         .map(|device| DeviceType::from_str(device)),
+                    specificity,
                 })
             } else {
                 Some(DeviceMatchResult {
@@ -763,6 +1045,7 @@ impl DeviceEntry {
                         .device
                         .as_ref()
                         .map(|device| DeviceType::from_str(device)),
+                    specificity,
                 })
             }
         } else {
@@ -772,10 +1055,25 @@ impl DeviceEntry {
         Ok(res)
     }
 
-    fn model_match(&self, ua: &str) -> Result<Option<ModelMatchResult>> {
+    fn model_match(
+        &self,
+        ua: &str,
+        captures: &Captures<'_>,
+        ctx: &mut ParseContext,
+    ) -> Result<Option<ModelMatchResult>> {
         for model in self.models.iter() {
-            let res = model_match(model, ua)?;
+            let res = model_match(model, ua, ctx)?;
             if res.is_some() {
+                // models without a regex of their own are expanded from the
+                // device regex captures afterwards.
+                if model.regex.is_none() {
+                    check_template(
+                        &model.model,
+                        &self.regex.pattern,
+                        captures,
+                        ctx.options.template_expansion,
+                    )?;
+                }
                 return Ok(res);
             }
         }
@@ -784,17 +1082,23 @@ impl DeviceEntry {
     }
 }
 
-fn model_match(model: &ModelEntry, ua: &str) -> Result<Option<ModelMatchResult>> {
+fn model_match(
+    model: &ModelEntry,
+    ua: &str,
+    ctx: &mut ParseContext,
+) -> Result<Option<ModelMatchResult>> {
     let res = match &model.regex {
         Some(regex) => match regex.captures(ua)? {
             Some(caps) => {
-                let mut m = "".to_owned();
-
-                crate::parsers::utils::expand(&model.model, &mut m, &caps);
-                // caps.expand(&model.model, &mut m);
+                check_template(
+                    &model.model,
+                    &regex.pattern,
+                    &caps,
+                    ctx.options.template_expansion,
+                )?;
 
                 Some(ModelMatchResult {
-                    model: m,
+                    model: ctx.expand_php(&model.model, &caps).to_owned(),
                     device: model.device.as_ref().map(|x| x.to_owned()),
                     brand: model.brand.as_ref().map(|x| x.to_owned()),
                 })
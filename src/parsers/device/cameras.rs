@@ -1,16 +1,9 @@
 use anyhow::Result;
 
-use super::{Device, DeviceList};
-use once_cell::sync::Lazy;
+use super::Device;
+use crate::database::Database;
+use crate::device_detector::ParseContext;
 
-static DEVICE_LIST: Lazy<DeviceList> = Lazy::new(|| {
-    let contents = include_str!(concat!(
-        env!("CARGO_MANIFEST_DIR"),
-        "/regexes/device/cameras.yml"
-    ));
-    DeviceList::from_file(contents).expect("loading cameras.yml")
-});
-
-pub fn lookup(ua: &str) -> Result<Option<Device>> {
-    DEVICE_LIST.lookup(ua, "camera")
+pub fn lookup(ua: &str, db: &Database, ctx: &mut ParseContext) -> Result<Option<Device>> {
+    db.cameras.lookup(ua, "camera", ctx)
 }
@@ -0,0 +1,171 @@
+//! The HbbTV capability string some TVs send, which says more than the bare
+//! version broadcasters need to pick which variant of their app to serve:
+//!
+//! ```text
+//! HbbTV/1.5.1 (+DRM+PVR; Samsung; SmartTV2021; T-NT11-1010.1; T-NT11; ;) FVC/5.0
+//! ```
+//!
+//! is version `1.5.1`, with the `DRM` and `PVR` capabilities, from vendor
+//! `Samsung`, and on a TV certified for Freeview Play, the UK's operator
+//! profile. The fields in parentheses are those of ETSI TS 102 796: the
+//! capabilities, vendor, model, software version, hardware version and
+//! family name, some of which are often left empty. Operator profiles are
+//! recognized by the tokens in [`OPERATORS`], anywhere in the user agent.
+
+use anyhow::Result;
+use fancy_regex::Regex;
+use once_cell::sync::Lazy;
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HbbTv {
+    /// Such as `1.5.1`.
+    pub version: String,
+    /// Such as `DRM` or `PVR`, without their `+`.
+    pub capabilities: Vec<String>,
+    pub vendor: Option<String>,
+    pub model: Option<String>,
+    pub software_version: Option<String>,
+    pub hardware_version: Option<String>,
+    pub family: Option<String>,
+    /// The operator profile the TV is certified for, if any.
+    pub operator: Option<TvOperator>,
+}
+
+/// An operator profile, the requirements a broadcaster or platform of a
+/// country puts on top of HbbTV.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct TvOperator {
+    pub name: &'static str,
+    /// The ISO 3166 code of the country the operator broadcasts in.
+    pub country: &'static str,
+}
+
+/// The operator profiles recognized, by a token of their user agents.
+pub const OPERATORS: [(&str, TvOperator); 7] = [
+    (
+        r#"FVC/\d"#,
+        TvOperator {
+            name: "Freeview Play",
+            country: "GB",
+        },
+    ),
+    (
+        r#"YouView"#,
+        TvOperator {
+            name: "YouView",
+            country: "GB",
+        },
+    ),
+    (
+        r#"Freesat"#,
+        TvOperator {
+            name: "Freesat",
+            country: "GB",
+        },
+    ),
+    (
+        r#"Saorview"#,
+        TvOperator {
+            name: "Saorview",
+            country: "IE",
+        },
+    ),
+    (
+        r#"Fransat"#,
+        TvOperator {
+            name: "Fransat",
+            country: "FR",
+        },
+    ),
+    (
+        r#"HD\+"#,
+        TvOperator {
+            name: "HD+",
+            country: "DE",
+        },
+    ),
+    (
+        r#"Tiv(?:ù|u)sat"#,
+        TvOperator {
+            name: "Tivùsat",
+            country: "IT",
+        },
+    ),
+];
+
+/// Operators are only ever those of [`OPERATORS`], so they are deserialized
+/// by looking their name up there.
+impl<'de> Deserialize<'de> for TvOperator {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Named {
+            name: String,
+        }
+
+        let Named { name } = Named::deserialize(deserializer)?;
+        OPERATORS
+            .iter()
+            .map(|(_, operator)| *operator)
+            .find(|operator| operator.name == name)
+            .ok_or_else(|| de::Error::custom(format!("unknown tv operator {}", name)))
+    }
+}
+
+static OPERATOR_REGEXES: Lazy<Vec<(Regex, TvOperator)>> = Lazy::new(|| {
+    OPERATORS
+        .iter()
+        .map(|(regex, operator)| (Regex::new(&format!("(?i){}", regex)).unwrap(), *operator))
+        .collect()
+});
+
+/// The HbbTV capability string of `ua`, if it has one.
+pub fn parse(ua: &str) -> Result<Option<HbbTv>> {
+    static HBBTV: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"(?i)HbbTV/(\d+(?:\.\d+)*)(?: ?\(([^)]*)\))?"#).unwrap());
+
+    let Some(captures) = HBBTV.captures(ua)? else {
+        return Ok(None);
+    };
+
+    let mut res = HbbTv {
+        version: captures[1].to_owned(),
+        ..Default::default()
+    };
+
+    if let Some(fields) = captures.get(2) {
+        let mut fields = fields.as_str().split(';').map(|x| {
+            let x = x.trim();
+            (!x.is_empty()).then(|| x.to_owned())
+        });
+        res.capabilities = fields
+            .next()
+            .flatten()
+            .map(|x| {
+                x.split('+')
+                    .map(str::trim)
+                    .filter(|x| !x.is_empty())
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+        res.vendor = fields.next().flatten();
+        res.model = fields.next().flatten();
+        res.software_version = fields.next().flatten();
+        res.hardware_version = fields.next().flatten();
+        res.family = fields.next().flatten();
+    }
+
+    for (regex, operator) in OPERATOR_REGEXES.iter() {
+        if regex.is_match(ua)? {
+            res.operator = Some(*operator);
+            break;
+        }
+    }
+
+    Ok(Some(res))
+}
@@ -0,0 +1,115 @@
+//! Splitting a model name such as `Galaxy S21 Ultra 5G` into the series it
+//! belongs to, its variant, and the connectivity, storage and screen size
+//! some names end with, so that detections can be joined against device spec
+//! catalogs which key them separately.
+//!
+//! ```ignore
+//! let parts = device.model_parts()?.unwrap_or_default();
+//! // Some("Galaxy S21"), Some("Ultra"), Some("5G")
+//! println!("{:?} {:?} {:?}", parts.series, parts.variant, parts.connectivity);
+//! ```
+//!
+//! Connectivity, storage and screen size are recognized in any model name.
+//! Series are only recognized for brands with a pattern in [`SERIES`], with
+//! whatever follows the series as the variant.
+
+use anyhow::Result;
+use fancy_regex::Regex;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelParts {
+    /// Such as `Galaxy S21` or `iPhone 14`.
+    pub series: Option<String>,
+    /// Such as `Ultra`, `+` or `Pro Max`.
+    pub variant: Option<String>,
+    /// Such as `5G`, `LTE` or `WiFi`.
+    pub connectivity: Option<String>,
+    /// Such as `128GB`.
+    pub storage: Option<String>,
+    /// Such as `12.4"`.
+    pub screen_size: Option<String>,
+}
+
+impl ModelParts {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The series of models of each brand, by its name in the database.
+pub const SERIES: [(&str, &str); 6] = [
+    (
+        "Samsung",
+        r#"^Galaxy (?:Z Fold|Z Flip|Tab [A-Z]|Note|[A-Z]) ?\d*\b"#,
+    ),
+    (
+        "Apple",
+        r#"^(?:iPhone (?:\d+|SE|XS|XR|X)|iPad(?: (?:Air|mini|Pro))?(?: \d+)?|iPod touch|Apple Watch|Apple TV)\b"#,
+    ),
+    ("Google", r#"^Pixel(?: \d+[a-z]?| Fold| Tablet)?\b"#),
+    (
+        "Xiaomi",
+        r#"^(?:Redmi Note|Redmi K|Redmi|Mi Note|Mi|POCO [A-Z]|Xiaomi) ?\d+[a-zA-Z]?\b"#,
+    ),
+    ("OnePlus", r#"^(?:Nord(?: CE)?(?: \d+)?|\d+[a-zA-Z]?)\b"#),
+    (
+        "Huawei",
+        r#"^(?:MatePad(?: Pro)?|Mate|Nova|Enjoy|P|Y) ?\d+[a-zA-Z]?\b"#,
+    ),
+];
+
+static SERIES_REGEXES: Lazy<Vec<(&str, Regex)>> = Lazy::new(|| {
+    SERIES
+        .iter()
+        .map(|(brand, regex)| (*brand, Regex::new(regex).unwrap()))
+        .collect()
+});
+
+const CONNECTIVITY: [&str; 7] = ["5G", "4G", "LTE", "3G", "UW", "WiFi", "Wi-Fi"];
+
+/// Splits `model`, a model name of `brand`.
+pub fn split(brand: &str, model: &str) -> Result<ModelParts> {
+    static STORAGE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^\d+ ?[GT]B$"#).unwrap());
+    static SCREEN_SIZE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^\d+(?:\.\d+)?"$"#).unwrap());
+
+    let mut res = ModelParts::default();
+    let mut connectivity = Vec::new();
+    let mut rest = Vec::new();
+
+    for word in model.split_whitespace() {
+        if CONNECTIVITY.contains(&word) {
+            connectivity.push(word);
+        } else if res.storage.is_none() && STORAGE.is_match(word)? {
+            res.storage = Some(word.to_owned());
+        } else if res.screen_size.is_none() && SCREEN_SIZE.is_match(word)? {
+            res.screen_size = Some(word.to_owned());
+        } else {
+            rest.push(word);
+        }
+    }
+    if !connectivity.is_empty() {
+        res.connectivity = Some(connectivity.join(" "));
+    }
+
+    let rest = rest.join(" ");
+    let regex = SERIES_REGEXES
+        .iter()
+        .find(|(x, _)| x.eq_ignore_ascii_case(brand))
+        .map(|(_, regex)| regex);
+    let series = match regex {
+        Some(regex) => regex.find(&rest)?,
+        None => None,
+    };
+
+    if let Some(series) = series.filter(|x| !x.as_str().is_empty()) {
+        res.series = Some(series.as_str().to_owned());
+        let variant = rest[series.end()..].trim();
+        if !variant.is_empty() {
+            res.variant = Some(variant.to_owned());
+        }
+    }
+
+    Ok(res)
+}
@@ -8,15 +8,14 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::client_hints::ClientHint;
-use crate::known_oss::AvailableOSs;
+use crate::database::{Database, Source};
+use crate::device_detector::ParseContext;
+use crate::hints_used::Hint;
+use crate::known_oss::{AvailableOS, AvailableOSs};
 use crate::parsers::utils::{
-    lazy_user_agent_match, static_user_agent_match, LazyRegex, SafeRegex as Regex,
+    check_template, lazy_user_agent_match, static_user_agent_match, LazyRegex, SafeRegex as Regex,
 };
 
-static OS_LIST: Lazy<OSList> = Lazy::new(|| {
-    let contents = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/regexes/oss.yml"));
-    OSList::from_file(contents).expect("loading oss.yml")
-});
 static CLIENT_HINT_MAPPING: Lazy<Vec<(String, Vec<String>)>> = Lazy::new(|| {
     [("GNU/Linux", vec!["Linux"]), ("Mac", vec!["MacOS"])]
         .into_iter()
@@ -28,6 +27,26 @@ static CLIENT_HINT_MAPPING: Lazy<Vec<(String, Vec<String>)>> = Lazy::new(|| {
 });
 static AVAILABLE_OSSES: Lazy<AvailableOSs> = Lazy::new(AvailableOSs::default);
 
+/// `Sec-CH-UA-Platform` values as browsers send them. There are very few, so
+/// they are resolved to an os once rather than on every request.
+static PLATFORM_HINTS: Lazy<HashMap<&str, Option<&AvailableOS>>> = Lazy::new(|| {
+    [
+        "Android",
+        "Chrome OS",
+        "Chromium OS",
+        "Fuchsia",
+        "iOS",
+        "Linux",
+        "macOS",
+        "Windows",
+        "Unknown",
+        "",
+    ]
+    .into_iter()
+    .map(|platform| (platform, resolve_platform_os(platform)))
+    .collect()
+});
+
 static FIRE_OS_VERSION: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
     [
         ("11", "8"),
@@ -75,7 +94,8 @@ static LINEAGE_OS_VERSION: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
     .collect::<HashMap<_, _>>()
 });
 
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "SerializedOs")]
 pub struct OS {
     pub name: String,
     pub version: Option<String>,
@@ -86,19 +106,61 @@ pub struct OS {
     pub(crate) desktop: bool,
 }
 
-pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<OS>> {
+/// An [`OS`] as serialized, which whether it is a desktop os is looked up
+/// again for by name.
+#[derive(Deserialize)]
+struct SerializedOs {
+    name: String,
+    version: Option<String>,
+    platform: Option<String>,
+    family: Option<String>,
+}
+
+impl From<SerializedOs> for OS {
+    fn from(os: SerializedOs) -> Self {
+        let desktop = AVAILABLE_OSSES
+            .search_by_name(&os.name)
+            .is_some_and(|x| x.desktop);
+
+        OS {
+            name: os.name,
+            version: os.version,
+            platform: os.platform,
+            family: os.family,
+            desktop,
+        }
+    }
+}
+
+fn resolve_platform_os(platform: &str) -> Option<&'static AvailableOS> {
+    let hint = CLIENT_HINT_MAPPING
+        .iter()
+        .find(|(_k, v)| {
+            v.iter()
+                .any(|v| *v.to_lowercase() == platform.to_lowercase())
+        })
+        .map(|x| x.0.as_str())
+        .unwrap_or(platform);
+
+    AVAILABLE_OSSES.search_by_name(hint)
+}
+
+fn platform_os(platform: &str) -> Option<&'static AvailableOS> {
+    match PLATFORM_HINTS.get(platform) {
+        Some(res) => *res,
+        None => resolve_platform_os(platform),
+    }
+}
+
+pub fn lookup(
+    ua: &str,
+    client_hints: Option<&ClientHint>,
+    db: &Database,
+    ctx: &mut ParseContext,
+) -> Result<Option<OS>> {
     let mut os_from_hints: Option<OS> = client_hints.and_then(|client_hints| {
         if let Some(platform) = client_hints.platform.as_ref() {
-            let hint = CLIENT_HINT_MAPPING
-                .iter()
-                .find(|(_k, v)| {
-                    v.iter()
-                        .any(|v| *v.to_lowercase() == platform.to_lowercase())
-                })
-                .map(|x| &x.0)
-                .or(Some(platform));
-
-            if let Some(os) = hint.and_then(|hint| AVAILABLE_OSSES.search_by_name(hint)) {
+            if let Some(os) = platform_os(platform) {
                 let mut version = client_hints.platform_version.clone();
 
                 if let Some(platform_version) = &client_hints.platform_version {
@@ -132,7 +194,10 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<OS>>
         }
     });
 
-    let os_from_ua: Option<OS> = OS_LIST.lookup(ua)?;
+    let os_from_ua: Option<OS> = db.oss.lookup(ua, ctx)?;
+
+    // whether the version of the os still is the one of the client hints
+    let mut version_from_hints = os_from_hints.as_ref().is_some_and(|x| x.version.is_some());
 
     // various occasional overrides of client hint information based on ua.
     if let Some(ref mut os_from_hints) = &mut os_from_hints {
@@ -143,6 +208,7 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<OS>>
                 && os_from_hints.family == os_from_ua.family
             {
                 os_from_hints.version = os_from_ua.version.clone();
+                version_from_hints = false;
             }
 
             // if OS name detected from client hints matches OS family of user agent but the user
@@ -154,10 +220,12 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<OS>>
 
                     if os_from_hints.name == "HarmonyOS" {
                         os_from_hints.version = None;
+                        version_from_hints = false;
                     }
 
                     if os_from_hints.name == "PICO OS" {
                         os_from_hints.version = os_from_ua.version.clone();
+                        version_from_hints = false;
                     }
 
                     if os_from_hints.name == "Fire OS" {
@@ -190,10 +258,17 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<OS>>
         }
     }
 
+    if os_from_hints.is_some() {
+        ctx.use_hint(Hint::Platform);
+        if version_from_hints {
+            ctx.use_hint(Hint::PlatformVersion);
+        }
+    }
+
     let mut res = os_from_hints.or(os_from_ua);
 
     if let Some(os) = &mut res {
-        if let platform @ Some(_) = parse_platform(ua, client_hints)? {
+        if let platform @ Some(_) = parse_platform(ua, client_hints, ctx)? {
             os.platform = platform
         }
     }
@@ -219,6 +294,7 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<OS>>
             if let Some(client_hints) = &client_hints {
                 if let Some(app_hint) = &client_hints.app {
                     if android_apps.iter().any(|app| *app == app_hint) {
+                        ctx.use_hint(Hint::RequestedWith);
                         os.name = "Android".to_owned();
                         os.family = Some("Android".to_owned());
                         os.version = None;
@@ -230,13 +306,14 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<OS>>
         if os.name != "Lineage OS" {
             if let Some(client_hints) = &client_hints {
                 if let Some("org.lineageos.jelly") = &client_hints.app.as_deref() {
+                    ctx.use_hint(Hint::RequestedWith);
                     os.name = "Lineage OS".to_owned();
                     os.family = Some("Android".to_owned());
                     os.version = LINEAGE_OS_VERSION
                         .get(os.version.as_deref().unwrap_or_else(|| {
                             os.version
                                 .as_deref()
-                                .map(|x| x.split('.').last().unwrap_or("0"))
+                                .map(|x| x.split('.').next_back().unwrap_or("0"))
                                 .unwrap_or("0")
                         }))
                         .map(|x| (*x).to_owned());
@@ -247,6 +324,7 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<OS>>
         if os.name != "Fire OS" {
             if let Some(client_hints) = &client_hints {
                 if let Some("org.mozilla.tv.firefox") = &client_hints.app.as_deref() {
+                    ctx.use_hint(Hint::RequestedWith);
                     os.name = "Fire OS".to_owned();
                     os.family = Some("Android".to_owned());
                     os.version = FIRE_OS_VERSION
@@ -265,51 +343,59 @@ pub fn lookup(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<OS>>
     Ok(res)
 }
 
-fn parse_platform(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<String>> {
-    if let Some(client_hints) = client_hints {
-        if let Some(architecture) = &client_hints.architecture {
-            let arch = architecture.to_lowercase();
+fn hinted_platform(client_hints: &ClientHint, ctx: &mut ParseContext) -> Option<&'static str> {
+    let arch = client_hints.architecture.as_ref()?.to_lowercase();
 
-            if arch.contains("arm") {
-                return Ok(Some("ARM".into()));
-            }
+    if arch.contains("arm") {
+        return Some("ARM");
+    }
 
-            if arch.contains("loongarch64") {
-                return Ok(Some("LoongArch64".into()));
-            }
+    if arch.contains("loongarch64") {
+        return Some("LoongArch64");
+    }
 
-            if arch.contains("mips") {
-                return Ok(Some("MIPS".into()));
-            }
+    if arch.contains("mips") {
+        return Some("MIPS");
+    }
 
-            if arch.contains("sh4") {
-                return Ok(Some("SuperH".into()));
-            }
+    if arch.contains("sh4") {
+        return Some("SuperH");
+    }
 
-            if arch.contains("sparc64") {
-                return Ok(Some("SPARC64".into()));
-            }
+    if arch.contains("sparc64") {
+        return Some("SPARC64");
+    }
 
-            if arch.contains("x64") {
-                return Ok(Some("x64".into()));
-            }
+    if arch.contains("x64") {
+        return Some("x64");
+    }
 
-            if arch.contains("x86") {
-                if let Some(bitness) = &client_hints.bitness {
-                    if bitness == "64" {
-                        return Ok(Some("x64".into()));
-                    }
-                }
-            }
+    if arch.contains("x86") {
+        if client_hints.bitness.as_deref() == Some("64") {
+            ctx.use_hint(Hint::Bitness);
+            return Some("x64");
+        }
+        return Some("x86");
+    }
 
-            if arch.contains("x86") {
-                return Ok(Some("x86".into()));
-            }
+    None
+}
+
+fn parse_platform(
+    ua: &str,
+    client_hints: Option<&ClientHint>,
+    ctx: &mut ParseContext,
+) -> Result<Option<String>> {
+    if let Some(client_hints) = client_hints {
+        if let Some(platform) = hinted_platform(client_hints, ctx) {
+            ctx.use_hint(Hint::Arch);
+            return Ok(Some(platform.into()));
         }
     }
 
-    static ARM_REG: Lazy<Regex> =
-        static_user_agent_match!("arm[ _;)ev]|.*arm$|.*arm64|aarch64|Apple ?TV|Watch ?OS|Watch1,[12]");
+    static ARM_REG: Lazy<Regex> = static_user_agent_match!(
+        "arm[ _;)ev]|.*arm$|.*arm64|aarch64|Apple ?TV|Watch ?OS|Watch1,[12]"
+    );
     static LONGARCH64_REG: Lazy<Regex> = static_user_agent_match!("loongarch64");
     static MIPS_REG: Lazy<Regex> = static_user_agent_match!("mips");
     static SH4_REG: Lazy<Regex> = static_user_agent_match!("sh4");
@@ -317,7 +403,10 @@ fn parse_platform(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<
     static X64_REG: Lazy<Regex> = Lazy::new(|| {
         // Don't match device model names like "Elephone_P3000S-64bit"
         // The negative lookbehind ensures we don't match if preceded by a letter or underscore
-        Regex::new(r"(?i)(?<![\w_-])(?:64-?bit|WOW64|(?:Intel)?x64|WINDOWS_64|win64|x86_?64)\b|.*amd64").expect("x64 regex")
+        Regex::new(
+            r"(?i)(?<![\w_-])(?:64-?bit|WOW64|(?:Intel)?x64|WINDOWS_64|win64|x86_?64)\b|.*amd64",
+        )
+        .expect("x64 regex")
     });
     static X86_REG: Lazy<Regex> = static_user_agent_match!(".*32bit|.*win32|(?:i[0-9]|x)86|i86pc");
 
@@ -352,7 +441,7 @@ fn parse_platform(ua: &str, client_hints: Option<&ClientHint>) -> Result<Option<
     Ok(None)
 }
 
-struct OSList {
+pub(crate) struct OSList {
     oss: Vec<OSEntry>,
 }
 
@@ -371,42 +460,50 @@ struct OSVersion {
 }
 
 impl OSEntry {
-    fn is_match(&self, ua: &str) -> Result<Option<OS>> {
+    fn is_match(&self, ua: &str, ctx: &mut ParseContext) -> Result<Option<OS>> {
         if self.regex.is_match(ua)? {
             let mut name = "".to_owned();
-            let mut v = "".to_owned();
+            let mut v = None;
             if let Some(captures) = self.regex.captures(ua)? {
-                captures.expand(&self.name, &mut name);
+                let mode = ctx.options.template_expansion;
+                check_template(&self.name, &self.regex.pattern, &captures, mode)?;
 
-                if let Some(res) = AVAILABLE_OSSES.search_by_name(&name) {
-                    name = res.name.to_owned();
-                }
+                let expanded = ctx.expand(&self.name, &captures);
+                name = match AVAILABLE_OSSES.search_by_name(expanded) {
+                    Some(res) => res.name.to_owned(),
+                    None => expanded.to_owned(),
+                };
 
+                let mut expanded = "";
                 for version in &self.versions {
                     if let Some(captures) = version.regex.captures(ua)? {
-                        captures.expand(&version.version, &mut v);
+                        check_template(&version.version, &version.regex.pattern, &captures, mode)?;
+                        expanded = ctx.expand(&version.version, &captures);
                         break;
                     }
                 }
 
-                if v.is_empty() {
+                if expanded.is_empty() {
                     if let Some(version) = &self.version {
-                        captures.expand(version, &mut v);
+                        check_template(version, &self.regex.pattern, &captures, mode)?;
+                        expanded = ctx.expand(version, &captures);
                     }
                 }
-                if v.contains('_') {
-                    v = v.replace('_', ".");
-                }
-                if v.ends_with(['.', ' ']) || v.starts_with(['.', ' ']) {
-                    v = v
-                        .trim_end_matches(['.', ' '])
-                        .trim_start_matches(['.', ' '])
-                        .to_owned();
+
+                let trimmed = if expanded.contains('_') {
+                    expanded
+                        .replace('_', ".")
+                        .trim_matches(['.', ' '])
+                        .to_owned()
+                } else {
+                    expanded.trim_matches(['.', ' ']).to_owned()
+                };
+
+                if !trimmed.is_empty() {
+                    v = Some(trimmed);
                 }
             }
 
-            let v = if !v.is_empty() { Some(v) } else { None };
-
             let mut os = OS {
                 name,
                 version: v,
@@ -425,9 +522,19 @@ impl OSEntry {
 }
 
 impl OSList {
-    fn lookup(&self, ua: &str) -> Result<Option<OS>> {
+    pub(crate) fn len(&self) -> usize {
+        self.oss.len()
+    }
+
+    pub(crate) fn regexes(&self) -> impl Iterator<Item = &LazyRegex> {
+        self.oss
+            .iter()
+            .flat_map(|x| std::iter::once(&x.regex).chain(x.versions.iter().map(|x| &x.regex)))
+    }
+
+    fn lookup(&self, ua: &str, ctx: &mut ParseContext) -> Result<Option<OS>> {
         for os in self.oss.iter() {
-            if let Some(res) = os.is_match(ua)? {
+            if let Some(res) = os.is_match(ua, ctx)? {
                 return Ok(Some(res));
             }
         }
@@ -435,7 +542,7 @@ impl OSList {
         Ok(None)
     }
 
-    fn from_file(contents: &str) -> Result<OSList> {
+    pub(crate) fn from_source(source: &Source<'_>) -> Result<OSList> {
         #[derive(Debug, Deserialize)]
         #[serde(try_from = "Value")]
         struct YamlVersion {
@@ -521,7 +628,7 @@ impl OSList {
             }
         }
 
-        let res: YamlOSList = serde_yaml::from_str(contents)?;
+        let res: YamlOSList = source.parse()?;
         Ok(res.into())
     }
 }
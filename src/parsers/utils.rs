@@ -1,21 +1,33 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use fancy_regex::{CaptureMatches, Captures, Error, Expander, Regex, Replacer};
 use once_cell::sync::Lazy;
 
 use once_cell::sync::OnceCell;
 
+use arc_swap::ArcSwapOption;
+
 #[derive(Debug)]
 pub(crate) struct LazyRegex {
     pub(crate) pattern: String,
-    regex: OnceCell<SafeRegex>,
+    regex: ArcSwapOption<SafeRegex>,
+    /// Set for the regexes of a database, see [`crate::memory`].
+    memory: OnceCell<Arc<RegexMemory>>,
+    last_used: AtomicU64,
+    /// See [`crate::profiler`].
+    pub(crate) timer: RegexTimer,
 }
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::sync::RwLock;
+use std::sync::{Mutex, RwLock};
 
 use std::borrow::Cow;
 
+use crate::memory::RegexMemory;
+use crate::profiler::RegexTimer;
+use crate::validation::{template_groups, TemplateExpansion};
+
 /// This is a regex that won't crash due to run time errors on match.
 /// This can still crash if passed an invalid regex in the first place.
 #[derive(Debug)]
@@ -24,6 +36,7 @@ pub(crate) struct SafeRegex {
 }
 
 impl SafeRegex {
+    #[allow(clippy::result_large_err)]
     fn squash_runtime_error<T>(err: Result<T, Error>, ret: T) -> Result<T, Error> {
         // this is either a stack overflow or a backtrack limit reached.
         // in either case, we don't want to crash, just deny a match and move on.
@@ -52,6 +65,11 @@ impl SafeRegex {
         self.regex.replace_all(text, rep)
     }
 
+    /// Number of capture groups, including the implicit whole match group.
+    pub fn captures_len(&self) -> usize {
+        self.regex.captures_len()
+    }
+
     pub fn captures_iter<'r, 'h>(&'r self, haystack: &'h str) -> CaptureMatches<'r, 'h> {
         self.regex.captures_iter(haystack)
     }
@@ -66,24 +84,87 @@ impl LazyRegex {
     pub(crate) fn new(pattern: String) -> Self {
         Self {
             pattern,
-            regex: OnceCell::new(),
+            regex: ArcSwapOption::empty(),
+            memory: OnceCell::new(),
+            last_used: AtomicU64::new(0),
+            timer: RegexTimer::default(),
+        }
+    }
+
+    /// Counts the regex in the compiled regexes of its database.
+    pub(crate) fn track(&self, memory: &Arc<RegexMemory>) {
+        let _ = self.memory.set(memory.clone());
+    }
+
+    /// Calls `f` with the compiled regex, compiling it if it isn't.
+    fn with_regex<T>(&self, f: impl FnOnce(&SafeRegex) -> Result<T>) -> Result<T> {
+        if let Some(memory) = self.memory.get() {
+            let epoch = memory.epoch();
+            if self.last_used.load(Ordering::Relaxed) != epoch {
+                self.last_used.store(epoch, Ordering::Relaxed);
+            }
         }
+
+        if let Some(regex) = &*self.regex.load() {
+            return self.timer.time(|| f(regex));
+        }
+
+        // println!("compilation: {}", &self.pattern);
+        let regex = SafeRegex::new(&self.pattern)
+            .with_context(|| format!("invalid regex {}", self.pattern))?;
+        let regex = Arc::new(regex);
+        let previous = self
+            .regex
+            .compare_and_swap(&None::<Arc<SafeRegex>>, Some(regex.clone()));
+        // unless another thread compiled it meanwhile
+        if previous.is_none() {
+            if let Some(memory) = self.memory.get() {
+                memory.compiled(&self.pattern);
+            }
+        }
+        self.timer.time(|| f(&regex))
+    }
+
+    /// Compiles the regex now rather than on first use.
+    pub(crate) fn compile(&self) -> Result<()> {
+        self.with_regex(|_| Ok(()))
+    }
+
+    /// The epoch the regex was last used in, if it is compiled.
+    pub(crate) fn last_used(&self) -> Option<u64> {
+        self.regex
+            .load()
+            .as_ref()
+            .map(|_| self.last_used.load(Ordering::Relaxed))
+    }
+
+    /// Frees the compiled regex, returning whether it was compiled. It is
+    /// compiled again on its next use.
+    pub(crate) fn evict(&self) -> bool {
+        let evicted = self.regex.swap(None).is_some();
+        if evicted {
+            if let Some(memory) = self.memory.get() {
+                memory.freed(&self.pattern);
+            }
+        }
+        evicted
     }
 
     pub(crate) fn is_match(&self, text: &str) -> Result<bool> {
-        let regex = self.regex.get_or_try_init(|| {
-            // println!("is_match compilation: {}", &self.pattern);
-            SafeRegex::new(&self.pattern)
-        })?;
-        regex.is_match(text)
+        self.with_regex(|x| x.is_match(text))
+    }
+
+    /// The pattern from the database, for patterns wrapped by
+    /// [`lazy_user_agent_match`], with `/` escaped.
+    pub(crate) fn user_agent_source(&self) -> &str {
+        self.pattern
+            .strip_prefix(USER_AGENT_PREFIX)
+            .and_then(|x| x.strip_suffix(')'))
+            .unwrap_or(&self.pattern)
     }
 
     pub(crate) fn captures<'t>(&self, text: &'t str) -> Result<Option<Captures<'t>>> {
-        let regex = self.regex.get_or_try_init(|| {
-            // println!("captures compilation: {}", &self.pattern);
-            SafeRegex::new(&self.pattern)
-        })?;
-        regex.captures(text)
+        self.with_regex(|x| x.captures(text))
     }
 }
 
@@ -142,13 +223,61 @@ macro_rules! static_user_agent_match {
 }
 pub(crate) use static_user_agent_match;
 
-pub(crate) fn lazy_user_agent_match(pattern: &str) -> LazyRegex {
-    let mut reg = r"(?i:^|[^A-Z0-9_-]|[^A-Z0-9-]_|sprd-|MZ-)(?i:".to_owned();
+const USER_AGENT_PREFIX: &str = r"(?i:^|[^A-Z0-9_-]|[^A-Z0-9-]_|sprd-|MZ-)(?i:";
+
+/// Wraps a pattern from the regex yml files the same way the php library does
+/// before it is matched against a user agent.
+pub(crate) fn user_agent_pattern(pattern: &str) -> String {
+    let mut reg = USER_AGENT_PREFIX.to_owned();
     reg.push_str(pattern.replace('/', r"\/").as_str());
     reg.push(')');
+    reg
+}
 
+pub(crate) fn lazy_user_agent_match(pattern: &str) -> LazyRegex {
     // println!("lazy_user_agent_match compilation: {}", reg);
-    LazyRegex::new(reg)
+    LazyRegex::new(user_agent_pattern(pattern))
+}
+
+/// Checks that every group referenced by `template` exists in `captures`, as
+/// missing groups would otherwise quietly expand to nothing.
+pub(crate) fn check_template(
+    template: &str,
+    pattern: &str,
+    captures: &Captures<'_>,
+    mode: TemplateExpansion,
+) -> Result<()> {
+    if mode == TemplateExpansion::Lenient || !template.contains('$') {
+        return Ok(());
+    }
+
+    // group 0 is the whole match
+    let available = captures.len() - 1;
+
+    if let Some(group) = template_groups(template)
+        .into_iter()
+        .find(|x| *x > available)
+    {
+        let msg = format!(
+            "template '{}' refers to group ${} but regex '{}' only has {} group(s)",
+            template, group, pattern, available
+        );
+
+        match mode {
+            TemplateExpansion::Strict => anyhow::bail!(msg),
+            _ => {
+                // the same entry matches over and over again, so it's only
+                // warned about once.
+                static WARNED: Lazy<Mutex<HashSet<(String, String)>>> = Lazy::new(Default::default);
+                let mut warned = WARNED.lock().unwrap();
+                if warned.insert((pattern.to_owned(), template.to_owned())) {
+                    eprintln!("{}", msg);
+                }
+            }
+        }
+    }
+
+    Ok(())
 }
 
 pub(crate) fn expand(template: &str, dst: &mut String, captures: &Captures<'_>) {
@@ -1,10 +1,10 @@
 use anyhow::Result;
 use serde::Deserialize;
 
-use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 use super::utils::lazy_user_agent_match;
+use crate::database::{Database, Source};
 use crate::parsers::utils::LazyRegex;
 
 #[derive(Debug)]
@@ -13,20 +13,12 @@ struct VendorFragments {
     fragments: Vec<LazyRegex>,
 }
 
-static FRAGMENT_LIST: Lazy<VendorFragmentList> = Lazy::new(|| {
-    let contents = include_str!(concat!(
-        env!("CARGO_MANIFEST_DIR"),
-        "/regexes/vendorfragments.yml"
-    ));
-    VendorFragmentList::from_file(contents).expect("loading vendorfragments.yml")
-});
-
-pub fn lookup(ua: &str) -> Result<Option<&str>> {
-    FRAGMENT_LIST.lookup(ua)
+pub fn lookup<'a>(ua: &str, db: &'a Database) -> Result<Option<&'a str>> {
+    db.vendor_fragments.lookup(ua)
 }
 
 #[derive(Debug)]
-struct VendorFragmentList {
+pub(crate) struct VendorFragmentList {
     list: Vec<VendorFragments>,
 }
 
@@ -42,6 +34,10 @@ impl VendorFragments {
 }
 
 impl VendorFragmentList {
+    pub(crate) fn regexes(&self) -> impl Iterator<Item = &LazyRegex> {
+        self.list.iter().flat_map(|x| x.fragments.iter())
+    }
+
     fn lookup(&self, ua: &str) -> Result<Option<&str>> {
         for x in self.list.iter() {
             if x.is_match(ua)? {
@@ -51,11 +47,11 @@ impl VendorFragmentList {
         Ok(None)
     }
 
-    fn from_file(contents: &str) -> Result<VendorFragmentList> {
+    pub(crate) fn from_source(source: &Source<'_>) -> Result<VendorFragmentList> {
         #[derive(Debug, Deserialize)]
         #[serde(transparent)]
         struct YamlVendorFragmentList {
-            list: HashMap<String, Vec<String>>,
+            list: IndexMap<String, Vec<String>>,
         }
 
         #[allow(clippy::from_over_into)]
@@ -80,7 +76,7 @@ impl VendorFragmentList {
             }
         }
 
-        let res: YamlVendorFragmentList = serde_yaml::from_str(contents)?;
+        let res: YamlVendorFragmentList = source.parse()?;
         Ok(res.into())
     }
 }
@@ -0,0 +1,154 @@
+//! Finding which regexes of the database take the most time on real
+//! traffic, to know which upstream patterns are worth optimizing or moving.
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().with_regex_profiling(1000);
+//! // ... serve traffic
+//! let profile = detector.regex_profile().expect("profiling");
+//! for timing in profile.entries.iter().take(10) {
+//!     log::info!("{} {}: {:?} over {} matches", timing.list, timing.pattern, timing.total, timing.matches);
+//! }
+//! ```
+//!
+//! Only one in `sample_rate` detections is timed, and the others only pay
+//! for checking a thread local before each match, so profiling can stay on in
+//! production. Timings are of matching alone, not of compiling regexes, and
+//! detections answered from a cache or an approximation aren't timed at all.
+//! They are kept with the regexes, so detectors profiling with the same
+//! database add up to the same profile.
+
+use serde::Serialize;
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::database::Database;
+
+thread_local! {
+    static SAMPLING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether the detection running on this thread is being timed.
+pub(crate) fn is_sampling() -> bool {
+    SAMPLING.with(Cell::get)
+}
+
+/// The time spent matching a regex, in sampled detections.
+#[derive(Debug, Default)]
+pub(crate) struct RegexTimer {
+    matches: AtomicU64,
+    nanos: AtomicU64,
+}
+
+impl RegexTimer {
+    /// Calls `f`, timing it when the detection is sampled.
+    pub(crate) fn time<T>(&self, f: impl FnOnce() -> T) -> T {
+        if !is_sampling() {
+            return f();
+        }
+
+        let start = Instant::now();
+        let res = f();
+        let elapsed = start.elapsed().as_nanos().min(u64::MAX as u128) as u64;
+        self.matches.fetch_add(1, Ordering::Relaxed);
+        self.nanos.fetch_add(elapsed, Ordering::Relaxed);
+        res
+    }
+
+    fn matches(&self) -> u64 {
+        self.matches.load(Ordering::Relaxed)
+    }
+
+    fn total(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+
+    fn reset(&self) {
+        self.matches.store(0, Ordering::Relaxed);
+        self.nanos.store(0, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RegexProfiler {
+    sample_rate: u64,
+    detections: AtomicU64,
+    sampled: AtomicU64,
+}
+
+impl RegexProfiler {
+    pub(crate) fn new(sample_rate: u64) -> Self {
+        Self {
+            sample_rate: sample_rate.max(1),
+            detections: AtomicU64::new(0),
+            sampled: AtomicU64::new(0),
+        }
+    }
+
+    /// Runs the detection `f`, timing its regexes if it is sampled.
+    pub(crate) fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        let n = self.detections.fetch_add(1, Ordering::Relaxed);
+        if !n.is_multiple_of(self.sample_rate) {
+            return f();
+        }
+
+        self.sampled.fetch_add(1, Ordering::Relaxed);
+        let previous = SAMPLING.with(|x| x.replace(true));
+        let res = f();
+        SAMPLING.with(|x| x.set(previous));
+        res
+    }
+
+    pub(crate) fn profile(&self, db: &Database) -> RegexProfile {
+        let mut entries: Vec<_> = db
+            .regex_lists()
+            .into_iter()
+            .flat_map(|(list, regexes)| regexes.into_iter().map(move |x| (list, x)))
+            .filter(|(_, regex)| regex.timer.matches() > 0)
+            .map(|(list, regex)| RegexTiming {
+                list,
+                pattern: regex.user_agent_source().to_owned(),
+                matches: regex.timer.matches(),
+                total: regex.timer.total(),
+            })
+            .collect();
+        entries.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.list.cmp(b.list)));
+
+        RegexProfile {
+            sampled_detections: self.sampled.load(Ordering::Relaxed),
+            entries,
+        }
+    }
+
+    pub(crate) fn reset(&self, db: &Database) {
+        self.sampled.store(0, Ordering::Relaxed);
+        for (_, regexes) in db.regex_lists() {
+            for regex in regexes {
+                regex.timer.reset();
+            }
+        }
+    }
+}
+
+/// The time spent matching each regex, slowest first, see
+/// [`crate::device_detector::DeviceDetector::regex_profile`].
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct RegexProfile {
+    /// How many detections were timed. Divide by it for the time per
+    /// detection.
+    pub sampled_detections: u64,
+    /// Regexes which were matched at all, by total time.
+    pub entries: Vec<RegexTiming>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RegexTiming {
+    /// The file of the database the regex is from, such as `oss.yml`.
+    pub list: &'static str,
+    /// The pattern as it is in the file.
+    pub pattern: String,
+    /// How many times it was matched in sampled detections.
+    pub matches: u64,
+    pub total: Duration,
+}
@@ -0,0 +1,191 @@
+//! Python bindings, for log analysis in python. Built with maturin, see
+//! `pyproject.toml`:
+//!
+//! ```python
+//! from rust_device_detector import ClientHint, DeviceDetector
+//!
+//! detector = DeviceDetector()
+//! detection = detector.parse(ua, {"sec-ch-ua-model": '"Pixel 7"'})
+//! detection["device"]["model"]
+//!
+//! hints = ClientHint.from_headers({"sec-ch-ua-mobile": "?1"})
+//! detector.parse_client_hints(ua, hints)
+//! ```
+//!
+//! Detections are dicts of the json the cli prints, see
+//! `includes/detection.d.ts`. The gil is released while detecting, so threads
+//! sharing a detector detect in parallel.
+
+// pyo3's macros convert errors to PyErr, even when they already are.
+#![allow(clippy::useless_conversion)]
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::Value;
+
+use std::collections::HashMap;
+
+use crate::client_hints;
+use crate::device_detector::{self, Detection};
+
+fn to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(x) => x.into_py(py),
+        Value::Number(x) => match x.as_i64() {
+            Some(x) => x.into_py(py),
+            None => x.as_f64().into_py(py),
+        },
+        Value::String(x) => x.into_py(py),
+        Value::Array(xs) => {
+            let xs = xs
+                .iter()
+                .map(|x| to_py(py, x))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new_bound(py, xs).into_py(py)
+        }
+        Value::Object(xs) => {
+            let dict = PyDict::new_bound(py);
+            for (k, v) in xs {
+                dict.set_item(k, to_py(py, v)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}
+
+fn detection(py: Python<'_>, res: anyhow::Result<Detection>) -> PyResult<PyObject> {
+    match res {
+        Ok(detection) => to_py(py, &detection.to_value()),
+        Err(err) => Err(PyValueError::new_err(format!("{:#}", err))),
+    }
+}
+
+/// The client hints of a request, see [`client_hints::ClientHint`].
+#[pyclass(frozen, name = "ClientHint")]
+#[derive(Clone)]
+pub struct ClientHint(client_hints::ClientHint);
+
+#[pymethods]
+impl ClientHint {
+    /// From a dict of the headers of a request, names being case insensitive.
+    #[staticmethod]
+    fn from_headers(headers: HashMap<String, String>) -> PyResult<Self> {
+        client_hints::ClientHint::from_headers(headers.into_iter().collect())
+            .map(Self)
+            .map_err(|err| PyValueError::new_err(format!("{:#}", err)))
+    }
+
+    #[getter]
+    fn architecture(&self) -> Option<&str> {
+        self.0.architecture.as_deref()
+    }
+
+    #[getter]
+    fn bitness(&self) -> Option<&str> {
+        self.0.bitness.as_deref()
+    }
+
+    #[getter]
+    fn mobile(&self) -> bool {
+        self.0.mobile
+    }
+
+    #[getter]
+    fn model(&self) -> Option<&str> {
+        self.0.model.as_deref()
+    }
+
+    #[getter]
+    fn ua_full_version(&self) -> Option<&str> {
+        self.0.ua_full_version.as_deref()
+    }
+
+    #[getter]
+    fn platform(&self) -> Option<&str> {
+        self.0.platform.as_deref()
+    }
+
+    #[getter]
+    fn platform_version(&self) -> Option<&str> {
+        self.0.platform_version.as_deref()
+    }
+
+    #[getter]
+    fn brands(&self) -> Vec<(String, String)> {
+        self.0.brands.clone()
+    }
+
+    #[getter]
+    fn full_version_list(&self) -> Vec<(String, String)> {
+        self.0.full_version_list.clone()
+    }
+
+    #[getter]
+    fn app(&self) -> Option<&str> {
+        self.0.app.as_deref()
+    }
+
+    #[getter]
+    fn form_factors(&self) -> Vec<String> {
+        self.0.form_factors.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[pyclass(frozen, name = "DeviceDetector")]
+pub struct DeviceDetector(device_detector::DeviceDetector);
+
+#[pymethods]
+impl DeviceDetector {
+    /// Caches up to `cache_size` detections, with the cache feature.
+    #[new]
+    #[pyo3(signature = (cache_size = 0))]
+    #[allow(unused)]
+    fn new(cache_size: u64) -> Self {
+        #[cfg(feature = "cache")]
+        if cache_size > 0 {
+            return Self(device_detector::DeviceDetector::new_with_cache(cache_size));
+        }
+
+        Self(device_detector::DeviceDetector::new())
+    }
+
+    /// Detects `ua`, with an optional dict of the other headers of the
+    /// request, for client hints.
+    #[pyo3(signature = (ua, headers = None))]
+    fn parse(
+        &self,
+        py: Python<'_>,
+        ua: &str,
+        headers: Option<HashMap<String, String>>,
+    ) -> PyResult<PyObject> {
+        let headers = headers.map(|x| x.into_iter().collect());
+        let res = py.allow_threads(|| self.0.parse(ua, headers));
+        detection(py, res)
+    }
+
+    #[pyo3(signature = (ua, client_hints = None))]
+    fn parse_client_hints(
+        &self,
+        py: Python<'_>,
+        ua: &str,
+        client_hints: Option<ClientHint>,
+    ) -> PyResult<PyObject> {
+        let client_hints = client_hints.map(|x| x.0);
+        let res = py.allow_threads(|| self.0.parse_client_hints(ua, client_hints));
+        detection(py, res)
+    }
+}
+
+/// The `rust_device_detector` python module.
+#[pymodule]
+pub fn rust_device_detector(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<DeviceDetector>()?;
+    m.add_class::<ClientHint>()?;
+    Ok(())
+}
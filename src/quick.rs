@@ -0,0 +1,139 @@
+//! Guesses from a handful of literal tokens of the user agent, for hot paths
+//! which can't afford to touch the detector, its database or any of its
+//! lazily compiled regexes:
+//!
+//! ```
+//! use rust_device_detector::quick;
+//!
+//! const IPHONE: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15";
+//! const _: () = assert!(quick::is_probably_mobile(IPHONE));
+//! ```
+//!
+//! Every function is a `const fn` over the bytes of the user agent, without
+//! allocating. They are wrong more often than [`crate::device_class`], which
+//! falls back to the full detection when unsure, and they don't look at client
+//! hints at all, so chromium's reduced user agents on android tablets pass for
+//! phones.
+
+use crate::device_class::DeviceClass;
+
+/// Whether `needle` occurs in `haystack`, ignoring ascii case when `fold`.
+const fn contains(haystack: &[u8], needle: &[u8], fold: bool) -> bool {
+    if needle.len() > haystack.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i <= haystack.len() - needle.len() {
+        let mut j = 0;
+        while j < needle.len() {
+            let (a, b) = (haystack[i + j], needle[j]);
+            let eq = if fold {
+                a.eq_ignore_ascii_case(&b)
+            } else {
+                a == b
+            };
+            if !eq {
+                break;
+            }
+            j += 1;
+        }
+        if j == needle.len() {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn contains_any(ua: &[u8], needles: &[&str], fold: bool) -> bool {
+    let mut i = 0;
+    while i < needles.len() {
+        if contains(ua, needles[i].as_bytes(), fold) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+const BOT_TOKENS: [&str; 10] = [
+    "bot/",
+    "bot;",
+    "bot)",
+    "crawl",
+    "spider",
+    "slurp",
+    "facebookexternalhit",
+    "curl/",
+    "wget/",
+    "python-requests",
+];
+
+const TV_TOKENS: [&str; 9] = [
+    "SmartTV", "SMART-TV", "HbbTV", "CrKey", "AppleTV", "BRAVIA", "GoogleTV", "AFT", " TV",
+];
+
+const TABLET_TOKENS: [&str; 5] = ["iPad", "Tablet;", "Kindle", "Silk/", "PlayBook"];
+
+const MOBILE_TOKENS: [&str; 9] = [
+    "Mobi",
+    "iPhone",
+    "iPod",
+    "Windows Phone",
+    "BlackBerry",
+    "BB10",
+    "Opera Mini",
+    "KAIOS",
+    "Symbian",
+];
+
+const DESKTOP_TOKENS: [&str; 4] = ["Windows", "Macintosh", "X11", "CrOS"];
+
+/// Whether the user agent names a crawler, or an http library.
+pub const fn is_probably_bot(ua: &str) -> bool {
+    contains_any(ua.as_bytes(), &BOT_TOKENS, true)
+}
+
+/// Whether the user agent is of a TV or a streaming stick.
+pub const fn is_probably_tv(ua: &str) -> bool {
+    contains_any(ua.as_bytes(), &TV_TOKENS, false)
+}
+
+/// Whether the user agent is of a tablet, android ones being those without
+/// `Mobile`.
+pub const fn is_probably_tablet(ua: &str) -> bool {
+    let ua = ua.as_bytes();
+    contains_any(ua, &TABLET_TOKENS, false)
+        || contains(ua, b"Android", false) && !contains(ua, b"Mobi", false)
+}
+
+/// Whether the user agent is of a phone.
+pub const fn is_probably_mobile(ua: &str) -> bool {
+    contains_any(ua.as_bytes(), &MOBILE_TOKENS, false) && !is_probably_tablet(ua)
+}
+
+/// Whether the user agent is of a desktop or notebook.
+pub const fn is_probably_desktop(ua: &str) -> bool {
+    contains_any(ua.as_bytes(), &DESKTOP_TOKENS, false)
+        && !is_probably_mobile(ua)
+        && !is_probably_tablet(ua)
+}
+
+/// The first of bot, tv, tablet, mobile and desktop the user agent probably
+/// is, or [`DeviceClass::Other`].
+pub const fn classify(ua: &str) -> DeviceClass {
+    if is_probably_bot(ua) {
+        DeviceClass::Bot
+    } else if is_probably_tv(ua) {
+        DeviceClass::Tv
+    } else if is_probably_tablet(ua) {
+        DeviceClass::Tablet
+    } else if is_probably_mobile(ua) {
+        DeviceClass::Mobile
+    } else if is_probably_desktop(ua) {
+        DeviceClass::Desktop
+    } else {
+        DeviceClass::Other
+    }
+}
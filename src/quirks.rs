@@ -0,0 +1,97 @@
+//! Special cases the detector hardcodes on top of the regex database, as the
+//! php library does. They are right for most traffic, but where one conflicts
+//! with your own ground truth it can be turned off on its own, by its id.
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().without_quirk("iridium-year".parse()?);
+//! ```
+//!
+//! Ids are stable, a quirk which is removed keeps its id unused. Quirks which
+//! only adjust a finished detection are declared as [`crate::rules`], in
+//! `src/rules/quirks.yml`.
+
+use anyhow::{anyhow, Result};
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Quirk {
+    /// `iridium-year`: a client hints version which is a year, such as 2022
+    /// or 2022.04, is Iridium, whatever brand it was sent with.
+    IridiumYear,
+    /// `360-secure-browser`: a client hints version of 15 with a user agent
+    /// version of 114 is 360 Secure Browser.
+    SecureBrowser360,
+    /// `duckduckgo-version`: the version of DuckDuckGo Privacy Browser from
+    /// client hints is dropped, as it is that of the Chromium it runs on.
+    DuckDuckGoVersion,
+    /// `opera-webview`: Chrome Webview with `OPR/` in the user agent is
+    /// detected again as Opera Mobile.
+    OperaWebView,
+    /// `ios-webkit`: Chrome, Firefox and Edge on iOS, `CriOS/`, `FxiOS/` and
+    /// `EdgiOS/`, run on WebKit like every iOS browser, whatever engine their
+    /// name or hints would map to.
+    IosWebKit,
+}
+
+impl Quirk {
+    pub const ALL: [Quirk; 5] = [
+        Quirk::IridiumYear,
+        Quirk::SecureBrowser360,
+        Quirk::DuckDuckGoVersion,
+        Quirk::OperaWebView,
+        Quirk::IosWebKit,
+    ];
+
+    /// The stable id of the quirk.
+    pub fn id(self) -> &'static str {
+        match self {
+            Quirk::IridiumYear => "iridium-year",
+            Quirk::SecureBrowser360 => "360-secure-browser",
+            Quirk::DuckDuckGoVersion => "duckduckgo-version",
+            Quirk::OperaWebView => "opera-webview",
+            Quirk::IosWebKit => "ios-webkit",
+        }
+    }
+
+    fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+impl fmt::Display for Quirk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for Quirk {
+    type Err = anyhow::Error;
+
+    fn from_str(id: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|x| x.id() == id)
+            .copied()
+            .ok_or_else(|| anyhow!("unknown quirk {}", id))
+    }
+}
+
+/// A set of quirks, such as those disabled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuirkSet(u32);
+
+impl QuirkSet {
+    pub fn insert(&mut self, quirk: Quirk) {
+        self.0 |= quirk.bit();
+    }
+
+    pub fn contains(&self, quirk: Quirk) -> bool {
+        self.0 & quirk.bit() != 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Quirk> + '_ {
+        Quirk::ALL.into_iter().filter(|x| self.contains(*x))
+    }
+}
@@ -0,0 +1,39 @@
+//! Counting events in fixed windows of time, without locking.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+pub(crate) struct RateWindow {
+    interval_ms: u64,
+    started: Instant,
+    window: AtomicU64,
+    count: AtomicU32,
+}
+
+impl RateWindow {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            interval_ms: (interval.as_millis() as u64).max(1),
+            started: Instant::now(),
+            window: AtomicU64::new(0),
+            count: AtomicU32::new(0),
+        }
+    }
+
+    /// Counts an event, returning how many there have been in the current
+    /// window including this one.
+    pub(crate) fn hit(&self) -> u32 {
+        let window = self.started.elapsed().as_millis() as u64 / self.interval_ms;
+        let current = self.window.load(Ordering::Relaxed);
+        if window != current
+            && self
+                .window
+                .compare_exchange(current, window, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            self.count.store(0, Ordering::Relaxed);
+        }
+
+        self.count.fetch_add(1, Ordering::Relaxed).saturating_add(1)
+    }
+}
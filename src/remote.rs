@@ -0,0 +1,98 @@
+//! Loading the regex database over http at startup, for targets such as
+//! WebAssembly edge functions where embedding the whole database makes the
+//! binary too large.
+//!
+//! The database is fetched as a bundle made by
+//! [`crate::database::bundle_dir`], and checked against its sha256 before it
+//! is used, so a compromised or truncated download fails rather than
+//! detecting with whatever it contained. Fetching is left to the platform,
+//! so that this works the same with `fetch` in a Worker as with reqwest:
+//!
+//! ```ignore
+//! let db = remote::fetch_database(BUNDLE_URL, BUNDLE_SHA256, |url| async move {
+//!     let mut res = Fetch::Url(url.parse()?).send().await?;
+//!     Ok(res.bytes().await?)
+//! })
+//! .await?;
+//!
+//! let detector = DeviceDetector::new_with_database(Arc::new(db));
+//! ```
+//!
+//! With the `signed-database` feature, [`fetch_signed_database`] checks an
+//! ed25519 signature instead, fetched from next to the bundle, so that the
+//! bundle can be updated without redeploying. See [`crate::signing`].
+//!
+//! [`DeviceDetector::new_with_database`](crate::device_detector::DeviceDetector::new_with_database)
+//! never touches the embedded database, so the linker can leave it out of the
+//! binary when nothing else does either.
+
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+
+use std::fmt::Write;
+use std::future::Future;
+
+use crate::database::Database;
+
+/// The hex encoded sha256 of `bundle`, as [`fetch_database`] expects it.
+pub fn sha256_hex(bundle: &[u8]) -> String {
+    let mut res = String::with_capacity(64);
+    for byte in Sha256::digest(bundle) {
+        write!(res, "{:02x}", byte).expect("write to string");
+    }
+    res
+}
+
+/// Fails unless the sha256 of `bundle` is `sha256`, hex encoded.
+pub fn verify_sha256(bundle: &[u8], sha256: &str) -> Result<()> {
+    let actual = sha256_hex(bundle);
+    if !actual.eq_ignore_ascii_case(sha256.trim()) {
+        return Err(anyhow!(
+            "database bundle has sha256 {}, expected {}",
+            actual,
+            sha256
+        ));
+    }
+    Ok(())
+}
+
+/// Loads a bundle which must have the sha256 `sha256`, hex encoded.
+pub fn database_from_bundle(bundle: &[u8], sha256: &str) -> Result<Database> {
+    verify_sha256(bundle, sha256)?;
+    Database::from_bundle(bundle)
+}
+
+/// Downloads the bundle at `url` with `fetch`, and loads it if it has the
+/// sha256 `sha256`, hex encoded.
+pub async fn fetch_database<F, Fut>(url: &str, sha256: &str, fetch: F) -> Result<Database>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<Vec<u8>>>,
+{
+    let bundle = fetch(url.to_owned())
+        .await
+        .map_err(|e| anyhow!("fetching {}: {:#}", url, e))?;
+    database_from_bundle(&bundle, sha256)
+}
+
+/// Downloads the bundle at `url` and its signature at `url` followed by
+/// `.sig` with `fetch`, and loads the bundle if it is signed by `key`.
+#[cfg(feature = "signed-database")]
+pub async fn fetch_signed_database<F, Fut>(
+    url: &str,
+    key: &crate::signing::BundleKey,
+    fetch: F,
+) -> Result<Database>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<u8>>>,
+{
+    let signature_url = format!("{}.sig", url);
+    let bundle = fetch(url.to_owned())
+        .await
+        .map_err(|e| anyhow!("fetching {}: {:#}", url, e))?;
+    let signature = fetch(signature_url.clone())
+        .await
+        .map_err(|e| anyhow!("fetching {}: {:#}", signature_url, e))?;
+    Database::from_signed_bundle(&bundle, &signature, key)
+}
@@ -0,0 +1,169 @@
+//! Replays a corpus of fixtures, such as the matomo test fixtures or ones
+//! produced by [`crate::fixture_recorder`], reporting where detection no longer
+//! agrees with what the fixture expects.
+//!
+//! A previous run's mismatches can be saved as a baseline, so that only new
+//! mismatches are reported. As the regex database is compiled in, finding the
+//! database change that caused a new mismatch is done with git, rebuilding at
+//! each step:
+//!
+//! ```shell
+//! > rust-device-detector replay corpus.yml --write-baseline old.json
+//! > git bisect start HEAD <known good> -- data/regexes/
+//! > git bisect run cargo run -p device-detector-server -- replay corpus.yml --baseline old.json
+//! ```
+
+use anyhow::Result;
+
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::device_detector::DeviceDetector;
+use crate::fixture_recorder;
+
+/// A field of a fixture whose detected value differs from the expected one.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Mismatch {
+    pub user_agent: String,
+    /// Path to the field, such as `device.model`.
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Loads a yaml file containing a list of fixtures.
+pub fn load_corpus<P: AsRef<Path>>(path: P) -> Result<Vec<Value>> {
+    let contents = std::fs::read_to_string(path)?;
+    let cases: Vec<Value> = serde_yaml::from_str(&contents)?;
+    Ok(cases)
+}
+
+/// Loads mismatches previously saved with [`save_baseline`].
+pub fn load_baseline<P: AsRef<Path>>(path: P) -> Result<Vec<Mismatch>> {
+    let contents = std::fs::read_to_string(path)?;
+    let res = serde_json::from_str(&contents)?;
+    Ok(res)
+}
+
+pub fn save_baseline<P: AsRef<Path>>(path: P, mismatches: &[Mismatch]) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(mismatches)?)?;
+    Ok(())
+}
+
+/// The mismatches in `current` that aren't in `baseline`.
+pub fn new_mismatches(current: &[Mismatch], baseline: &[Mismatch]) -> Vec<Mismatch> {
+    let baseline = baseline.iter().collect::<HashSet<_>>();
+    current
+        .iter()
+        .filter(|x| !baseline.contains(x))
+        .cloned()
+        .collect()
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(x) => x.to_owned(),
+        Value::Number(x) => x.to_string(),
+        Value::Bool(x) => x.to_string(),
+        _ => "".to_owned(),
+    }
+}
+
+// php renders missing sections as null or empty lists
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Sequence(x) => x.is_empty(),
+        Value::Mapping(x) => x.values().all(is_empty),
+        Value::String(x) => x.is_empty(),
+        _ => false,
+    }
+}
+
+pub(crate) fn compare(
+    ua: &str,
+    path: &str,
+    expected: &Value,
+    actual: &Value,
+    res: &mut Vec<Mismatch>,
+) {
+    match expected {
+        Value::Mapping(expected) => {
+            for (key, expected) in expected {
+                let key = scalar(key);
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                let actual = actual.get(key.as_str()).unwrap_or(&Value::Null);
+                compare(ua, &path, expected, actual, res);
+            }
+        }
+        Value::Null | Value::Sequence(_) => {
+            if !is_empty(actual) {
+                res.push(Mismatch {
+                    user_agent: ua.to_owned(),
+                    field: path.to_owned(),
+                    expected: "".to_owned(),
+                    actual: serde_json::to_string(actual).unwrap_or_default(),
+                });
+            }
+        }
+        expected => {
+            let expected = scalar(expected);
+            let actual = scalar(actual);
+            if expected != actual {
+                res.push(Mismatch {
+                    user_agent: ua.to_owned(),
+                    field: path.to_owned(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+}
+
+type Headers = Vec<(String, String)>;
+
+/// The user agent and headers of a fixture.
+pub(crate) fn fixture_request(case: &Value) -> Result<(String, Option<Headers>)> {
+    let ua = case
+        .get("user_agent")
+        .map(scalar)
+        .ok_or(anyhow::anyhow!("fixture without a user_agent"))?;
+
+    let headers = case.get("headers").and_then(|x| x.as_mapping()).map(|x| {
+        x.iter()
+            .map(|(k, v)| (scalar(k), scalar(v)))
+            .collect::<Vec<_>>()
+    });
+
+    Ok((ua, headers))
+}
+
+/// Detects each fixture and compares the result to what it expects.
+pub fn replay(detector: &DeviceDetector, cases: &[Value]) -> Result<Vec<Mismatch>> {
+    let mut res = Vec::new();
+
+    for case in cases {
+        let (ua, headers) = fixture_request(case)?;
+
+        let detection = detector.parse(&ua, headers.clone())?;
+        let actual = fixture_recorder::fixture(&ua, headers.as_deref(), &detection);
+
+        let mut expected = case.clone();
+        if let Some(expected) = expected.as_mapping_mut() {
+            expected.remove("user_agent");
+            expected.remove("headers");
+        }
+
+        compare(&ua, "", &expected, &actual, &mut res);
+    }
+
+    Ok(res)
+}
@@ -0,0 +1,89 @@
+//! Detection which carries on past a stage that fails, rather than failing
+//! the whole parse. A bad regex in an overlay, or one which runs into the
+//! backtracking limit, would otherwise throw away a browser and device which
+//! were detected fine:
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().with_resilience(true);
+//! let detection = detector.parse(ua, None)?;
+//! for error in detection.stage_errors() {
+//!     log::warn!("{} detection failed: {}", error.stage.as_str(), error.message);
+//! }
+//! ```
+//!
+//! A stage which fails is left out of the detection, as if it had detected
+//! nothing, and its error is recorded in [`KnownDevice::stage_errors`]. When
+//! the bot stage fails, the request is detected as a known device. Errors
+//! from [`crate::rules`] still fail the parse, as do those of every stage
+//! when resilience is off, as it is by default. Detections with stage errors
+//! are never cached, so the stage is tried again on the next request.
+//!
+//! [`KnownDevice::stage_errors`]: crate::device_detector::KnownDevice::stage_errors
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// A stage of detection, each of which looks up one part of a detection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Bot,
+    Os,
+    Client,
+    Device,
+}
+
+impl Stage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Bot => "bot",
+            Self::Os => "os",
+            Self::Client => "client",
+            Self::Device => "device",
+        }
+    }
+}
+
+/// The error a stage failed with.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageError {
+    pub stage: Stage,
+    /// The error with its causes, such as the regex which failed.
+    pub message: String,
+}
+
+/// Collects the errors of the stages of one detection.
+#[derive(Debug, Default)]
+pub(crate) struct Recovery {
+    enabled: bool,
+    errors: Vec<StageError>,
+}
+
+impl Recovery {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            errors: Vec::new(),
+        }
+    }
+
+    /// The output of `stage`, or nothing with its error recorded if it failed
+    /// and recovering is enabled.
+    pub(crate) fn recover<T: Default>(&mut self, stage: Stage, res: Result<T>) -> Result<T> {
+        match res {
+            Ok(res) => Ok(res),
+            Err(err) if self.enabled => {
+                self.errors.push(StageError {
+                    stage,
+                    message: format!("{:#}", err),
+                });
+                Ok(T::default())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    pub(crate) fn into_errors(self) -> Vec<StageError> {
+        self.errors
+    }
+}
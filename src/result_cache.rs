@@ -0,0 +1,85 @@
+//! Caching whole detections keyed by the user agent and client hints, so that
+//! the user agents a service sees millions of times a day skip the regexes
+//! entirely after the first:
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().with_result_cache(100_000);
+//! ```
+//!
+//! A detector has a single cache, which keys on the hints of every request,
+//! so two requests share a detection only if they'd be detected the same.
+//! With the `cache` feature, [`DeviceDetector::with_result_cache`] and
+//! `DeviceDetector::new_with_cache` make it a moka cache, which doesn't lock
+//! and evicts by frequency as well as recency. Services with a cache of their
+//! own, such as one shared between processes, plug it in with
+//! [`DeviceDetector::with_custom_result_cache`] by implementing
+//! [`ResultCache`]. Memory constrained services leave it off, as it is by
+//! default, or turn it back off with [`DeviceDetector::without_result_cache`].
+//!
+//! [`DeviceDetector::with_result_cache`]: crate::device_detector::DeviceDetector::with_result_cache
+//! [`DeviceDetector::with_custom_result_cache`]: crate::device_detector::DeviceDetector::with_custom_result_cache
+//! [`DeviceDetector::without_result_cache`]: crate::device_detector::DeviceDetector::without_result_cache
+
+use crate::client_hints::ClientHint;
+use crate::device_detector::Detection;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub user_agent: String,
+    pub client_hints: Option<ClientHint>,
+}
+
+impl CacheKey {
+    pub fn new(user_agent: &str, client_hints: Option<&ClientHint>) -> Self {
+        Self {
+            user_agent: user_agent.to_owned(),
+            client_hints: client_hints.cloned(),
+        }
+    }
+}
+
+/// A cache of detections, see [`crate::result_cache`].
+pub trait ResultCache: Send + Sync {
+    fn get(&self, key: &CacheKey) -> Option<Detection>;
+    fn insert(&self, key: CacheKey, detection: Detection);
+    /// How many detections are cached, for
+    /// [`crate::device_detector::DeviceDetector::memory_usage`].
+    fn entry_count(&self) -> u64;
+    /// The most detections kept, if bounded, which a memory budget lowers by
+    /// replacing the cache with a smaller moka cache, see
+    /// [`crate::memory`]. `None` by default, which leaves the cache be.
+    fn capacity(&self) -> Option<u64> {
+        None
+    }
+    /// Drops every cached detection, such as when the database is reloaded,
+    /// see [`crate::device_detector::DeviceDetector::reload`]. Does nothing
+    /// by default.
+    fn clear(&self) {}
+}
+
+/// Moka's cache, which evicts by frequency as well as recency and doesn't
+/// lock, for services detecting on many threads at once.
+#[cfg(feature = "cache")]
+impl ResultCache for moka::sync::Cache<CacheKey, Detection> {
+    fn get(&self, key: &CacheKey) -> Option<Detection> {
+        moka::sync::Cache::get(self, key)
+    }
+
+    fn insert(&self, key: CacheKey, detection: Detection) {
+        moka::sync::Cache::insert(self, key, detection)
+    }
+
+    fn entry_count(&self) -> u64 {
+        // the count is only brought up to date by moka's housekeeping
+        moka::sync::ConcurrentCacheExt::sync(self);
+        moka::sync::Cache::entry_count(self)
+    }
+
+    fn capacity(&self) -> Option<u64> {
+        self.policy().max_capacity()
+    }
+
+    fn clear(&self) {
+        moka::sync::Cache::invalidate_all(self)
+    }
+}
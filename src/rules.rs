@@ -0,0 +1,567 @@
+//! Declarative overrides applied to detections after the detector is done
+//! with them, for correcting or relabelling clients, oss and devices without
+//! patching the regex database.
+//!
+//! ```yaml
+//! - id: my-company-app
+//!   when:
+//!     - ua: 'MyCompanyApp/(?P<version>[\d.]+)'
+//!   then:
+//!     - rename: My Company App
+//!     - set: client.version
+//!       value: '${version}'
+//!
+//! - id: kiosks
+//!   when:
+//!     - field: device.model
+//!       matches: '^Kiosk'
+//!     - hint: sec-ch-ua-platform
+//!   then:
+//!     - set: device.type
+//!       value: smart display
+//!
+//! - id: internal-monitor
+//!   when:
+//!     - field: bot.name
+//!       equals: Uptime Robot
+//!   then:
+//!     - drop_bot: true
+//! ```
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().with_rules(Rules::from_file("rules.yml")?);
+//! ```
+//!
+//! A rule applies when all of its conditions hold, each being one of
+//!
+//! - `field` with `equals` or `matches`, a regex, on one of the [`Field`]s,
+//! - `ua`, a regex matching the user agent, whose named groups can be used as
+//!   `${name}` in the values the rule sets,
+//! - `hint`, a client hint header which was sent, optionally with `matches`
+//!   on its value, unquoted for hints with a single value.
+//!
+//! Its actions are, in order,
+//!
+//! - `set` a field to `value`, or to the first group of `ua` matched against
+//!   the user agent, leaving it unchanged when it doesn't match,
+//! - `unset` a field,
+//! - `rename` the client, also updating its short code and family,
+//! - `drop_bot`, detecting a bot as the client it would otherwise be. Its
+//!   other actions apply to that detection if its conditions still hold.
+//!
+//! Fields of a missing client, os or bot are left alone, setting a device
+//! field adds the device if there is none. Rules apply in order, the
+//! detector's own, see [`crate::quirks`], before those added with
+//! [`crate::device_detector::DeviceDetector::with_rules`].
+
+use anyhow::{anyhow, bail, Context, Result};
+use fancy_regex::Regex;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::client_hints::ClientHint;
+use crate::device_detector::{Detection, ParseOptions};
+use crate::parsers::client::browsers::AVAILABLE_BROWSERS;
+use crate::parsers::client::ClientType;
+use crate::parsers::device::{Confidence, Device, DeviceType};
+use crate::quirks::Quirk;
+
+/// The detector's own rules, ids being those of [`Quirk`].
+pub(crate) static QUIRK_RULES: Lazy<Rules> = Lazy::new(|| {
+    let mut rules = Rules::from_yaml(include_str!("rules/quirks.yml")).expect("valid quirk rules");
+    for rule in rules.rules.iter_mut() {
+        rule.quirk = Some(rule.id.parse().expect("quirk rule ids are quirks"));
+    }
+    rules
+});
+
+/// A field of a detection rules can test and set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    ClientName,
+    ClientVersion,
+    ClientEngine,
+    ClientEngineVersion,
+    OsName,
+    OsVersion,
+    OsPlatform,
+    OsFamily,
+    DeviceType,
+    DeviceBrand,
+    DeviceModel,
+    BotName,
+    BotCategory,
+}
+
+impl Field {
+    pub const ALL: [Field; 13] = [
+        Field::ClientName,
+        Field::ClientVersion,
+        Field::ClientEngine,
+        Field::ClientEngineVersion,
+        Field::OsName,
+        Field::OsVersion,
+        Field::OsPlatform,
+        Field::OsFamily,
+        Field::DeviceType,
+        Field::DeviceBrand,
+        Field::DeviceModel,
+        Field::BotName,
+        Field::BotCategory,
+    ];
+
+    /// The name of the field in rules, such as `client.name`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::ClientName => "client.name",
+            Field::ClientVersion => "client.version",
+            Field::ClientEngine => "client.engine",
+            Field::ClientEngineVersion => "client.engine_version",
+            Field::OsName => "os.name",
+            Field::OsVersion => "os.version",
+            Field::OsPlatform => "os.platform",
+            Field::OsFamily => "os.family",
+            Field::DeviceType => "device.type",
+            Field::DeviceBrand => "device.brand",
+            Field::DeviceModel => "device.model",
+            Field::BotName => "bot.name",
+            Field::BotCategory => "bot.category",
+        }
+    }
+
+    fn get(self, detection: &Detection) -> Option<&str> {
+        let known = match detection {
+            Detection::Bot(bot) => {
+                return match self {
+                    Field::BotName => Some(&bot.name),
+                    Field::BotCategory => bot.category.as_deref(),
+                    _ => None,
+                }
+            }
+            Detection::Known(known) => known,
+        };
+
+        let client = known.client.as_ref();
+        let os = known.os.as_ref();
+        let device = known.device.as_ref();
+
+        match self {
+            Field::ClientName => client.map(|x| x.name.as_str()),
+            Field::ClientVersion => client.and_then(|x| x.version.as_deref()),
+            Field::ClientEngine => client.and_then(|x| x.engine.as_deref()),
+            Field::ClientEngineVersion => client.and_then(|x| x.engine_version.as_deref()),
+            Field::OsName => os.map(|x| x.name.as_str()),
+            Field::OsVersion => os.and_then(|x| x.version.as_deref()),
+            Field::OsPlatform => os.and_then(|x| x.platform.as_deref()),
+            Field::OsFamily => os.and_then(|x| x.family.as_deref()),
+            Field::DeviceType => device
+                .and_then(|x| x.device_type.as_ref())
+                .map(|x| x.as_str()),
+            Field::DeviceBrand => device.and_then(|x| x.brand.as_deref()),
+            Field::DeviceModel => device.and_then(|x| x.model.as_deref()),
+            Field::BotName | Field::BotCategory => None,
+        }
+    }
+
+    fn set(self, detection: &mut Detection, value: Option<String>) {
+        let known = match detection {
+            Detection::Bot(bot) => {
+                match (self, value) {
+                    (Field::BotName, Some(value)) => bot.name = value,
+                    (Field::BotCategory, value) => bot.category = value,
+                    _ => {}
+                }
+                return;
+            }
+            Detection::Known(known) => known,
+        };
+
+        match self {
+            Field::ClientName
+            | Field::ClientVersion
+            | Field::ClientEngine
+            | Field::ClientEngineVersion => {
+                let Some(client) = known.client.as_mut() else {
+                    return;
+                };
+                match (self, value) {
+                    (Field::ClientName, Some(value)) => client.name = value,
+                    (Field::ClientName, None) => {}
+                    (Field::ClientVersion, value) => client.version = value,
+                    (Field::ClientEngine, value) => client.engine = value,
+                    (_, value) => client.engine_version = value,
+                }
+            }
+            Field::OsName | Field::OsVersion | Field::OsPlatform | Field::OsFamily => {
+                let Some(os) = known.os.as_mut() else {
+                    return;
+                };
+                match (self, value) {
+                    (Field::OsName, Some(value)) => os.name = value,
+                    (Field::OsName, None) => {}
+                    (Field::OsVersion, value) => os.version = value,
+                    (Field::OsPlatform, value) => os.platform = value,
+                    (_, value) => os.family = value,
+                }
+            }
+            Field::DeviceType | Field::DeviceBrand | Field::DeviceModel => {
+                let device = known.device.get_or_insert_with(Device::default);
+                let confidence = value.as_ref().map(|_| Confidence::Exact);
+                match self {
+                    Field::DeviceType => {
+                        device.device_type = value.as_deref().and_then(DeviceType::parse);
+                        device.confidence.device_type = device.device_type.as_ref().and(confidence);
+                    }
+                    Field::DeviceBrand => {
+                        device.brand = value;
+                        device.confidence.brand = confidence;
+                    }
+                    _ => {
+                        device.model = value;
+                        device.confidence.model = confidence;
+                    }
+                }
+            }
+            Field::BotName | Field::BotCategory => {}
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|x| x.name() == name)
+            .copied()
+            .ok_or_else(|| anyhow!("unknown field {}", name))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct YamlRule {
+    id: String,
+    #[serde(default)]
+    when: Vec<YamlCondition>,
+    then: Vec<YamlAction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct YamlCondition {
+    field: Option<String>,
+    equals: Option<String>,
+    matches: Option<String>,
+    ua: Option<String>,
+    hint: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct YamlAction {
+    set: Option<String>,
+    value: Option<String>,
+    ua: Option<String>,
+    unset: Option<String>,
+    rename: Option<String>,
+    #[serde(default)]
+    drop_bot: bool,
+}
+
+#[derive(Debug)]
+enum Condition {
+    Equals(Field, String),
+    Matches(Field, Regex),
+    Ua(Regex),
+    Hint(String, Option<Regex>),
+}
+
+#[derive(Debug)]
+enum Action {
+    Set(Field, String),
+    SetFromUa(Field, Regex),
+    Unset(Field),
+    Rename(String),
+    DropBot,
+}
+
+#[derive(Debug)]
+struct Rule {
+    id: String,
+    quirk: Option<Quirk>,
+    when: Vec<Condition>,
+    then: Vec<Action>,
+}
+
+fn regex(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).with_context(|| format!("invalid regex {}", pattern))
+}
+
+impl Condition {
+    fn from_yaml(x: YamlCondition) -> Result<Self> {
+        match x {
+            YamlCondition {
+                field: Some(field),
+                equals,
+                matches,
+                ua: None,
+                hint: None,
+            } => {
+                let field = field.parse()?;
+                match (equals, matches) {
+                    (Some(value), None) => Ok(Condition::Equals(field, value)),
+                    (None, Some(pattern)) => Ok(Condition::Matches(field, regex(&pattern)?)),
+                    _ => bail!("a field condition needs one of equals or matches"),
+                }
+            }
+            YamlCondition {
+                field: None,
+                equals: None,
+                matches: None,
+                ua: Some(pattern),
+                hint: None,
+            } => Ok(Condition::Ua(regex(&pattern)?)),
+            YamlCondition {
+                field: None,
+                equals: None,
+                matches,
+                ua: None,
+                hint: Some(hint),
+            } => Ok(Condition::Hint(
+                hint.to_lowercase(),
+                matches.as_deref().map(regex).transpose()?,
+            )),
+            _ => bail!("a condition needs exactly one of field, ua or hint"),
+        }
+    }
+}
+
+impl Action {
+    fn from_yaml(x: YamlAction) -> Result<Self> {
+        match x {
+            YamlAction {
+                set: Some(field),
+                value,
+                ua,
+                unset: None,
+                rename: None,
+                drop_bot: false,
+            } => {
+                let field: Field = field.parse()?;
+                let res = match (value, ua) {
+                    (Some(value), None) => Action::Set(field, value),
+                    (None, Some(pattern)) => Action::SetFromUa(field, regex(&pattern)?),
+                    _ => bail!("set needs one of value or ua"),
+                };
+                if let Action::Set(Field::DeviceType, value) = &res {
+                    if !value.contains("${") && DeviceType::parse(value).is_none() {
+                        bail!("unknown device type {}", value);
+                    }
+                }
+                Ok(res)
+            }
+            YamlAction {
+                set: None,
+                value: None,
+                ua: None,
+                unset: Some(field),
+                rename: None,
+                drop_bot: false,
+            } => Ok(Action::Unset(field.parse()?)),
+            YamlAction {
+                set: None,
+                value: None,
+                ua: None,
+                unset: None,
+                rename: Some(name),
+                drop_bot: false,
+            } => Ok(Action::Rename(name)),
+            YamlAction {
+                set: None,
+                value: None,
+                ua: None,
+                unset: None,
+                rename: None,
+                drop_bot: true,
+            } => Ok(Action::DropBot),
+            _ => bail!("an action needs exactly one of set, unset, rename or drop_bot"),
+        }
+    }
+}
+
+/// Replaces `${name}` in `template` with the named groups captured.
+fn expand(template: &str, captures: &[(String, String)]) -> String {
+    let mut res = template.to_owned();
+    for (name, value) in captures {
+        res = res.replace(&format!("${{{}}}", name), value);
+    }
+    res
+}
+
+impl Rule {
+    /// The named groups captured, if the rule applies.
+    fn matches(
+        &self,
+        ua: &str,
+        hints: Option<&ClientHint>,
+        detection: &Detection,
+    ) -> Result<Option<Vec<(String, String)>>> {
+        let mut captures = Vec::new();
+
+        for condition in &self.when {
+            let holds = match condition {
+                Condition::Equals(field, value) => field.get(detection) == Some(value.as_str()),
+                Condition::Matches(field, regex) => match field.get(detection) {
+                    Some(value) => regex.is_match(value)?,
+                    None => false,
+                },
+                Condition::Ua(regex) => match regex.captures(ua)? {
+                    Some(caps) => {
+                        for name in regex.capture_names().flatten() {
+                            let value = caps.name(name).map(|x| x.as_str()).unwrap_or_default();
+                            captures.push((name.to_owned(), value.to_owned()));
+                        }
+                        true
+                    }
+                    None => false,
+                },
+                Condition::Hint(name, regex) => {
+                    match (hints.and_then(|x| x.header_value(name)), regex) {
+                        (Some(value), Some(regex)) => regex.is_match(&value)?,
+                        (Some(_), None) => true,
+                        (None, _) => false,
+                    }
+                }
+            };
+
+            if !holds {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(captures))
+    }
+
+    fn apply(
+        &self,
+        ua: &str,
+        detection: &mut Detection,
+        captures: &[(String, String)],
+    ) -> Result<()> {
+        for action in &self.then {
+            match action {
+                Action::Set(field, value) => {
+                    let value = expand(value, captures);
+                    field.set(detection, Some(value).filter(|x| !x.is_empty()));
+                }
+                Action::SetFromUa(field, regex) => {
+                    if let Some(value) = regex.captures(ua)?.and_then(|x| x.get(1)) {
+                        field.set(detection, Some(value.as_str().to_owned()));
+                    }
+                }
+                Action::Unset(field) => field.set(detection, None),
+                Action::Rename(name) => {
+                    let Detection::Known(known) = detection else {
+                        continue;
+                    };
+                    let Some(client) = known.client.as_mut() else {
+                        continue;
+                    };
+                    client.name.clone_from(name);
+                    if client.r#type == ClientType::Browser {
+                        client.browser = AVAILABLE_BROWSERS.search_by_name(name).cloned();
+                        client.short_code = client.browser.as_ref().map(|x| x.short_code.clone());
+                    }
+                }
+                Action::DropBot => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    pub fn from_yaml(contents: &str) -> Result<Self> {
+        let rules: Vec<YamlRule> = serde_yaml::from_str(contents)?;
+
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let id = rule.id;
+                let when: Result<Vec<_>> =
+                    rule.when.into_iter().map(Condition::from_yaml).collect();
+                let then: Result<Vec<_>> = rule.then.into_iter().map(Action::from_yaml).collect();
+                Ok(Rule {
+                    when: when.with_context(|| format!("in rule {}", id))?,
+                    then: then.with_context(|| format!("in rule {}", id))?,
+                    id,
+                    quirk: None,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { rules })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading rules from {}", path.display()))?;
+        Self::from_yaml(&contents).with_context(|| format!("invalid rules in {}", path.display()))
+    }
+
+    /// Ids of the rules, in the order they apply.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|x| x.id.as_str())
+    }
+
+    fn enabled<'a>(&'a self, options: &'a ParseOptions) -> impl Iterator<Item = &'a Rule> {
+        self.rules
+            .iter()
+            .filter(|x| x.quirk.is_none_or(|quirk| options.quirk(quirk)))
+    }
+
+    /// Whether a rule with `drop_bot` applies to the bot `detection`.
+    pub(crate) fn drops_bot(
+        &self,
+        ua: &str,
+        hints: Option<&ClientHint>,
+        detection: &Detection,
+        options: &ParseOptions,
+    ) -> Result<bool> {
+        for rule in self.enabled(options) {
+            if rule.then.iter().any(|x| matches!(x, Action::DropBot))
+                && rule.matches(ua, hints, detection)?.is_some()
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    pub(crate) fn apply(
+        &self,
+        ua: &str,
+        hints: Option<&ClientHint>,
+        detection: &mut Detection,
+        options: &ParseOptions,
+    ) -> Result<()> {
+        for rule in self.enabled(options) {
+            if let Some(captures) = rule.matches(ua, hints, detection)? {
+                rule.apply(ua, detection, &captures)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+//! Post-detection rules written in [rhai](https://rhai.rs), for adjusting
+//! detections to your own traffic without writing or deploying Rust.
+//!
+//! A script sees the user agent as `ua` and the detection as `detection`, in
+//! the stable [`crate::v1`] schema, and changes `detection` in place:
+//!
+//! ```text
+//! if ua.contains("MyCompanyApp/") {
+//!     detection.client = #{ type: "mobile app", name: "My Company App", version: () };
+//! }
+//!
+//! if detection.device != () && detection.device.brand == "Amazon" && detection.device.type == "tablet" {
+//!     detection.device.type = "smart display";
+//! }
+//! ```
+//!
+//! ```ignore
+//! let rules = ScriptRules::from_file("rules.rhai")?;
+//!
+//! let detection = rules.apply(ua, &detector.parse(ua, headers)?)?;
+//! ```
+//!
+//! Scripts are sandboxed: they can't touch the filesystem or the network, and
+//! are stopped past a number of operations, so a runaway rule fails the
+//! request rather than hanging it. Rules loaded from a file are reloaded when
+//! the file changes, see [`ScriptRules::with_reload_interval`]. A change which
+//! doesn't compile is reported and the previous rules kept.
+
+use anyhow::{anyhow, Context, Result};
+use rhai::{Dynamic, Engine, Scope, AST};
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::device_detector::Detection;
+use crate::v1;
+
+/// Operations a script may run for each detection.
+const MAX_OPERATIONS: u64 = 100_000;
+
+struct Script {
+    ast: Arc<AST>,
+    modified: Option<SystemTime>,
+    checked: Instant,
+}
+
+pub struct ScriptRules {
+    engine: Engine,
+    path: Option<PathBuf>,
+    reload_interval: Duration,
+    script: RwLock<Script>,
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(64 * 1024);
+    engine.set_max_array_size(1024);
+    engine.set_max_map_size(1024);
+    engine.disable_symbol("eval");
+    // no `import` of other files
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|x| x.modified()).ok()
+}
+
+impl ScriptRules {
+    /// Rules from the script `source`, which are never reloaded.
+    pub fn new(source: &str) -> Result<Self> {
+        let engine = engine();
+        let ast = engine
+            .compile(source)
+            .map_err(|e| anyhow!("invalid rules: {}", e))?;
+
+        Ok(Self {
+            engine,
+            path: None,
+            reload_interval: Duration::from_secs(1),
+            script: RwLock::new(Script {
+                ast: Arc::new(ast),
+                modified: None,
+                checked: Instant::now(),
+            }),
+        })
+    }
+
+    /// Rules from the script at `path`, reloaded when it changes.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let engine = engine();
+        let modified = modified(path);
+        let ast = engine
+            .compile_file(path.to_owned())
+            .map_err(|e| anyhow!("{}", e))
+            .with_context(|| format!("invalid rules in {}", path.display()))?;
+
+        Ok(Self {
+            engine,
+            path: Some(path.to_owned()),
+            reload_interval: Duration::from_secs(1),
+            script: RwLock::new(Script {
+                ast: Arc::new(ast),
+                modified,
+                checked: Instant::now(),
+            }),
+        })
+    }
+
+    /// How often to check whether the file the rules were loaded from has
+    /// changed, once a second by default.
+    pub fn with_reload_interval(mut self, interval: Duration) -> Self {
+        self.reload_interval = interval;
+        self
+    }
+
+    /// Reloads the rules if their file has changed since they were loaded,
+    /// returning whether it had. A change which doesn't compile is an error,
+    /// and the previous rules are kept.
+    pub fn reload(&self) -> Result<bool> {
+        let Some(path) = &self.path else {
+            return Ok(false);
+        };
+
+        let mut script = self.script.write().expect("rules lock");
+        script.checked = Instant::now();
+
+        let modified = modified(path);
+        if modified == script.modified {
+            return Ok(false);
+        }
+        // only tried once per change, rather than on every check
+        script.modified = modified;
+
+        let ast = self
+            .engine
+            .compile_file(path.to_owned())
+            .map_err(|e| anyhow!("{}", e))
+            .with_context(|| format!("invalid rules in {}", path.display()))?;
+        script.ast = Arc::new(ast);
+
+        Ok(true)
+    }
+
+    fn ast(&self) -> Arc<AST> {
+        if self.path.is_some()
+            && self.script.read().expect("rules lock").checked.elapsed() >= self.reload_interval
+        {
+            if let Err(e) = self.reload() {
+                eprintln!("keeping the previous rules: {:#}", e);
+            }
+        }
+
+        self.script.read().expect("rules lock").ast.clone()
+    }
+
+    /// Runs the rules on `detection` of `ua`.
+    pub fn apply(&self, ua: &str, detection: &Detection) -> Result<v1::Detection> {
+        let ast = self.ast();
+
+        let mut scope = Scope::new();
+        scope.push("ua", ua.to_owned());
+        scope.push(
+            "detection",
+            rhai::serde::to_dynamic(detection.to_v1()).map_err(|e| anyhow!("{}", e))?,
+        );
+
+        self.engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| anyhow!("rules failed: {}", e))?;
+
+        let res = scope
+            .get_value::<Dynamic>("detection")
+            .ok_or_else(|| anyhow!("rules removed the detection"))?;
+        rhai::serde::from_dynamic(&res)
+            .map_err(|e| anyhow!("rules left an invalid detection: {}", e))
+    }
+}
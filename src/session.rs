@@ -0,0 +1,145 @@
+//! Detecting once per connection rather than once per request, for servers
+//! where a keep-alive or HTTP/2 connection sends the same user agent and
+//! hints with each of its requests:
+//!
+//! ```ignore
+//! let detector = SessionDetector::from(DeviceDetector::new());
+//! // for each request
+//! let detection = detector.parse(connection_id, ua, Some(headers))?;
+//! // once the connection is closed
+//! detector.end_session(&connection_id);
+//! ```
+//!
+//! The detection of a session is reused as long as its requests come with
+//! the same user agent and hints, and the session is detected again as soon
+//! as either changes, so that its detection is always that of its latest
+//! request. Any key works, such as a connection id or the id of a login
+//! session. Sessions which are never ended are bounded by
+//! [`SessionDetector::with_capacity`], all of them being dropped once it's
+//! reached, as with the cache of [`crate::local::LocalDeviceDetector`]. They
+//! are also dropped when the database is reloaded, see
+//! [`DeviceDetector::reload`].
+
+use anyhow::Result;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::client_hints::ClientHint;
+use crate::database::Database;
+use crate::device_detector::{Detection, DeviceDetector};
+use crate::result_cache::CacheKey;
+
+/// How many sessions are kept by default.
+pub const DEFAULT_CAPACITY: usize = 10_000;
+
+pub struct SessionDetector<K> {
+    detector: DeviceDetector,
+    sessions: Mutex<Sessions<K>>,
+    capacity: usize,
+    reused: AtomicU64,
+}
+
+struct Sessions<K> {
+    /// The database the sessions were detected with, which keeps its
+    /// allocation from being reused by another while sessions refer to it.
+    db: Weak<Database>,
+    /// The user agent and hints of the latest request of each session, with
+    /// its detection.
+    entries: HashMap<K, (CacheKey, Detection)>,
+}
+
+impl<K> From<DeviceDetector> for SessionDetector<K> {
+    fn from(detector: DeviceDetector) -> Self {
+        Self {
+            detector,
+            sessions: Mutex::new(Sessions {
+                db: Weak::new(),
+                entries: HashMap::new(),
+            }),
+            capacity: DEFAULT_CAPACITY,
+            reused: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<K: Hash + Eq> SessionDetector<K> {
+    /// Keeps up to `sessions` sessions rather than [`DEFAULT_CAPACITY`].
+    pub fn with_capacity(mut self, sessions: usize) -> Self {
+        self.capacity = sessions;
+        self
+    }
+
+    /// The detector sessions are detected with.
+    pub fn detector(&self) -> &DeviceDetector {
+        &self.detector
+    }
+
+    /// Number of sessions currently kept.
+    pub fn sessions(&self) -> usize {
+        self.sessions.lock().unwrap().entries.len()
+    }
+
+    /// Number of requests given the detection of their session rather than
+    /// detected.
+    pub fn detections_reused(&self) -> u64 {
+        self.reused.load(Ordering::Relaxed)
+    }
+
+    pub fn parse(
+        &self,
+        session: K,
+        ua: &str,
+        headers: Option<Vec<(String, String)>>,
+    ) -> Result<Detection> {
+        let client_hints = match headers {
+            Some(headers) => Some(ClientHint::from_headers(headers)?),
+            None => None,
+        };
+        self.parse_client_hints(session, ua, client_hints)
+    }
+
+    pub fn parse_client_hints(
+        &self,
+        session: K,
+        ua: &str,
+        client_hints: Option<ClientHint>,
+    ) -> Result<Detection> {
+        let key = CacheKey::new(ua, client_hints.as_ref());
+        let db = self.detector.database();
+        {
+            let mut sessions = self.sessions.lock().unwrap();
+            if sessions.db.as_ptr() != Arc::as_ptr(&db) {
+                sessions.db = Arc::downgrade(&db);
+                sessions.entries.clear();
+            }
+            if let Some((_, detection)) = sessions.entries.get(&session).filter(|x| x.0 == key) {
+                self.reused.fetch_add(1, Ordering::Relaxed);
+                return Ok(detection.clone());
+            }
+        }
+
+        // not locked while detecting, so that other sessions aren't held up
+        let detection = self.detector.parse_client_hints(ua, client_hints)?;
+        if self.capacity == 0 || detection.approximate() {
+            return Ok(detection);
+        }
+
+        let mut sessions = self.sessions.lock().unwrap();
+        // unless reloaded in the meantime
+        if sessions.db.as_ptr() == Arc::as_ptr(&db) {
+            if sessions.entries.len() >= self.capacity && !sessions.entries.contains_key(&session) {
+                sessions.entries.clear();
+            }
+            sessions.entries.insert(session, (key, detection.clone()));
+        }
+        Ok(detection)
+    }
+
+    /// Forgets `session`, such as once its connection is closed.
+    pub fn end_session(&self, session: &K) {
+        self.sessions.lock().unwrap().entries.remove(session);
+    }
+}
@@ -0,0 +1,95 @@
+//! Ed25519 signatures over database bundles, so that deployments can check
+//! the detection data they load is what its publisher signed, and wasn't
+//! tampered with on disk or on its way over the network.
+//!
+//! The publisher signs the bundle made by [`crate::database::bundle_dir`]
+//! with a secret seed, and ships the signature alongside it:
+//!
+//! ```ignore
+//! let bundle = database::bundle_dir("regexes")?;
+//! std::fs::write("regexes.json", &bundle)?;
+//! std::fs::write("regexes.json.sig", BundleSigner::from_seed(seed).sign(&bundle))?;
+//! ```
+//!
+//! Deployments only need the public key, see [`BundleSigner::key`]:
+//!
+//! ```ignore
+//! let key = BundleKey::from_hex(PUBLISHER_KEY)?;
+//! let db = Database::from_signed_bundle(&std::fs::read("regexes.json")?, &std::fs::read("regexes.json.sig")?, &key)?;
+//! ```
+//!
+//! A directory laid out like `regexes/` can be checked against a signature of
+//! its bundle with [`Database::from_signed_dir`], and a bundle fetched over
+//! http with `remote::fetch_signed_database`.
+
+use anyhow::{anyhow, Result};
+use ed25519_compact::{KeyPair, PublicKey, Seed, Signature};
+
+use std::fmt::Write;
+
+#[cfg(doc)]
+use crate::database::Database;
+
+/// The public key bundles are checked against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BundleKey(PublicKey);
+
+impl BundleKey {
+    /// The key from its 32 raw bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let key = PublicKey::from_slice(bytes).map_err(|e| anyhow!("invalid public key: {}", e))?;
+        Ok(Self(key))
+    }
+
+    /// The key from its 64 hex digits, as printed by [`Self::to_hex`].
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let hex = hex.trim();
+        if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+            return Err(anyhow!("invalid public key: not hex encoded"));
+        }
+
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| anyhow!("invalid public key: not hex encoded"))?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn to_hex(&self) -> String {
+        let mut res = String::with_capacity(64);
+        for byte in self.0.as_ref() {
+            write!(res, "{:02x}", byte).expect("write to string");
+        }
+        res
+    }
+
+    /// Fails unless `signature` is this key's signature of `bundle`.
+    pub fn verify(&self, bundle: &[u8], signature: &[u8]) -> Result<()> {
+        let signature = Signature::from_slice(signature)
+            .map_err(|e| anyhow!("invalid bundle signature: {}", e))?;
+        self.0
+            .verify(bundle, &signature)
+            .map_err(|_| anyhow!("the database bundle doesn't match its signature"))
+    }
+}
+
+/// Signs bundles, for publishers of a database.
+pub struct BundleSigner(KeyPair);
+
+impl BundleSigner {
+    /// The signer for `seed`, which must be random and kept secret.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self(KeyPair::from_seed(Seed::new(seed)))
+    }
+
+    /// The key to check this signer's signatures with.
+    pub fn key(&self) -> BundleKey {
+        BundleKey(self.0.pk)
+    }
+
+    /// The 64 byte signature of `bundle`.
+    pub fn sign(&self, bundle: &[u8]) -> Vec<u8> {
+        self.0.sk.sign(bundle, None).to_vec()
+    }
+}
@@ -0,0 +1,126 @@
+//! Detecting with the regex database as it was at some point, so that
+//! reprocessing old logs detects them as they were detected at the time, and
+//! detections made again later come out the same:
+//!
+//! ```ignore
+//! let snapshots = Snapshots::from_dir("snapshots/")?;
+//! let detector = snapshots.detector_at("2023-06-15")?;
+//! let detection = detector.parse(ua, None)?;
+//! assert_eq!(detection.snapshot(), Some("2023-06-01"));
+//! ```
+//!
+//! Each snapshot in the directory is either a directory laid out like
+//! `regexes/` or a bundle made by [`crate::database::bundle_dir`] with a
+//! `.json` extension, named by its id. Ids are compared as strings, so dates
+//! in `YYYY-MM-DD` form, or anything else which sorts the same way, select
+//! the latest snapshot taken by a date. Each database is loaded once, the
+//! first time a detector needs it, and shared by every detector pinned to
+//! it.
+//!
+//! Pinned detectors tag their detections with the id of their snapshot, see
+//! [`DeviceDetector::with_snapshot`].
+
+use anyhow::{anyhow, Context, Result};
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::database::Database;
+use crate::device_detector::DeviceDetector;
+
+#[derive(Clone, Debug)]
+enum Source {
+    Dir(PathBuf),
+    Bundle(PathBuf),
+}
+
+/// The database snapshots in a directory.
+pub struct Snapshots {
+    sources: BTreeMap<String, Source>,
+    loaded: Mutex<HashMap<String, Arc<Database>>>,
+}
+
+impl Snapshots {
+    /// The snapshots in `dir`, which are only loaded once needed.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut sources = BTreeMap::new();
+
+        let entries =
+            std::fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            let source = if path.is_dir() {
+                Source::Dir(path.clone())
+            } else if path.extension().is_some_and(|x| x == "json") {
+                Source::Bundle(path.clone())
+            } else {
+                continue;
+            };
+
+            let Some(id) = path.file_stem().and_then(|x| x.to_str()) else {
+                continue;
+            };
+            if sources.insert(id.to_owned(), source).is_some() {
+                return Err(anyhow!("snapshot {} is both a directory and a bundle", id));
+            }
+        }
+
+        Ok(Self {
+            sources,
+            loaded: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// The ids of the snapshots, oldest first.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.sources.keys().map(String::as_str)
+    }
+
+    /// The id of the latest snapshot taken by `at`, if any was.
+    pub fn select(&self, at: &str) -> Option<&str> {
+        self.sources
+            .range::<str, _>((Bound::Unbounded, Bound::Included(at)))
+            .next_back()
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// The database of snapshot `id`.
+    pub fn database(&self, id: &str) -> Result<Arc<Database>> {
+        let source = self
+            .sources
+            .get(id)
+            .ok_or_else(|| anyhow!("no snapshot {}", id))?;
+
+        if let Some(db) = self.loaded.lock().unwrap().get(id) {
+            return Ok(db.clone());
+        }
+
+        // loaded without the lock, a database takes a while to load
+        let db = match source {
+            Source::Dir(path) => Database::from_dir(path),
+            Source::Bundle(path) => std::fs::read(path)
+                .with_context(|| format!("reading {}", path.display()))
+                .and_then(|x| Database::from_bundle(&x)),
+        };
+        let db = Arc::new(db.with_context(|| format!("loading snapshot {}", id))?);
+
+        let mut loaded = self.loaded.lock().unwrap();
+        Ok(loaded.entry(id.to_owned()).or_insert(db).clone())
+    }
+
+    /// A detector pinned to snapshot `id`.
+    pub fn detector(&self, id: &str) -> Result<DeviceDetector> {
+        Ok(DeviceDetector::new_with_database(self.database(id)?).with_snapshot(id))
+    }
+
+    /// A detector pinned to the latest snapshot taken by `at`.
+    pub fn detector_at(&self, at: &str) -> Result<DeviceDetector> {
+        let id = self
+            .select(at)
+            .ok_or_else(|| anyhow!("no snapshot was taken by {}", at))?;
+        self.detector(id)
+    }
+}
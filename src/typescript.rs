@@ -0,0 +1,362 @@
+//! TypeScript definitions of the JSON detections are served as, by the http
+//! server and by [`Detection::to_value`], for frontend and node consumers:
+//!
+//! ```sh
+//! rust-device-detector typescript > detection.d.ts
+//! ```
+//!
+//! The definitions are built from [`declarations`], whose string literal
+//! unions are the names serde gives the enums, so they can't drift from what
+//! is serialized. A copy is kept in `includes/detection.d.ts`, next to the C
+//! header.
+//!
+//! [`Detection::to_value`]: crate::device_detector::Detection::to_value
+
+use serde::Serialize;
+
+use std::fmt::Write;
+
+use crate::bot_spoof::SpoofSignal;
+use crate::parsers::client::ClientType;
+use crate::parsers::device::{Confidence, DeviceType};
+use crate::resilience::Stage;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TsType {
+    String,
+    Number,
+    Boolean,
+    Null,
+    /// A string literal type, such as `"browser"`.
+    Literal(String),
+    /// A type declared in [`declarations`].
+    Named(&'static str),
+    Array(Box<TsType>),
+    Union(Vec<TsType>),
+}
+
+impl TsType {
+    fn nullable(self) -> Self {
+        TsType::Union(vec![self, TsType::Null])
+    }
+
+    fn render(&self) -> String {
+        match self {
+            TsType::String => "string".to_owned(),
+            TsType::Number => "number".to_owned(),
+            TsType::Boolean => "boolean".to_owned(),
+            TsType::Null => "null".to_owned(),
+            TsType::Literal(x) => format!("{:?}", x),
+            TsType::Named(x) => (*x).to_owned(),
+            TsType::Array(x) => match **x {
+                TsType::Union(_) => format!("({})[]", x.render()),
+                _ => format!("{}[]", x.render()),
+            },
+            TsType::Union(x) => x.iter().map(TsType::render).collect::<Vec<_>>().join(" | "),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    pub ty: TsType,
+    /// Left out of the json rather than `null` when unset.
+    pub optional: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Declaration {
+    Interface {
+        name: &'static str,
+        fields: Vec<Field>,
+    },
+    Alias {
+        name: &'static str,
+        ty: TsType,
+    },
+}
+
+impl Declaration {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Declaration::Interface { name, .. } | Declaration::Alias { name, .. } => name,
+        }
+    }
+}
+
+fn field(name: &'static str, ty: TsType) -> Field {
+    Field {
+        name,
+        ty,
+        optional: false,
+    }
+}
+
+fn optional(name: &'static str, ty: TsType) -> Field {
+    Field {
+        name,
+        ty,
+        optional: true,
+    }
+}
+
+fn nullable_string(name: &'static str) -> Field {
+    field(name, TsType::String.nullable())
+}
+
+/// The union of the names serde gives `values`.
+fn literals<T: Serialize>(values: &[T]) -> TsType {
+    TsType::Union(
+        values
+            .iter()
+            .map(|x| {
+                let value = serde_json::to_value(x).expect("enums serialize");
+                TsType::Literal(value.as_str().expect("unit variants").to_owned())
+            })
+            .collect(),
+    )
+}
+
+/// The flags of the `is` block of known devices.
+const IS_FLAGS: [&str; 24] = [
+    "touch_enabled",
+    "mobile",
+    "browser",
+    "feed_reader",
+    "library",
+    "media_player",
+    "mobile_app",
+    "pim",
+    "desktop",
+    "notebook",
+    "smart_phone",
+    "feature_phone",
+    "camera",
+    "car_browser",
+    "console",
+    "portable_media_player",
+    "television",
+    "smart_display",
+    "tablet",
+    "smart_speaker",
+    "peripheral",
+    "wearable",
+    "phablet",
+    "robot",
+];
+
+/// The types of the json of a detection, `Detection` being that of a whole
+/// one.
+pub fn declarations() -> Vec<Declaration> {
+    use TsType::*;
+
+    vec![
+        Declaration::Alias {
+            name: "Detection",
+            ty: Union(vec![Named("KnownDetection"), Named("BotDetection")]),
+        },
+        Declaration::Interface {
+            name: "KnownDetection",
+            fields: vec![
+                field("client", Named("Client").nullable()),
+                field("device", Named("Device").nullable()),
+                field("os", Named("Os").nullable()),
+                optional("stage_errors", Array(Box::new(Named("StageError")))),
+                optional("snapshot", String),
+                optional("hints_used", Array(Box::new(String))),
+                optional("user_agent_decoded", Boolean),
+                optional("approximate", Boolean),
+                field("is", Named("DeviceFlags")),
+            ],
+        },
+        Declaration::Interface {
+            name: "BotDetection",
+            fields: vec![field("bot", Named("Bot"))],
+        },
+        Declaration::Interface {
+            name: "Client",
+            fields: vec![
+                field("name", String),
+                nullable_string("version"),
+                field("type", Named("ClientType")),
+                nullable_string("short_code"),
+                nullable_string("engine"),
+                nullable_string("engine_version"),
+            ],
+        },
+        Declaration::Alias {
+            name: "ClientType",
+            ty: literals(&[
+                ClientType::Browser,
+                ClientType::FeedReader,
+                ClientType::MobileApp,
+                ClientType::Pim,
+                ClientType::Library,
+                ClientType::MediaPlayer,
+                ClientType::DownloadManager,
+            ]),
+        },
+        Declaration::Interface {
+            name: "Device",
+            fields: vec![
+                field("type", Named("DeviceType").nullable()),
+                nullable_string("brand"),
+                nullable_string("model"),
+                optional("mobile_client_hint", Boolean),
+                optional("touch_enabled", Boolean),
+                optional("confidence", Named("DeviceConfidence")),
+                optional("spec", Named("DeviceSpec")),
+                optional("hbbtv", Named("HbbTv")),
+            ],
+        },
+        Declaration::Alias {
+            name: "DeviceType",
+            ty: literals(&[
+                DeviceType::Desktop,
+                DeviceType::SmartPhone,
+                DeviceType::FeaturePhone,
+                DeviceType::Tablet,
+                DeviceType::Phablet,
+                DeviceType::Console,
+                DeviceType::PortableMediaPlayer,
+                DeviceType::CarBrowser,
+                DeviceType::Television,
+                DeviceType::SmartDisplay,
+                DeviceType::SmartSpeaker,
+                DeviceType::Camera,
+                DeviceType::Notebook,
+                DeviceType::Wearable,
+                DeviceType::Peripheral,
+            ]),
+        },
+        Declaration::Interface {
+            name: "DeviceConfidence",
+            fields: vec![
+                field("type", Named("Confidence").nullable()),
+                field("brand", Named("Confidence").nullable()),
+                field("model", Named("Confidence").nullable()),
+            ],
+        },
+        Declaration::Alias {
+            name: "Confidence",
+            ty: literals(&[
+                Confidence::Exact,
+                Confidence::Inferred,
+                Confidence::Fallback,
+            ]),
+        },
+        Declaration::Interface {
+            name: "DeviceSpec",
+            fields: vec![
+                field("screen_size", Number.nullable()),
+                field("ram_mb", Number.nullable()),
+                field("release_year", Number.nullable()),
+            ],
+        },
+        Declaration::Interface {
+            name: "HbbTv",
+            fields: vec![
+                field("version", String),
+                field("capabilities", Array(Box::new(String))),
+                nullable_string("vendor"),
+                nullable_string("model"),
+                nullable_string("software_version"),
+                nullable_string("hardware_version"),
+                nullable_string("family"),
+                field("operator", Named("TvOperator").nullable()),
+            ],
+        },
+        Declaration::Interface {
+            name: "TvOperator",
+            fields: vec![field("name", String), field("country", String)],
+        },
+        Declaration::Interface {
+            name: "Os",
+            fields: vec![
+                field("name", String),
+                nullable_string("version"),
+                nullable_string("platform"),
+                nullable_string("family"),
+            ],
+        },
+        Declaration::Interface {
+            name: "StageError",
+            fields: vec![field("stage", Named("Stage")), field("message", String)],
+        },
+        Declaration::Alias {
+            name: "Stage",
+            ty: literals(&[Stage::Bot, Stage::Os, Stage::Client, Stage::Device]),
+        },
+        Declaration::Interface {
+            name: "DeviceFlags",
+            fields: IS_FLAGS.iter().map(|x| field(x, Boolean)).collect(),
+        },
+        Declaration::Interface {
+            name: "Bot",
+            fields: vec![
+                field("name", String),
+                nullable_string("category"),
+                nullable_string("url"),
+                field("producer", Named("BotProducer").nullable()),
+                optional("bot_spoof_suspected", Boolean),
+                optional("spoof_signals", Array(Box::new(Named("SpoofSignal")))),
+                optional("claimed", Named("BotClaims")),
+                optional("snapshot", String),
+                optional("user_agent_decoded", Boolean),
+                optional("approximate", Boolean),
+            ],
+        },
+        Declaration::Interface {
+            name: "BotClaims",
+            fields: vec![
+                field("client", Named("Client").nullable()),
+                field("device", Named("Device").nullable()),
+                field("os", Named("Os").nullable()),
+                optional("stage_errors", Array(Box::new(Named("StageError")))),
+                optional("hints_used", Array(Box::new(String))),
+                optional("user_agent_decoded", Boolean),
+                optional("approximate", Boolean),
+            ],
+        },
+        Declaration::Interface {
+            name: "BotProducer",
+            fields: vec![nullable_string("name"), nullable_string("url")],
+        },
+        Declaration::Alias {
+            name: "SpoofSignal",
+            ty: literals(&[
+                SpoofSignal::BrowserBrands,
+                SpoofSignal::DeviceModel,
+                SpoofSignal::FormFactors,
+            ]),
+        },
+    ]
+}
+
+/// The `.d.ts` of [`declarations`].
+pub fn definitions() -> String {
+    let mut res = String::from(
+        "// Generated by rust-device-detector, the json of a detection.\n\
+         // Regenerate with `rust-device-detector typescript`.\n",
+    );
+
+    for declaration in declarations() {
+        res.push('\n');
+        match declaration {
+            Declaration::Interface { name, fields } => {
+                let _ = writeln!(res, "export interface {} {{", name);
+                for field in fields {
+                    let optional = if field.optional { "?" } else { "" };
+                    let _ = writeln!(res, "  {}{}: {};", field.name, optional, field.ty.render());
+                }
+                res.push_str("}\n");
+            }
+            Declaration::Alias { name, ty } => {
+                let _ = writeln!(res, "export type {} = {};", name, ty.render());
+            }
+        }
+    }
+
+    res
+}
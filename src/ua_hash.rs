@@ -0,0 +1,115 @@
+//! Privacy preserving logging of user agents. Rather than the raw user agent,
+//! which is close to identifying on its own, a keyed hash of it is logged
+//! along with the coarse detection, which is enough to correlate requests of
+//! the same client without being able to recover the user agent.
+//!
+//! ```ignore
+//! let hasher = UaHasher::new(secret).with_rotation(Duration::from_secs(24 * 60 * 60));
+//!
+//! let detection = detector.parse(ua, headers)?;
+//! log::info!("{}", serde_json::to_string(&hasher.hash(ua, &detection))?);
+//! ```
+//!
+//! The hash is an HMAC-SHA256, so it can't be brute forced over known user
+//! agents without the key. With a rotation period, the period is hashed too,
+//! so the same user agent hashes differently from one period to the next and
+//! clients can only be followed within a period.
+
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+
+use std::fmt::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::device_detector::Detection;
+
+#[derive(Clone)]
+pub struct UaHasher {
+    key: Vec<u8>,
+    rotation: Option<Duration>,
+}
+
+/// A user agent as logged instead of the raw string.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct UaHash {
+    /// Hex encoded HMAC-SHA256 of the user agent.
+    pub hash: String,
+    /// Which rotation period the hash belongs to, counted from the unix
+    /// epoch, or 0 without rotation. Hashes of different periods can't be
+    /// compared.
+    pub period: u64,
+    pub is_bot: bool,
+    /// The bot or client name, without a version.
+    pub name: Option<String>,
+    pub os: Option<String>,
+    /// Such as `smartphone` or `desktop`.
+    pub device_type: Option<String>,
+}
+
+impl UaHasher {
+    /// Hashes with `key`, which should be random and kept secret.
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            key: key.into(),
+            rotation: None,
+        }
+    }
+
+    /// Changes the hashes every `period`, such as every day, starting from
+    /// the unix epoch. A zero period is ignored.
+    pub fn with_rotation(mut self, period: Duration) -> Self {
+        self.rotation = Some(period).filter(|x| !x.is_zero());
+        self
+    }
+
+    /// The rotation period `time` is in.
+    pub fn period(&self, time: SystemTime) -> u64 {
+        let Some(rotation) = self.rotation else {
+            return 0;
+        };
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        (since_epoch.as_nanos() / rotation.as_nanos()) as u64
+    }
+
+    /// Hashes `ua`, detected as `detection`, for the current period.
+    pub fn hash(&self, ua: &str, detection: &Detection) -> UaHash {
+        self.hash_at(ua, detection, SystemTime::now())
+    }
+
+    /// Hashes `ua`, detected as `detection`, for the period `time` is in.
+    pub fn hash_at(&self, ua: &str, detection: &Detection, time: SystemTime) -> UaHash {
+        let period = self.period(time);
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("hmac takes any key");
+        mac.update(&period.to_be_bytes());
+        mac.update(ua.as_bytes());
+
+        let mut hash = String::with_capacity(64);
+        for byte in mac.finalize().into_bytes() {
+            write!(hash, "{:02x}", byte).expect("write to string");
+        }
+
+        let (name, os, device_type) = match detection {
+            Detection::Bot(bot) => (Some(bot.name.clone()), None, None),
+            Detection::Known(known) => (
+                known.client.as_ref().map(|x| x.name.clone()),
+                known.os.as_ref().map(|x| x.name.clone()),
+                known
+                    .device
+                    .as_ref()
+                    .and_then(|x| x.device_type.as_ref())
+                    .map(|x| x.as_str().to_owned()),
+            ),
+        };
+
+        UaHash {
+            hash,
+            period,
+            is_bot: detection.is_bot(),
+            name,
+            os,
+            device_type,
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Reporting of user agents which could only be partially classified, so that
+//! they can be collected and contributed upstream.
+//!
+//! ```ignore
+//! let detector = DeviceDetector::new().on_unknown(10, Duration::from_secs(60), |ua, hints, known| {
+//!     log::info!("unknown: {} {:?} {:?}", ua, hints, known);
+//! });
+//! ```
+//!
+//! Reports are rate limited, so that a flood of unusual traffic can't turn
+//! into a flood of reports. With the cache enabled, only parses which miss the
+//! cache are reported.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::client_hints::ClientHint;
+use crate::device_detector::KnownDevice;
+use crate::rate::RateWindow;
+
+pub type UnknownCallback = dyn Fn(&str, Option<&ClientHint>, &KnownDevice) + Send + Sync;
+
+/// Whether the client, the os or the device type is missing.
+pub fn is_incomplete(known: &KnownDevice) -> bool {
+    known.client.is_none()
+        || known.os.is_none()
+        || known
+            .device
+            .as_ref()
+            .and_then(|x| x.device_type.as_ref())
+            .is_none()
+}
+
+/// Calls a callback for incomplete detections, at most `limit` times per
+/// `interval`.
+pub(crate) struct UnknownReporter {
+    callback: Box<UnknownCallback>,
+    limit: u32,
+    window: RateWindow,
+    dropped: AtomicU64,
+}
+
+impl UnknownReporter {
+    pub(crate) fn new(limit: u32, interval: Duration, callback: Box<UnknownCallback>) -> Arc<Self> {
+        Arc::new(Self {
+            callback,
+            limit,
+            window: RateWindow::new(interval),
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    pub(crate) fn report(&self, ua: &str, client_hints: Option<&ClientHint>, known: &KnownDevice) {
+        if !is_incomplete(known) {
+            return;
+        }
+
+        if self.window.hit() <= self.limit {
+            (self.callback)(ua, client_hints, known);
+        } else {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Number of reports skipped by the rate limit so far.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
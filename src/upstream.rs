@@ -0,0 +1,186 @@
+//! Updating `data/regexes/` from the matomo device detector repository, and
+//! checking that the detector can use what upstream has before it does:
+//!
+//! ```shell
+//! > cargo run -p device-detector-server --features upstream-sync -- sync-upstream
+//! ```
+//!
+//! [`sync`] resolves a branch or tag to its commit, downloads each of
+//! [`FILES`] at that commit and validates them, see
+//! [`crate::validation`], including that every regex compiles with
+//! fancy_regex, then loads them as a [`Database`]. The files are only written
+//! when all of that succeeds. Fetching is left to the caller, as in
+//! [`crate::remote`], so that the library doesn't depend on an http client.
+//!
+//! Templates referring to groups their regex doesn't have are warnings
+//! rather than errors, as upstream relies on a few of them. Files upstream
+//! has which aren't part of the database, such as a new device list, are
+//! reported too, as the detector needs code to use them.
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::database::{Database, FILES};
+use crate::validation::{
+    unknown_keys, validate_regex_str, RegexFileKind, UnknownKey, ValidationError,
+    ValidationErrorKind,
+};
+
+/// The upstream repository on github.
+pub const REPOSITORY: &str = "matomo-org/device-detector";
+
+/// The url of the github api giving the commit `git_ref` is at.
+pub fn commit_url(git_ref: &str) -> String {
+    format!(
+        "https://api.github.com/repos/{}/commits/{}",
+        REPOSITORY, git_ref
+    )
+}
+
+/// The url of the github api listing the files of `commit`.
+pub fn tree_url(commit: &str) -> String {
+    format!(
+        "https://api.github.com/repos/{}/git/trees/{}?recursive=1",
+        REPOSITORY, commit
+    )
+}
+
+/// The url of `file`, one of [`FILES`], at `commit`.
+pub fn file_url(commit: &str, file: &str) -> String {
+    format!(
+        "https://raw.githubusercontent.com/{}/{}/regexes/{}",
+        REPOSITORY, commit, file
+    )
+}
+
+/// What [`sync`] found.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// The upstream commit the files are those of.
+    pub commit: String,
+    /// Files which differ from those in the directory.
+    pub changed: Vec<&'static str>,
+    /// Files under `regexes/` upstream which aren't part of the database.
+    pub new_files: Vec<String>,
+    /// Problems which keep the detector from using a file, by file.
+    pub errors: BTreeMap<&'static str, Vec<ValidationError>>,
+    /// Problems the detector works around, by file.
+    pub warnings: BTreeMap<&'static str, Vec<ValidationError>>,
+    /// Keys upstream has added which the detector ignores, by file.
+    pub unknown_keys: BTreeMap<&'static str, Vec<UnknownKey>>,
+    /// Why the files failed to load as a database, if they did.
+    pub load_error: Option<String>,
+    /// Whether the files were written to the directory.
+    pub written: bool,
+}
+
+impl SyncReport {
+    /// Whether the detector can use the files as they are.
+    pub fn is_compatible(&self) -> bool {
+        self.errors.is_empty() && self.load_error.is_none()
+    }
+}
+
+fn fetch_json<F>(fetch: &mut F, url: &str) -> Result<Value>
+where
+    F: FnMut(&str) -> Result<String>,
+{
+    let body = fetch(url).with_context(|| format!("fetching {}", url))?;
+    serde_json::from_str(&body).with_context(|| format!("invalid json from {}", url))
+}
+
+/// Downloads the database files upstream has at `git_ref`, a branch, tag or
+/// commit, with `fetch`, and checks them against the detector. They are
+/// written to `dir`, laid out like `regexes/`, if `write` and they are
+/// compatible. See [`crate::upstream`].
+pub fn sync<F>(git_ref: &str, dir: &Path, write: bool, mut fetch: F) -> Result<SyncReport>
+where
+    F: FnMut(&str) -> Result<String>,
+{
+    let commit = fetch_json(&mut fetch, &commit_url(git_ref))?["sha"]
+        .as_str()
+        .ok_or_else(|| anyhow!("no commit for {}", git_ref))?
+        .to_owned();
+
+    let tree = fetch_json(&mut fetch, &tree_url(&commit))?;
+    let new_files = tree["tree"]
+        .as_array()
+        .ok_or_else(|| anyhow!("no files at {}", commit))?
+        .iter()
+        .filter_map(|x| x["path"].as_str()?.strip_prefix("regexes/"))
+        .filter(|x| x.ends_with(".yml") && !FILES.contains(x))
+        .map(str::to_owned)
+        .collect();
+
+    let mut files = BTreeMap::new();
+    for file in FILES {
+        let url = file_url(&commit, file);
+        let contents = fetch(&url).with_context(|| format!("fetching {}", url))?;
+        files.insert(*file, contents);
+    }
+
+    let mut report = check(&files);
+    report.commit = commit;
+    report.new_files = new_files;
+    report.changed = files
+        .iter()
+        .filter(|(file, contents)| {
+            std::fs::read_to_string(dir.join(file)).ok().as_ref() != Some(*contents)
+        })
+        .map(|(file, _)| *file)
+        .collect();
+
+    if write && report.is_compatible() {
+        for file in &report.changed {
+            let path = dir.join(file);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, &files[file])
+                .with_context(|| format!("writing {}", path.display()))?;
+        }
+        report.written = true;
+    }
+
+    Ok(report)
+}
+
+/// Validates `files`, each of [`FILES`] by its path, and loads them as a
+/// database.
+pub fn check(files: &BTreeMap<&'static str, String>) -> SyncReport {
+    let mut report = SyncReport::default();
+
+    for (file, contents) in files {
+        let Some(kind) = RegexFileKind::for_path(file) else {
+            continue;
+        };
+        let (warnings, errors): (Vec<_>, Vec<_>) = validate_regex_str(contents, kind)
+            .into_iter()
+            .partition(|x| matches!(x.kind, ValidationErrorKind::CaptureGroupOutOfRange { .. }));
+        if !errors.is_empty() {
+            report.errors.insert(file, errors);
+        }
+        if !warnings.is_empty() {
+            report.warnings.insert(file, warnings);
+        }
+        let unknown = unknown_keys(contents, kind);
+        if !unknown.is_empty() {
+            report.unknown_keys.insert(file, unknown);
+        }
+    }
+
+    let db = Database::load(|path: &str| {
+        files
+            .get(path)
+            .map(|x| x.as_str().into())
+            .ok_or_else(|| anyhow!("{} is missing", path))
+    });
+    if let Err(err) = db {
+        report.load_error = Some(format!("{:#}", err));
+    }
+
+    report
+}
@@ -0,0 +1,133 @@
+//! Version 1 of the stable result schema, for storing detections somewhere
+//! which outlives the crate version, such as a data warehouse.
+//!
+//! The internal types, such as [`crate::device_detector::Detection`], change
+//! as the engine does. The types in this module never will once published: no
+//! field is added, removed, renamed or changes type. Anything new goes into a
+//! new version module instead, leaving this one as it is.
+//!
+//! Types such as the client and device type are strings rather than enums, so
+//! that new values, which upstream adds from time to time, don't change the
+//! shape either. Missing values are `null` rather than empty strings.
+//!
+//! ```ignore
+//! let detection = detector.parse(ua, headers)?;
+//! let row = serde_json::to_string(&rust_device_detector::v1::Detection::from(&detection))?;
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::device_detector;
+
+/// The value of [`Detection::schema_version`].
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Detection {
+    /// Always [`SCHEMA_VERSION`].
+    pub schema_version: u32,
+    pub is_bot: bool,
+    /// Set only for bots, in which case the other sections are `null`.
+    pub bot: Option<Bot>,
+    pub client: Option<Client>,
+    pub os: Option<Os>,
+    pub device: Option<Device>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bot {
+    pub name: String,
+    /// Such as `Search bot` or `Crawler`.
+    pub category: Option<String>,
+    pub url: Option<String>,
+    pub producer_name: Option<String>,
+    pub producer_url: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Client {
+    /// One of `browser`, `feed reader`, `mobile app`, `pim`, `library` or
+    /// `mediaplayer`, so far.
+    #[serde(rename = "type")]
+    pub client_type: String,
+    pub name: String,
+    pub version: Option<String>,
+    /// Browsers only.
+    pub short_code: Option<String>,
+    /// Browsers only.
+    pub engine: Option<String>,
+    pub engine_version: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Os {
+    pub name: String,
+    pub version: Option<String>,
+    /// Such as `x64` or `ARM`.
+    pub platform: Option<String>,
+    pub family: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Device {
+    /// Such as `smartphone`, `tablet` or `desktop`.
+    #[serde(rename = "type")]
+    pub device_type: Option<String>,
+    pub brand: Option<String>,
+    pub model: Option<String>,
+    pub is_mobile: bool,
+    pub is_touch_enabled: bool,
+}
+
+impl From<&device_detector::Detection> for Detection {
+    fn from(detection: &device_detector::Detection) -> Self {
+        match detection {
+            device_detector::Detection::Bot(bot) => Detection {
+                schema_version: SCHEMA_VERSION,
+                is_bot: true,
+                bot: Some(Bot {
+                    name: bot.name.clone(),
+                    category: bot.category.clone(),
+                    url: bot.url.clone(),
+                    producer_name: bot.producer.as_ref().and_then(|x| x.name.clone()),
+                    producer_url: bot.producer.as_ref().and_then(|x| x.url.clone()),
+                }),
+                client: None,
+                os: None,
+                device: None,
+            },
+            device_detector::Detection::Known(known) => Detection {
+                schema_version: SCHEMA_VERSION,
+                is_bot: false,
+                bot: None,
+                client: known.client.as_ref().map(|x| Client {
+                    client_type: x.r#type.as_str().to_owned(),
+                    name: x.name.clone(),
+                    version: x.version.clone(),
+                    short_code: x.short_code.clone(),
+                    engine: x.engine.clone(),
+                    engine_version: x.engine_version.clone(),
+                }),
+                os: known.os.as_ref().map(|x| Os {
+                    name: x.name.clone(),
+                    version: x.version.clone(),
+                    platform: x.platform.clone(),
+                    family: x.family.clone(),
+                }),
+                // the device section is always present, as is_mobile and
+                // is_touch_enabled are known even for unknown devices
+                device: Some(Device {
+                    device_type: known
+                        .device
+                        .as_ref()
+                        .and_then(|x| x.device_type.as_ref())
+                        .map(|x| x.as_str().to_owned()),
+                    brand: known.device.as_ref().and_then(|x| x.brand.clone()),
+                    model: known.device.as_ref().and_then(|x| x.model.clone()),
+                    is_mobile: known.is_mobile(),
+                    is_touch_enabled: known.is_touch_enabled(),
+                }),
+            },
+        }
+    }
+}
@@ -0,0 +1,677 @@
+//! Validation of regex yml files, for those maintaining their own copies or
+//! overlays of the matomo regex database.
+//!
+//! The detector itself will happily load a file that is missing keys or that
+//! references capture groups that don't exist, and then quietly produce empty
+//! names and versions at runtime. This catches those problems up front and
+//! reports where in the file they are.
+
+use anyhow::Result;
+
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+use std::fmt;
+use std::path::Path;
+
+use crate::parsers::utils::{user_agent_pattern, SafeRegex};
+
+/// What to do at parse time when a name, version or model template refers to a
+/// capture group its regex doesn't have. These always expand to nothing, which
+/// upstream occasionally relies upon, so by default nothing is done about it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TemplateExpansion {
+    /// Silently expand missing groups to nothing, as the php library does.
+    #[default]
+    Lenient,
+    /// Expand to nothing, but print a warning to stderr, once per regex and
+    /// template.
+    Warn,
+    /// Fail the parse with an error.
+    Strict,
+}
+
+/// The layout of a regex yml file, which determines which keys are required.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegexFileKind {
+    /// `bots.yml`
+    Bots,
+    /// `oss.yml`
+    OperatingSystems,
+    /// `client/browsers.yml`
+    Browsers,
+    /// `client/browser_engine.yml`
+    BrowserEngines,
+    /// `client/feed_readers.yml`, `libraries.yml`, `mediaplayers.yml`,
+    /// `mobile_apps.yml` and `pim.yml`
+    Clients,
+    /// any of the `device/*.yml` files
+    Devices,
+    /// `vendorfragments.yml`
+    VendorFragments,
+    /// `client/hints/*.yml`
+    Hints,
+}
+
+impl RegexFileKind {
+    /// The kind of a file in the database, from its path relative to the
+    /// database directory, such as `client/browsers.yml`.
+    pub fn for_path(path: &str) -> Option<Self> {
+        let res = match path {
+            "bots.yml" => Self::Bots,
+            "oss.yml" => Self::OperatingSystems,
+            "vendorfragments.yml" => Self::VendorFragments,
+            "client/browsers.yml" => Self::Browsers,
+            "client/browser_engine.yml" => Self::BrowserEngines,
+            _ if path.starts_with("client/hints/") => Self::Hints,
+            _ if path.starts_with("client/") => Self::Clients,
+            _ if path.starts_with("device/") => Self::Devices,
+            _ => return None,
+        };
+        Some(res)
+    }
+
+    /// The keys entries may have, `.` separating those of nested entries.
+    /// Some, such as the `url` of clients, aren't used but are known.
+    fn known_keys(self) -> &'static [&'static str] {
+        match self {
+            Self::Bots => &["regex", "name", "url", "category", "producer"],
+            Self::OperatingSystems => &["regex", "name", "version", "versions"],
+            Self::Browsers => &["regex", "name", "version", "engine"],
+            Self::BrowserEngines => &["regex", "name"],
+            Self::Clients => &["regex", "name", "version", "url"],
+            Self::Devices => &["regex", "device", "model", "models"],
+            Self::VendorFragments | Self::Hints => &[],
+        }
+    }
+
+    fn known_nested_keys(self, key: &str) -> Option<&'static [&'static str]> {
+        match (self, key) {
+            (Self::Bots, "producer") => Some(&["name", "url"]),
+            (Self::OperatingSystems, "versions") => Some(&["regex", "version"]),
+            (Self::Browsers, "engine") => Some(&["default", "versions"]),
+            (Self::Devices, "model" | "models") => Some(&["regex", "device", "model", "brand"]),
+            _ => None,
+        }
+    }
+}
+
+/// A key in a regex yml file which the detector doesn't know about, and so
+/// ignores. Usually an attribute upstream has added before this crate
+/// supports it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct UnknownKey {
+    /// The key, `.` separating those of nested entries, such as `models.foo`.
+    pub key: String,
+    /// Number of entries which have it.
+    pub count: usize,
+    /// 1 based line number of the first entry which has it, if it could be
+    /// determined.
+    pub line: Option<usize>,
+}
+
+/// The keys in a regex yml file which the detector ignores, in the order
+/// they first appear. Files which aren't valid yaml have none, see
+/// [`validate_regex_str`] for those.
+pub fn unknown_keys(contents: &str, kind: RegexFileKind) -> Vec<UnknownKey> {
+    let Ok(value) = serde_yaml::from_str::<Value>(contents) else {
+        return Vec::new();
+    };
+
+    let entries: Vec<&Value> = match &value {
+        Value::Sequence(entries) => entries.iter().collect(),
+        Value::Mapping(entries) if kind == RegexFileKind::Devices => entries.values().collect(),
+        _ => return Vec::new(),
+    };
+
+    let lines = LineIndex::new(contents);
+    let mut res: Vec<UnknownKey> = Vec::new();
+    let mut add = |key: String, line: Option<usize>| match res.iter_mut().find(|x| x.key == key) {
+        Some(unknown) => unknown.count += 1,
+        None => res.push(UnknownKey {
+            key,
+            count: 1,
+            line,
+        }),
+    };
+
+    for (idx, entry) in entries.into_iter().enumerate() {
+        let Some(entry) = entry.as_mapping() else {
+            continue;
+        };
+
+        for (key, value) in entry {
+            let Some(key) = key.as_str() else {
+                continue;
+            };
+
+            if !kind.known_keys().contains(&key) {
+                add(key.to_owned(), lines.entry(idx));
+                continue;
+            }
+
+            let Some(known) = kind.known_nested_keys(key) else {
+                continue;
+            };
+
+            let nested: Vec<(usize, &Mapping)> = match value {
+                Value::Mapping(nested) => vec![(0, nested)],
+                Value::Sequence(nested) => nested
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, x)| Some((i, x.as_mapping()?)))
+                    .collect(),
+                _ => continue,
+            };
+
+            for (nested_idx, nested) in nested {
+                for nested_key in nested.keys().filter_map(|x| x.as_str()) {
+                    if !known.contains(&nested_key) {
+                        let line = match value {
+                            Value::Sequence(_) => lines.nested(idx, nested_idx),
+                            _ => lines.entry(idx),
+                        };
+                        add(format!("{}.{}", key, nested_key), line);
+                    }
+                }
+            }
+        }
+    }
+
+    res
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationErrorKind {
+    /// The file is not valid yaml, or is not shaped like the kind of file it was
+    /// supposed to be.
+    Yaml(String),
+    /// An entry is missing a key the parser requires.
+    MissingKey(&'static str),
+    /// A key is present, but holds the wrong type of value.
+    InvalidValue { key: &'static str, message: String },
+    /// A regex doesn't compile.
+    InvalidRegex { pattern: String, message: String },
+    /// A template such as `version: '$2'` refers to a group the regex does not have.
+    CaptureGroupOutOfRange {
+        key: &'static str,
+        template: String,
+        group: usize,
+        available: usize,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    /// 1 based line number of the offending entry, if it could be determined.
+    pub line: Option<usize>,
+    pub kind: ValidationErrorKind,
+}
+
+impl fmt::Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Yaml(message) => write!(f, "invalid yaml: {}", message),
+            Self::MissingKey(key) => write!(f, "missing required key '{}'", key),
+            Self::InvalidValue { key, message } => {
+                write!(f, "invalid value for '{}': {}", key, message)
+            }
+            Self::InvalidRegex { pattern, message } => {
+                write!(f, "invalid regex '{}': {}", pattern, message)
+            }
+            Self::CaptureGroupOutOfRange {
+                key,
+                template,
+                group,
+                available,
+            } => write!(
+                f,
+                "'{}' template '{}' refers to group ${} but the regex only has {} group(s)",
+                key, template, group, available
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.kind),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Validates a regex yml file on disk. An `Err` is only returned if the file
+/// could not be read, problems with its contents are returned as a list.
+pub fn validate_regex_file<P: AsRef<Path>>(
+    path: P,
+    kind: RegexFileKind,
+) -> Result<Vec<ValidationError>> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(validate_regex_str(&contents, kind))
+}
+
+/// Same as [`validate_regex_file`] but for contents already in memory.
+pub fn validate_regex_str(contents: &str, kind: RegexFileKind) -> Vec<ValidationError> {
+    let value: Value = match serde_yaml::from_str(contents) {
+        Ok(value) => value,
+        Err(err) => {
+            return vec![ValidationError {
+                line: err.location().map(|x| x.line()),
+                kind: ValidationErrorKind::Yaml(err.to_string()),
+            }];
+        }
+    };
+
+    let lines = LineIndex::new(contents);
+    let mut validator = Validator {
+        lines,
+        errors: Vec::new(),
+    };
+
+    match kind {
+        RegexFileKind::Bots | RegexFileKind::BrowserEngines => {
+            validator.sequence(&value, &["regex", "name"], &["name"])
+        }
+        RegexFileKind::OperatingSystems => validator.oss(&value),
+        RegexFileKind::Browsers | RegexFileKind::Clients => {
+            validator.sequence(&value, &["regex", "name", "version"], &["name", "version"])
+        }
+        RegexFileKind::Devices => validator.devices(&value),
+        RegexFileKind::VendorFragments => validator.vendor_fragments(&value),
+        RegexFileKind::Hints => validator.hints(&value),
+    }
+
+    validator.errors
+}
+
+/// serde_yaml doesn't give us the location of values, so instead we find the
+/// lines on which top level entries and their nested list items begin.
+/// The regex files are all formatted consistently enough for this to work.
+struct LineIndex {
+    entries: Vec<usize>,
+    nested: Vec<Vec<usize>>,
+}
+
+impl LineIndex {
+    fn new(contents: &str) -> Self {
+        let mut entries = Vec::new();
+        let mut nested: Vec<Vec<usize>> = Vec::new();
+
+        for (i, line) in contents.lines().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if line.len() == trimmed.len() {
+                entries.push(i + 1);
+                nested.push(Vec::new());
+            } else if trimmed.starts_with("- ") {
+                if let Some(items) = nested.last_mut() {
+                    items.push(i + 1);
+                }
+            }
+        }
+
+        Self { entries, nested }
+    }
+
+    fn entry(&self, idx: usize) -> Option<usize> {
+        self.entries.get(idx).copied()
+    }
+
+    fn nested(&self, idx: usize, nested_idx: usize) -> Option<usize> {
+        self.nested
+            .get(idx)
+            .and_then(|x| x.get(nested_idx))
+            .copied()
+            .or_else(|| self.entry(idx))
+    }
+}
+
+struct Validator {
+    lines: LineIndex,
+    errors: Vec<ValidationError>,
+}
+
+impl Validator {
+    fn error(&mut self, line: Option<usize>, kind: ValidationErrorKind) {
+        self.errors.push(ValidationError { line, kind });
+    }
+
+    fn sequence(&mut self, value: &Value, required: &[&'static str], templates: &[&'static str]) {
+        let Some(entries) = value.as_sequence() else {
+            self.error(
+                None,
+                ValidationErrorKind::Yaml("expected a list".to_owned()),
+            );
+            return;
+        };
+
+        for (idx, entry) in entries.iter().enumerate() {
+            let line = self.lines.entry(idx);
+            let Some(entry) = self.mapping(line, entry) else {
+                continue;
+            };
+
+            if !self.required(line, entry, required) {
+                continue;
+            }
+
+            let groups = self.regex(line, entry, "regex", "");
+            self.templates(line, entry, templates, groups);
+        }
+    }
+
+    fn oss(&mut self, value: &Value) {
+        let Some(entries) = value.as_sequence() else {
+            self.error(
+                None,
+                ValidationErrorKind::Yaml("expected a list".to_owned()),
+            );
+            return;
+        };
+
+        for (idx, entry) in entries.iter().enumerate() {
+            let line = self.lines.entry(idx);
+            let Some(entry) = self.mapping(line, entry) else {
+                continue;
+            };
+
+            if !self.required(line, entry, &["regex", "name"]) {
+                continue;
+            }
+
+            let groups = self.regex(line, entry, "regex", "");
+            self.templates(line, entry, &["name", "version"], groups);
+
+            let Some(versions) = entry.get("versions") else {
+                continue;
+            };
+
+            let Some(versions) = versions.as_sequence() else {
+                self.invalid(line, "versions", "expected a list");
+                continue;
+            };
+
+            for (nested_idx, version) in versions.iter().enumerate() {
+                let line = self.lines.nested(idx, nested_idx);
+                let Some(version) = self.mapping(line, version) else {
+                    continue;
+                };
+
+                if !self.required(line, version, &["version"]) {
+                    continue;
+                }
+
+                // versions without their own regex reuse the top level one.
+                let groups = if version.contains_key("regex") {
+                    self.regex(line, version, "regex", "")
+                } else {
+                    groups
+                };
+                self.templates(line, version, &["version"], groups);
+            }
+        }
+    }
+
+    fn devices(&mut self, value: &Value) {
+        let Some(brands) = value.as_mapping() else {
+            self.error(
+                None,
+                ValidationErrorKind::Yaml("expected a mapping of brands".to_owned()),
+            );
+            return;
+        };
+
+        for (idx, (_brand, entry)) in brands.iter().enumerate() {
+            let line = self.lines.entry(idx);
+            let Some(entry) = self.mapping(line, entry) else {
+                continue;
+            };
+
+            if !self.required(line, entry, &["regex"]) {
+                continue;
+            }
+
+            let groups = self.regex(line, entry, "regex", "");
+
+            // a lone model (string or mapping) is on the same line as the entry,
+            // whereas a list of models each get their own line.
+            if let Some(model) = entry.get("model") {
+                self.model(line, model, groups);
+            }
+
+            if let Some(models) = entry.get("models") {
+                let Some(models) = models.as_sequence() else {
+                    self.invalid(line, "models", "expected a list");
+                    continue;
+                };
+
+                for (nested_idx, model) in models.iter().enumerate() {
+                    let line = self.lines.nested(idx, nested_idx);
+                    self.model(line, model, groups);
+                }
+            }
+        }
+    }
+
+    fn model(&mut self, line: Option<usize>, model: &Value, device_groups: Option<usize>) {
+        match model {
+            Value::String(template) => {
+                self.template(line, "model", template, device_groups);
+            }
+            Value::Mapping(model) => {
+                if !self.required(line, model, &["model"]) {
+                    return;
+                }
+                let groups = if model.contains_key("regex") {
+                    self.regex(line, model, "regex", "")
+                } else {
+                    device_groups
+                };
+                self.templates(line, model, &["model"], groups);
+            }
+            _ => self.invalid(line, "model", "expected a string or a mapping"),
+        }
+    }
+
+    fn vendor_fragments(&mut self, value: &Value) {
+        let Some(vendors) = value.as_mapping() else {
+            self.error(
+                None,
+                ValidationErrorKind::Yaml("expected a mapping of vendors".to_owned()),
+            );
+            return;
+        };
+
+        for (idx, (_vendor, fragments)) in vendors.iter().enumerate() {
+            let Some(fragments) = fragments.as_sequence() else {
+                self.invalid(self.lines.entry(idx), "fragments", "expected a list");
+                continue;
+            };
+
+            for (nested_idx, fragment) in fragments.iter().enumerate() {
+                let line = self.lines.nested(idx, nested_idx);
+                match fragment.as_str() {
+                    Some(fragment) => {
+                        // the vendor fragment parser appends this to every fragment.
+                        self.compile(line, fragment, "[^a-z0-9]+");
+                    }
+                    None => self.invalid(line, "fragments", "expected a string"),
+                }
+            }
+        }
+    }
+
+    fn hints(&mut self, value: &Value) {
+        let Some(hints) = value.as_mapping() else {
+            self.error(
+                None,
+                ValidationErrorKind::Yaml("expected a mapping of hints".to_owned()),
+            );
+            return;
+        };
+
+        for (idx, (key, value)) in hints.iter().enumerate() {
+            let line = self.lines.entry(idx);
+            if !key.is_string() || !value.is_string() {
+                self.invalid(line, "hint", "expected a string to string mapping");
+            }
+        }
+    }
+
+    fn mapping<'a>(&mut self, line: Option<usize>, value: &'a Value) -> Option<&'a Mapping> {
+        let res = value.as_mapping();
+        if res.is_none() {
+            self.error(
+                line,
+                ValidationErrorKind::Yaml("expected a mapping".to_owned()),
+            );
+        }
+        res
+    }
+
+    fn invalid(&mut self, line: Option<usize>, key: &'static str, message: &str) {
+        self.error(
+            line,
+            ValidationErrorKind::InvalidValue {
+                key,
+                message: message.to_owned(),
+            },
+        );
+    }
+
+    /// Returns false if any of the required keys are missing.
+    fn required(&mut self, line: Option<usize>, entry: &Mapping, keys: &[&'static str]) -> bool {
+        let mut res = true;
+        for key in keys {
+            match entry.get(*key) {
+                None => {
+                    self.error(line, ValidationErrorKind::MissingKey(key));
+                    res = false;
+                }
+                Some(value) if !value.is_string() => {
+                    self.invalid(line, key, "expected a string");
+                    res = false;
+                }
+                _ => {}
+            }
+        }
+        res
+    }
+
+    /// Compiles the regex under `key`, returning its number of capture groups.
+    fn regex(
+        &mut self,
+        line: Option<usize>,
+        entry: &Mapping,
+        key: &'static str,
+        suffix: &str,
+    ) -> Option<usize> {
+        match entry.get(key).map(|x| x.as_str()) {
+            Some(Some(pattern)) => self.compile(line, pattern, suffix),
+            Some(None) => {
+                self.invalid(line, key, "expected a string");
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn compile(&mut self, line: Option<usize>, pattern: &str, suffix: &str) -> Option<usize> {
+        let mut full = pattern.to_owned();
+        full.push_str(suffix);
+
+        match SafeRegex::new(&user_agent_pattern(&full)) {
+            // group 0 is the whole match, which templates can't refer to.
+            Ok(regex) => Some(regex.captures_len() - 1),
+            Err(err) => {
+                self.error(
+                    line,
+                    ValidationErrorKind::InvalidRegex {
+                        pattern: pattern.to_owned(),
+                        message: err.to_string(),
+                    },
+                );
+                None
+            }
+        }
+    }
+
+    fn templates(
+        &mut self,
+        line: Option<usize>,
+        entry: &Mapping,
+        keys: &[&'static str],
+        groups: Option<usize>,
+    ) {
+        for key in keys {
+            if let Some(template) = entry.get(*key).and_then(|x| x.as_str()) {
+                self.template(line, key, template, groups);
+            }
+        }
+    }
+
+    fn template(
+        &mut self,
+        line: Option<usize>,
+        key: &'static str,
+        template: &str,
+        groups: Option<usize>,
+    ) {
+        // if the regex didn't compile, that has already been reported.
+        let Some(available) = groups else {
+            return;
+        };
+
+        for group in template_groups(template) {
+            if group > available {
+                self.error(
+                    line,
+                    ValidationErrorKind::CaptureGroupOutOfRange {
+                        key,
+                        template: template.to_owned(),
+                        group,
+                        available,
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Capture groups referenced by a template, either as `$1` (a single digit, as
+/// php interprets it) or `${12}`.
+pub(crate) fn template_groups(template: &str) -> Vec<usize> {
+    let mut res = Vec::new();
+    let bytes = template.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'$' {
+            if let Some(digit) = bytes.get(i + 1).filter(|x| x.is_ascii_digit()) {
+                res.push((digit - b'0') as usize);
+                i += 2;
+                continue;
+            }
+
+            if bytes.get(i + 1) == Some(&b'{') {
+                if let Some(end) = template[i + 2..].find('}') {
+                    if let Ok(group) = template[i + 2..i + 2 + end].parse::<usize>() {
+                        res.push(group);
+                    }
+                    i += end + 3;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+
+    res
+}
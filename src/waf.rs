@@ -0,0 +1,522 @@
+//! Perimeter rules generated from the bot and library databases, so that a
+//! WAF or reverse proxy can act on the same clients the detector recognizes.
+//!
+//! nginx and ModSecurity don't support every regex feature the database uses,
+//! so rather than the regexes themselves the rules match literal tokens which
+//! every user agent matched by a definition must contain. A token is necessary
+//! rather than sufficient, so the rules can match slightly more than the
+//! detector would, and should be reviewed before they are used to block.
+//!
+//! ```ignore
+//! let db = Database::embedded();
+//! let rules = WafRules::new(4)
+//!     .with_bots(&db, &["Crawler", "Security Checker"])
+//!     .with_libraries(&db);
+//! std::fs::write("bots.conf", rules.nginx_map("$bot_category"))?;
+//! ```
+
+use serde::Serialize;
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+use crate::database::Database;
+
+/// What the detector requires before a database pattern, see
+/// [`crate::parsers::utils::user_agent_pattern`]. Tokens starting a pattern
+/// are matched after it too.
+const BOUNDARY: &str = "(?:^|[^a-z0-9_-]|[^a-z0-9-]_|sprd-|mz-)";
+
+/// Limits how many strings a run of small groups is expanded into.
+const MAX_VARIANTS: usize = 32;
+
+/// A literal every user agent matched by a definition contains.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct WafToken {
+    /// Matched case insensitively.
+    pub token: String,
+    /// Whether the token starts the pattern, so has to be preceded by
+    /// something other than a letter or digit.
+    pub boundary: bool,
+    /// Name of the definition the token was extracted from.
+    pub name: String,
+    pub category: String,
+}
+
+impl WafToken {
+    /// The token as a pcre pattern.
+    fn pattern(&self) -> String {
+        let token = fancy_regex::escape(&self.token);
+        if self.boundary {
+            format!("{}{}", BOUNDARY, token)
+        } else {
+            token.into_owned()
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WafRules {
+    min_len: usize,
+    tokens: Vec<WafToken>,
+    incomplete: Vec<String>,
+    seen: HashSet<(String, bool)>,
+}
+
+impl WafRules {
+    /// Tokens shorter than `min_len` match too much to be used.
+    pub fn new(min_len: usize) -> Self {
+        Self {
+            min_len: min_len.max(1),
+            tokens: Vec::new(),
+            incomplete: Vec::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Adds the bots in any of `categories`, compared case insensitively, or
+    /// every bot if `categories` is empty.
+    pub fn with_bots(mut self, db: &Database, categories: &[&str]) -> Self {
+        for bot in db.bot_definitions() {
+            let category = bot.category.filter(|x| !x.is_empty()).unwrap_or("Bot");
+            if categories.is_empty() || categories.iter().any(|x| x.eq_ignore_ascii_case(category))
+            {
+                self.add(bot.name, category, bot.regex);
+            }
+        }
+        self
+    }
+
+    /// Adds the http libraries, such as curl, under the `Library` category.
+    pub fn with_libraries(mut self, db: &Database) -> Self {
+        for (name, pattern) in db.libraries.patterns() {
+            self.add(name, "Library", pattern);
+        }
+        self
+    }
+
+    fn add(&mut self, name: &str, category: &str, pattern: &str) {
+        let (tokens, complete) = extract_tokens(pattern, self.min_len);
+        if !complete {
+            self.incomplete.push(name.to_owned());
+        }
+
+        for (token, boundary) in tokens {
+            if self.seen.insert((token.to_lowercase(), boundary)) {
+                self.tokens.push(WafToken {
+                    token,
+                    boundary,
+                    name: name.to_owned(),
+                    category: category.to_owned(),
+                });
+            }
+        }
+    }
+
+    /// The tokens, in the order the definitions are matched.
+    pub fn tokens(&self) -> &[WafToken] {
+        &self.tokens
+    }
+
+    /// Names of definitions with patterns some of which no token could be
+    /// found for, so the rules won't match all the user agents they do.
+    pub fn incomplete(&self) -> &[String] {
+        &self.incomplete
+    }
+
+    /// A single case insensitive pcre pattern matching any of the tokens.
+    pub fn regex(&self) -> String {
+        regex(self.tokens.iter())
+    }
+
+    /// An nginx `map` setting `variable` to the category of the first
+    /// matching token, or an empty string.
+    pub fn nginx_map(&self, variable: &str) -> String {
+        let mut res = String::new();
+        self.header(&mut res);
+        let _ = writeln!(res, "map $http_user_agent {} {{", variable);
+        let _ = writeln!(res, "    default \"\";");
+        for token in self.tokens.iter() {
+            let _ = writeln!(
+                res,
+                "    \"~*{}\" \"{}\"; # {}",
+                token.pattern(),
+                token.category,
+                token.name
+            );
+        }
+        res.push_str("}\n");
+        res
+    }
+
+    /// An nginx `if` block running `action`, such as `return 403;`, for any
+    /// matching token.
+    pub fn nginx_if(&self, action: &str) -> String {
+        let mut res = String::new();
+        self.header(&mut res);
+        let _ = writeln!(res, "if ($http_user_agent ~* \"{}\") {{", self.regex());
+        let _ = writeln!(res, "    {}", action);
+        res.push_str("}\n");
+        res
+    }
+
+    /// A ModSecurity rule per category, numbered from `first_id`, with
+    /// `actions` such as `deny,status:403,log`.
+    pub fn modsecurity(&self, first_id: u32, actions: &str) -> String {
+        let mut categories = BTreeMap::<&str, Vec<&WafToken>>::new();
+        for token in self.tokens.iter() {
+            categories.entry(&token.category).or_default().push(token);
+        }
+
+        let mut res = String::new();
+        self.header(&mut res);
+        for (id, (category, tokens)) in (first_id..).zip(categories) {
+            let _ = writeln!(
+                res,
+                "SecRule REQUEST_HEADERS:User-Agent \"@rx (?i){}\" \"id:{},phase:1,t:none,{},msg:'{} user agent'\"",
+                regex(tokens.into_iter()),
+                id,
+                actions,
+                category.replace('\'', "\\'"),
+            );
+        }
+        res
+    }
+
+    fn header(&self, res: &mut String) {
+        let _ = writeln!(
+            res,
+            "# {} tokens generated by rust-device-detector, {} definitions not fully covered",
+            self.tokens.len(),
+            self.incomplete.len()
+        );
+    }
+}
+
+fn regex<'a>(tokens: impl Iterator<Item = &'a WafToken>) -> String {
+    let (bounded, unbounded): (Vec<_>, Vec<_>) = tokens.partition(|x| x.boundary);
+    let alternation = |tokens: Vec<&WafToken>| {
+        tokens
+            .iter()
+            .map(|x| fancy_regex::escape(&x.token).into_owned())
+            .collect::<Vec<_>>()
+            .join("|")
+    };
+
+    let mut res = Vec::new();
+    if !bounded.is_empty() {
+        res.push(format!("{}(?:{})", BOUNDARY, alternation(bounded)));
+    }
+    if !unbounded.is_empty() {
+        res.push(alternation(unbounded));
+    }
+    res.join("|")
+}
+
+// characters tokens may contain. Anything else would need escaping in one of
+// the config formats, or is rare enough not to bother.
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || " -_./:+!,=@%&~()".contains(c)
+}
+
+#[derive(Debug)]
+enum Node {
+    Literal(char),
+    /// Anything other than a fixed string, including zero width assertions.
+    Other,
+    Group(Vec<Vec<Node>>),
+    /// A node repeated at least this many times.
+    Repeat(Box<Node>, u32),
+}
+
+/// Just enough of a regex parser to find the literals in the database's
+/// patterns. Returns `None` for syntax it doesn't know.
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let res = self.peek();
+        self.pos += 1;
+        res
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn alternatives(&mut self) -> Option<Vec<Vec<Node>>> {
+        let mut res = vec![self.sequence()?];
+        while self.eat('|') {
+            res.push(self.sequence()?);
+        }
+        Some(res)
+    }
+
+    fn sequence(&mut self) -> Option<Vec<Node>> {
+        let mut res = Vec::new();
+        loop {
+            let atom = match self.peek() {
+                None | Some(')') | Some('|') => return Some(res),
+                Some('(') => {
+                    self.pos += 1;
+                    match self.group()? {
+                        Some(atom) => atom,
+                        None => continue,
+                    }
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    self.class()?;
+                    Node::Other
+                }
+                Some('.') | Some('^') | Some('$') => {
+                    self.pos += 1;
+                    Node::Other
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.next()? {
+                        c if c.is_ascii_alphanumeric() => Node::Other,
+                        c => Node::Literal(c),
+                    }
+                }
+                Some('*') | Some('+') | Some('?') | Some('{') => return None,
+                Some(c) => {
+                    self.pos += 1;
+                    Node::Literal(c)
+                }
+            };
+
+            let min = match self.peek() {
+                Some('?') | Some('*') => Some(0),
+                Some('+') => Some(1),
+                Some('{') => Some(self.bounds()?),
+                _ => None,
+            };
+
+            match min {
+                Some(min) => {
+                    self.pos += 1;
+                    // lazy and possessive quantifiers
+                    if !self.eat('?') {
+                        self.eat('+');
+                    }
+                    res.push(Node::Repeat(Box::new(atom), min));
+                }
+                None => res.push(atom),
+            }
+        }
+    }
+
+    // after an opening parenthesis, returns None for inline flags
+    fn group(&mut self) -> Option<Option<Node>> {
+        let mut assertion = false;
+
+        if self.eat('?') {
+            match self.peek()? {
+                ':' => self.pos += 1,
+                '=' | '!' => {
+                    self.pos += 1;
+                    assertion = true;
+                }
+                '<' if matches!(self.chars.get(self.pos + 1), Some('=') | Some('!')) => {
+                    self.pos += 2;
+                    assertion = true;
+                }
+                '<' | 'P' => while self.next()? != '>' {},
+                _ => {
+                    // inline flags, which don't matter when matching case
+                    // insensitively anyway
+                    loop {
+                        match self.next()? {
+                            ')' => return Some(None),
+                            ':' => break,
+                            c if c.is_ascii_alphabetic() || c == '-' => {}
+                            _ => return None,
+                        }
+                    }
+                }
+            }
+        }
+
+        let alternatives = self.alternatives()?;
+        if !self.eat(')') {
+            return None;
+        }
+
+        Some(Some(if assertion {
+            Node::Other
+        } else {
+            Node::Group(alternatives)
+        }))
+    }
+
+    // after an opening bracket
+    fn class(&mut self) -> Option<()> {
+        self.eat('^');
+        self.eat(']');
+        loop {
+            match self.next()? {
+                ']' => return Some(()),
+                '\\' => {
+                    self.next()?;
+                }
+                '[' if self.peek() == Some(':') => while self.next()? != ']' {},
+                _ => {}
+            }
+        }
+    }
+
+    // the minimum of a {n}, {n,} or {n,m} quantifier, leaving the closing brace
+    fn bounds(&mut self) -> Option<u32> {
+        let start = self.pos + 1;
+        let mut end = start;
+        while self.chars.get(end)?.is_ascii_digit() {
+            end += 1;
+        }
+        let min = self.chars[start..end]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()?;
+
+        while *self.chars.get(end)? != '}' {
+            let c = self.chars[end];
+            if !(c.is_ascii_digit() || c == ',') {
+                return None;
+            }
+            end += 1;
+        }
+        self.pos = end;
+        Some(min)
+    }
+}
+
+fn product(a: &[String], b: &[String]) -> Option<Vec<String>> {
+    if a.len() * b.len() > MAX_VARIANTS {
+        return None;
+    }
+    Some(
+        a.iter()
+            .flat_map(|x| b.iter().map(move |y| format!("{}{}", x, y)))
+            .collect(),
+    )
+}
+
+/// Every string a node can match, if there are only a few.
+fn exact(node: &Node) -> Option<Vec<String>> {
+    match node {
+        Node::Literal(c) if is_token_char(*c) => Some(vec![c.to_string()]),
+        Node::Group(alternatives) => {
+            let mut res = Vec::new();
+            for alternative in alternatives {
+                let mut strings = vec![String::new()];
+                for node in alternative {
+                    strings = product(&strings, &exact(node)?)?;
+                }
+                res.extend(strings);
+            }
+            (res.len() <= MAX_VARIANTS).then_some(res)
+        }
+        _ => None,
+    }
+}
+
+/// A run of consecutive literals, as the strings it can be, and whether it
+/// starts its sequence.
+type Run = (Vec<String>, bool);
+
+fn runs(sequence: &[Node]) -> Vec<Run> {
+    let mut res = Vec::new();
+    let mut current = vec![String::new()];
+    let mut start = true;
+
+    let mut flush = |current: &mut Vec<String>, start: &mut bool| {
+        let run = std::mem::replace(current, vec![String::new()]);
+        if run.iter().any(|x| !x.is_empty()) {
+            res.push((run, *start));
+        }
+        *start = false;
+    };
+
+    for node in sequence {
+        // a repeated node is required once, but what follows it may be
+        // another repetition
+        let (strings, ends_run) = match node {
+            Node::Repeat(node, min) if *min > 0 => (exact(node), true),
+            Node::Repeat(..) => (None, true),
+            node => (exact(node), false),
+        };
+
+        match strings {
+            Some(strings) => {
+                match product(&current, &strings) {
+                    Some(next) => current = next,
+                    None => {
+                        flush(&mut current, &mut start);
+                        current = strings;
+                    }
+                }
+                if ends_run {
+                    flush(&mut current, &mut start);
+                }
+            }
+            None => flush(&mut current, &mut start),
+        }
+    }
+    flush(&mut current, &mut start);
+
+    res
+}
+
+/// The tokens for `pattern`, one run of literals for each of its top level
+/// alternatives, and whether every alternative had one at least `min_len`
+/// long.
+fn extract_tokens(pattern: &str, min_len: usize) -> (Vec<(String, bool)>, bool) {
+    let mut parser = Parser {
+        chars: pattern.chars().collect(),
+        pos: 0,
+    };
+
+    let mut alternatives = match parser.alternatives() {
+        Some(x) if parser.pos >= parser.chars.len() => x,
+        _ => return (Vec::new(), false),
+    };
+
+    // a top level group is no different to top level alternatives
+    if let [sequence] = alternatives.as_mut_slice() {
+        if let [Node::Group(group)] = sequence.as_mut_slice() {
+            alternatives = std::mem::take(group);
+        }
+    }
+
+    let mut tokens = Vec::new();
+    let mut complete = true;
+
+    for alternative in alternatives {
+        let shortest = |run: &Run| run.0.iter().map(|x| x.len()).min().unwrap_or(0);
+        let best = runs(&alternative)
+            .into_iter()
+            .filter(|x| shortest(x) >= min_len)
+            .max_by_key(|x| (shortest(x), x.1));
+
+        match best {
+            Some((strings, boundary)) => tokens.extend(strings.into_iter().map(|x| (x, boundary))),
+            None => complete = false,
+        }
+    }
+
+    (tokens, complete)
+}
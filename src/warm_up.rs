@@ -0,0 +1,126 @@
+//! Compiling the regex database before the first requests, rather than as
+//! they need each regex, so that those requests aren't slow.
+//!
+//! ```ignore
+//! detector.warm_up_with_progress(|progress| {
+//!     log::info!(
+//!         "{}: {}/{} in {:?}",
+//!         progress.list,
+//!         progress.compiled,
+//!         progress.total,
+//!         progress.elapsed
+//!     );
+//! })?;
+//! ```
+//!
+//! Lists are compiled on as many threads as there are cores, the largest
+//! first, and all of them have been compiled once warming up returns.
+//!
+//! Compiling everything takes a while, and most of it is never used. Instead,
+//! `detector.prime_from_file("top_user_agents.txt")` parses a list of the
+//! user agents most seen in your traffic, one per line, which compiles only
+//! the regexes they need and, with a cache, caches their detections.
+
+use anyhow::{Context, Result};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::database::Database;
+use crate::device_detector::DeviceDetector;
+
+/// How often progress is reported within a list.
+const REPORT_EVERY: usize = 1000;
+
+#[derive(Clone, Debug)]
+pub struct WarmUpProgress {
+    /// The file the list was loaded from, such as `device/mobiles.yml`.
+    pub list: &'static str,
+    pub compiled: usize,
+    pub total: usize,
+    /// Time spent on this list so far.
+    pub elapsed: Duration,
+}
+
+impl WarmUpProgress {
+    pub fn is_done(&self) -> bool {
+        self.compiled == self.total
+    }
+}
+
+pub(crate) fn warm_up<F>(db: &Database, progress: F) -> Result<()>
+where
+    F: Fn(&WarmUpProgress) + Sync,
+{
+    let mut lists = db.regex_lists();
+    lists.sort_by_key(|(_, regexes)| std::cmp::Reverse(regexes.len()));
+
+    let threads = std::thread::available_parallelism()
+        .map(|x| x.get())
+        .unwrap_or(1)
+        .min(lists.len());
+    let next = AtomicUsize::new(0);
+
+    let compile_lists = || -> Result<()> {
+        while let Some((list, regexes)) = lists.get(next.fetch_add(1, Ordering::Relaxed)) {
+            let started = Instant::now();
+            let report = |compiled| {
+                progress(&WarmUpProgress {
+                    list,
+                    compiled,
+                    total: regexes.len(),
+                    elapsed: started.elapsed(),
+                })
+            };
+
+            for (i, regex) in regexes.iter().enumerate() {
+                regex
+                    .compile()
+                    .with_context(|| format!("compiling {} in {}", regex.pattern, list))?;
+                if i > 0 && i.is_multiple_of(REPORT_EVERY) {
+                    report(i);
+                }
+            }
+            report(regexes.len());
+        }
+        Ok(())
+    };
+
+    std::thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|_| scope.spawn(compile_lists))
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .try_for_each(|x| x.join().expect("warm up thread"))
+    })
+}
+
+/// Parses each of `uas` on as many threads as there are cores.
+pub(crate) fn prime(detector: &DeviceDetector, uas: &[&str]) -> Result<()> {
+    let threads = std::thread::available_parallelism()
+        .map(|x| x.get())
+        .unwrap_or(1)
+        .min(uas.len());
+    let next = AtomicUsize::new(0);
+
+    let parse_uas = || -> Result<()> {
+        while let Some(ua) = uas.get(next.fetch_add(1, Ordering::Relaxed)) {
+            detector
+                .parse(ua, None)
+                .with_context(|| format!("priming with {}", ua))?;
+        }
+        Ok(())
+    };
+
+    std::thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|_| scope.spawn(parse_uas))
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .try_for_each(|x| x.join().expect("prime thread"))
+    })
+}
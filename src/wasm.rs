@@ -0,0 +1,88 @@
+//! A wasm-bindgen wrapper, for running detection in javascript edge workers
+//! such as Cloudflare Workers. Built with `wasm-pack build --target web --
+//! --features wasm`:
+//!
+//! ```js
+//! import init, { DeviceDetector } from "./pkg/rust_device_detector.js";
+//!
+//! await init();
+//! const detector = new DeviceDetector();
+//!
+//! export default {
+//!   async fetch(request) {
+//!     const headers = Object.fromEntries(request.headers);
+//!     const detection = detector.parse(headers["user-agent"] ?? "", headers);
+//!
+//!     const forwarded = new Request(request);
+//!     for (const [name, value] of Object.entries(detector.annotate(headers))) {
+//!       forwarded.headers.set(name, value);
+//!     }
+//!     return fetch(forwarded);
+//!   },
+//! };
+//! ```
+//!
+//! Headers are plain objects of strings, with lowercase names as
+//! `Object.fromEntries(request.headers)` gives them. Detections are objects of
+//! the json the cli prints, see `includes/detection.d.ts`.
+//!
+//! Rust workers, with the `worker` or `fastly` crates, rather use
+//! [`crate::edge`] directly.
+
+use js_sys::{Array, Object, JSON};
+use wasm_bindgen::prelude::*;
+
+use crate::device_detector;
+use crate::edge;
+
+fn headers(headers: &Object) -> Result<Vec<(String, String)>, JsError> {
+    Object::entries(headers)
+        .iter()
+        .map(|entry| {
+            let entry = Array::from(&entry);
+            let name = entry.get(0).as_string().unwrap_or_default();
+            let value = entry
+                .get(1)
+                .as_string()
+                .ok_or_else(|| JsError::new(&format!("header {} is not a string", name)))?;
+            Ok((name, value))
+        })
+        .collect()
+}
+
+fn error(err: anyhow::Error) -> JsError {
+    JsError::new(&format!("{:#}", err))
+}
+
+#[wasm_bindgen]
+pub struct DeviceDetector(device_detector::DeviceDetector);
+
+#[wasm_bindgen]
+impl DeviceDetector {
+    #[allow(clippy::new_without_default)]
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self(device_detector::DeviceDetector::new())
+    }
+
+    /// Detects `ua`, with the other headers of the request for client hints.
+    pub fn parse(&self, ua: &str, headers: Option<Object>) -> Result<JsValue, JsError> {
+        let headers = headers.as_ref().map(self::headers).transpose()?;
+        let detection = self.0.parse(ua, headers).map_err(error)?;
+
+        JSON::parse(&detection.to_value().to_string())
+            .map_err(|_| JsError::new("unparsable detection"))
+    }
+
+    /// The `x-detected-*` headers to set on the request before passing it
+    /// on, see [`edge::annotate`].
+    pub fn annotate(&self, headers: &Object) -> Result<Object, JsError> {
+        let headers = self::headers(headers)?;
+        let res = Object::new();
+        for (name, value) in edge::annotate(&self.0, headers.as_slice()).map_err(error)? {
+            js_sys::Reflect::set(&res, &name.into(), &value.into())
+                .map_err(|_| JsError::new("setting header"))?;
+        }
+        Ok(res)
+    }
+}
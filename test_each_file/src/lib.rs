@@ -91,7 +91,12 @@ fn generate_from_tree(tree: &Paths, parsed: &ForEachFile, stream: &mut TokenStre
     for file in &tree.here {
         let mut diff = diff_paths(file, &parsed.path).unwrap();
         diff.set_extension("");
-        let file_name_str = diff.file_name().unwrap().to_str().unwrap().replace("-", "_");
+        let file_name_str = diff
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .replace("-", "_");
 
         // println!("file_name_str: {}", file_name_str);
 
@@ -0,0 +1,44 @@
+#![cfg(feature = "alloc-counters")]
+
+use anyhow::Result;
+
+use rust_device_detector::alloc_counters::{measure, CountingAllocator};
+use rust_device_detector::device_detector::{DeviceDetector, ParseContext};
+
+use std::alloc::System;
+
+#[global_allocator]
+static GLOBAL: CountingAllocator<System> = CountingAllocator::new(System);
+
+const UA: &str = "Mozilla/5.0 (Linux; Android 10; SM-G960F) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/83.0.4103.106 Mobile Safari/537.36";
+
+#[test]
+fn test_allocation_counts() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    // the first parse compiles regexes, which is not what we want to count.
+    detector.parse(UA, None)?;
+
+    let (_, stats) = detector.parse_with_stats(UA, None)?;
+    assert!(stats.allocations > 0);
+    assert!(stats.bytes_allocated > 0);
+
+    // counts are deterministic for the same input
+    let (_, again) = detector.parse_with_stats(UA, None)?;
+    assert_eq!(stats, again);
+
+    // and reusing a context saves at least some of them
+    let mut ctx = ParseContext::new();
+    detector.parse_with_context(UA, None, &mut ctx)?;
+    let (res, with_context) = measure(|| detector.parse_with_context(UA, None, &mut ctx));
+    res?;
+    assert!(
+        with_context.allocations + with_context.reallocations
+            < stats.allocations + stats.reallocations,
+        "{:?} vs {:?}",
+        with_context,
+        stats
+    );
+
+    Ok(())
+}
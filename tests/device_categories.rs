@@ -0,0 +1,37 @@
+#![cfg(not(feature = "televisions"))]
+
+use anyhow::Result;
+
+use rust_device_detector::device_detector::{Detection, DeviceDetector};
+use rust_device_detector::parsers::device::DeviceType;
+
+const TV: &str = "Mozilla/5.0 (Linux; U; HbbTV/1.1.1 (; Altech UEC; DTR6000; 2.2; ;) ; en) AppleWebKit/533.1 (KHTML, like Gecko) Version/4.0 Safari/533.1";
+
+fn check_tv(res: &Detection) {
+    let known = res.get_known_device().expect("known device");
+    assert_eq!(
+        known.client.as_ref().map(|x| x.name.as_str()),
+        Some("Safari")
+    );
+    assert_eq!(
+        known.os.as_ref().map(|x| x.name.as_str()),
+        Some("GNU/Linux")
+    );
+    // the brand and model only come from televisions.yml, the type from
+    // hbbtv as well
+    let device = known.device.as_ref().expect("device");
+    assert_eq!(device.brand, None);
+    assert_eq!(device.model, None);
+    assert_eq!(device.device_type, Some(DeviceType::Television));
+}
+
+#[test]
+fn test_without_televisions() -> Result<()> {
+    check_tv(&DeviceDetector::new().parse(TV, None)?);
+
+    let detector =
+        DeviceDetector::with_database_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/data/regexes"))?;
+    check_tv(&detector.parse(TV, None)?);
+
+    Ok(())
+}
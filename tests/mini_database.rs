@@ -0,0 +1,50 @@
+#![cfg(feature = "mini-database")]
+
+use anyhow::Result;
+
+use rust_device_detector::device_detector::{Detection, DeviceDetector};
+
+fn detect(ua: &str) -> Result<Detection> {
+    DeviceDetector::new().parse(ua, None)
+}
+
+#[test]
+fn test_mini_database_common_traffic() -> Result<()> {
+    let res = detect("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")?;
+    let known = res.get_known_device().expect("known device");
+    assert_eq!(
+        known.client.as_ref().map(|x| x.name.as_str()),
+        Some("Chrome")
+    );
+    assert_eq!(known.os.as_ref().map(|x| x.name.as_str()), Some("Windows"));
+
+    let res = detect("Mozilla/5.0 (Linux; Android 13; SM-S911B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36")?;
+    let device = res
+        .get_known_device()
+        .and_then(|x| x.device.as_ref())
+        .expect("device");
+    assert_eq!(device.brand.as_deref(), Some("Samsung"));
+
+    let res = detect("Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1")?;
+    let known = res.get_known_device().expect("known device");
+    assert_eq!(
+        known.client.as_ref().map(|x| x.name.as_str()),
+        Some("Mobile Safari")
+    );
+    assert_eq!(known.os.as_ref().map(|x| x.name.as_str()), Some("iOS"));
+
+    let res = detect("Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)")?;
+    assert_eq!(res.get_bot().map(|x| x.name.as_str()), Some("Googlebot"));
+
+    Ok(())
+}
+
+#[test]
+fn test_mini_database_leaves_out_the_long_tail() -> Result<()> {
+    // a browser outside of the mini database's selection
+    let res = detect("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) QupZilla/1.8.6 Safari/537.36")?;
+    let client = res.get_known_device().and_then(|x| x.client.as_ref());
+    assert_ne!(client.map(|x| x.name.as_str()), Some("QupZilla"));
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+
+const CHROME_120: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+const CHROME_121: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36";
+const FIREFOX: &str = "Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0";
+
+fn client_version(detector: &DeviceDetector, ua: &str) -> Result<Option<String>> {
+    let detection = detector.parse(ua, None)?;
+    Ok(detection
+        .get_known_device()
+        .and_then(|x| x.client.as_ref())
+        .and_then(|x| x.version.clone()))
+}
+
+#[test]
+fn test_approximation() -> Result<()> {
+    // a rate of 0 approximates from the first request on
+    let detector = DeviceDetector::new().with_approximation(0, 80);
+
+    assert_eq!(
+        client_version(&detector, CHROME_120)?.as_deref(),
+        Some("120.0.0.0")
+    );
+    assert_eq!(detector.approximations_served(), 0);
+    assert!(!detector.parse(FIREFOX, None)?.approximate());
+
+    // the same first 80 bytes, so the earlier detection is used
+    assert_eq!(
+        client_version(&detector, CHROME_121)?.as_deref(),
+        Some("120.0.0.0")
+    );
+    assert_eq!(detector.approximations_served(), 1);
+    assert!(detector.parse(CHROME_121, None)?.approximate());
+    assert_eq!(detector.approximations_served(), 2);
+
+    // a novel prefix is parsed
+    let linux = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    let detection = detector.parse(linux, None)?;
+    assert!(!detection.approximate());
+    let os = detection.get_known_device().and_then(|x| x.os.as_ref());
+    assert_eq!(os.map(|x| x.name.as_str()), Some("GNU/Linux"));
+    assert_eq!(detector.approximations_served(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_approximation_below_rate() -> Result<()> {
+    let detector = DeviceDetector::new().with_approximation(u32::MAX, 80);
+
+    assert_eq!(
+        client_version(&detector, CHROME_120)?.as_deref(),
+        Some("120.0.0.0")
+    );
+    assert_eq!(
+        client_version(&detector, CHROME_121)?.as_deref(),
+        Some("121.0.0.0")
+    );
+    assert_eq!(detector.approximations_served(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_approximation_client_hints() -> Result<()> {
+    let detector = DeviceDetector::new().with_approximation(0, 80);
+
+    let hints = |platform: &str| {
+        Some(vec![(
+            "Sec-CH-UA-Platform".to_owned(),
+            format!("\"{}\"", platform),
+        )])
+    };
+
+    detector.parse(CHROME_120, hints("Windows"))?;
+    detector.parse(CHROME_121, hints("Linux"))?;
+    assert_eq!(detector.approximations_served(), 0);
+
+    detector.parse(CHROME_121, hints("Windows"))?;
+    assert_eq!(detector.approximations_served(), 1);
+
+    Ok(())
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_approximation_not_cached() -> Result<()> {
+    let detector = DeviceDetector::new_with_cache(100).with_approximation(0, 80);
+
+    detector.parse(CHROME_120, None)?;
+    assert!(detector.parse(CHROME_121, None)?.approximate());
+
+    // once load drops, the user agent is to be parsed rather than answered
+    // from the cache with the detection of another
+    assert!(detector.parse(CHROME_121, None)?.approximate());
+    assert_eq!(detector.approximations_served(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+
+const USER_AGENTS: [&str; 3] = [
+    "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36",
+    "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+    "",
+];
+
+#[test]
+fn test_parse_async() -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+    let detector = DeviceDetector::new();
+
+    for ua in USER_AGENTS {
+        let detection = runtime.block_on(detector.parse_async(ua, None))?;
+        let expected = detector.parse(ua, None)?;
+        assert_eq!(
+            format!("{:?}", detection),
+            format!("{:?}", expected),
+            "{}",
+            ua
+        );
+    }
+
+    let headers = vec![("Sec-CH-UA-Model".to_owned(), r#""Pixel 7""#.to_owned())];
+    let detection = runtime.block_on(detector.parse_async(
+        "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+        Some(headers),
+    ))?;
+    let model = detection
+        .get_known_device()
+        .and_then(|x| x.device.as_ref())
+        .and_then(|x| x.model.as_deref());
+    assert_eq!(model, Some("Pixel 7"));
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+
+use rust_device_detector::batch::CancellationToken;
+use rust_device_detector::device_detector::DeviceDetector;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const TRAFFIC: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+    "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+];
+
+#[test]
+fn test_parse_batch() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let detections = detector.parse_batch(TRAFFIC);
+    assert_eq!(detections.len(), TRAFFIC.len());
+    for (ua, detection) in TRAFFIC.iter().zip(&detections) {
+        let expected = detector.parse(ua, None)?;
+        let detection = detection.as_ref().expect("detection");
+        assert_eq!(format!("{:?}", detection), format!("{:?}", expected));
+    }
+
+    assert!(detector.parse_batch(&[]).is_empty());
+
+    Ok(())
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_par_parse_batch() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    // enough user agents for rayon to split them between threads
+    let uas: Vec<&str> = TRAFFIC.iter().copied().cycle().take(300).collect();
+    let detections = detector.par_parse_batch(&uas);
+    assert_eq!(detections.len(), uas.len());
+    for (ua, detection) in uas.iter().zip(&detections) {
+        let expected = detector.parse(ua, None)?;
+        let detection = detection.as_ref().expect("detection");
+        assert_eq!(format!("{:?}", detection), format!("{:?}", expected));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_batch_with_cancellation() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let batch = detector.parse_batch_with_cancellation(TRAFFIC, &CancellationToken::new());
+    assert!(!batch.cancelled);
+    assert_eq!(batch.detections.len(), TRAFFIC.len());
+    for (ua, detection) in TRAFFIC.iter().zip(&batch.detections) {
+        let expected = detector.parse(ua, None)?;
+        let detection = detection.as_ref().expect("detection");
+        assert_eq!(format!("{:?}", detection), format!("{:?}", expected));
+    }
+
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let batch = detector.parse_batch_with_cancellation(TRAFFIC, &cancel);
+    assert!(batch.cancelled);
+    assert!(batch.detections.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_detect_stream_cancelled_part_way() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let flag = Arc::new(AtomicBool::new(false));
+    let mut stream = detector.detect_stream(TRAFFIC.iter().cycle(), flag.clone().into());
+
+    let mut detections = Vec::new();
+    for detection in stream.by_ref() {
+        detections.push(detection?);
+        if detections.len() == 5 {
+            flag.store(true, Ordering::Relaxed);
+        }
+    }
+    assert!(stream.cancelled());
+    assert_eq!(detections.len(), 5);
+    assert!(detections[2].is_bot());
+    assert!(!detections[3].is_bot());
+
+    let stream = detector.detect_stream(vec!["curl/7.68.0".to_owned()], CancellationToken::new());
+    assert_eq!(stream.count(), 1);
+
+    Ok(())
+}
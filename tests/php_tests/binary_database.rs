@@ -0,0 +1,79 @@
+use anyhow::Result;
+
+use std::sync::Arc;
+
+use rust_device_detector::binary_database::{compile_dir, compile_embedded};
+use rust_device_detector::database::{diff_detectors, Database};
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::replay::load_corpus;
+
+use crate::database::copy_database;
+
+#[test]
+fn test_binary_database_detects_the_same() -> Result<()> {
+    let db = Database::from_binary(&compile_embedded()?)?;
+    assert_eq!(db.info(), Database::from_dir("data/regexes")?.info());
+
+    let mut corpus = Vec::new();
+    for file in [
+        "bots.yml",
+        "camera.yml",
+        "clienthints-app.yml",
+        "desktop.yml",
+        "smartphone-1.yml",
+        "tv.yml",
+    ] {
+        corpus.extend(load_corpus(format!("tests/data/fixtures/{}", file))?);
+    }
+
+    let yaml = DeviceDetector::new_with_database(Arc::new(Database::from_dir("data/regexes")?));
+    let binary = DeviceDetector::new_with_database(Arc::new(db));
+    let diff = diff_detectors(&yaml, &binary, &corpus)?;
+    assert_eq!(diff.total, corpus.len());
+    assert_eq!(diff.changed, 0, "{:#?}", diff.fields);
+
+    Ok(())
+}
+
+#[test]
+fn test_binary_database_from_dir() -> Result<()> {
+    let dir = copy_database("binary")?;
+    let bots = dir.join("bots.yml");
+    let contents = std::fs::read_to_string(&bots)?;
+    std::fs::write(
+        &bots,
+        contents.replace("name: 'Googlebot'", "name: 'Renamed Googlebot'"),
+    )?;
+
+    let db = Database::from_binary(&compile_dir(&dir)?)?;
+    assert!(db.bot_definitions().any(|x| x.name == "Renamed Googlebot"));
+
+    std::fs::remove_file(dir.join("oss.yml"))?;
+    assert!(compile_dir(&dir).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_binary_database() -> Result<()> {
+    let bytes = compile_embedded()?;
+
+    let err = Database::from_binary(b"not a database")
+        .err()
+        .expect("no magic");
+    assert!(err.to_string().contains("not a binary database"), "{}", err);
+
+    let mut other_version = bytes.clone();
+    other_version[4] += 1;
+    let err = Database::from_binary(&other_version)
+        .err()
+        .expect("other format");
+    assert!(err.to_string().contains("format"), "{}", err);
+
+    let err = Database::from_binary(&bytes[..bytes.len() / 2])
+        .err()
+        .expect("truncated");
+    assert!(err.to_string().contains("truncated"), "{}", err);
+
+    Ok(())
+}
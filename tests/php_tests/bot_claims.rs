@@ -0,0 +1,69 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::{Detection, DeviceDetector};
+use rust_device_detector::parsers::device::DeviceType;
+
+use crate::utils;
+
+const GOOGLEBOT_SMARTPHONE: &str = "Mozilla/5.0 (Linux; Android 6.0.1; Nexus 5X Build/MMB29P) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.71 Mobile Safari/537.36 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+
+#[test]
+fn test_bot_claims() -> Result<()> {
+    let detector = DeviceDetector::new().with_bot_claims(true);
+
+    let detection = detector.parse(GOOGLEBOT_SMARTPHONE, None)?;
+    let bot = detection.get_bot().expect("still a bot");
+    assert_eq!(bot.name, "Googlebot");
+
+    let claimed = bot.claimed.as_ref().expect("claims detected");
+    assert_eq!(claimed.client.as_ref().unwrap().name, "Chrome Mobile");
+    assert_eq!(claimed.os.as_ref().unwrap().name, "Android");
+    let device = claimed.device.as_ref().unwrap();
+    assert_eq!(device.device_type, Some(DeviceType::SmartPhone));
+    assert_eq!(device.model.as_deref(), Some("Nexus 5X"));
+
+    let json = detection.clone().to_value();
+    assert_eq!(json["bot"]["claimed"]["client"]["name"], "Chrome Mobile");
+    assert_eq!(json["bot"]["claimed"]["os"]["name"], "Android");
+
+    let parsed: Detection = serde_json::from_str(&serde_json::to_string(&detection)?)?;
+    assert_eq!(parsed.to_value(), json);
+
+    // matomo never has them
+    assert_eq!(
+        detection.to_matomo_json(GOOGLEBOT_SMARTPHONE),
+        utils::DD
+            .parse(GOOGLEBOT_SMARTPHONE, None)?
+            .to_matomo_json(GOOGLEBOT_SMARTPHONE)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_bot_claims_off_by_default() -> Result<()> {
+    let detection = utils::DD.parse(GOOGLEBOT_SMARTPHONE, None)?;
+    assert!(detection.get_bot().unwrap().claimed.is_none());
+    assert!(detection.to_value()["bot"].get("claimed").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_bot_claims_known_devices() -> Result<()> {
+    let detector = DeviceDetector::new().with_bot_claims(true);
+
+    // requests which aren't from bots are detected as usual
+    let ua = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    assert_eq!(
+        detector.parse(ua, None)?.to_value(),
+        utils::DD.parse(ua, None)?.to_value()
+    );
+
+    // bots which claim nothing
+    let detection = detector.parse("Googlebot-News", None)?;
+    let claimed = detection.get_bot().unwrap().claimed.as_ref().unwrap();
+    assert!(claimed.client.is_none() && claimed.os.is_none());
+
+    Ok(())
+}
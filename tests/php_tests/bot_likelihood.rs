@@ -0,0 +1,92 @@
+use anyhow::Result;
+
+use rust_device_detector::bot_likelihood::{HeaderAnomaly, HeaderFingerprint};
+use rust_device_detector::device_detector::DeviceDetector;
+
+const CHROME: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+fn headers(names: &[&str]) -> Vec<(String, String)> {
+    names
+        .iter()
+        .map(|x| (x.to_string(), "*".to_string()))
+        .collect()
+}
+
+#[test]
+fn test_fingerprint_off_by_default() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let (detection, likelihood) = detector.parse_with_bot_likelihood(CHROME, &[])?;
+    assert!(!detection.is_bot());
+    assert_eq!(likelihood.score, 0.0);
+    assert!(likelihood.anomalies.is_empty());
+
+    let (_, likelihood) = detector.parse_with_bot_likelihood("Googlebot/2.1", &[])?;
+    assert_eq!(likelihood.score, 1.0);
+
+    let (_, likelihood) = detector.parse_with_bot_likelihood("curl/7.68.0", &[])?;
+    assert!(likelihood.score > 0.5);
+
+    Ok(())
+}
+
+#[test]
+fn test_header_anomalies() -> Result<()> {
+    let detector = DeviceDetector::new().with_header_fingerprint(HeaderFingerprint::new());
+
+    let browser = headers(&[
+        "Host",
+        "User-Agent",
+        "Accept",
+        "Accept-Encoding",
+        "Accept-Language",
+    ]);
+    let (_, likelihood) = detector.parse_with_bot_likelihood(CHROME, &browser)?;
+    assert_eq!(likelihood.score, 0.0);
+    assert!(likelihood.anomalies.is_empty());
+
+    // what python's requests sends, with a browser user agent
+    let script = headers(&[
+        "HTTP_USER_AGENT",
+        "HTTP_ACCEPT_ENCODING",
+        "HTTP_ACCEPT",
+        "HTTP_CONNECTION",
+    ]);
+    let (detection, likelihood) = detector.parse_with_bot_likelihood(CHROME, &script)?;
+    assert!(!detection.is_bot());
+    assert_eq!(likelihood.user_agent_score, 0.0);
+    assert_eq!(
+        likelihood.anomalies,
+        vec![
+            HeaderAnomaly::MissingAcceptLanguage,
+            HeaderAnomaly::AcceptEncodingBeforeAccept
+        ]
+    );
+    assert!((likelihood.score - 0.5).abs() < 1e-6);
+
+    let (_, likelihood) = detector.parse_with_bot_likelihood(CHROME, &[])?;
+    assert_eq!(likelihood.anomalies.len(), 3);
+    assert!((likelihood.score - 0.8).abs() < 1e-6);
+
+    Ok(())
+}
+
+#[test]
+fn test_header_fingerprint_weights() -> Result<()> {
+    let fingerprint = HeaderFingerprint::new()
+        .with_weight(HeaderAnomaly::MissingAcceptLanguage, 0.0)
+        .with_weight(HeaderAnomaly::MissingAccept, 0.9);
+    assert_eq!(fingerprint.weight(HeaderAnomaly::MissingAccept), 0.9);
+
+    let detector = DeviceDetector::new().with_header_fingerprint(fingerprint);
+
+    let (_, likelihood) =
+        detector.parse_with_bot_likelihood(CHROME, &headers(&["accept-encoding"]))?;
+    assert_eq!(likelihood.anomalies, vec![HeaderAnomaly::MissingAccept]);
+    assert!((likelihood.score - 0.9).abs() < 1e-6);
+
+    let (_, likelihood) = detector.parse_with_bot_likelihood(CHROME, &[])?;
+    assert_eq!(likelihood.score, 1.0);
+
+    Ok(())
+}
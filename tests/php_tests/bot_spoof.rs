@@ -0,0 +1,80 @@
+use anyhow::Result;
+
+use rust_device_detector::bot_spoof::{BotSpoofRules, SpoofSignal};
+use rust_device_detector::device_detector::{Bot, DeviceDetector};
+
+const GOOGLEBOT: &str = "Mozilla/5.0 (Linux; Android 6.0.1; Nexus 5X Build/MMB29P) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+
+fn bot(detector: &DeviceDetector, ua: &str, headers: &[(&str, &str)]) -> Result<Bot> {
+    let headers = headers
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    let detection = detector.parse(ua, Some(headers))?;
+    Ok(detection.get_bot().cloned().expect("bot"))
+}
+
+#[test]
+fn test_bot_spoof() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let googlebot = bot(&detector, GOOGLEBOT, &[])?;
+    assert_eq!(googlebot.name, "Googlebot");
+    assert!(!googlebot.bot_spoof_suspected);
+    assert!(googlebot.spoof_signals.is_empty());
+
+    // browsers mix made up brands in, which alone say nothing
+    let grease = bot(
+        &detector,
+        GOOGLEBOT,
+        &[("Sec-CH-UA", r#""Not_A Brand";v="8""#)],
+    )?;
+    assert!(!grease.bot_spoof_suspected);
+
+    let spoofed = bot(
+        &detector,
+        GOOGLEBOT,
+        &[
+            (
+                "Sec-CH-UA",
+                r#""Not_A Brand";v="8", "Chromium";v="120", "Google Chrome";v="120""#,
+            ),
+            ("Sec-CH-UA-Mobile", "?1"),
+            ("Sec-CH-UA-Model", "Pixel 7"),
+        ],
+    )?;
+    assert_eq!(spoofed.name, "Googlebot");
+    assert!(spoofed.bot_spoof_suspected);
+    assert_eq!(
+        spoofed.spoof_signals,
+        vec![SpoofSignal::BrowserBrands, SpoofSignal::DeviceModel]
+    );
+
+    // bots other than search engines aren't checked
+    let brands = [("Sec-CH-UA", r#""Chromium";v="120""#)];
+    let other = bot(&detector, "AhrefsBot/7.0", &brands)?;
+    assert!(!other.bot_spoof_suspected);
+
+    let detector = DeviceDetector::new().with_bot_spoof_rules(BotSpoofRules::none());
+    assert!(!bot(&detector, GOOGLEBOT, &brands)?.bot_spoof_suspected);
+
+    let detector =
+        DeviceDetector::new().with_bot_spoof_rules(BotSpoofRules::none().with_bot("aHrefs Bot"));
+    let other = bot(&detector, "AhrefsBot/7.0", &brands)?;
+    assert_eq!(other.spoof_signals, vec![SpoofSignal::BrowserBrands]);
+
+    Ok(())
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_bot_spoof_cached() -> Result<()> {
+    let detector = DeviceDetector::new_with_cache(100);
+    let brands = [("Sec-CH-UA", r#""Chromium";v="120""#)];
+
+    assert!(!bot(&detector, GOOGLEBOT, &[])?.bot_spoof_suspected);
+    assert!(bot(&detector, GOOGLEBOT, &brands)?.bot_spoof_suspected);
+    assert!(!bot(&detector, GOOGLEBOT, &[])?.bot_spoof_suspected);
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+
+use std::sync::Arc;
+
+use rust_device_detector::canary::Canary;
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::{Detection, DeviceDetector};
+
+use crate::database::copy_database;
+
+const GOOGLEBOT: &str = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+const CHROME: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+fn candidate() -> Result<DeviceDetector> {
+    let dir = copy_database("canary")?;
+    let bots = dir.join("bots.yml");
+    let contents = std::fs::read_to_string(&bots)?;
+    std::fs::write(
+        &bots,
+        contents.replace("name: 'Googlebot'", "name: 'Renamed Googlebot'"),
+    )?;
+
+    let db = Database::from_dir(&dir)?;
+    std::fs::remove_dir_all(dir)?;
+    Ok(DeviceDetector::new().with_database(Arc::new(db)))
+}
+
+#[test]
+fn test_canary_records_divergences() -> Result<()> {
+    let canary = Canary::new(DeviceDetector::new(), candidate()?, 1.0);
+
+    for _ in 0..3 {
+        // always answered by the primary detector
+        match canary.parse(GOOGLEBOT, None)? {
+            Detection::Bot(bot) => assert_eq!(bot.name, "Googlebot"),
+            x => panic!("expected a bot, got {:?}", x),
+        }
+        canary.parse(CHROME, None)?;
+    }
+
+    let diff = canary.divergences();
+    assert_eq!(diff.total, 6);
+    assert_eq!(diff.changed, 3);
+    assert_eq!(diff.fields.keys().collect::<Vec<_>>(), vec!["bot.name"]);
+    assert_eq!(diff.fields["bot.name"].count, 3);
+    assert_eq!(canary.candidate_errors(), 0);
+
+    canary.reset();
+    assert_eq!(canary.divergences().total, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_canary_sampling() -> Result<()> {
+    let canary = Canary::new(DeviceDetector::new(), DeviceDetector::new(), 0.25);
+    for _ in 0..100 {
+        canary.parse(CHROME, None)?;
+    }
+    let diff = canary.divergences();
+    assert_eq!(diff.total, 25);
+    assert_eq!(diff.changed, 0);
+
+    let canary = Canary::new(DeviceDetector::new(), DeviceDetector::new(), 0.0);
+    for _ in 0..10 {
+        canary.parse(CHROME, None)?;
+    }
+    assert_eq!(canary.divergences().total, 0);
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+
+use rust_device_detector::catalog::DeviceSpec;
+use rust_device_detector::device_detector::DeviceDetector;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const S23_ULTRA: &str = "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+const DESKTOP: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+fn spec(detector: &DeviceDetector, ua: &str) -> Result<Option<DeviceSpec>> {
+    let detection = detector.parse(ua, None)?;
+    Ok(detection
+        .get_known_device()
+        .and_then(|x| x.device.as_ref()?.spec.clone()))
+}
+
+#[test]
+fn test_device_catalog() -> Result<()> {
+    let s23_ultra = DeviceSpec {
+        screen_size: Some(6.8),
+        ram_mb: Some(8192),
+        release_year: Some(2023),
+    };
+
+    assert_eq!(spec(&DeviceDetector::new(), S23_ULTRA)?, None);
+
+    let mut catalog = HashMap::new();
+    catalog.insert(
+        ("Samsung".to_owned(), "Galaxy S23 Ultra".to_owned()),
+        s23_ultra.clone(),
+    );
+    let detector = DeviceDetector::new().with_device_catalog(catalog);
+    assert_eq!(spec(&detector, S23_ULTRA)?, Some(s23_ultra.clone()));
+    assert_eq!(spec(&detector, DESKTOP)?, None);
+
+    let detection = detector.parse(S23_ULTRA, None)?;
+    let device = detection.get_known_device().and_then(|x| x.device.as_ref());
+    let json = serde_json::to_value(device)?;
+    assert_eq!(json["spec"]["release_year"], 2023);
+
+    // only devices with a brand and model are looked up
+    let lookups = Arc::new(AtomicUsize::new(0));
+    let counter = lookups.clone();
+    let detector = DeviceDetector::new().with_device_catalog(move |brand: &str, model: &str| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        (brand == "Samsung" && model == "Galaxy S23 Ultra").then(|| s23_ultra.clone())
+    });
+    assert!(spec(&detector, S23_ULTRA)?.is_some());
+    assert_eq!(spec(&detector, DESKTOP)?, None);
+    assert_eq!(lookups.load(Ordering::Relaxed), 1);
+
+    Ok(())
+}
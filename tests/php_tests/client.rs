@@ -0,0 +1,116 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::parsers::client::browsers::engines::Engine;
+use rust_device_detector::parsers::client::{Client, ClientType};
+
+fn client(detector: &DeviceDetector, ua: &str) -> Result<Option<Client>> {
+    let detection = detector.parse(ua, None)?;
+    Ok(detection.get_known_device().and_then(|x| x.client.clone()))
+}
+
+#[test]
+fn test_browser_short_code() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let chrome = client(
+        &detector,
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    )?
+    .expect("client");
+    assert_eq!(chrome.r#type, ClientType::Browser);
+    assert_eq!(chrome.short_code.as_deref(), Some("CH"));
+    assert_eq!(chrome.engine.as_deref(), Some("Blink"));
+    assert!(chrome.engine_version.is_some());
+
+    let firefox = client(
+        &detector,
+        "Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0",
+    )?
+    .expect("client");
+    assert_eq!(firefox.short_code.as_deref(), Some("FF"));
+    assert_eq!(firefox.engine.as_deref(), Some("Gecko"));
+
+    Ok(())
+}
+
+#[test]
+fn test_non_browser_has_no_short_code_or_engine() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let curl = client(&detector, "curl/7.68.0")?.expect("client");
+    assert_eq!(curl.r#type, ClientType::Library);
+    assert_eq!(curl.short_code, None);
+    assert_eq!(curl.engine, None);
+    assert_eq!(curl.engine_version, None);
+
+    let spotify = client(&detector, "Spotify/8.6.72 iOS/13.5.1 (iPhone9,2)")?.expect("client");
+    assert_eq!(spotify.r#type, ClientType::MobileApp);
+    assert_eq!(spotify.short_code, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_engine_never_empty() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    for ua in [
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1",
+        "Opera/9.80 (J2ME/MIDP; Opera Mini/4.2.14912/870; U; id) Presto/2.4.15",
+        "Mozilla/4.0 (compatible; MSIE 6.0; Windows NT 5.1)",
+        "Lynx/2.8.9rel.1 libwww-FM/2.14",
+        "curl/7.68.0",
+    ] {
+        let Some(client) = client(&detector, ua)? else {
+            continue;
+        };
+
+        assert_ne!(client.engine.as_deref(), Some(""), "{}", ua);
+        assert_ne!(client.engine_version.as_deref(), Some(""), "{}", ua);
+        if client.engine.is_none() {
+            assert_eq!(client.engine_version, None, "{}", ua);
+        }
+        if client.r#type != ClientType::Browser {
+            assert_eq!(client.short_code, None, "{}", ua);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_engine_lineage() {
+    assert_eq!(Engine::from_name("blink"), Some(Engine::Blink));
+    assert_eq!(Engine::from_name("Text-based"), Some(Engine::TextBased));
+    assert_eq!(Engine::from_name("Mosaic"), None);
+
+    assert_eq!(Engine::Presto.vendor(), Some("Opera Software"));
+    assert_eq!(Engine::Presto.successor(), Some(Engine::Blink));
+    assert_eq!(
+        Engine::Trident.successors().collect::<Vec<_>>(),
+        vec![Engine::Edge, Engine::Blink]
+    );
+    assert_eq!(Engine::Blink.successor(), None);
+    assert_eq!(Engine::Blink.forked_from(), Some(Engine::WebKit));
+    assert_eq!(Engine::Goanna.forked_from(), Some(Engine::Gecko));
+}
+
+#[test]
+fn test_detected_engines_are_known() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    for ua in [
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/70.0.3538.102 Safari/537.36 Edge/18.18362",
+        "Opera/9.80 (Windows NT 6.1; WOW64) Presto/2.12.388 Version/12.18",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:102.0) Gecko/20100101 Goanna/6.6 Firefox/102.0 PaleMoon/33.0.0",
+        "Lynx/2.8.9rel.1 libwww-FM/2.14 SSL-MM/1.4.1 OpenSSL/1.1.1d",
+    ] {
+        let client = client(&detector, ua)?.expect("client");
+        let engine = client.engine.as_deref().expect("engine");
+        assert!(Engine::from_name(engine).is_some(), "{}: {}", ua, engine);
+    }
+
+    Ok(())
+}
@@ -2,21 +2,24 @@ use anyhow::Result;
 
 use rust_device_detector::client_hints::ClientHint;
 use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::parsers::device::{Confidence, DeviceType};
 
 #[test]
 fn test_form_factors_parsing() -> Result<()> {
     // Test FormFactors header parsing like in PHP tests
-    let headers = vec![
-        ("sec-ch-ua-form-factors".to_string(), r#""Desktop""#.to_string()),
-    ];
+    let headers = vec![(
+        "sec-ch-ua-form-factors".to_string(),
+        r#""Desktop""#.to_string(),
+    )];
 
     let client_hint = ClientHint::from_headers(headers)?;
     assert_eq!(client_hint.form_factors, vec!["desktop"]);
 
     // Test multiple form factors
-    let headers = vec![
-        ("sec-ch-ua-form-factors".to_string(), r#""Mobile", "Touch""#.to_string()),
-    ];
+    let headers = vec![(
+        "sec-ch-ua-form-factors".to_string(),
+        r#""Mobile", "Touch""#.to_string(),
+    )];
 
     let client_hint = ClientHint::from_headers(headers)?;
     assert_eq!(client_hint.form_factors, vec!["mobile", "touch"]);
@@ -28,21 +31,508 @@ fn test_form_factors_parsing() -> Result<()> {
 fn test_form_factors_device_detection() -> Result<()> {
     // Test that FormFactors correctly detects device type
     let detector = DeviceDetector::new();
-    
-    let headers = vec![
-        ("sec-ch-ua-form-factors".to_string(), r#""Desktop""#.to_string()),
-    ];
-    
+
+    let headers = vec![(
+        "sec-ch-ua-form-factors".to_string(),
+        r#""Desktop""#.to_string(),
+    )];
+
     let result = detector.parse("", Some(headers))?;
-    
+
     let device_type: Option<&str> = result
         .get_known_device()
         .and_then(|dev| dev.device.as_ref())
         .and_then(|dev| dev.device_type.as_ref())
         .map(|t| t.as_str());
-    
+
     // Should detect desktop device type from FormFactors
     assert_eq!(device_type, Some("desktop"));
 
     Ok(())
-}
\ No newline at end of file
+}
+
+fn form_factor_device_type(ua: &str, form_factors: &str) -> Result<Option<String>> {
+    let detector = DeviceDetector::new();
+
+    let headers = vec![(
+        "sec-ch-ua-form-factors".to_string(),
+        form_factors.to_string(),
+    )];
+
+    let res = detector
+        .parse(ua, Some(headers))?
+        .get_known_device()
+        .and_then(|dev| dev.device.as_ref())
+        .and_then(|dev| dev.device_type.as_ref())
+        .map(|t| t.as_str().to_owned());
+
+    Ok(res)
+}
+
+#[test]
+fn test_wearable_form_factors_parsing() -> Result<()> {
+    let headers = vec![("sec-ch-ua-form-factors".to_string(), r#""XR""#.to_string())];
+
+    let client_hint = ClientHint::from_headers(headers)?;
+    assert_eq!(client_hint.form_factors, vec!["xr"]);
+
+    let headers = vec![(
+        "sec-ch-ua-form-factors".to_string(),
+        r#""Watch", "Mobile""#.to_string(),
+    )];
+
+    let client_hint = ClientHint::from_headers(headers)?;
+    assert_eq!(client_hint.form_factors, vec!["watch", "mobile"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_wearable_form_factors_device_detection() -> Result<()> {
+    let phone = "Mozilla/5.0 (Linux; Android 11; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+    let desktop = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    let tv = "Mozilla/5.0 (Linux; Android 9; Android TV) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+    // without a user agent, the form factors are all there is
+    assert_eq!(
+        form_factor_device_type("", r#""XR""#)?.as_deref(),
+        Some("wearable")
+    );
+    assert_eq!(
+        form_factor_device_type("", r#""Watch""#)?.as_deref(),
+        Some("wearable")
+    );
+
+    // without form factors, the user agent decides
+    assert_eq!(
+        form_factor_device_type(phone, "")?.as_deref(),
+        Some("smartphone")
+    );
+    assert_eq!(
+        form_factor_device_type(desktop, "")?.as_deref(),
+        Some("desktop")
+    );
+
+    // headsets and watches win over the generic types their user agents look like
+    assert_eq!(
+        form_factor_device_type(phone, r#""Watch""#)?.as_deref(),
+        Some("wearable")
+    );
+    assert_eq!(
+        form_factor_device_type(phone, r#""Mobile", "XR""#)?.as_deref(),
+        Some("wearable")
+    );
+    assert_eq!(
+        form_factor_device_type(desktop, r#""XR""#)?.as_deref(),
+        Some("wearable")
+    );
+
+    // but not over more specific ones
+    assert_eq!(
+        form_factor_device_type(tv, r#""XR""#)?.as_deref(),
+        Some("tv")
+    );
+    assert_eq!(
+        form_factor_device_type("", r#""Automotive", "XR""#)?.as_deref(),
+        Some("car browser")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_to_headers_round_trip() -> Result<()> {
+    let headers = vec![
+        (
+            "sec-ch-ua".to_string(),
+            r#""Not_A Brand";v="8", "Chromium";v="120", "Google Chrome";v="120""#.to_string(),
+        ),
+        ("sec-ch-ua-arch".to_string(), r#""arm""#.to_string()),
+        ("sec-ch-ua-bitness".to_string(), r#""64""#.to_string()),
+        ("sec-ch-ua-mobile".to_string(), "?1".to_string()),
+        ("sec-ch-ua-model".to_string(), r#""Pixel 7""#.to_string()),
+        ("sec-ch-ua-platform".to_string(), r#""Android""#.to_string()),
+        ("sec-ch-ua-platform-version".to_string(), r#""13.0.0""#.to_string()),
+        ("sec-ch-ua-full-version".to_string(), r#""120.0.6099.43""#.to_string()),
+        (
+            "sec-ch-ua-full-version-list".to_string(),
+            r#""Not_A Brand";v="8.0.0.0", "Chromium";v="120.0.6099.43", "Google Chrome";v="120.0.6099.43""#.to_string(),
+        ),
+        ("sec-ch-ua-form-factors".to_string(), r#""Mobile", "XR""#.to_string()),
+        ("x-requested-with".to_string(), "com.example.app".to_string()),
+    ];
+
+    let client_hint = ClientHint::from_headers(headers)?;
+    let res = client_hint.to_headers();
+
+    let get = |name: &str| {
+        res.iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    assert_eq!(get("Sec-CH-UA-Mobile"), Some("?1"));
+    assert_eq!(get("Sec-CH-UA-Model"), Some(r#""Pixel 7""#));
+    assert_eq!(
+        get("Sec-CH-UA"),
+        Some(r#""Not_A Brand";v="8", "Chromium";v="120", "Google Chrome";v="120""#)
+    );
+    assert_eq!(get("Sec-CH-UA-Form-Factors"), Some(r#""Mobile", "XR""#));
+
+    assert_eq!(ClientHint::from_headers(res)?, client_hint);
+
+    // nothing set, not even the mobile flag, means no headers
+    let res = ClientHint::default().to_headers();
+    assert_eq!(res, vec![]);
+    assert_eq!(ClientHint::from_headers(res)?, ClientHint::default());
+
+    let client_hint = ClientHint {
+        model: Some(r#"Pixel "7" \ Pro"#.to_owned()),
+        full_version_list: vec![(r#"Brand "X""#.to_owned(), "1.0".to_owned())],
+        ..Default::default()
+    };
+    let res = client_hint.to_headers();
+    assert!(res.contains(&(
+        "Sec-CH-UA-Model".to_string(),
+        r#""Pixel \"7\" \\ Pro""#.to_string()
+    )));
+    assert_eq!(ClientHint::from_headers(res)?, client_hint);
+
+    Ok(())
+}
+
+#[test]
+fn test_to_headers_round_trip_chrome() -> Result<()> {
+    // what Chrome on Windows sends once the server asks for every hint, in the
+    // order to_headers lists them
+    let headers: Vec<(String, String)> = [
+        (
+            "Sec-CH-UA",
+            r#""Not_A Brand";v="8", "Chromium";v="120", "Google Chrome";v="120""#,
+        ),
+        ("Sec-CH-UA-Mobile", "?0"),
+        ("Sec-CH-UA-Platform", r#""Windows""#),
+        ("Sec-CH-UA-Platform-Version", r#""15.0.0""#),
+        ("Sec-CH-UA-Arch", r#""x86""#),
+        ("Sec-CH-UA-Bitness", r#""64""#),
+        ("Sec-CH-UA-Full-Version", r#""120.0.6099.130""#),
+        (
+            "Sec-CH-UA-Full-Version-List",
+            r#""Not_A Brand";v="8.0.0.0", "Chromium";v="120.0.6099.130", "Google Chrome";v="120.0.6099.130""#,
+        ),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_owned(), value.to_owned()))
+    .collect();
+
+    assert_eq!(
+        ClientHint::from_headers(headers.clone())?.to_headers(),
+        headers
+    );
+
+    // as is what it sends by default
+    let headers = headers[..3].to_vec();
+    assert_eq!(
+        ClientHint::from_headers(headers.clone())?.to_headers(),
+        headers
+    );
+
+    Ok(())
+}
+
+const SEC_CH_UA: &str = r#""Not_A Brand";v="8", "Chromium";v="120", "Google Chrome";v="120""#;
+
+fn browser(ua: &str, headers: Vec<(String, String)>) -> Result<Option<(String, Option<String>)>> {
+    let detector = DeviceDetector::new();
+    let res = detector
+        .parse(ua, Some(headers))?
+        .get_known_device()
+        .and_then(|x| x.client.as_ref())
+        .map(|x| (x.name.clone(), x.version.clone()));
+    Ok(res)
+}
+
+#[test]
+fn test_sec_ch_ua_only_parsing() -> Result<()> {
+    let majors = vec![
+        ("Not_A Brand".to_owned(), "8".to_owned()),
+        ("Chromium".to_owned(), "120".to_owned()),
+        ("Google Chrome".to_owned(), "120".to_owned()),
+    ];
+
+    let headers = vec![("sec-ch-ua".to_string(), SEC_CH_UA.to_string())];
+    let hints = ClientHint::from_headers(headers)?;
+    assert_eq!(hints.brands, majors);
+    assert!(hints.full_version_list.is_empty());
+    assert_eq!(hints.brand_versions(), majors);
+
+    // an empty full version list, as some proxies forward, doesn't lose the brands
+    let headers = vec![
+        ("sec-ch-ua".to_string(), SEC_CH_UA.to_string()),
+        ("sec-ch-ua-full-version-list".to_string(), "".to_string()),
+    ];
+    assert_eq!(ClientHint::from_headers(headers)?.brand_versions(), majors);
+
+    // the full version list wins regardless of header order
+    let full = r#""Not_A Brand";v="8.0.0.0", "Chromium";v="120.0.6099.71", "Google Chrome";v="120.0.6099.71""#;
+    for headers in [
+        vec![
+            ("sec-ch-ua".to_string(), SEC_CH_UA.to_string()),
+            ("sec-ch-ua-full-version-list".to_string(), full.to_string()),
+        ],
+        vec![
+            ("sec-ch-ua-full-version-list".to_string(), full.to_string()),
+            ("sec-ch-ua".to_string(), SEC_CH_UA.to_string()),
+        ],
+    ] {
+        let hints = ClientHint::from_headers(headers)?;
+        assert_eq!(hints.brand_versions()[2].1, "120.0.6099.71");
+        assert_eq!(hints.brands, majors);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_sec_ch_ua_only_detection() -> Result<()> {
+    let headers = || {
+        vec![
+            ("sec-ch-ua".to_string(), SEC_CH_UA.to_string()),
+            ("sec-ch-ua-mobile".to_string(), "?0".to_string()),
+            ("sec-ch-ua-platform".to_string(), r#""Windows""#.to_string()),
+        ]
+    };
+
+    // the hints alone give the major version
+    assert_eq!(
+        browser("", headers())?,
+        Some(("Chrome".to_owned(), Some("120".to_owned())))
+    );
+
+    // which the user agent adds detail to, whether reduced or not
+    let reduced = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    assert_eq!(
+        browser(reduced, headers())?,
+        Some(("Chrome".to_owned(), Some("120.0.0.0".to_owned())))
+    );
+
+    let full = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.71 Safari/537.36";
+    assert_eq!(
+        browser(full, headers())?,
+        Some(("Chrome".to_owned(), Some("120.0.6099.71".to_owned())))
+    );
+
+    // brands other than chrome are picked over chromium
+    let opera = vec![(
+        "sec-ch-ua".to_string(),
+        r#""Opera";v="106", "Not:A-Brand";v="99", "Chromium";v="120""#.to_string(),
+    )];
+    assert_eq!(
+        browser(&format!("{} OPR/106.0.0.0", reduced), opera)?,
+        Some(("Opera".to_owned(), Some("106.0.0.0".to_owned())))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_device_user_agent_parsing() -> Result<()> {
+    let headers = vec![
+        ("X-Device-User-Agent".to_string(), "device".to_string()),
+        (
+            "HTTP_X_OPERAMINI_PHONE_UA".to_string(),
+            " phone ".to_string(),
+        ),
+        ("X-UCBrowser-Device-UA".to_string(), "uc".to_string()),
+    ];
+
+    let client_hint = ClientHint::from_headers(headers)?;
+    assert_eq!(client_hint.device_user_agent.as_deref(), Some("phone"));
+
+    // not a client hint, so not round tripped
+    assert!(client_hint
+        .to_headers()
+        .iter()
+        .all(|(_, value)| value != "phone"));
+
+    let headers = vec![("X-OperaMini-Phone-UA".to_string(), "".to_string())];
+    let client_hint = ClientHint::from_headers(headers)?;
+    assert_eq!(client_hint.device_user_agent, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_device_user_agent_detection() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let headers = vec![(
+        "X-OperaMini-Phone-UA".to_string(),
+        "Mozilla/5.0 (Linux; Android 10; SM-A505F) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/83.0.4103.106 Mobile Safari/537.36".to_string(),
+    )];
+    let result = detector.parse(
+        "Opera/9.80 (J2ME/MIDP; Opera Mini/4.2.14912/870; U; id) Presto/2.4.15",
+        Some(headers),
+    )?;
+    let known = result.get_known_device().expect("known device");
+
+    assert_eq!(
+        known.client.as_ref().map(|x| x.name.as_str()),
+        Some("Opera Mini")
+    );
+    assert_eq!(known.os.as_ref().map(|x| x.name.as_str()), Some("Android"));
+    assert_eq!(
+        known.device.as_ref().and_then(|x| x.brand.as_deref()),
+        Some("Samsung")
+    );
+
+    let headers = vec![(
+        "X-UCBrowser-Device-UA".to_string(),
+        "Mozilla/5.0 (Linux; Android 9; Redmi Note 7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/74.0.3729.136 Mobile Safari/537.36".to_string(),
+    )];
+    let result = detector.parse(
+        "UCWEB/2.0 (MIDP-2.0; U; Adr 9; en-US; Redmi_Note_7) U2/1.0.0 UCBrowser/12.13.0.1207 U2/1.0.0 Mobile",
+        Some(headers),
+    )?;
+    let known = result.get_known_device().expect("known device");
+
+    assert_eq!(
+        known.client.as_ref().map(|x| x.name.as_str()),
+        Some("UC Browser")
+    );
+    assert_eq!(known.os.as_ref().map(|x| x.name.as_str()), Some("Android"));
+    assert_eq!(
+        known.device.as_ref().and_then(|x| x.brand.as_deref()),
+        Some("Xiaomi")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_is_reduced_user_agent() {
+    use rust_device_detector::client_hints::is_reduced_user_agent;
+
+    for ua in [
+        "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/99.0.0.0 Safari/537.36",
+        "Mozilla/5.0 (X11; CrOS x86_64 14541.0.0) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    ] {
+        assert!(is_reduced_user_agent(ua), "{}", ua);
+    }
+
+    for ua in [
+        "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0",
+        "Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0",
+    ] {
+        assert!(!is_reduced_user_agent(ua), "{}", ua);
+    }
+}
+
+#[test]
+fn test_mobile_hint_fallback() -> Result<()> {
+    use rust_device_detector::parsers::device::MobileHintFallback;
+
+    let device_type = |detector: &DeviceDetector, mobile: Option<&str>| -> Result<Option<String>> {
+        let headers = mobile
+            .map(|x| ("sec-ch-ua-mobile".to_string(), x.to_string()))
+            .into_iter()
+            .collect();
+        let res = detector
+            .parse_hints_only(headers)?
+            .get_known_device()
+            .and_then(|dev| dev.device.as_ref())
+            .and_then(|dev| dev.device_type.as_ref())
+            .map(|t| t.as_str().to_owned());
+        Ok(res)
+    };
+
+    // off by default, as in php
+    let detector = DeviceDetector::new();
+    assert_eq!(device_type(&detector, Some("?1"))?, None);
+    assert_eq!(device_type(&detector, Some("?0"))?, None);
+
+    let detector =
+        DeviceDetector::new().with_mobile_hint_fallback(MobileHintFallback::SmartPhoneOrDesktop);
+    assert_eq!(
+        device_type(&detector, Some("?1"))?.as_deref(),
+        Some("smartphone")
+    );
+    assert_eq!(
+        device_type(&detector, Some("?0"))?.as_deref(),
+        Some("desktop")
+    );
+    assert_eq!(device_type(&detector, None)?, None);
+
+    let detector = DeviceDetector::new().with_mobile_hint_fallback(MobileHintFallback::SmartPhone);
+    assert_eq!(
+        device_type(&detector, Some("?1"))?.as_deref(),
+        Some("smartphone")
+    );
+    assert_eq!(device_type(&detector, Some("?0"))?, None);
+
+    // a device type from the user agent wins
+    let detector =
+        DeviceDetector::new().with_mobile_hint_fallback(MobileHintFallback::SmartPhoneOrDesktop);
+    let headers = vec![("sec-ch-ua-mobile".to_string(), "?0".to_string())];
+    let detection = detector.parse(
+        "Mozilla/5.0 (iPad; CPU OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1",
+        Some(headers),
+    )?;
+    assert!(detection.get_known_device().is_some_and(|x| x.is_tablet()));
+
+    Ok(())
+}
+
+#[test]
+fn test_chrome_os_platform() -> Result<()> {
+    let detector = DeviceDetector::new();
+    let known = |ua: &str, headers: &[(&str, &str)]| -> Result<_> {
+        let headers = headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let res = detector.parse(ua, Some(headers))?;
+        Ok(res.get_known_device().cloned().expect("known device"))
+    };
+
+    // Chrome's user agent on Chrome OS is frozen to the one of Linux.
+    let linux = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    let res = known(linux, &[("Sec-CH-UA-Platform", r#""Chrome OS""#)])?;
+    assert_eq!(res.os.as_ref().map(|x| x.name.as_str()), Some("Chrome OS"));
+    let device = res.device.expect("device");
+    assert_eq!(device.device_type, Some(DeviceType::Desktop));
+    assert_eq!(device.confidence.device_type, Some(Confidence::Inferred));
+    assert_eq!(device.brand, None);
+
+    let res = known(linux, &[("Sec-CH-UA-Platform", r#""Linux""#)])?;
+    assert_eq!(res.os.as_ref().map(|x| x.name.as_str()), Some("GNU/Linux"));
+    assert_eq!(
+        res.device.and_then(|x| x.confidence.device_type),
+        Some(Confidence::Fallback)
+    );
+
+    // android apps name the Chromebook they run on.
+    let app = "Mozilla/5.0 (Linux; Android 9; HP Chromebook x360 14) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    let res = known(app, &[("Sec-CH-UA-Platform", r#""Chrome OS""#)])?;
+    assert_eq!(res.os.as_ref().map(|x| x.name.as_str()), Some("Chrome OS"));
+    let device = res.device.expect("device");
+    assert_eq!(device.device_type, Some(DeviceType::Desktop));
+    assert_eq!(device.brand.as_deref(), Some("HP"));
+
+    let app = "Mozilla/5.0 (Linux; Android 11; Lenovo Chromebook Duet) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    let res = known(
+        app,
+        &[
+            ("Sec-CH-UA-Platform", r#""Chrome OS""#),
+            ("Sec-CH-UA-Form-Factors", r#""Tablet""#),
+        ],
+    )?;
+    let device = res.device.expect("device");
+    assert_eq!(device.device_type, Some(DeviceType::Tablet));
+    assert_eq!(device.brand.as_deref(), Some("Lenovo"));
+
+    Ok(())
+}
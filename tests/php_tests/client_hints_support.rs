@@ -0,0 +1,54 @@
+use rust_device_detector::client_hints::is_supported;
+use rust_device_detector::device_detector::DeviceDetector;
+
+#[test]
+fn test_client_hints_support() {
+    let cases = [
+        ("Chrome", "120.0.6099.144", Some(true)),
+        ("Chrome", "89", Some(true)),
+        ("Chrome", "88.0.4324.190", Some(false)),
+        ("chrome mobile", "100.0", Some(true)),
+        ("Microsoft Edge", "18.17763", Some(false)),
+        ("Microsoft Edge", "120.0.0.0", Some(true)),
+        ("Samsung Browser", "14.2", Some(false)),
+        ("Samsung Browser", "23.0", Some(true)),
+        ("Firefox", "121.0", Some(false)),
+        ("Mobile Safari", "17.1", Some(false)),
+        ("Chrome Mobile iOS", "120.0.6099.119", Some(false)),
+        // a fork of firefox, by its family
+        ("Waterfox", "6.0", Some(false)),
+        // chromium based, but unknown when it started sending hints
+        ("Kiwi", "120.0", None),
+        ("Not A Browser", "1.0", None),
+    ];
+
+    for (browser, version, expected) in cases {
+        assert_eq!(
+            is_supported(browser, version),
+            expected,
+            "{} {}",
+            browser,
+            version
+        );
+    }
+}
+
+#[test]
+fn test_client_hints_support_of_detection() -> anyhow::Result<()> {
+    let detector = DeviceDetector::new();
+    let detection = detector.parse(
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        None,
+    )?;
+    let client = detection
+        .get_known_device()
+        .and_then(|x| x.client.as_ref())
+        .expect("client");
+
+    assert_eq!(
+        is_supported(&client.name, client.version.as_deref().unwrap_or("")),
+        Some(true)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use anyhow::Result;
+
+use rust_device_detector::client_tags::{ClientTag, ClientTagMapping};
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::parsers::client::Client;
+
+fn client(detector: &DeviceDetector, ua: &str) -> Result<Client> {
+    let detection = detector.parse(ua, None)?;
+    Ok(detection
+        .get_known_device()
+        .and_then(|x| x.client.clone())
+        .expect("client"))
+}
+
+#[test]
+fn test_client_tags() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let cases = [
+        (
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+            vec![],
+        ),
+        (
+            "Mozilla/5.0 (Linux; Android 12) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/120.0.0.0 Mobile DuckDuckGo/5 Safari/537.36",
+            vec![ClientTag::PrivacyBrowser],
+        ),
+        (
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:102.0) Gecko/20100101 Firefox/102.0 Waterfox/G5.1.0",
+            vec![ClientTag::ModifiedClient],
+        ),
+        (
+            "Wget/1.21.2",
+            vec![ClientTag::DownloadManager, ClientTag::Scraper],
+        ),
+        (
+            "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) HeadlessChrome/120.0.0.0 Safari/537.36",
+            vec![ClientTag::Scraper],
+        ),
+    ];
+
+    for (ua, expected) in cases {
+        let client = client(&detector, ua)?;
+        assert_eq!(client.tags(), expected, "{} for {}", client.name, ua);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_client_tags() -> Result<()> {
+    let mapping = ClientTagMapping::from_yaml(
+        "
+privacy_browser:
+  clients: [Chrome]
+scraper:
+  types: [mobile app]
+",
+    )?;
+
+    let detector = DeviceDetector::new();
+    let chrome = client(
+        &detector,
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    )?;
+    assert_eq!(mapping.tags(&chrome), vec![ClientTag::PrivacyBrowser]);
+    assert!(mapping.tags(&client(&detector, "Wget/1.21.2")?).is_empty());
+
+    assert!(ClientTagMapping::from_yaml("scraper:\n  types: [robot]\n").is_err());
+    assert!(ClientTagMapping::from_yaml("ad_blocker:\n  clients: [Brave]\n").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::parsers::device::{Confidence, Device, DeviceConfidence, DeviceType};
+
+fn device(detector: &DeviceDetector, ua: &str) -> Result<Device> {
+    let detection = detector.parse(ua, None)?;
+    Ok(detection
+        .get_known_device()
+        .and_then(|x| x.device.clone())
+        .expect("device"))
+}
+
+#[test]
+fn test_device_confidence() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let samsung = device(
+        &detector,
+        "Mozilla/5.0 (Linux; Android 13; SM-S911B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+    )?;
+    assert_eq!(samsung.device_type, Some(DeviceType::SmartPhone));
+    assert_eq!(
+        samsung.confidence,
+        DeviceConfidence {
+            device_type: Some(Confidence::Exact),
+            brand: Some(Confidence::Exact),
+            model: Some(Confidence::Exact),
+        }
+    );
+
+    let desktop = device(
+        &detector,
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    )?;
+    assert_eq!(desktop.device_type, Some(DeviceType::Desktop));
+    assert_eq!(
+        desktop.confidence,
+        DeviceConfidence {
+            device_type: Some(Confidence::Fallback),
+            brand: None,
+            model: None,
+        }
+    );
+
+    let tablet = device(
+        &detector,
+        "Mozilla/5.0 (Linux; Android 12; Tablet) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    )?;
+    assert_eq!(tablet.device_type, Some(DeviceType::Tablet));
+    assert_eq!(tablet.confidence.device_type, Some(Confidence::Inferred));
+
+    let fragment = device(
+        &detector,
+        "Mozilla/5.0 (compatible; MSIE 9.0; Windows NT 6.1; Trident/5.0; MDDRJS)",
+    )?;
+    assert_eq!(fragment.brand.as_deref(), Some("Dell"));
+    assert_eq!(fragment.confidence.brand, Some(Confidence::Inferred));
+    assert_eq!(fragment.confidence.device_type, Some(Confidence::Fallback));
+    assert_eq!(fragment.confidence.model, None);
+
+    Ok(())
+}
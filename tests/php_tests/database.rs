@@ -0,0 +1,263 @@
+use anyhow::Result;
+
+use std::path::{Path, PathBuf};
+
+use rust_device_detector::database::{diff_databases, diff_user_agents, Database, FILES};
+use rust_device_detector::device_detector::{Detection, DeviceDetector};
+use rust_device_detector::replay::load_corpus;
+
+use crate::utils;
+
+const GOOGLEBOT: &str = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+
+pub(crate) fn copy_database(name: &str) -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("rdd-{}-{}", name, std::process::id()));
+    for file in FILES {
+        let to = dir.join(file);
+        std::fs::create_dir_all(to.parent().unwrap())?;
+        std::fs::copy(Path::new("data/regexes").join(file), to)?;
+    }
+    Ok(dir)
+}
+
+#[test]
+fn test_database_from_dir() -> Result<()> {
+    let dir = copy_database("from-dir")?;
+    let bots = dir.join("bots.yml");
+    let contents = std::fs::read_to_string(&bots)?;
+    std::fs::write(
+        &bots,
+        contents.replace("name: 'Googlebot'", "name: 'Renamed Googlebot'"),
+    )?;
+
+    let dd = DeviceDetector::new().with_database(Database::from_dir(&dir)?.into());
+    match dd.parse(GOOGLEBOT, None)? {
+        Detection::Bot(bot) => assert_eq!(bot.name, "Renamed Googlebot"),
+        x => panic!("expected a bot, got {:?}", x),
+    }
+
+    std::fs::remove_file(dir.join("oss.yml"))?;
+    let err = Database::from_dir(&dir).err().expect("missing oss.yml");
+    assert!(format!("{:#}", err).contains("oss.yml"), "{:#}", err);
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_detector_with_database_dir() -> Result<()> {
+    let dir = copy_database("with-database-dir")?;
+    let bots = dir.join("bots.yml");
+    let contents = std::fs::read_to_string(&bots)?;
+    std::fs::write(
+        &bots,
+        contents.replace("name: 'Googlebot'", "name: 'Renamed Googlebot'"),
+    )?;
+
+    let dd = DeviceDetector::with_database_dir(&dir)?;
+    match dd.parse(GOOGLEBOT, None)? {
+        Detection::Bot(bot) => assert_eq!(bot.name, "Renamed Googlebot"),
+        x => panic!("expected a bot, got {:?}", x),
+    }
+
+    std::fs::remove_dir_all(&dir)?;
+    let err = DeviceDetector::with_database_dir(&dir)
+        .err()
+        .expect("missing database");
+    assert!(format!("{:#}", err).contains("reading"), "{:#}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_database_from_dir_ignores_unknown_keys() -> Result<()> {
+    let dir = copy_database("unknown-keys")?;
+    let bots = dir.join("bots.yml");
+    let contents = std::fs::read_to_string(&bots)?;
+    std::fs::write(
+        &bots,
+        format!(
+            "- regex: 'FooBot'\n  name: 'FooBot'\n  reputation: 'good'\n{}",
+            contents
+        ),
+    )?;
+
+    let db = Database::from_dir(&dir)?;
+    let unknown = &db.unknown_keys()["bots.yml"];
+    assert_eq!(unknown.len(), 1);
+    assert_eq!(unknown[0].key, "reputation");
+    assert_eq!(unknown[0].count, 1);
+    assert_eq!(db.unknown_keys().len(), 1);
+    assert!(Database::embedded().unknown_keys().is_empty());
+
+    let dd = DeviceDetector::new().with_database(db.into());
+    match dd.parse("FooBot", None)? {
+        Detection::Bot(bot) => assert_eq!(bot.name, "FooBot"),
+        x => panic!("expected a bot, got {:?}", x),
+    }
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_diff_databases() -> Result<()> {
+    let mut corpus = load_corpus("tests/data/fixtures/bots.yml")?;
+    corpus.truncate(20);
+    corpus.extend(load_corpus("tests/data/fixtures/camera.yml")?);
+
+    let dir = copy_database("diff")?;
+
+    let unchanged = diff_databases("data/regexes", &dir, &corpus)?;
+    assert_eq!(unchanged.total, corpus.len());
+    assert_eq!(unchanged.changed, 0);
+    assert!(unchanged.fields.is_empty(), "{:#?}", unchanged.fields);
+
+    let bots = dir.join("bots.yml");
+    let contents = std::fs::read_to_string(&bots)?;
+    std::fs::write(
+        &bots,
+        contents.replace("name: 'Googlebot'", "name: 'Renamed Googlebot'"),
+    )?;
+    corpus.push(serde_yaml::from_str(&format!(
+        "user_agent: '{}'",
+        GOOGLEBOT
+    ))?);
+
+    let diff = diff_databases("data/regexes", &dir, &corpus)?;
+
+    let mut googlebots = Vec::new();
+    for case in &corpus {
+        let ua = case["user_agent"].as_str().unwrap();
+        if let Detection::Bot(bot) = utils::DD.parse(ua, None)? {
+            if bot.name == "Googlebot" {
+                googlebots.push(ua.to_owned());
+            }
+        }
+    }
+
+    assert_eq!(diff.changed, googlebots.len());
+    assert_eq!(diff.fields.keys().collect::<Vec<_>>(), vec!["bot.name"]);
+
+    let change = &diff.fields["bot.name"];
+    assert_eq!(change.count, googlebots.len());
+    assert!(!change.examples.is_empty());
+    assert!(change.examples.iter().all(|x| googlebots.contains(x)));
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_diff_user_agents() -> Result<()> {
+    let phone = "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36";
+    let tablet = "Mozilla/5.0 (Linux; Android 13; SM-X700) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Safari/537.36";
+
+    let diff = diff_user_agents(&utils::DD, phone, tablet)?;
+    let fields = diff
+        .fields
+        .iter()
+        .map(|x| (x.field.as_str(), x.first.as_str(), x.second.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fields,
+        vec![
+            ("client.name", "Chrome Mobile", "Chrome"),
+            ("device.type", "smartphone", "tablet"),
+            (
+                "device.model",
+                "Galaxy S23 Ultra",
+                "Galaxy Tab S8 11.0\" WiFi"
+            ),
+        ]
+    );
+
+    let lists = diff.regexes.iter().map(|x| x.list).collect::<Vec<_>>();
+    assert!(lists.contains(&"oss.yml"));
+    assert!(lists.contains(&"device/mobiles.yml"));
+
+    // same brand, different browser entries
+    let mobiles = diff.regexes.iter().find(|x| x.list == "device/mobiles.yml");
+    assert!(
+        mobiles.is_some_and(|x| x.is_same() && x.first.as_ref().unwrap().starts_with("SAMSUNG"))
+    );
+    let browsers = diff
+        .regexes
+        .iter()
+        .find(|x| x.list == "client/browsers.yml");
+    assert!(browsers.is_some_and(|x| !x.is_same()));
+
+    let same = diff_user_agents(&utils::DD, GOOGLEBOT, GOOGLEBOT)?;
+    assert!(same.fields.is_empty());
+    assert!(same.regexes.iter().all(|x| x.is_same()));
+    assert_eq!(same.regexes[0].list, "bots.yml");
+
+    Ok(())
+}
+
+#[test]
+fn test_bot_definitions() -> Result<()> {
+    let detector = DeviceDetector::new();
+    let db = detector.database();
+
+    let bots = db.bot_definitions().collect::<Vec<_>>();
+    assert!(bots.len() > 100);
+    assert!(bots
+        .iter()
+        .all(|x| !x.name.is_empty() && !x.regex.is_empty()));
+
+    let googlebot = bots
+        .iter()
+        .find(|x| x.name == "Googlebot")
+        .expect("googlebot");
+    assert_eq!(googlebot.category, Some("Search bot"));
+    assert_eq!(
+        googlebot.producer.and_then(|x| x.name.as_deref()),
+        Some("Google Inc.")
+    );
+    assert!(googlebot.regex.starts_with("Adwords-(?:DisplayAds"));
+
+    // the first definition that matches is the one detected
+    let detected = detector.parse(GOOGLEBOT, None)?;
+    let first = bots
+        .iter()
+        .find(|x| {
+            fancy_regex::Regex::new(&format!("(?i){}", x.regex))
+                .unwrap()
+                .is_match(GOOGLEBOT)
+                .unwrap()
+        })
+        .expect("matching definition");
+    assert_eq!(
+        detected.get_bot().map(|x| x.name.as_str()),
+        Some(first.name)
+    );
+
+    let json = serde_json::to_value(googlebot)?;
+    assert_eq!(json["name"], "Googlebot");
+    assert_eq!(json["regex"], googlebot.regex);
+
+    Ok(())
+}
+
+#[test]
+fn test_database_info() -> Result<()> {
+    let info = DeviceDetector::new().database_info();
+    assert!(info.version.is_some());
+    assert_eq!(info.upstream_commit.map(str::len), Some(40));
+    assert!(info.browsers > 500);
+    assert!(info.browser_engines > 10);
+    assert!(info.other_clients > 500);
+    assert!(info.oss > 200);
+    assert!(info.device_brands > 1000);
+    assert!(info.device_models > info.device_brands);
+    assert!(info.bots > 500);
+
+    // the counts of a database from elsewhere, without the embedded version
+    let from_dir = Database::from_dir("data/regexes")?.info();
+    assert_eq!((from_dir.version, from_dir.upstream_commit), (None, None));
+    assert_eq!(from_dir.bots, info.bots);
+    assert_eq!(from_dir.device_models, info.device_models);
+
+    Ok(())
+}
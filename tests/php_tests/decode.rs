@@ -0,0 +1,82 @@
+use anyhow::Result;
+
+use rust_device_detector::decode::{decode_user_agent, MAX_LEN};
+use rust_device_detector::device_detector::{Detection, DeviceDetector};
+use rust_device_detector::local::LocalDeviceDetector;
+
+const UA: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+#[test]
+fn test_decode_user_agent() {
+    let encoded = "Mozilla%2F5.0%20(Windows%20NT%2010.0%3B%20Win64%3B%20x64)%20AppleWebKit%2F537.36%20(KHTML%2C%20like%20Gecko)%20Chrome%2F120.0.0.0%20Safari%2F537.36";
+    assert_eq!(decode_user_agent(encoded), UA);
+
+    // as forms encode them, with + for spaces
+    let encoded = "Mozilla%2F5.0+%28compatible%3B+Googlebot%2F2.1%3B+%2Bhttp%3A%2F%2Fwww.google.com%2Fbot.html%29";
+    assert_eq!(
+        decode_user_agent(encoded),
+        "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)"
+    );
+
+    // escaped twice
+    let encoded = "Mozilla%252F5.0%2520(Windows%2520NT%252010.0%253B%2520Win64%253B%2520x64)%2520AppleWebKit%252F537.36%2520(KHTML%252C%2520like%2520Gecko)%2520Chrome%252F120.0.0.0%2520Safari%252F537.36";
+    assert_eq!(decode_user_agent(encoded), UA);
+
+    // escaped three times, more than is decoded, is left as it is
+    let encoded = "Mozilla%25252F5.0%252520(X11%25253B%252520Linux)";
+    assert_eq!(decode_user_agent(encoded), encoded);
+}
+
+#[test]
+fn test_decode_user_agent_left_as_is() {
+    for ua in [
+        UA,
+        // spaces and escapes, which aren't those of an encoded user agent
+        "Mozilla/5.0 (Linux; Android 10; K) Version%2F4.0",
+        "Mozilla%2F5.0%20%FF%FE",
+        "Mozilla%2F5.0%0A%0D",
+        "100%",
+    ] {
+        assert_eq!(decode_user_agent(ua), ua);
+    }
+
+    let long = format!("Mozilla%2F5.0%20{}", "a".repeat(MAX_LEN));
+    assert_eq!(decode_user_agent(&long), long);
+}
+
+#[test]
+fn test_detection_user_agent_decoded() -> Result<()> {
+    let encoded = "Mozilla%2F5.0%20(Windows%20NT%2010.0%3B%20Win64%3B%20x64)%20AppleWebKit%2F537.36%20(KHTML%2C%20like%20Gecko)%20Chrome%2F120.0.0.0%20Safari%2F537.36";
+
+    let detector = DeviceDetector::new();
+    let detection = detector.parse(encoded, None)?;
+    assert!(!detection.user_agent_decoded());
+    assert!(detection.to_value().get("user_agent_decoded").is_none());
+
+    let detector = detector.with_user_agent_decoding(true);
+    let detection = detector.parse(encoded, None)?;
+    assert!(detection.user_agent_decoded());
+    assert_eq!(detection.clone().to_value()["user_agent_decoded"], true);
+    let known = detection.get_known_device().unwrap();
+    assert_eq!(known.client.as_ref().unwrap().name, "Chrome");
+    assert_eq!(known.os.as_ref().unwrap().name, "Windows");
+
+    assert!(!detector.parse(UA, None)?.user_agent_decoded());
+
+    let mut local = LocalDeviceDetector::from(detector.clone()).with_cache(10);
+    for _ in 0..2 {
+        assert!(local.parse(encoded, None)?.user_agent_decoded());
+        assert!(!local.parse(UA, None)?.user_agent_decoded());
+    }
+
+    let googlebot = "Mozilla%2F5.0%20(compatible%3B%20Googlebot%2F2.1%3B%20%2Bhttp%3A%2F%2Fwww.google.com%2Fbot.html)";
+    match detector.parse(googlebot, None)? {
+        Detection::Bot(bot) => {
+            assert_eq!(bot.name, "Googlebot");
+            assert!(bot.user_agent_decoded);
+        }
+        x => panic!("expected a bot, got {:?}", x),
+    }
+
+    Ok(())
+}
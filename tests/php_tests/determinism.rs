@@ -0,0 +1,62 @@
+use anyhow::Result;
+
+use std::sync::Arc;
+
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::known_browsers::AvailableBrowsers;
+
+// names which only differ by their spaces, and engines decided by version
+const USER_AGENTS: [&str; 4] = [
+    "Mozilla/5.0 (Linux; Android 11; SM-A125F) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/96.0.4664.104 Mobile Safari/537.36 U Browser1.2.3",
+    "Mozilla/5.0 (Windows NT 6.1; WOW64) AppleWebKit/537.36 (KHTML, like Gecko) UBrowser/5.6.13705.206 Safari/537.36",
+    "Opera/9.80 (Windows NT 6.1; WOW64) Presto/2.12.388 Version/12.18",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 OPR/106.0.0.0",
+];
+
+#[test]
+fn test_browser_names_differing_by_spaces() {
+    // each map is seeded differently, so a lookup depending on iteration
+    // order would sooner or later disagree
+    for _ in 0..16 {
+        let browsers = AvailableBrowsers::default();
+        let short_code = |name: &str| {
+            browsers
+                .search_by_name(name)
+                .map(|x| x.short_code.clone())
+                .expect(name)
+        };
+
+        assert_eq!(short_code("U Browser"), "U0");
+        assert_eq!(short_code("UBrowser"), "UB");
+        assert_eq!(short_code("GoBrowser"), "GR");
+        assert_eq!(short_code("GO Browser"), "G2");
+        assert_eq!(short_code("Super Fast Browser"), "SU");
+        assert_eq!(short_code("SuperFast Browser"), "5S");
+    }
+}
+
+#[test]
+fn test_detection_independent_of_map_order() -> Result<()> {
+    let expected = detections(&DeviceDetector::new())?;
+    assert!(expected[0].contains("\"U Browser\""), "{}", expected[0]);
+    assert!(expected[0].contains("\"U0\""), "{}", expected[0]);
+    assert!(expected[1].contains("\"UB\""), "{}", expected[1]);
+    assert!(expected[2].contains("\"Presto\""), "{}", expected[2]);
+    assert!(expected[3].contains("\"Blink\""), "{}", expected[3]);
+
+    for _ in 0..3 {
+        let db = Arc::new(Database::from_dir("data/regexes")?);
+        let detector = DeviceDetector::new().with_database(db);
+        assert_eq!(detections(&detector)?, expected);
+    }
+
+    Ok(())
+}
+
+fn detections(detector: &DeviceDetector) -> Result<Vec<String>> {
+    USER_AGENTS
+        .iter()
+        .map(|ua| Ok(serde_json::to_string(&detector.parse(ua, None)?.to_v1())?))
+        .collect()
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+
+use rust_device_detector::client_hints::ClientHint;
+use rust_device_detector::device_class::{fast_path, DeviceClass};
+
+use crate::utils;
+
+const COMMON: [(&str, DeviceClass); 10] = [
+    ("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36", DeviceClass::Desktop),
+    ("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0", DeviceClass::Desktop),
+    ("Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0", DeviceClass::Desktop),
+    ("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Safari/605.1.15", DeviceClass::Desktop),
+    ("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36", DeviceClass::Desktop),
+    ("Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36", DeviceClass::Mobile),
+    ("Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36", DeviceClass::Tablet),
+    ("Mozilla/5.0 (Android 14; Mobile; rv:121.0) Gecko/121.0 Firefox/121.0", DeviceClass::Mobile),
+    ("Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1", DeviceClass::Mobile),
+    ("Mozilla/5.0 (iPad; CPU OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1", DeviceClass::Tablet),
+];
+
+#[test]
+fn test_fast_path_common() -> Result<()> {
+    for (ua, class) in COMMON {
+        assert_eq!(fast_path(ua, None), Some(class), "{}", ua);
+        assert_eq!(
+            DeviceClass::of(&utils::DD.parse(ua, None)?),
+            class,
+            "{}",
+            ua
+        );
+    }
+    Ok(())
+}
+
+// the fast path must never disagree with the full detection.
+#[test]
+fn test_fast_path_fixtures() -> Result<()> {
+    let mut answered = 0;
+    for path in glob::glob("tests/data/fixtures/*.yml")? {
+        let path = path?;
+        let cases: Vec<serde_yaml::Value> = serde_yaml::from_str(&std::fs::read_to_string(&path)?)?;
+
+        for case in cases {
+            let ua = case["user_agent"].as_str().unwrap();
+            let client_hints: Option<ClientHint> = case
+                .get("headers")
+                .and_then(|headers| headers.as_mapping())
+                .and_then(|headers| utils::client_hint_mock(headers).ok());
+
+            let Some(class) = fast_path(ua, client_hints.as_ref()) else {
+                continue;
+            };
+            answered += 1;
+            let detection = utils::DD.parse_client_hints(ua, client_hints)?;
+            assert_eq!(
+                class,
+                DeviceClass::of(&detection),
+                "{}: {}",
+                path.display(),
+                ua
+            );
+        }
+    }
+    assert!(answered > 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_fast_path_gives_up() -> Result<()> {
+    for ua in [
+        "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+        "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Googlebot/2.1",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; Touch) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        "Mozilla/5.0 (SMART-TV; LINUX; Tizen 6.0) AppleWebKit/537.36 (KHTML, like Gecko) 76.0.3809.146/6.0 TV Safari/537.36",
+        "curl/8.4.0",
+        "",
+    ] {
+        assert_eq!(fast_path(ua, None), None, "{}", ua);
+    }
+
+    let mobile = ClientHint {
+        mobile: true,
+        ..Default::default()
+    };
+    assert_eq!(fast_path(COMMON[0].0, Some(&mobile)), None);
+
+    let headers = |platform: &str| {
+        ClientHint::from_headers(vec![
+            ("sec-ch-ua-mobile".to_owned(), "?0".to_owned()),
+            (
+                "sec-ch-ua-platform".to_owned(),
+                format!(r#""{}""#, platform),
+            ),
+        ])
+    };
+    assert_eq!(
+        fast_path(COMMON[0].0, Some(&headers("Windows")?)),
+        Some(DeviceClass::Desktop)
+    );
+    assert_eq!(fast_path(COMMON[0].0, Some(&headers("Android")?)), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_classify_device_class() -> Result<()> {
+    let classify = |ua: &str| utils::DD.classify_device_class(ua, None);
+
+    assert_eq!(classify(COMMON[0].0)?, DeviceClass::Desktop);
+    assert_eq!(
+        classify("Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)")?,
+        DeviceClass::Bot
+    );
+    assert_eq!(
+        classify("Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36")?,
+        DeviceClass::Mobile
+    );
+    assert_eq!(
+        classify("Mozilla/5.0 (SMART-TV; LINUX; Tizen 6.0) AppleWebKit/537.36 (KHTML, like Gecko) 76.0.3809.146/6.0 TV Safari/537.36")?,
+        DeviceClass::Tv
+    );
+    assert_eq!(classify("curl/8.4.0")?, DeviceClass::Other);
+
+    Ok(())
+}
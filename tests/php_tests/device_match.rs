@@ -0,0 +1,40 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::parsers::device::{Device, DeviceMatchStrategy};
+
+fn device(detector: &DeviceDetector, ua: &str) -> Result<Option<Device>> {
+    let res = detector
+        .parse(ua, None)?
+        .get_known_device()
+        .and_then(|x| x.device.clone());
+
+    Ok(res)
+}
+
+#[test]
+fn test_device_match_strategies() -> Result<()> {
+    let first = DeviceDetector::new();
+    let best = DeviceDetector::new().with_device_match(DeviceMatchStrategy::Best);
+
+    // unambiguous user agents come out the same either way
+    let ua = "Mozilla/5.0 (Linux; Android 10; SM-G960F) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/83.0.4103.106 Mobile Safari/537.36";
+    let a = device(&first, ua)?.expect("device");
+    let b = device(&best, ua)?.expect("device");
+    assert_eq!(a.brand.as_deref(), Some("Samsung"));
+    assert_eq!((a.brand, a.model), (b.brand, b.model));
+
+    // both Blu and Vivo match here, the first listed wins by default while
+    // the Vivo regex matches more of the user agent.
+    let ua = "Mozilla/5.0 (Linux; Android 5.0; BLU VIVO SELFIE) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/80.0.3987.149 Mobile Safari/537.36";
+    let a = device(&first, ua)?.expect("device");
+    let b = device(&best, ua)?.expect("device");
+    assert_eq!(a.brand.as_deref(), Some("Blu"));
+    assert_eq!(b.brand.as_deref(), Some("Vivo"));
+
+    // nothing is invented when no brand matches at all
+    let ua = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    assert!(device(&best, ua)?.and_then(|x| x.brand).is_none());
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::device_type_mapping::DeviceTypeMapping;
+use rust_device_detector::parsers::device::DeviceType;
+
+const TABLET: &str = "Mozilla/5.0 (Linux; Android 12; Tablet) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+const DESKTOP: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+fn device_type(detector: &DeviceDetector, ua: &str) -> Result<Option<DeviceType>> {
+    let detection = detector.parse(ua, None)?;
+    Ok(detection
+        .get_known_device()
+        .and_then(|x| x.device.as_ref()?.device_type.clone()))
+}
+
+#[test]
+fn test_device_type_mapping() -> Result<()> {
+    let detector = DeviceDetector::new();
+    assert_eq!(device_type(&detector, TABLET)?, Some(DeviceType::Tablet));
+
+    let mapped = detector.clone().with_device_type_mapping(
+        DeviceTypeMapping::new()
+            .collapse(DeviceType::Tablet, DeviceType::SmartPhone)
+            // applied once, tablets don't become desktops
+            .collapse(DeviceType::SmartPhone, DeviceType::Desktop),
+    );
+    assert_eq!(device_type(&mapped, TABLET)?, Some(DeviceType::SmartPhone));
+    assert_eq!(device_type(&mapped, DESKTOP)?, Some(DeviceType::Desktop));
+
+    // the detector it was cloned from is unaffected
+    assert_eq!(device_type(&detector, TABLET)?, Some(DeviceType::Tablet));
+
+    let mapping = DeviceTypeMapping::from_names([("tablet", "tv"), ("phablet", "smartphone")])?;
+    assert_eq!(mapping.map(&DeviceType::Tablet), &DeviceType::Television);
+    assert_eq!(mapping.map(&DeviceType::Phablet), &DeviceType::SmartPhone);
+    assert_eq!(mapping.map(&DeviceType::Desktop), &DeviceType::Desktop);
+    assert!(DeviceTypeMapping::from_names([("tablet", "tabletop")]).is_err());
+
+    Ok(())
+}
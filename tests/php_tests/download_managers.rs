@@ -0,0 +1,78 @@
+use anyhow::Result;
+
+use rust_device_detector::client_tags::ClientTag;
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::parsers::client::{Client, ClientType};
+
+fn client(detector: &DeviceDetector, ua: &str) -> Result<Option<Client>> {
+    let detection = detector.parse(ua, None)?;
+    Ok(detection.get_known_device().and_then(|x| x.client.clone()))
+}
+
+#[test]
+fn test_download_managers_off_by_default() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let wget = client(&detector, "Wget/1.21.2")?.expect("client");
+    assert_eq!(wget.name, "Wget");
+    assert_eq!(wget.r#type, ClientType::Library);
+
+    Ok(())
+}
+
+#[test]
+fn test_download_managers() -> Result<()> {
+    let detector = DeviceDetector::new().with_download_managers(true);
+
+    let cases = [
+        ("Wget/1.21.2", "Wget", Some("1.21.2")),
+        ("aria2/1.36.0", "Aria2", Some("1.36.0")),
+        (
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) Internet Download Manager/6.41",
+            "Internet Download Manager",
+            Some("6.41"),
+        ),
+        ("JDownloader", "JDownloader", None),
+        (
+            "Mozilla/4.5 (compatible; HTTrack 3.0x; Windows 98)",
+            "HTTrack",
+            Some("3.0"),
+        ),
+        ("Teleport Pro/1.29", "Teleport Pro", Some("1.29")),
+    ];
+
+    for (ua, name, version) in cases {
+        let client = client(&detector, ua)?.expect(ua);
+        assert_eq!(client.name, name, "{}", ua);
+        assert_eq!(client.version.as_deref(), version, "{}", ua);
+        assert_eq!(client.r#type, ClientType::DownloadManager, "{}", ua);
+        assert_eq!(client.short_code, None, "{}", ua);
+        assert!(
+            client.tags().contains(&ClientTag::DownloadManager),
+            "{}",
+            ua
+        );
+    }
+
+    let detection = detector.parse("Offline Explorer/2.5", None)?;
+    assert!(detection
+        .get_known_device()
+        .is_some_and(|x| x.is_download_manager()));
+
+    Ok(())
+}
+
+#[test]
+fn test_download_managers_leave_browsers() -> Result<()> {
+    let detector = DeviceDetector::new().with_download_managers(true);
+
+    let chrome = client(
+        &detector,
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    )?
+    .expect("client");
+    assert_eq!(chrome.name, "Chrome");
+    assert_eq!(chrome.r#type, ClientType::Browser);
+
+    Ok(())
+}
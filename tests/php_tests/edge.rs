@@ -0,0 +1,67 @@
+use anyhow::Result;
+
+use std::collections::HashMap;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::edge::{self, DETECTION_HEADERS};
+
+#[test]
+fn test_edge_annotate() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let request: HashMap<&str, &str> = [
+        ("user-agent", "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36"),
+        ("sec-ch-ua-model", "\"SM-S918B\""),
+        ("sec-ch-ua-platform", "\"Android\""),
+        ("sec-ch-ua-platform-version", "\"13.0.0\""),
+        ("accept", "*/*"),
+    ]
+    .into_iter()
+    .collect();
+
+    let headers = edge::annotate(&detector, &|name: &str| {
+        request.get(name).map(|x| x.to_string())
+    })?;
+
+    assert_eq!(
+        headers.iter().map(|x| x.0).collect::<Vec<_>>(),
+        DETECTION_HEADERS
+    );
+    let get = |name| &headers.iter().find(|x| x.0 == name).unwrap().1;
+    assert_eq!(get("x-detected-bot"), "false");
+    assert_eq!(get("x-detected-bot-name"), "");
+    assert_eq!(get("x-detected-client"), "Chrome Mobile");
+    assert_eq!(get("x-detected-os"), "Android");
+    assert_eq!(get("x-detected-os-version"), "13.0.0");
+    assert_eq!(get("x-detected-device-type"), "smartphone");
+    assert_eq!(get("x-detected-device-brand"), "Samsung");
+    assert_eq!(get("x-detected-device-model"), "Galaxy S23 Ultra");
+
+    Ok(())
+}
+
+#[test]
+fn test_edge_request_headers() -> Result<()> {
+    let request = vec![
+        (
+            "User-Agent".to_owned(),
+            "Googlebot/2.1 (+http://www.google.com/bot.html)".to_owned(),
+        ),
+        ("Cookie".to_owned(), "a=b".to_owned()),
+        ("Sec-CH-UA-Mobile".to_owned(), "?0".to_owned()),
+    ];
+
+    let (ua, headers) = edge::request_headers(request.as_slice());
+    assert_eq!(ua, "Googlebot/2.1 (+http://www.google.com/bot.html)");
+    assert_eq!(
+        headers,
+        vec![("sec-ch-ua-mobile".to_owned(), "?0".to_owned())]
+    );
+
+    let headers = edge::annotate(&DeviceDetector::new(), request.as_slice())?;
+    assert_eq!(headers[0], ("x-detected-bot", "true".to_owned()));
+    assert_eq!(headers[1], ("x-detected-bot-name", "Googlebot".to_owned()));
+    assert!(headers[2..].iter().all(|x| x.1.is_empty()));
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::embed::{check_overlay_file, EmbeddedOverlay};
+use rust_device_detector::include_device_db;
+
+static VALID: EmbeddedOverlay = include_device_db!("../data/overlays/valid.yml");
+static INVALID: EmbeddedOverlay = include_device_db!("../data/overlays/invalid.yml");
+
+const PHONE: &str = "Mozilla/5.0 (Linux; Android 15; SM-S998B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Mobile Safari/537.36";
+
+#[test]
+fn test_include_device_db() -> Result<()> {
+    let db = VALID.database()?;
+    // loaded once
+    assert!(std::sync::Arc::ptr_eq(&db, &VALID.database()?));
+
+    let detector = DeviceDetector::new_with_database(db);
+    let detection = detector.parse("Qwertbot/1.0", None)?;
+    assert_eq!(
+        detection.get_bot().map(|x| x.name.as_str()),
+        Some("Qwertbot")
+    );
+
+    let detection = detector.parse(PHONE, None)?;
+    let model = detection
+        .get_known_device()
+        .and_then(|x| x.device.as_ref())
+        .and_then(|x| x.model.as_deref());
+    assert_eq!(model, Some("Galaxy Embedded"));
+
+    Ok(())
+}
+
+#[test]
+fn test_include_device_db_validates() {
+    let err = INVALID.database().err().expect("invalid overlay");
+    let message = format!("{:#}", err);
+    assert!(message.contains("invalid.yml"), "{}", message);
+    assert!(message.contains("bots.yml"), "{}", message);
+    assert!(message.contains("Qwertbot/("), "{}", message);
+}
+
+#[test]
+fn test_check_overlay_file() {
+    assert!(check_overlay_file("tests/data/overlays/valid.yml").is_ok());
+    assert!(check_overlay_file("tests/data/overlays/invalid.yml").is_err());
+    assert!(check_overlay_file("tests/data/overlays/missing.yml").is_err());
+}
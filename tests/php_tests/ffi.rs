@@ -0,0 +1,62 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use std::ffi::{CStr, CString};
+use std::ptr::null;
+
+use rust_device_detector::ffi::*;
+
+const PIXEL: &str = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+
+fn parse_json(ua: &str, headers: Option<&str>) -> Result<Value> {
+    let ua = CString::new(ua)?;
+    let headers = headers.map(CString::new).transpose()?;
+
+    unsafe {
+        let dd = device_detector_new();
+        let res = device_detector_parse_json(
+            dd,
+            ua.as_ptr(),
+            headers.as_ref().map_or(null(), |x| x.as_ptr()),
+        );
+        let value = serde_json::from_str(CStr::from_ptr(res).to_str()?)?;
+
+        device_detector_free_string(res);
+        device_detector_free(dd);
+        Ok(value)
+    }
+}
+
+#[test]
+fn test_parse_json() -> Result<()> {
+    let res = parse_json(PIXEL, None)?;
+    assert_eq!(res["client"]["name"], "Chrome Mobile");
+    assert_eq!(res["os"]["name"], "Android");
+
+    let res = parse_json(PIXEL, Some(r#"[["sec-ch-ua-model", "\"Pixel 7\""]]"#))?;
+    assert_eq!(res["device"]["model"], "Pixel 7");
+
+    // repeated headers are kept in order, the last one winning as with
+    // DeviceDetector::parse
+    let res = parse_json(
+        PIXEL,
+        Some(r#"[["sec-ch-ua-model", "\"Pixel 6\""], ["sec-ch-ua-model", "\"Pixel 7\""]]"#),
+    )?;
+    assert_eq!(res["device"]["model"], "Pixel 7");
+
+    let res = parse_json("Googlebot-News", None)?;
+    assert_eq!(res["bot"]["name"], "Googlebot News");
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_json_error() -> Result<()> {
+    let res = parse_json(PIXEL, Some(r#"{"sec-ch-ua-mobile": "?1"}"#))?;
+    assert!(res["error"]
+        .as_str()
+        .expect("error")
+        .starts_with("headers is not an array of name and value pairs"));
+
+    Ok(())
+}
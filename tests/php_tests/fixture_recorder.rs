@@ -0,0 +1,77 @@
+use anyhow::Result;
+use serde_yaml::Value;
+
+use crate::utils;
+use rust_device_detector::fixture_recorder::{redact_user_agent, FixtureRecorder};
+
+type Headers = Vec<(String, String)>;
+
+#[test]
+fn test_recorded_fixtures_pass() -> Result<()> {
+    let requests: Vec<(&str, Option<Headers>)> = vec![
+        ("Mozilla/5.0 (Linux; Android 10; SM-G960F) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/83.0.4103.106 Mobile Safari/537.36", None),
+        ("Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0", None),
+        ("Googlebot/2.1 (+http://www.google.com/bot.html)", None),
+        ("", None),
+        (
+            "Mozilla/5.0 (Linux; Android 11; Pixel 3) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/92.0.4515.131 Mobile Safari/537.36",
+            Some(vec![
+                ("X-Requested-With".to_owned(), "org.telegram.messenger".to_owned()),
+                ("Cookie".to_owned(), "session=secret".to_owned()),
+            ]),
+        ),
+    ];
+
+    let recorder = FixtureRecorder::new(1);
+
+    for (ua, headers) in requests.iter() {
+        let detection = utils::DD.parse(ua, headers.clone())?;
+        assert!(recorder.record(ua, headers.as_deref(), &detection));
+    }
+
+    let yaml = recorder.drain_yaml()?;
+    assert!(recorder.is_empty());
+    assert!(!yaml.contains("secret"), "{}", yaml);
+
+    let cases: Value = serde_yaml::from_str(&yaml)?;
+    let cases = cases.as_sequence().expect("sequence");
+    assert_eq!(cases.len(), requests.len());
+
+    for (i, case) in cases.iter().enumerate() {
+        crate::fixtures::basic("recorded", i + 1, case)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_recorder_sampling() -> Result<()> {
+    let ua = "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0";
+    let detection = utils::DD.parse(ua, None)?;
+
+    let recorder = FixtureRecorder::new(3).with_limit(2);
+    let sampled = (0..10)
+        .map(|_| recorder.record(ua, None, &detection))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        sampled,
+        vec![true, false, false, true, false, false, false, false, false, false]
+    );
+    assert_eq!(recorder.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_redact_user_agent() {
+    let ua = "MyApp/1.0 (john.doe@example.org; device 3f2504e0-4f89-11d3-9a0c-0305e82c3301; +https://example.com/bot?user=1234) token/a94a8fe5ccb19ba61c4c0873d391e987982fbbd3";
+    assert_eq!(
+        redact_user_agent(ua),
+        "MyApp/1.0 (user@example.com; device 00000000-0000-0000-0000-000000000000; +https://example.com/bot) token/0000000000000000"
+    );
+
+    // versions and models are left alone
+    let ua = "Mozilla/5.0 (Linux; Android 10; SM-G960F) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/83.0.4103.106 Mobile Safari/537.36";
+    assert_eq!(redact_user_agent(ua), ua);
+}
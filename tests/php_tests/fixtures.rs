@@ -42,12 +42,12 @@ fn base_fixture_tests(file_path: &str, contents: &str) {
 
     let cases = cases.as_sequence_mut().expect("sequence");
 
-    for (i, case) in cases.into_iter().enumerate() {
+    for (i, case) in cases.iter_mut().enumerate() {
         basic(file_path, i + 1, case).expect("basic test");
     }
 }
 
-fn basic(test_file: &str, idx: usize, value: &Value) -> Result<()> {
+pub(crate) fn basic(test_file: &str, idx: usize, value: &Value) -> Result<()> {
     if value
         .as_mapping()
         .map(|m| m.contains_key("bot"))
@@ -0,0 +1,117 @@
+use anyhow::Result;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{Code, Request};
+
+use std::sync::Arc;
+
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::grpc::proto::detect_response::Detection;
+use rust_device_detector::grpc::proto::device_detector_client::DeviceDetectorClient;
+use rust_device_detector::grpc::proto::device_detector_server::DeviceDetector as _;
+use rust_device_detector::grpc::proto::{DetectRequest, DetectResponse, Header};
+use rust_device_detector::grpc::{self, Service};
+use rust_device_detector::overlay::Overlay;
+
+const PIXEL: &str = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+
+fn request(ua: &str, headers: &[(&str, &str)]) -> DetectRequest {
+    DetectRequest {
+        user_agent: ua.to_owned(),
+        headers: headers
+            .iter()
+            .map(|(name, value)| Header {
+                name: (*name).to_owned(),
+                value: (*value).to_owned(),
+            })
+            .collect(),
+    }
+}
+
+fn runtime() -> Result<tokio::runtime::Runtime> {
+    Ok(tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?)
+}
+
+#[test]
+fn test_detect_known() -> Result<()> {
+    let service = Service::new(Arc::new(DeviceDetector::new()));
+    let req = request(
+        PIXEL,
+        &[
+            ("Sec-CH-UA-Mobile", "?1"),
+            ("Sec-CH-UA-Model", r#""Pixel 7""#),
+        ],
+    );
+
+    let res = runtime()?.block_on(service.detect(Request::new(req)))?;
+    let Some(Detection::Known(known)) = res.into_inner().detection else {
+        panic!("known device");
+    };
+
+    let client = known.client.expect("client");
+    assert_eq!(client.name, "Chrome Mobile");
+    assert_eq!(client.r#type, "browser");
+    assert_eq!(client.engine.as_deref(), Some("Blink"));
+
+    let device = known.device.expect("device");
+    assert_eq!(device.r#type.as_deref(), Some("smartphone"));
+    assert_eq!(device.model.as_deref(), Some("Pixel 7"));
+    assert!(device.mobile_client_hint);
+
+    assert_eq!(known.os.expect("os").name, "Android");
+    assert!(known.is.expect("flags").smart_phone);
+
+    Ok(())
+}
+
+#[test]
+fn test_detect_bot() -> Result<()> {
+    let detection = DeviceDetector::new().parse("Googlebot-News", None)?;
+    let Some(Detection::Bot(bot)) = DetectResponse::from(detection).detection else {
+        panic!("bot");
+    };
+
+    assert_eq!(bot.name, "Googlebot News");
+    assert_eq!(bot.category.as_deref(), Some("Search bot"));
+    assert_eq!(
+        bot.producer.and_then(|x| x.name).as_deref(),
+        Some("Google Inc.")
+    );
+    assert!(bot.claimed.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_detect_error() -> Result<()> {
+    let overlay = Overlay::from_yaml("oss.yml:\n  - regex: 'Brokenos('\n    name: 'Brokenos'\n")?;
+    let db = Database::embedded_with_overlay(&overlay)?;
+    let service = Service::new(Arc::new(DeviceDetector::new_with_database(Arc::new(db))));
+
+    let res = runtime()?.block_on(service.detect(Request::new(request(PIXEL, &[]))));
+    assert_eq!(res.expect_err("failed detection").code(), Code::Internal);
+
+    Ok(())
+}
+
+#[test]
+fn test_serve() -> Result<()> {
+    runtime()?.block_on(async {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let server = tonic::transport::Server::builder()
+            .add_service(grpc::service(Arc::new(DeviceDetector::new())))
+            .serve_with_incoming(TcpListenerStream::new(listener));
+        let server = tokio::spawn(server);
+
+        let mut client = DeviceDetectorClient::connect(format!("http://{}", addr)).await?;
+        let res = client.detect(request(PIXEL, &[])).await?.into_inner();
+        assert!(matches!(res.detection, Some(Detection::Known(_))));
+
+        server.abort();
+        Ok(())
+    })
+}
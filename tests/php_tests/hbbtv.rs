@@ -0,0 +1,57 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::parsers::device::hbbtv::{parse, HbbTv, TvOperator};
+
+#[test]
+fn test_parse_hbbtv() -> Result<()> {
+    let hbbtv = parse(
+        "Mozilla/5.0 (SMART-TV; LINUX; Tizen 6.0) AppleWebKit/537.36 (KHTML, like Gecko) 76.0.3809.146/6.0 TV Safari/537.36 HbbTV/1.5.1 (+DRM+PVR; Samsung; SmartTV2021; T-NT11-1010.1; T-NT11; ;) FVC/5.0",
+    )?;
+    assert_eq!(
+        hbbtv,
+        Some(HbbTv {
+            version: "1.5.1".to_owned(),
+            capabilities: vec!["DRM".to_owned(), "PVR".to_owned()],
+            vendor: Some("Samsung".to_owned()),
+            model: Some("SmartTV2021".to_owned()),
+            software_version: Some("T-NT11-1010.1".to_owned()),
+            hardware_version: Some("T-NT11".to_owned()),
+            family: None,
+            operator: Some(TvOperator {
+                name: "Freeview Play",
+                country: "GB",
+            }),
+        })
+    );
+
+    let hbbtv = parse("Opera/9.80 (Linux mips; U; HbbTV/1.1.1 (; Sony; N/A; v1.000; 2015;) sony.hbbtv.tv.2015HE YouView; en) Presto/2.12.362 Version/12.11")?
+        .expect("hbbtv");
+    assert_eq!(hbbtv.version, "1.1.1");
+    assert!(hbbtv.capabilities.is_empty());
+    assert_eq!(hbbtv.vendor.as_deref(), Some("Sony"));
+    assert_eq!(hbbtv.hardware_version.as_deref(), Some("2015"));
+    assert_eq!(hbbtv.operator.map(|x| x.name), Some("YouView"));
+
+    assert_eq!(parse("Mozilla/5.0 (Windows NT 10.0; Win64; x64)")?, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_device_hbbtv() -> Result<()> {
+    let detector = DeviceDetector::new();
+    let detection = detector.parse(
+        "Mozilla/5.0 (Linux; Tizen 2.3) AppleWebKit/538.1 (KHTML, like Gecko) Version/2.3 TV Safari/538.1 HbbTV/1.2.1 (+DL; Samsung; SmartTV2015; T-HKMDEUC-1490.3; ; ) Saorview",
+        None,
+    )?;
+    let device = detection
+        .get_known_device()
+        .and_then(|x| x.device.as_ref())
+        .expect("device");
+    let hbbtv = device.hbbtv.as_ref().expect("hbbtv");
+    assert_eq!(hbbtv.capabilities, vec!["DL".to_owned()]);
+    assert_eq!(hbbtv.operator.map(|x| x.country), Some("IE"));
+
+    Ok(())
+}
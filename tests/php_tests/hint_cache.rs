@@ -0,0 +1,67 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+
+const REDUCED: &str = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+
+fn hints(model: &str) -> Vec<(String, String)> {
+    vec![
+        (
+            "Sec-CH-UA".to_owned(),
+            r#""Chromium";v="120", "Google Chrome";v="120", "Not?A_Brand";v="24""#.to_owned(),
+        ),
+        ("Sec-CH-UA-Mobile".to_owned(), "?1".to_owned()),
+        ("Sec-CH-UA-Platform".to_owned(), r#""Android""#.to_owned()),
+        (
+            "Sec-CH-UA-Platform-Version".to_owned(),
+            r#""13.0.0""#.to_owned(),
+        ),
+        ("Sec-CH-UA-Model".to_owned(), format!("\"{}\"", model)),
+    ]
+}
+
+fn model(detector: &DeviceDetector, headers: Vec<(String, String)>) -> Result<Option<String>> {
+    let detection = detector.parse(REDUCED, Some(headers))?;
+    Ok(detection
+        .get_known_device()
+        .and_then(|x| x.device.as_ref())
+        .and_then(|x| x.model.clone()))
+}
+
+#[test]
+fn test_cache_keys_on_hints() -> Result<()> {
+    // a cache keyed by the user agent alone would answer the second request
+    // with the first device, as the user agents are the same
+    let detector = DeviceDetector::new_with_cache(100);
+
+    for _ in 0..2 {
+        assert_eq!(
+            model(&detector, hints("SM-S918B"))?.as_deref(),
+            Some("Galaxy S23 Ultra")
+        );
+        assert_eq!(
+            model(&detector, hints("Pixel 7"))?.as_deref(),
+            Some("Pixel 7")
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_hints_only_not_cached_by_user_agent() -> Result<()> {
+    let detector = DeviceDetector::new_with_cache(100);
+
+    for _ in 0..2 {
+        for (hint, expected) in [("SM-S918B", "Galaxy S23 Ultra"), ("Pixel 7", "Pixel 7")] {
+            let detection = detector.parse_hints_only(hints(hint))?;
+            let model = detection
+                .get_known_device()
+                .and_then(|x| x.device.as_ref())
+                .and_then(|x| x.model.as_deref());
+            assert_eq!(model, Some(expected));
+        }
+    }
+
+    Ok(())
+}
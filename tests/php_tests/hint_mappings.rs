@@ -0,0 +1,63 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::parsers::client::{Client, ClientType};
+
+const WEBVIEW: &str = "Mozilla/5.0 (Linux; Android 13; SM-S901B Build/TP1A.220624.014; wv) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/119.0.6045.163 Mobile Safari/537.36";
+
+fn client(detector: &DeviceDetector, package: &str) -> Result<Option<Client>> {
+    let headers = vec![("X-Requested-With".to_owned(), package.to_owned())];
+    let detection = detector.parse(WEBVIEW, Some(headers))?;
+    Ok(detection.get_known_device().and_then(|x| x.client.clone()))
+}
+
+#[test]
+fn test_hint_app_mapping() -> Result<()> {
+    let package = "com.example.internal.field";
+
+    let default = client(&DeviceDetector::new(), package)?.expect("client");
+    assert_ne!(default.name, "Example Field");
+
+    let detector = DeviceDetector::new().with_hint_app(package, "Example Field");
+    let app = client(&detector, package)?.expect("client");
+    assert_eq!(app.r#type, ClientType::MobileApp);
+    assert_eq!(app.name, "Example Field");
+
+    // other packages are unaffected
+    assert_eq!(
+        client(&detector, "com.example.other")?.map(|x| x.name),
+        Some(default.name)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_hint_browser_mapping() -> Result<()> {
+    let package = "com.example.internal.browser";
+
+    let detector = DeviceDetector::new().with_hint_browser(package, "Kiwi");
+    let browser = client(&detector, package)?.expect("client");
+    assert_eq!(browser.r#type, ClientType::Browser);
+    assert_eq!(browser.name, "Kiwi");
+
+    Ok(())
+}
+
+#[test]
+fn test_hint_mapping_overrides_database() -> Result<()> {
+    // mapped to Facebook in client/hints/apps.yml
+    let package = "com.facebook.katana";
+    assert_eq!(
+        client(&DeviceDetector::new(), package)?.map(|x| x.name),
+        Some("Facebook".to_owned())
+    );
+
+    let detector = DeviceDetector::new().with_hint_app(package, "Facebook Lite");
+    assert_eq!(
+        client(&detector, package)?.map(|x| x.name),
+        Some("Facebook Lite".to_owned())
+    );
+
+    Ok(())
+}
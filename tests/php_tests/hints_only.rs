@@ -0,0 +1,84 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::{DeviceDetector, KnownDevice};
+
+fn headers(headers: &[(&str, &str)]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+fn hints_only(detector: &DeviceDetector, hints: &[(&str, &str)]) -> Result<KnownDevice> {
+    let detection = detector.parse_hints_only(headers(hints))?;
+    assert!(!detection.is_bot());
+    Ok(detection.get_known_device().cloned().expect("known device"))
+}
+
+#[test]
+fn test_parse_hints_only() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let known = hints_only(
+        &detector,
+        &[
+            (
+                "sec-ch-ua-full-version-list",
+                r#""Chromium";v="120.0.6099.144", "Google Chrome";v="120.0.6099.144""#,
+            ),
+            ("sec-ch-ua-mobile", "?1"),
+            ("sec-ch-ua-model", r#""SM-S918B""#),
+            ("sec-ch-ua-platform", r#""Android""#),
+            ("sec-ch-ua-platform-version", r#""13.0.0""#),
+        ],
+    )?;
+
+    let client = known.client.as_ref().expect("client");
+    assert_eq!(client.name, "Chrome");
+    assert_eq!(client.version.as_deref(), Some("120.0.6099.144"));
+    let os = known.os.as_ref().expect("os");
+    assert_eq!(os.name, "Android");
+    assert_eq!(os.version.as_deref(), Some("13.0.0"));
+    let device = known.device.as_ref().expect("device");
+    assert_eq!(
+        device.device_type.as_ref().map(|x| x.as_str()),
+        Some("smartphone")
+    );
+    assert_eq!(device.brand.as_deref(), Some("Samsung"));
+    assert_eq!(device.model.as_deref(), Some("Galaxy S23 Ultra"));
+
+    let known = hints_only(
+        &detector,
+        &[
+            ("sec-ch-ua-mobile", "?0"),
+            ("sec-ch-ua-platform", r#""Windows""#),
+            ("sec-ch-ua-platform-version", r#""15.0.0""#),
+        ],
+    )?;
+    assert_eq!(known.os.as_ref().map(|x| x.name.as_str()), Some("Windows"));
+    assert_eq!(
+        known.os.as_ref().and_then(|x| x.version.as_deref()),
+        Some("11")
+    );
+    assert!(known.is_desktop());
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_hints_only_without_hints() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    for ua in ["", "  "] {
+        let detection = detector.parse(ua, Some(Vec::new()))?;
+        let known = detection.get_known_device().expect("known device");
+        assert!(known.client.is_none());
+        assert!(known.os.is_none());
+        assert!(known.device.is_none());
+    }
+
+    let known = hints_only(&detector, &[])?;
+    assert!(known.client.is_none() && known.os.is_none() && known.device.is_none());
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_yaml::Mapping;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::utils;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::parsers::client::Client;
+use rust_device_detector::quirks::Quirk;
+
+const SPEC: &str = "tests/data/hints/browsers.yml";
+const SOURCE: &str = "src/parsers/client/browsers.rs";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Case {
+    rule: String,
+    description: String,
+    user_agent: String,
+    #[serde(default)]
+    headers: Option<Mapping>,
+    #[serde(default)]
+    without_quirks: Vec<String>,
+    expected: BTreeMap<String, Option<String>>,
+}
+
+fn cases() -> Result<Vec<Case>> {
+    let contents = std::fs::read_to_string(SPEC)?;
+    Ok(serde_yaml::from_str(&contents)?)
+}
+
+/// The ids of the rules marked `// rule: <id>` in the browser hints merge.
+fn rules() -> Result<BTreeSet<String>> {
+    let source = std::fs::read_to_string(SOURCE)?;
+    Ok(source
+        .lines()
+        .filter_map(|x| x.trim().strip_prefix("// rule: "))
+        .map(|x| x.trim().to_owned())
+        .collect())
+}
+
+fn field<'a>(client: &'a Client, name: &str) -> Option<&'a str> {
+    match name {
+        "name" => Some(client.name.as_str()),
+        "version" => client.version.as_deref(),
+        "engine" => client.engine.as_deref(),
+        "engine_version" => client.engine_version.as_deref(),
+        _ => panic!("no client field {} in {}", name, SPEC),
+    }
+}
+
+fn check(case: &Case) -> Result<Vec<String>> {
+    let client_hints = case
+        .headers
+        .as_ref()
+        .map(utils::client_hint_mock)
+        .transpose()?;
+
+    let mut detector = DeviceDetector::new();
+    for quirk in &case.without_quirks {
+        detector = detector.without_quirk(quirk.parse::<Quirk>()?);
+    }
+
+    let detection = detector.parse_client_hints(&case.user_agent, client_hints)?;
+    let client = detection
+        .get_known_device()
+        .and_then(|x| x.client.as_ref())
+        .expect("client");
+
+    Ok(case
+        .expected
+        .iter()
+        .filter(|(name, expected)| field(client, name) != expected.as_deref())
+        .map(|(name, expected)| {
+            format!(
+                "{}: {:?}, expected {:?}",
+                name,
+                field(client, name),
+                expected
+            )
+        })
+        .collect())
+}
+
+#[test]
+fn test_hints_precedence() -> Result<()> {
+    let mut failures = Vec::new();
+    for (i, case) in cases()?.iter().enumerate() {
+        let mismatches = check(case)?;
+        if !mismatches.is_empty() {
+            failures.push(format!(
+                "case {} ({}): {}\n  ua: {}\n  {}",
+                i + 1,
+                case.rule,
+                case.description,
+                case.user_agent,
+                mismatches.join("\n  ")
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+    Ok(())
+}
+
+#[test]
+fn test_every_rule_has_a_case() -> Result<()> {
+    let rules = rules()?;
+    assert!(!rules.is_empty());
+
+    let quirks: BTreeSet<_> = Quirk::ALL.iter().map(|x| x.id().to_owned()).collect();
+    let covered: BTreeSet<_> = cases()?.into_iter().map(|x| x.rule).collect();
+
+    for rule in rules.iter().chain(&quirks) {
+        assert!(covered.contains(rule), "no case in {} for {}", SPEC, rule);
+    }
+    for rule in &covered {
+        assert!(
+            rules.contains(rule) || quirks.contains(rule),
+            "{} has a case of {}, which isn't a rule marked in {} or a quirk",
+            SPEC,
+            rule,
+            SOURCE
+        );
+    }
+
+    Ok(())
+}
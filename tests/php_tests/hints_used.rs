@@ -0,0 +1,101 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::hints_used::Hint;
+
+const REDUCED_ANDROID: &str = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+const REDUCED_WINDOWS: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+fn headers(headers: &[(&str, &str)]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+fn used(detector: &DeviceDetector, ua: &str, hints: &[(&str, &str)]) -> Result<Vec<Hint>> {
+    let hints = (!hints.is_empty()).then(|| headers(hints));
+    Ok(detector.parse(ua, hints)?.hints_used().to_vec())
+}
+
+#[test]
+fn test_hints_used_reduced_user_agents() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let hints = [
+        (
+            "sec-ch-ua-full-version-list",
+            r#""Chromium";v="120.0.6099.144", "Google Chrome";v="120.0.6099.144""#,
+        ),
+        ("sec-ch-ua-mobile", "?1"),
+        ("sec-ch-ua-model", r#""SM-S918B""#),
+        ("sec-ch-ua-platform", r#""Android""#),
+        ("sec-ch-ua-platform-version", r#""14.0.0""#),
+    ];
+    assert_eq!(
+        used(&detector, REDUCED_ANDROID, &hints)?,
+        vec![
+            Hint::Mobile,
+            Hint::Model,
+            Hint::Platform,
+            Hint::PlatformVersion,
+            Hint::FullVersionList,
+        ]
+    );
+
+    let hints = [
+        ("sec-ch-ua-arch", r#""x86""#),
+        ("sec-ch-ua-bitness", r#""64""#),
+        ("sec-ch-ua-platform", r#""Windows""#),
+        ("sec-ch-ua-platform-version", r#""15.0.0""#),
+    ];
+    assert_eq!(
+        used(&detector, REDUCED_WINDOWS, &hints)?,
+        vec![
+            Hint::Arch,
+            Hint::Bitness,
+            Hint::Platform,
+            Hint::PlatformVersion
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_hints_used_only_those_which_influenced() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    assert_eq!(used(&detector, REDUCED_ANDROID, &[])?, vec![]);
+
+    // the user agent already names the model, the hint is sent for nothing
+    let ua = "Mozilla/5.0 (Linux; Android 13; Pixel 7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36";
+    assert_eq!(
+        used(&detector, ua, &[("sec-ch-ua-model", r#""Pixel 7""#)])?,
+        vec![]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_hints_used_serialized_as_headers() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let hints = headers(&[("sec-ch-ua-model", r#""SM-S918B""#)]);
+    let detection = detector.parse(REDUCED_ANDROID, Some(hints))?;
+    assert_eq!(
+        detection.to_value()["hints_used"],
+        serde_json::json!(["Sec-CH-UA-Model"])
+    );
+
+    let detection = detector.parse(REDUCED_ANDROID, None)?;
+    assert!(detection.to_value().get("hints_used").is_none());
+
+    assert_eq!(
+        Hint::PlatformVersion.to_string(),
+        "Sec-CH-UA-Platform-Version"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_yaml::Mapping;
+
+use std::collections::BTreeMap;
+
+use crate::utils;
+
+use rust_device_detector::device_detector::{Detection, DeviceDetector};
+use rust_device_detector::hints_used::Hint;
+
+const FIXTURES: &str = "tests/data/ipad/desktop_class.yml";
+
+const WEBVIEW: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Mobile/15E148";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Case {
+    user_agent: String,
+    #[serde(default)]
+    headers: Option<Mapping>,
+    #[serde(default)]
+    os: BTreeMap<String, Option<String>>,
+    #[serde(default)]
+    client: BTreeMap<String, Option<String>>,
+    #[serde(default)]
+    device: BTreeMap<String, Option<String>>,
+}
+
+fn detect(detector: &DeviceDetector, case: &Case) -> Result<Detection> {
+    let client_hints = case
+        .headers
+        .as_ref()
+        .map(utils::client_hint_mock)
+        .transpose()?;
+    detector.parse_client_hints(&case.user_agent, client_hints)
+}
+
+fn field(detection: &Detection, part: &str, name: &str) -> Option<String> {
+    let known = detection.get_known_device().expect("known device");
+    match (part, name) {
+        ("os", "name") => known.os.as_ref().map(|x| x.name.clone()),
+        ("os", "version") => known.os.as_ref().and_then(|x| x.version.clone()),
+        ("client", "name") => known.client.as_ref().map(|x| x.name.clone()),
+        ("client", "version") => known.client.as_ref().and_then(|x| x.version.clone()),
+        ("device", "type") => known
+            .device
+            .as_ref()
+            .and_then(|x| x.device_type.as_ref())
+            .map(|x| x.as_str().to_owned()),
+        ("device", "brand") => known.device.as_ref().and_then(|x| x.brand.clone()),
+        ("device", "model") => known.device.as_ref().and_then(|x| x.model.clone()),
+        _ => panic!("no field {}.{} in {}", part, name, FIXTURES),
+    }
+}
+
+#[test]
+fn test_desktop_class_ipads() -> Result<()> {
+    let cases: Vec<Case> = serde_yaml::from_str(&std::fs::read_to_string(FIXTURES)?)?;
+    let detector = DeviceDetector::new().with_desktop_class_ipads(true);
+
+    for (i, case) in cases.iter().enumerate() {
+        let detection = detect(&detector, case)?;
+        for (part, fields) in [
+            ("os", &case.os),
+            ("client", &case.client),
+            ("device", &case.device),
+        ] {
+            for (name, expected) in fields {
+                assert_eq!(
+                    field(&detection, part, name).as_ref(),
+                    expected.as_ref(),
+                    "{}.{} of case {} in {}: {}",
+                    part,
+                    name,
+                    i + 1,
+                    FIXTURES,
+                    case.user_agent
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_desktop_class_ipads_off_by_default() -> Result<()> {
+    let detection = DeviceDetector::new().parse(WEBVIEW, None)?;
+    let os = detection.get_known_device().and_then(|x| x.os.as_ref());
+    assert_eq!(os.map(|x| x.name.as_str()), Some("Mac"));
+
+    Ok(())
+}
+
+#[test]
+fn test_desktop_class_ipads_hints_used() -> Result<()> {
+    let detector = DeviceDetector::new().with_desktop_class_ipads(true);
+
+    let detection = detector.parse(WEBVIEW, None)?;
+    assert!(detection.hints_used().is_empty());
+
+    let detection = detector.parse(
+        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Safari/605.1.15",
+        Some(vec![(
+            "Sec-CH-UA-Form-Factors".to_owned(),
+            "\"Tablet\"".to_owned(),
+        )]),
+    )?;
+    assert_eq!(detection.hints_used(), &[Hint::FormFactors]);
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use anyhow::Result;
+use jsonschema::JSONSchema;
+use serde_json::{json, Value};
+
+use rust_device_detector::client_hints::ClientHint;
+use rust_device_detector::json_schema;
+
+use crate::utils;
+
+const USER_AGENTS: [&str; 6] = [
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1",
+    "Mozilla/5.0 (SMART-TV; LINUX; Tizen 6.0) AppleWebKit/537.36 (KHTML, like Gecko) 76.0.3809.146/6.0 TV Safari/537.36 HbbTV/1.5.1 (+DRM+PVR; Samsung; SmartTV2021; T-NT11-1010.1; T-NT11; ;) FVC/5.0",
+    "Googlebot-News",
+    "curl/8.4.0",
+    "",
+];
+
+fn compile(schema: &Value) -> JSONSchema {
+    JSONSchema::compile(schema).unwrap_or_else(|err| panic!("invalid schema: {}", err))
+}
+
+fn assert_valid(schema: &JSONSchema, value: &Value) {
+    if let Err(errors) = schema.validate(value) {
+        let errors: Vec<_> = errors.map(|x| x.to_string()).collect();
+        panic!("{}: {:?}", value, errors);
+    }
+}
+
+#[test]
+fn test_json_schema() -> Result<()> {
+    let schema = compile(&json_schema::json_schema());
+
+    for ua in USER_AGENTS {
+        assert_valid(&schema, &utils::DD.parse(ua, None)?.to_value());
+    }
+
+    for file in ["bots.yml", "clienthints-app.yml", "tv.yml"] {
+        let contents = std::fs::read_to_string(format!("tests/data/fixtures/{}", file))?;
+        let cases: Vec<serde_yaml::Value> = serde_yaml::from_str(&contents)?;
+
+        for case in cases {
+            let ua = case["user_agent"].as_str().unwrap();
+            let client_hints: Option<ClientHint> = case
+                .get("headers")
+                .and_then(|headers| headers.as_mapping())
+                .and_then(|headers| utils::client_hint_mock(headers).ok());
+
+            let detection = utils::DD.parse_client_hints(ua, client_hints)?;
+            assert_valid(&schema, &detection.to_value());
+        }
+    }
+
+    // fields which aren't declared
+    let mut value = utils::DD.parse("curl/8.4.0", None)?.to_value();
+    value["client"]["extra"] = json!(1);
+    assert!(!schema.is_valid(&value));
+
+    let mut value = utils::DD.parse("curl/8.4.0", None)?.to_value();
+    value["client"]["type"] = json!("spaceship");
+    assert!(!schema.is_valid(&value));
+
+    Ok(())
+}
+
+#[test]
+fn test_openapi() -> Result<()> {
+    let openapi = json_schema::openapi();
+    assert_eq!(openapi["openapi"], "3.1.0");
+
+    let detect = &openapi["paths"]["/detect"]["post"];
+    let response = &detect["responses"]["200"]["content"]["application/json"]["schema"];
+    assert_eq!(response["$ref"], "#/components/schemas/Detection");
+
+    // the response schema, with the components it refers to
+    let schema = compile(&json!({
+        "$ref": response["$ref"],
+        "components": openapi["components"],
+    }));
+    for ua in USER_AGENTS {
+        assert_valid(&schema, &utils::DD.parse(ua, None)?.to_value());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::local::LocalDeviceDetector;
+
+const TRAFFIC: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+    "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+];
+
+#[test]
+fn test_local_detector() -> Result<()> {
+    let shared = DeviceDetector::new();
+    let mut local = LocalDeviceDetector::from(shared.clone()).with_cache(2);
+
+    for pass in 0..2 {
+        for ua in TRAFFIC {
+            let expected = shared.parse(ua, None)?;
+            let detection = local.parse(ua, None)?;
+            assert_eq!(format!("{:?}", detection), format!("{:?}", expected));
+        }
+        if pass == 0 {
+            // emptied once full, before caching the third
+            assert_eq!(local.cached_detections(), 1);
+        }
+    }
+    assert!(local.cached_detections() <= 2);
+
+    // the same user agent with other hints is detected again
+    let reduced = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+    let model = |model: &str| {
+        Some(vec![(
+            "Sec-CH-UA-Model".to_owned(),
+            format!("\"{}\"", model),
+        )])
+    };
+    for hint in ["Pixel 7", "SM-S918B", "Pixel 7"] {
+        let detection = local.parse(reduced, model(hint))?;
+        let expected = shared.parse(reduced, model(hint))?;
+        assert_eq!(format!("{:?}", detection), format!("{:?}", expected));
+    }
+
+    Ok(())
+}
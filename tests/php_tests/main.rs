@@ -3,8 +3,89 @@
 // #[global_allocator]
 //static GLOBAL: &StatsAlloc<System> = &INSTRUMENTED_SYSTEM;
 
+mod approximate;
+#[cfg(feature = "tokio")]
+mod async_parse;
+mod batch;
+#[cfg(feature = "binary-database")]
+mod binary_database;
+mod bot_claims;
+mod bot_likelihood;
+mod bot_spoof;
 mod bots;
+mod canary;
+mod catalog;
+mod client;
 mod client_hints;
+mod client_hints_support;
+mod client_tags;
+mod confidence;
+mod database;
+mod decode;
+mod determinism;
+mod device_class;
+mod device_match;
+mod device_type_mapping;
+mod download_managers;
+#[cfg(feature = "edge")]
+mod edge;
+mod embed;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod fixture_recorder;
 mod fixtures;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod hbbtv;
+#[cfg(feature = "cache")]
+mod hint_cache;
+mod hint_mappings;
+mod hints_only;
+mod hints_precedence;
+mod hints_used;
+mod ipad;
+#[cfg(feature = "json-schema")]
+mod json_schema;
+mod local;
+mod matomo;
+mod media_formats;
+mod memory;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod missing_hints;
+mod model_parts;
+mod normalize;
+mod otel;
+mod output_style;
+mod overlay;
+mod parse_context;
 mod parser;
+mod profiler;
+#[cfg(feature = "python")]
+mod python;
+mod quick;
+mod quirks;
+mod reload;
+#[cfg(feature = "remote-database")]
+mod remote_database;
+mod replay;
+mod resilience;
+mod result_cache;
+mod rules;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod serialization;
+mod session;
+#[cfg(feature = "signed-database")]
+mod signing;
+mod snapshots;
+mod typescript;
+#[cfg(feature = "ua-hash")]
+mod ua_hash;
+mod unknown;
+mod upstream;
 mod utils;
+mod v1;
+mod validation;
+mod waf;
+mod warm_up;
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use rust_device_detector::client_hints::ClientHint;
+
+use crate::utils;
+
+// the fixtures are what the php device detector's getInfoFromUserAgent gives,
+// along with the headers of the request.
+const FIXTURES: [&str; 10] = [
+    "bots.yml",
+    "camera.yml",
+    "clienthints-app.yml",
+    "desktop.yml",
+    "feed_reader.yml",
+    "mediaplayer.yml",
+    "mobile_apps.yml",
+    "smartphone.yml",
+    "tv.yml",
+    "unknown.yml",
+];
+
+/// The fixture as json, with yaml numbers such as unquoted versions as the
+/// strings php has them as.
+fn expected(value: &serde_yaml::Value) -> Value {
+    match value {
+        serde_yaml::Value::Null => Value::Null,
+        serde_yaml::Value::Bool(x) => Value::String(if *x { "1" } else { "" }.to_owned()),
+        serde_yaml::Value::Number(x) => Value::String(x.to_string()),
+        serde_yaml::Value::String(x) => Value::String(x.clone()),
+        serde_yaml::Value::Sequence(x) => Value::Array(x.iter().map(expected).collect()),
+        serde_yaml::Value::Mapping(x) => Value::Object(
+            x.iter()
+                .filter(|(k, _)| k.as_str() != Some("headers"))
+                .map(|(k, v)| (k.as_str().unwrap().to_owned(), expected(v)))
+                .collect(),
+        ),
+        serde_yaml::Value::Tagged(x) => expected(&x.value),
+    }
+}
+
+#[test]
+fn test_matomo_json_fixtures() -> Result<()> {
+    for file in FIXTURES {
+        let contents = std::fs::read_to_string(format!("tests/data/fixtures/{}", file))?;
+        let cases: Vec<serde_yaml::Value> = serde_yaml::from_str(&contents)?;
+
+        for case in cases {
+            let ua = case["user_agent"].as_str().unwrap();
+            // the one producer without a url, which gets an empty one.
+            if ua.starts_with("TactiScout") {
+                continue;
+            }
+            let client_hints: Option<ClientHint> = case
+                .get("headers")
+                .and_then(|headers| headers.as_mapping())
+                .and_then(|headers| utils::client_hint_mock(headers).ok());
+
+            let detection = utils::DD.parse_client_hints(ua, client_hints)?;
+            let json: Value = serde_json::from_str(&detection.to_matomo_json(ua))?;
+
+            assert_eq!(json, expected(&case), "file: {}, ua: {}", file, ua);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_matomo_json() -> Result<()> {
+    let ua = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    assert_eq!(
+        utils::DD.parse(ua, None)?.to_matomo_json(ua),
+        r#"{"user_agent":"Mozilla\/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit\/537.36 (KHTML, like Gecko) Chrome\/120.0.0.0 Safari\/537.36","os":{"name":"Windows","version":"10","platform":"x64"},"client":{"type":"browser","name":"Chrome","version":"120.0.0.0","engine":"Blink","engine_version":"120.0.0.0"},"device":{"type":"desktop","brand":"","model":""},"os_family":"Windows","browser_family":"Chrome"}"#
+    );
+
+    let ua = "curl/8.4.0 ünïcode";
+    assert_eq!(
+        utils::DD.parse(ua, None)?.to_matomo_json(ua),
+        r#"{"user_agent":"curl\/8.4.0 \u00fcn\u00efcode","os":[],"client":{"type":"library","name":"curl","version":"8.4.0"},"device":{"type":"","brand":"","model":""},"os_family":"Unknown","browser_family":"Unknown"}"#
+    );
+
+    let ua = "Googlebot-News";
+    assert_eq!(
+        utils::DD.parse(ua, None)?.to_matomo_json(ua),
+        r#"{"user_agent":"Googlebot-News","bot":{"name":"Googlebot News","category":"Search bot","url":"https:\/\/developers.google.com\/search\/docs\/crawling-indexing\/overview-google-crawlers","producer":{"name":"Google Inc.","url":"https:\/\/www.google.com\/"}}}"#
+    );
+
+    Ok(())
+}
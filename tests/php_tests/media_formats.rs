@@ -0,0 +1,70 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::media_formats::{FormatSupport, MediaFormat};
+
+const CHROME_WINDOWS: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+const CHROME_LINUX: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+const SAMSUNG: &str = "Mozilla/5.0 (Linux; Android 13; SM-S911B) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/23.0 Chrome/115.0.0.0 Mobile Safari/537.36";
+const FIREFOX: &str =
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0";
+const SAFARI: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Safari/605.1.15";
+const IPHONE: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1";
+const CHROME_IPHONE_13: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 13_7 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) CriOS/120.0.6099.119 Mobile/15E148 Safari/604.1";
+const IE: &str = "Mozilla/5.0 (Windows NT 10.0; WOW64; Trident/7.0; rv:11.0) like Gecko";
+const GOOGLEBOT: &str = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+
+#[test]
+fn test_media_formats() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    // avif, webp, hevc
+    for (ua, expected) in [
+        (CHROME_WINDOWS, [true, true, true]),
+        (CHROME_LINUX, [true, true, false]),
+        (SAMSUNG, [true, true, true]),
+        (FIREFOX, [true, true, false]),
+        (SAFARI, [false, true, true]),
+        (IPHONE, [true, true, true]),
+        (CHROME_IPHONE_13, [false, false, true]),
+        (IE, [false, false, false]),
+        (GOOGLEBOT, [false, false, false]),
+    ] {
+        let detection = detector.parse(ua, None)?;
+        let supported = [
+            detection.supports_avif(),
+            detection.supports_webp(),
+            detection.supports_hevc(),
+        ];
+        assert_eq!(supported, expected, "{}", ua);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_media_formats() -> Result<()> {
+    let detector = DeviceDetector::new();
+    let support = FormatSupport::from_yaml(
+        "
+avif:
+  - clients: [Firefox]
+    client_version: '122'
+  - oss: [Windows]
+    engines: [Blink]
+",
+    )?;
+
+    let firefox = detector.parse(FIREFOX, None)?;
+    assert!(!support.supports(&firefox, MediaFormat::Avif));
+    let chrome = detector.parse(CHROME_WINDOWS, None)?;
+    assert!(support.supports(&chrome, MediaFormat::Avif));
+    assert!(!support.supports(&chrome, MediaFormat::Webp));
+    let chrome = detector.parse(CHROME_LINUX, None)?;
+    assert!(!support.supports(&chrome, MediaFormat::Avif));
+
+    assert!(FormatSupport::from_yaml("avif:\n  - browsers: [Firefox]\n").is_err());
+    assert!(FormatSupport::from_yaml("jpegxl: []\n").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+
+use std::sync::Arc;
+
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::DeviceDetector;
+
+const BUDGET: usize = 4 * 1024 * 1024;
+
+const USER_AGENTS: [&str; 4] = [
+    "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36",
+    "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0",
+    "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+];
+
+#[test]
+fn test_memory_budget() -> Result<()> {
+    // a database of its own, so that other tests don't compile its regexes
+    let db = Arc::new(Database::from_dir("data/regexes")?);
+    let detector = DeviceDetector::new()
+        .with_database(db)
+        .with_memory_budget(BUDGET);
+    let unbounded = DeviceDetector::new();
+
+    assert_eq!(detector.memory_usage().compiled_regexes, 0);
+
+    let mut compiled = 0;
+    // a second round compiles again what the first had to free
+    for _ in 0..2 {
+        for ua in USER_AGENTS {
+            let detection = detector.parse(ua, None)?;
+            assert_eq!(
+                serde_json::to_value(detection.to_v1())?,
+                serde_json::to_value(unbounded.parse(ua, None)?.to_v1())?,
+                "{}",
+                ua
+            );
+
+            let usage = detector.memory_usage();
+            assert!(usage.total_bytes() <= BUDGET, "{:?}", usage);
+            assert!(usage.compiled_regexes > 0);
+            compiled = compiled.max(usage.compiled_regexes);
+        }
+    }
+
+    // far more than fit in the budget were compiled along the way
+    let unbounded_usage = unbounded.memory_usage();
+    assert!(
+        unbounded_usage.regex_bytes > BUDGET,
+        "{:?}",
+        unbounded_usage
+    );
+    assert!(compiled < unbounded_usage.compiled_regexes);
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use anyhow::Result;
+
+use std::sync::Arc;
+
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::local::LocalDeviceDetector;
+use rust_device_detector::metrics::LATENCY_BUCKETS;
+use rust_device_detector::overlay::Overlay;
+
+const DESKTOP: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+#[test]
+fn test_metrics() -> Result<()> {
+    let detector = DeviceDetector::new();
+    assert_eq!(detector.metrics().snapshot().detections, 0);
+
+    detector.parse(DESKTOP, None)?;
+    detector.parse("Googlebot-News", None)?;
+    // clones count into the same metrics
+    detector.clone().parse("curl/8.4.0", None)?;
+
+    let snapshot = detector.metrics().snapshot();
+    assert_eq!(snapshot.detections, 3);
+    assert_eq!(snapshot.bots, 1);
+    assert_eq!(snapshot.errors, 0);
+    assert_eq!(snapshot.cache_hit_rate(), None);
+    assert_eq!(snapshot.latency_buckets.len(), LATENCY_BUCKETS.len());
+    assert!(snapshot.latency_buckets.windows(2).all(|x| x[0] <= x[1]));
+    assert!(snapshot.latency_buckets.last().unwrap() <= &3);
+    assert!(!snapshot.latency_sum.is_zero());
+
+    Ok(())
+}
+
+#[test]
+fn test_metrics_errors() -> Result<()> {
+    // an os regex which fails to compile, which every user agent runs into
+    let overlay = Overlay::from_yaml("oss.yml:\n  - regex: 'Brokenos('\n    name: 'Brokenos'\n")?;
+    let detector =
+        DeviceDetector::new_with_database(Arc::new(Database::embedded_with_overlay(&overlay)?));
+
+    assert!(detector.parse(DESKTOP, None).is_err());
+
+    let snapshot = detector.metrics().snapshot();
+    assert_eq!((snapshot.detections, snapshot.errors), (1, 1));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "cache")]
+fn test_metrics_cache() -> Result<()> {
+    let detector = DeviceDetector::new().with_result_cache(10);
+
+    detector.parse(DESKTOP, None)?;
+    detector.parse(DESKTOP, None)?;
+    detector.parse(DESKTOP, None)?;
+    detector.parse("curl/8.4.0", None)?;
+
+    let snapshot = detector.metrics().snapshot();
+    assert_eq!((snapshot.cache_hits, snapshot.cache_misses), (2, 2));
+    assert_eq!(snapshot.cache_hit_rate(), Some(0.5));
+
+    Ok(())
+}
+
+#[test]
+fn test_metrics_local_cache() -> Result<()> {
+    let mut local = LocalDeviceDetector::from(DeviceDetector::new()).with_cache(10);
+    local.parse(DESKTOP, None)?;
+    local.parse(DESKTOP, None)?;
+    let snapshot = local.detector().metrics().snapshot();
+    assert_eq!(snapshot.detections, 2);
+    assert_eq!((snapshot.cache_hits, snapshot.cache_misses), (1, 1));
+
+    Ok(())
+}
+
+#[test]
+fn test_prometheus() -> Result<()> {
+    let detector = DeviceDetector::new();
+    detector.parse(DESKTOP, None)?;
+    detector.parse("Googlebot-News", None)?;
+
+    let text = detector.metrics().to_prometheus();
+    let lines: Vec<_> = text.lines().collect();
+    assert!(lines.contains(&"# TYPE device_detector_detections_total counter"));
+    assert!(lines.contains(&"device_detector_detections_total 2"));
+    assert!(lines.contains(&"device_detector_bot_detections_total 1"));
+    assert!(lines.contains(&"# TYPE device_detector_detection_duration_seconds histogram"));
+    assert!(lines.contains(&"device_detector_detection_duration_seconds_bucket{le=\"+Inf\"} 2"));
+    assert!(lines.contains(&"device_detector_detection_duration_seconds_count 2"));
+    assert!(lines.iter().any(
+        |x| x.starts_with("device_detector_detection_duration_seconds_bucket{le=\"0.00001\"} ")
+    ));
+
+    for line in lines.iter().filter(|x| !x.starts_with('#')) {
+        let (_, value) = line.rsplit_once(' ').unwrap();
+        assert!(value.parse::<f64>().is_ok(), "{}", line);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_unknowns_window() -> Result<()> {
+    let detector = DeviceDetector::new();
+    detector.parse("Zyxwv", None)?;
+    assert_eq!(detector.metrics().snapshot().unknowns, None);
+
+    let detector = DeviceDetector::new().with_unknowns_window(3);
+    let unknowns = detector.metrics().snapshot().unknowns.expect("unknowns");
+    assert_eq!((unknowns.window, unknowns.parses), (3, 0));
+    assert_eq!(unknowns.unknown_browser_rate(), None);
+
+    detector.parse("Zyxwv/1.0 (Example)", None)?;
+    detector.parse("Zyxwv/2.0 (Example)", None)?;
+    // bots are never unknown
+    detector.parse("Googlebot-News", None)?;
+
+    let unknowns = detector.metrics().snapshot().unknowns.expect("unknowns");
+    assert_eq!(unknowns.parses, 3);
+    assert_eq!(
+        (unknowns.unknown_browsers, unknowns.unknown_devices),
+        (2, 2)
+    );
+    assert_eq!(
+        unknowns.top_prefixes,
+        vec![
+            ("Zyxwv/1.0 (Example)".to_owned(), 1),
+            ("Zyxwv/2.0 (Example)".to_owned(), 1)
+        ]
+    );
+
+    // the oldest parses leave the window
+    detector.parse(DESKTOP, None)?;
+    detector.parse(DESKTOP, None)?;
+    let unknowns = detector.metrics().snapshot().unknowns.expect("unknowns");
+    assert_eq!(unknowns.parses, 3);
+    assert_eq!(unknowns.unknown_browsers, 0);
+    assert_eq!(unknowns.unknown_browser_rate(), Some(0.0));
+    assert!(unknowns.top_prefixes.is_empty());
+
+    let long = "Zyxwv/1.0 (Example; with a comment longer than the prefix)";
+    detector.parse(long, None)?;
+    let unknowns = detector.metrics().snapshot().unknowns.expect("unknowns");
+    assert_eq!(unknowns.top_prefixes, vec![(long[..40].to_owned(), 1)]);
+
+    let prometheus = detector.metrics().to_prometheus();
+    assert!(prometheus.contains("device_detector_window_parses 3\n"));
+    assert!(prometheus.contains(&format!(
+        "device_detector_unknown_prefix_parses{{prefix=\"{}\"}} 1\n",
+        &long[..40]
+    )));
+
+    Ok(())
+}
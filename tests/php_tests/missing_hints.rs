@@ -0,0 +1,97 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::missing_hints::Improvement;
+
+const REDUCED_ANDROID: &str = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+const REDUCED_WINDOWS: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+fn headers(headers: &[(&str, &str)]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+fn missing(
+    detector: &DeviceDetector,
+    ua: &str,
+    hints: &[(&str, &str)],
+) -> Result<Vec<(&'static str, Improvement)>> {
+    let hints = (!hints.is_empty()).then(|| headers(hints));
+    let detection = detector.parse(ua, hints)?;
+    Ok(detection
+        .missing_hints()
+        .into_iter()
+        .map(|x| (x.header, x.improves))
+        .collect())
+}
+
+#[test]
+fn test_missing_hints_reduced_user_agents() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    assert_eq!(
+        missing(&detector, REDUCED_ANDROID, &[])?,
+        vec![
+            ("Sec-CH-UA-Model", Improvement::DeviceModel),
+            ("Sec-CH-UA-Platform-Version", Improvement::OsVersion),
+            ("Sec-CH-UA-Full-Version-List", Improvement::ClientVersion),
+        ]
+    );
+
+    assert_eq!(
+        missing(&detector, REDUCED_WINDOWS, &[])?,
+        vec![
+            ("Sec-CH-UA-Platform-Version", Improvement::OsVersion),
+            ("Sec-CH-UA-Full-Version-List", Improvement::ClientVersion),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_missing_hints_delegated() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let hints = [
+        (
+            "sec-ch-ua-full-version-list",
+            r#""Chromium";v="120.0.6099.144", "Google Chrome";v="120.0.6099.144""#,
+        ),
+        ("sec-ch-ua-mobile", "?1"),
+        ("sec-ch-ua-model", r#""SM-S918B""#),
+        ("sec-ch-ua-platform", r#""Android""#),
+        ("sec-ch-ua-platform-version", r#""14.0.0""#),
+    ];
+    assert_eq!(missing(&detector, REDUCED_ANDROID, &hints)?, vec![]);
+
+    let hints = [
+        (
+            "sec-ch-ua-full-version-list",
+            r#""Chromium";v="120.0.6099.144", "Google Chrome";v="120.0.6099.144""#,
+        ),
+        ("sec-ch-ua-platform", r#""Windows""#),
+        ("sec-ch-ua-platform-version", r#""15.0.0""#),
+    ];
+    assert_eq!(missing(&detector, REDUCED_WINDOWS, &hints)?, vec![]);
+
+    Ok(())
+}
+
+#[test]
+fn test_missing_hints_without_client_hints_support() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let uas = [
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0",
+        "Mozilla/5.0 (iPhone; CPU iPhone OS 17_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Mobile/15E148 Safari/604.1",
+        "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+    ];
+    for ua in uas {
+        assert_eq!(missing(&detector, ua, &[])?, vec![], "{}", ua);
+    }
+
+    Ok(())
+}
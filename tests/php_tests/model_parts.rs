@@ -0,0 +1,121 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::parsers::device::model_parts::{split, ModelParts};
+
+fn parts(
+    series: Option<&str>,
+    variant: Option<&str>,
+    connectivity: Option<&str>,
+    storage: Option<&str>,
+    screen_size: Option<&str>,
+) -> ModelParts {
+    ModelParts {
+        series: series.map(str::to_owned),
+        variant: variant.map(str::to_owned),
+        connectivity: connectivity.map(str::to_owned),
+        storage: storage.map(str::to_owned),
+        screen_size: screen_size.map(str::to_owned),
+    }
+}
+
+#[test]
+fn test_split_model() -> Result<()> {
+    let cases = [
+        (
+            "Samsung",
+            "Galaxy S21 Ultra 5G",
+            parts(Some("Galaxy S21"), Some("Ultra"), Some("5G"), None, None),
+        ),
+        (
+            "Samsung",
+            "Galaxy S20+",
+            parts(Some("Galaxy S20"), Some("+"), None, None, None),
+        ),
+        (
+            "Samsung",
+            "Galaxy S21 5G UW",
+            parts(Some("Galaxy S21"), None, Some("5G UW"), None, None),
+        ),
+        (
+            "Samsung",
+            "Galaxy Tab S9 FE+ 12.4\" WiFi",
+            parts(
+                Some("Galaxy Tab S9"),
+                Some("FE+"),
+                Some("WiFi"),
+                None,
+                Some("12.4\""),
+            ),
+        ),
+        (
+            "Samsung",
+            "Galaxy Z Flip 5",
+            parts(Some("Galaxy Z Flip 5"), None, None, None, None),
+        ),
+        (
+            "Apple",
+            "iPhone 14 Pro Max",
+            parts(Some("iPhone 14"), Some("Pro Max"), None, None, None),
+        ),
+        (
+            "Google",
+            "Pixel 7a",
+            parts(Some("Pixel 7a"), None, None, None, None),
+        ),
+        (
+            "Xiaomi",
+            "Redmi Note 12 Pro 5G",
+            parts(Some("Redmi Note 12"), Some("Pro"), Some("5G"), None, None),
+        ),
+        (
+            "OnePlus",
+            "Nord CE 3 Lite",
+            parts(Some("Nord CE 3"), Some("Lite"), None, None, None),
+        ),
+        // no series for brands without a pattern
+        (
+            "Nokia",
+            "G21 128GB LTE",
+            parts(None, None, Some("LTE"), Some("128GB"), None),
+        ),
+    ];
+
+    for (brand, model, expected) in cases {
+        assert_eq!(split(brand, model)?, expected, "{} {}", brand, model);
+    }
+    assert!(split("Nokia", "G21")?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_device_model_parts() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let detection = detector.parse(
+        "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+        None,
+    )?;
+    let device = detection
+        .get_known_device()
+        .and_then(|x| x.device.clone())
+        .expect("device");
+    assert_eq!(device.model.as_deref(), Some("Galaxy S23 Ultra"));
+
+    let parts = device.model_parts()?.expect("model parts");
+    assert_eq!(parts.series.as_deref(), Some("Galaxy S23"));
+    assert_eq!(parts.variant.as_deref(), Some("Ultra"));
+
+    let detection = detector.parse(
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        None,
+    )?;
+    let device = detection
+        .get_known_device()
+        .and_then(|x| x.device.clone())
+        .expect("device");
+    assert_eq!(device.model_parts()?, None);
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::{Detection, DeviceDetector};
+
+// os, os major version, client type, client major version and device type
+fn coarse(detection: &Detection) -> Vec<Option<String>> {
+    let known = detection.get_known_device().expect("known device");
+    let major = |x: Option<&String>| x.and_then(|x| x.split('.').next()).map(|x| x.to_owned());
+
+    vec![
+        known.os.as_ref().map(|x| x.name.clone()),
+        major(known.os.as_ref().and_then(|x| x.version.as_ref())),
+        known.client.as_ref().map(|x| x.r#type.as_str().to_owned()),
+        major(known.client.as_ref().and_then(|x| x.version.as_ref())),
+        known
+            .device
+            .as_ref()
+            .and_then(|x| x.device_type.as_ref())
+            .map(|x| x.as_str().to_owned()),
+    ]
+}
+
+#[test]
+fn test_normalize_keeps_coarse_classification() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let uas = [
+        "Mozilla/5.0 (Linux; Android 13; SM-S911B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.43 Mobile Safari/537.36",
+        "Mozilla/5.0 (Linux; Android 12; SM-X700) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/119.0.6045.163 Safari/537.36",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.71 Safari/537.36",
+        "Mozilla/5.0 (Windows NT 6.1; WOW64; rv:115.0) Gecko/20100101 Firefox/115.3",
+        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Safari/605.1.15",
+        "Mozilla/5.0 (iPhone; CPU iPhone OS 16_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.5 Mobile/15E148 Safari/604.1",
+        "Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0",
+    ];
+
+    for ua in uas {
+        let res = detector.normalize(ua, None)?;
+        assert!(res.headers.is_empty());
+
+        // normalizing is idempotent
+        assert_eq!(detector.normalize(&res.user_agent, None)?, res);
+
+        let expected = detector.parse(ua, None)?;
+        let normalized = detector.parse(&res.user_agent, None)?;
+        assert_eq!(
+            coarse(&normalized),
+            coarse(&expected),
+            "ua: {}\nnormalized: {}",
+            ua,
+            res.user_agent
+        );
+
+        // the device model never makes it through, apple's generic ones aside
+        let model = normalized
+            .get_known_device()
+            .and_then(|x| x.device.as_ref())
+            .and_then(|x| x.model.as_deref());
+        assert!(
+            matches!(model, None | Some("iPhone") | Some("iPad")),
+            "normalized: {}",
+            res.user_agent
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_normalize_hints() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let ua = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+    let headers = vec![
+        ("sec-ch-ua-mobile".to_owned(), "?1".to_owned()),
+        ("sec-ch-ua-model".to_owned(), r#""Pixel 7""#.to_owned()),
+        ("sec-ch-ua-platform".to_owned(), r#""Android""#.to_owned()),
+        (
+            "sec-ch-ua-platform-version".to_owned(),
+            r#""13.0.0""#.to_owned(),
+        ),
+        ("sec-ch-ua-arch".to_owned(), r#""arm""#.to_owned()),
+    ];
+
+    let res = detector.normalize(ua, Some(headers))?;
+
+    let names = res
+        .headers
+        .iter()
+        .map(|(x, _)| x.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec!["Sec-CH-UA", "Sec-CH-UA-Mobile", "Sec-CH-UA-Platform"]
+    );
+    assert!(res.user_agent.contains("Android 13;"), "{}", res.user_agent);
+
+    // bots are left alone
+    let ua = "Googlebot/2.1 (+http://www.google.com/bot.html)";
+    assert_eq!(detector.normalize(ua, None)?.user_agent, ua);
+
+    Ok(())
+}
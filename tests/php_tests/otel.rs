@@ -0,0 +1,72 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::otel::{Attribute, AttributeValue};
+
+fn attributes(ua: &str) -> Result<Vec<(&'static str, AttributeValue)>> {
+    let detection = DeviceDetector::new().parse(ua, None)?;
+    Ok(detection
+        .to_otel_attributes()
+        .into_iter()
+        .map(|Attribute { key, value }| (key, value))
+        .collect())
+}
+
+fn string(value: &str) -> AttributeValue {
+    AttributeValue::String(value.to_owned())
+}
+
+#[test]
+fn test_otel_attributes() -> Result<()> {
+    let attributes = attributes(
+        "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36",
+    )?;
+
+    assert_eq!(
+        attributes,
+        vec![
+            ("user_agent.name", string("Chrome Mobile")),
+            ("user_agent.version", string("120.0.6099.144")),
+            ("os.name", string("Android")),
+            ("os.version", string("13")),
+            ("os.type", string("linux")),
+            ("browser.platform", string("Android")),
+            ("browser.mobile", AttributeValue::Bool(true)),
+            ("device.manufacturer", string("Samsung")),
+            ("device.model.name", string("Galaxy S23 Ultra")),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_otel_attributes_desktop() -> Result<()> {
+    let attributes = attributes(
+        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Safari/605.1.15",
+    )?;
+
+    assert!(attributes.contains(&("os.type", string("darwin"))));
+    assert!(attributes.contains(&("browser.mobile", AttributeValue::Bool(false))));
+    assert!(attributes
+        .iter()
+        .all(|(key, _)| *key != "device.model.name"));
+
+    Ok(())
+}
+
+#[test]
+fn test_otel_attributes_bot() -> Result<()> {
+    let attributes =
+        attributes("Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)")?;
+
+    assert_eq!(
+        attributes,
+        vec![
+            ("user_agent.name", string("Googlebot")),
+            ("user_agent.synthetic.type", string("bot")),
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use rust_device_detector::output_style::{FieldCase, Layout, OutputStyle};
+
+use crate::utils;
+
+const UA: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+#[test]
+fn test_output_style_layouts() -> Result<()> {
+    let detection = utils::DD.parse(UA, None)?;
+
+    assert_eq!(
+        detection.to_styled_value(UA, &OutputStyle::DEFAULT),
+        detection.clone().to_value()
+    );
+
+    let matomo: Value = serde_json::from_str(&detection.to_matomo_json(UA))?;
+    assert_eq!(detection.to_styled_value(UA, &OutputStyle::MATOMO), matomo);
+
+    Ok(())
+}
+
+#[test]
+fn test_output_style_camel_case() -> Result<()> {
+    let detection = utils::DD.parse(UA, None)?;
+
+    let style = OutputStyle {
+        layout: Layout::Matomo,
+        case: FieldCase::Camel,
+        flatten: false,
+    };
+    let value = detection.to_styled_value(UA, &style);
+    assert_eq!(value["userAgent"], UA);
+    assert_eq!(value["osFamily"], "Windows");
+    assert_eq!(value["client"]["engineVersion"], "120.0.0.0");
+    assert!(value.get("os_family").is_none());
+
+    assert_eq!(
+        FieldCase::Camel.field("portable_media_player"),
+        "portableMediaPlayer"
+    );
+    assert_eq!(
+        FieldCase::Snake.field("portable_media_player"),
+        "portable_media_player"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_output_style_flatten() -> Result<()> {
+    let detection = utils::DD.parse(UA, None)?;
+
+    let style = OutputStyle {
+        flatten: true,
+        ..OutputStyle::DEFAULT
+    };
+    let value = detection.to_styled_value(UA, &style);
+    assert_eq!(value["client_name"], "Chrome");
+    assert_eq!(value["os_version"], "10");
+    assert_eq!(value["device_type"], "desktop");
+    assert_eq!(value["is_desktop"], true);
+    assert!(value.as_object().unwrap().values().all(|x| !x.is_object()));
+
+    let style = OutputStyle {
+        case: FieldCase::Camel,
+        ..style
+    };
+    let ua = "curl/8.4.0";
+    let value = utils::DD.parse(ua, None)?.to_styled_value(ua, &style);
+    assert_eq!(value["clientName"], "curl");
+    assert_eq!(value["os"], Value::Null);
+
+    let style = OutputStyle {
+        layout: Layout::Matomo,
+        ..style
+    };
+    let ua = "Googlebot-News";
+    assert_eq!(
+        utils::DD.parse(ua, None)?.to_styled_value(ua, &style),
+        json!({
+            "userAgent": "Googlebot-News",
+            "botName": "Googlebot News",
+            "botCategory": "Search bot",
+            "botUrl": "https://developers.google.com/search/docs/crawling-indexing/overview-google-crawlers",
+            "botProducerName": "Google Inc.",
+            "botProducerUrl": "https://www.google.com/",
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_output_style_deserialize() -> Result<()> {
+    let style: OutputStyle = serde_json::from_str(r#"{"case": "camel", "flatten": true}"#)?;
+    assert_eq!(
+        style,
+        OutputStyle {
+            layout: Layout::Detection,
+            case: FieldCase::Camel,
+            flatten: true,
+        }
+    );
+
+    Ok(())
+}
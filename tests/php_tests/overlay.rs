@@ -0,0 +1,250 @@
+use anyhow::Result;
+
+use std::sync::Arc;
+
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::{Detection, DeviceDetector};
+use rust_device_detector::overlay::{
+    BotRule, BrowserRule, DeviceRule, MobileAppRule, Overlay, Position, Precedence,
+};
+use rust_device_detector::parsers::device::DeviceType;
+
+const OVERLAY: &str = r#"
+bots.yml:
+  - regex: 'Zyxwv/'
+    name: 'Zyxwv'
+    category: 'Crawler'
+  - regex: 'Googlebot'
+    name: 'Googlebot'
+    category: 'Overlaid'
+device/mobiles.yml:
+  Samsung:
+    models:
+      - regex: 'SM-S999'
+        model: 'Galaxy Example'
+"#;
+
+const PHONE: &str = "Mozilla/5.0 (Linux; Android 15; SM-S999B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Mobile Safari/537.36";
+
+fn bot(detector: &DeviceDetector, ua: &str) -> Result<Option<(String, Option<String>)>> {
+    Ok(match detector.parse(ua, None)? {
+        Detection::Bot(bot) => Some((bot.name, bot.category)),
+        Detection::Known(_) => None,
+    })
+}
+
+fn model(detector: &DeviceDetector, ua: &str) -> Result<Option<String>> {
+    let detection = detector.parse(ua, None)?;
+    Ok(detection
+        .get_known_device()
+        .and_then(|x| x.device.as_ref())
+        .and_then(|x| x.model.clone()))
+}
+
+#[test]
+fn test_overlay() -> Result<()> {
+    let overlay = Overlay::from_yaml(OVERLAY)?;
+    assert_eq!(
+        overlay.files().collect::<Vec<_>>(),
+        vec!["bots.yml", "device/mobiles.yml"]
+    );
+
+    let base = DeviceDetector::new();
+    let detector =
+        DeviceDetector::new_with_database(Arc::new(Database::embedded_with_overlay(&overlay)?));
+
+    // added
+    assert_eq!(bot(&base, "Zyxwv/1.0")?, None);
+    assert_eq!(
+        bot(&detector, "Zyxwv/1.0")?,
+        Some(("Zyxwv".to_owned(), Some("Crawler".to_owned())))
+    );
+
+    // replaced by name, in place
+    let googlebot = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+    assert_eq!(
+        bot(&detector, googlebot)?,
+        Some(("Googlebot".to_owned(), Some("Overlaid".to_owned())))
+    );
+
+    // models are added to the brand's own
+    assert_ne!(model(&base, PHONE)?.as_deref(), Some("Galaxy Example"));
+    assert_eq!(model(&detector, PHONE)?.as_deref(), Some("Galaxy Example"));
+    assert_eq!(
+        model(
+            &detector,
+            "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36"
+        )?
+        .as_deref(),
+        Some("Galaxy S23 Ultra")
+    );
+
+    let from_dir = DeviceDetector::new_with_database(Arc::new(Database::from_dir_with_overlay(
+        "data/regexes",
+        &overlay,
+    )?));
+    assert_eq!(model(&from_dir, PHONE)?.as_deref(), Some("Galaxy Example"));
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_overlay() -> Result<()> {
+    assert!(Overlay::from_yaml("device/phones.yml: {}").is_err());
+
+    let unnamed = Overlay::from_yaml("bots.yml:\n  - regex: 'ExampleBot'\n")?;
+    assert!(Database::embedded_with_overlay(&unnamed).is_err());
+
+    let mismatched = Overlay::from_yaml("bots.yml:\n  ExampleBot: 'ExampleBot'\n")?;
+    assert!(Database::embedded_with_overlay(&mismatched).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rules() -> Result<()> {
+    let overlay = Overlay::default()
+        .with_browser(
+            BrowserRule::new(r"AcmeBrowser/(\d+[.\d]+)", "Acme Browser").engine("Blink"),
+            Position::Before,
+        )
+        .with_browser(
+            BrowserRule::new(r"Chrome/(\d+[.\d]+)", "Not Chrome"),
+            Position::After,
+        )
+        .with_mobile_app(
+            MobileAppRule::new(r"AcmeApp/(\d+[.\d]+)", "Acme App"),
+            Position::Before,
+        )
+        .with_bot(
+            BotRule::new("AcmeMonitor", "Acme Monitor").category("Site Monitor"),
+            Position::Before,
+        )
+        .with_device(
+            DeviceRule::new("AcmeKiosk", "Acme", "Kiosk", DeviceType::SmartDisplay),
+            Position::Before,
+        )
+        .with_device(
+            DeviceRule::new("SM-X999", "Samsung", "Example Tab", DeviceType::Tablet),
+            Position::Before,
+        );
+    assert_eq!(
+        overlay.files().collect::<Vec<_>>(),
+        vec![
+            "bots.yml",
+            "client/browsers.yml",
+            "client/mobile_apps.yml",
+            "device/mobiles.yml"
+        ]
+    );
+
+    let detector =
+        DeviceDetector::new_with_database(Arc::new(Database::embedded_with_overlay(&overlay)?));
+
+    let detection = detector.parse(
+        "Mozilla/5.0 (Linux; Android 12; AcmeKiosk) AppleWebKit/537.36 (KHTML, like Gecko) AcmeBrowser/3.2.1 Chrome/120.0.0.0 Safari/537.36",
+        None,
+    )?;
+    let known = detection.get_known_device().expect("known");
+    let client = known.client.as_ref().expect("client");
+    assert_eq!(client.name, "Acme Browser");
+    assert_eq!(client.version.as_deref(), Some("3.2.1"));
+    assert_eq!(client.engine.as_deref(), Some("Blink"));
+    let device = known.device.as_ref().expect("device");
+    assert_eq!(device.brand.as_deref(), Some("Acme"));
+    assert_eq!(device.model.as_deref(), Some("Kiosk"));
+    assert_eq!(device.device_type, Some(DeviceType::SmartDisplay));
+
+    // after the bundled entries, so Chrome is still Chrome
+    let detection = detector.parse(
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        None,
+    )?;
+    let client = detection.get_known_device().and_then(|x| x.client.as_ref());
+    assert_eq!(client.map(|x| x.name.as_str()), Some("Chrome"));
+
+    let detection = detector.parse("AcmeApp/1.4 (iPhone; iOS 17.0)", None)?;
+    let client = detection.get_known_device().and_then(|x| x.client.as_ref());
+    assert_eq!(client.map(|x| x.name.as_str()), Some("Acme App"));
+
+    assert_eq!(
+        bot(&detector, "AcmeMonitor/1.0")?,
+        Some(("Acme Monitor".to_owned(), Some("Site Monitor".to_owned())))
+    );
+
+    // a model of a brand the database already has, which keeps its own
+    assert_eq!(
+        model(
+            &detector,
+            "Mozilla/5.0 (Linux; Android 14; SM-X999) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"
+        )?
+        .as_deref(),
+        Some("Example Tab")
+    );
+    assert_eq!(
+        model(&detector, PHONE)?,
+        model(&DeviceDetector::new(), PHONE)?
+    );
+
+    Ok(())
+}
+
+const PARTNER_BOTS: &str = r#"
+- regex: 'AcmePartner'
+  name: 'Partner Crawler'
+  category: 'Partner'
+- regex: 'Googlebot'
+  name: 'Googlebot'
+  category: 'Partner'
+"#;
+
+#[test]
+fn test_bots() -> Result<()> {
+    let googlebot = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+
+    let extra = Overlay::default().with_bots_yaml(PARTNER_BOTS, Precedence::Extra)?;
+    assert_eq!(extra.files().collect::<Vec<_>>(), vec!["bots.yml"]);
+    let detector =
+        DeviceDetector::new_with_database(Arc::new(Database::embedded_with_overlay(&extra)?));
+    assert_eq!(
+        bot(&detector, "AcmePartner/2.0")?,
+        Some(("Partner Crawler".to_owned(), Some("Partner".to_owned())))
+    );
+    assert_eq!(
+        bot(&detector, googlebot)?,
+        Some(("Googlebot".to_owned(), Some("Partner".to_owned())))
+    );
+
+    let bundled = Overlay::default().with_bots_yaml(PARTNER_BOTS, Precedence::Bundled)?;
+    let detector =
+        DeviceDetector::new_with_database(Arc::new(Database::embedded_with_overlay(&bundled)?));
+    assert_eq!(
+        bot(&detector, "AcmePartner/2.0")?,
+        Some(("Partner Crawler".to_owned(), Some("Partner".to_owned())))
+    );
+    assert_eq!(
+        bot(&detector, googlebot)?,
+        bot(&DeviceDetector::new(), googlebot)?
+    );
+
+    // in memory, the same as from yaml
+    let in_memory = Overlay::default().with_bots(
+        [BotRule::new("AcmePartner", "Partner Crawler").category("Partner")],
+        Precedence::Extra,
+    );
+    let detector =
+        DeviceDetector::new_with_database(Arc::new(Database::embedded_with_overlay(&in_memory)?));
+    assert_eq!(
+        bot(&detector, "AcmePartner/2.0")?,
+        Some(("Partner Crawler".to_owned(), Some("Partner".to_owned())))
+    );
+
+    assert!(Overlay::default()
+        .with_bots_yaml("- regex: 'AcmePartner'", Precedence::Extra)
+        .is_err());
+    assert!(Overlay::default()
+        .with_bots_file("does/not/exist.yml", Precedence::Extra)
+        .is_err());
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::{DeviceDetector, ParseContext};
+use rust_device_detector::parsers::device::DeviceMatchStrategy;
+
+#[test]
+fn test_parse_with_context() -> Result<()> {
+    let uas = [
+        "Mozilla/5.0 (Linux; Android 10; SM-G960F) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/83.0.4103.106 Mobile Safari/537.36",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        "Mozilla/5.0 (iPhone; CPU iPhone OS 16_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.5 Mobile/15E148 Safari/604.1",
+        "Mozilla/5.0 (Linux; Android 5.0; BLU VIVO SELFIE) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/80.0.3987.149 Mobile Safari/537.36",
+        "Googlebot/2.1 (+http://www.google.com/bot.html)",
+        "",
+    ];
+
+    let first = DeviceDetector::new();
+    let best = DeviceDetector::new().with_device_match(DeviceMatchStrategy::Best);
+
+    // one context shared between detectors and calls must not leak state
+    // from one parse into the next.
+    let mut ctx = ParseContext::new();
+
+    for _ in 0..2 {
+        for ua in uas {
+            for detector in [&first, &best] {
+                let expected = detector.parse(ua, None)?.to_value();
+                let res = detector.parse_with_context(ua, None, &mut ctx)?.to_value();
+                assert_eq!(res, expected, "ua: {}", ua);
+            }
+        }
+    }
+
+    Ok(())
+}
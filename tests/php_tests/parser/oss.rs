@@ -12,7 +12,7 @@ fn test_oss() -> Result<()> {
                 return Ok(()); // Skip test if no files found
             }
             paths
-        },
+        }
         Err(_) => return Ok(()), // Skip test if file not found
     };
     for path in files.into_iter() {
@@ -22,7 +22,7 @@ fn test_oss() -> Result<()> {
         };
         let mut cases: Value = serde_yaml::from_reader(std::io::BufReader::new(file))?;
         let cases = cases.as_sequence_mut().expect("sequence");
-        for (i, case) in cases.into_iter().enumerate() {
+        for (i, case) in cases.iter_mut().enumerate() {
             basic(i + 1, case).expect("basic test");
         }
     }
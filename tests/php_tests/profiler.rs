@@ -0,0 +1,55 @@
+use anyhow::Result;
+
+use std::sync::Arc;
+
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::DeviceDetector;
+
+const USER_AGENTS: [&str; 4] = [
+    "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36",
+    "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0",
+    "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+];
+
+#[test]
+fn test_regex_profile() -> Result<()> {
+    // a database of its own, so that other tests don't add to its timings
+    let db = Arc::new(Database::from_dir("data/regexes")?);
+    let detector = DeviceDetector::new()
+        .with_database(db)
+        .with_regex_profiling(2);
+
+    let profile = detector.regex_profile().expect("profiling");
+    assert_eq!(profile.sampled_detections, 0);
+    assert!(profile.entries.is_empty());
+
+    for ua in USER_AGENTS {
+        detector.parse(ua, None)?;
+    }
+
+    let profile = detector.regex_profile().expect("profiling");
+    assert_eq!(profile.sampled_detections, 2);
+    assert!(!profile.entries.is_empty());
+    assert!(profile.entries.iter().all(|x| x.matches > 0));
+    assert!(profile.entries.windows(2).all(|x| x[0].total >= x[1].total));
+    // every detection starts by checking for a bot
+    assert!(profile.entries.iter().any(|x| x.list == "bots.yml"));
+    assert!(profile.entries.iter().all(|x| !x.pattern.is_empty()));
+
+    detector.reset_regex_profile();
+    let profile = detector.regex_profile().expect("profiling");
+    assert_eq!(profile.sampled_detections, 0);
+    assert!(profile.entries.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_regex_profile_off_by_default() -> Result<()> {
+    let detector = DeviceDetector::new();
+    detector.parse(USER_AGENTS[0], None)?;
+    assert!(detector.regex_profile().is_none());
+
+    Ok(())
+}
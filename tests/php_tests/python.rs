@@ -0,0 +1,29 @@
+use pyo3::prelude::*;
+
+use rust_device_detector::python::rust_device_detector;
+
+fn run(code: &str) -> PyResult<()> {
+    pyo3::append_to_inittab!(rust_device_detector);
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| py.run_bound(code, None, None))
+}
+
+#[test]
+fn test_python_bindings() -> PyResult<()> {
+    run(r#"
+from rust_device_detector import ClientHint, DeviceDetector
+
+ua = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36"
+detector = DeviceDetector()
+
+detection = detector.parse(ua, {"Sec-CH-UA-Model": '"Pixel 7"'})
+assert detection["client"]["name"] == "Chrome Mobile", detection
+assert detection["device"]["model"] == "Pixel 7", detection
+
+hints = ClientHint.from_headers({"sec-ch-ua-mobile": "?1", "sec-ch-ua-platform": '"Android"'})
+assert hints.mobile and hints.platform == "Android", hints
+assert detector.parse_client_hints(ua, hints)["os"]["name"] == "Android"
+
+assert detector.parse("Googlebot-News")["bot"]["name"] == "Googlebot News"
+"#)
+}
@@ -0,0 +1,68 @@
+use anyhow::Result;
+
+use rust_device_detector::device_class::DeviceClass;
+use rust_device_detector::quick;
+
+use crate::utils;
+
+const COMMON: [(&str, DeviceClass); 12] = [
+    ("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36", DeviceClass::Desktop),
+    ("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Safari/605.1.15", DeviceClass::Desktop),
+    ("Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36", DeviceClass::Mobile),
+    ("Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36", DeviceClass::Tablet),
+    ("Mozilla/5.0 (Android 14; Mobile; rv:121.0) Gecko/121.0 Firefox/121.0", DeviceClass::Mobile),
+    ("Mozilla/5.0 (Android 14; Tablet; rv:121.0) Gecko/121.0 Firefox/121.0", DeviceClass::Tablet),
+    ("Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1", DeviceClass::Mobile),
+    ("Mozilla/5.0 (iPad; CPU OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1", DeviceClass::Tablet),
+    ("Mozilla/5.0 (SMART-TV; LINUX; Tizen 6.0) AppleWebKit/537.36 (KHTML, like Gecko) 76.0.3809.146/6.0 TV Safari/537.36", DeviceClass::Tv),
+    ("Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)", DeviceClass::Bot),
+    ("curl/8.4.0", DeviceClass::Bot),
+    ("Spotify/8.6.72 iOS/13.5.1 (iPhone9,2)", DeviceClass::Mobile),
+];
+
+// usable in const contexts
+const _: () = assert!(quick::is_probably_mobile(COMMON[2].0));
+
+#[test]
+fn test_quick_common() {
+    for (ua, class) in COMMON {
+        assert_eq!(quick::classify(ua), class, "{}", ua);
+    }
+    assert_eq!(quick::classify(""), DeviceClass::Other);
+}
+
+#[test]
+fn test_quick_is_not_fooled_by_brands() {
+    // cubot phones, and the tablet pc token of internet explorer
+    assert!(!quick::is_probably_bot(
+        "Mozilla/5.0 (Linux; Android 9; CUBOT_X19) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36"
+    ));
+    assert!(quick::is_probably_desktop(
+        "Mozilla/5.0 (Windows NT 6.1; WOW64; Trident/7.0; Tablet PC 2.0; rv:11.0) like Gecko"
+    ));
+}
+
+// mostly agrees with the full detection on the browsers of the fixtures.
+#[test]
+fn test_quick_fixtures() -> Result<()> {
+    let (mut total, mut agreed) = (0, 0);
+    for name in ["desktop", "smartphone-1", "tablet-1", "tv"] {
+        let path = format!("tests/data/fixtures/{}.yml", name);
+        let cases: Vec<serde_yaml::Value> = serde_yaml::from_str(&std::fs::read_to_string(path)?)?;
+
+        for case in cases {
+            let ua = case["user_agent"].as_str().unwrap();
+            if !ua.starts_with("Mozilla/5.0 (") {
+                continue;
+            }
+
+            total += 1;
+            if quick::classify(ua) == DeviceClass::of(&utils::DD.parse(ua, None)?) {
+                agreed += 1;
+            }
+        }
+    }
+    assert!(agreed * 10 > total * 8, "{} of {}", agreed, total);
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::parsers::client::Client;
+use rust_device_detector::quirks::{Quirk, QuirkSet};
+
+const CHROME: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+fn client(detector: &DeviceDetector, ua: &str, brands: &str) -> Result<Client> {
+    let headers = vec![("Sec-CH-UA-Full-Version-List".to_owned(), brands.to_owned())];
+    let detection = detector.parse(ua, Some(headers))?;
+    Ok(detection
+        .get_known_device()
+        .and_then(|x| x.client.clone())
+        .expect("client"))
+}
+
+#[test]
+fn test_quirk_ids() -> Result<()> {
+    for quirk in Quirk::ALL {
+        assert_eq!(quirk.id().parse::<Quirk>()?, quirk);
+        assert_eq!(quirk.to_string(), quirk.id());
+    }
+    assert!("no-such-quirk".parse::<Quirk>().is_err());
+
+    let mut set = QuirkSet::default();
+    set.insert(Quirk::OperaWebView);
+    set.insert(Quirk::IridiumYear);
+    assert!(set.contains(Quirk::OperaWebView));
+    assert!(!set.contains(Quirk::DuckDuckGoVersion));
+    assert_eq!(
+        set.iter().collect::<Vec<_>>(),
+        vec![Quirk::IridiumYear, Quirk::OperaWebView]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_disable_iridium_year() -> Result<()> {
+    let brands = r#""Chromium";v="2022.04", "Not?A_Brand";v="8.0.0.0""#;
+
+    let iridium = client(&DeviceDetector::new(), CHROME, brands)?;
+    assert_eq!(iridium.name, "Iridium");
+
+    let detector = DeviceDetector::new().without_quirk(Quirk::IridiumYear);
+    assert_ne!(client(&detector, CHROME, brands)?.name, "Iridium");
+
+    Ok(())
+}
+
+#[test]
+fn test_disable_duckduckgo_version() -> Result<()> {
+    let brands =
+        r#""DuckDuckGo";v="120.0.0.0", "Chromium";v="120.0.0.0", "Not?A_Brand";v="8.0.0.0""#;
+
+    let duckduckgo = client(&DeviceDetector::new(), CHROME, brands)?;
+    assert_eq!(duckduckgo.name, "DuckDuckGo Privacy Browser");
+    assert_eq!(duckduckgo.version, None);
+
+    let detector = DeviceDetector::new().without_quirk(Quirk::DuckDuckGoVersion);
+    let duckduckgo = client(&detector, CHROME, brands)?;
+    assert_eq!(duckduckgo.name, "DuckDuckGo Privacy Browser");
+    assert!(duckduckgo.version.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_disable_opera_webview() -> Result<()> {
+    let ua = "Mozilla/5.0 (Linux; Android 10; K; wv) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/119.0.6045.163 Mobile Safari/537.36 OPR/79.0.2254.70000";
+    let brands = r#""Android WebView";v="119.0.6045.163", "Chromium";v="119.0.6045.163", "Not?A_Brand";v="24.0.0.0""#;
+
+    let opera = client(&DeviceDetector::new(), ua, brands)?;
+    assert_eq!(opera.name, "Opera Mobile");
+
+    let detector = DeviceDetector::new().without_quirk(Quirk::OperaWebView);
+    assert_eq!(client(&detector, ua, brands)?.name, "Chrome Webview");
+
+    Ok(())
+}
+
+#[test]
+fn test_ios_webkit() -> Result<()> {
+    let uas = [
+        "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) CriOS/119.0.6045.169 Mobile/15E148 Safari/604.1",
+        "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) FxiOS/120.0 Mobile/15E148 Safari/605.1.15",
+        "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 EdgiOS/119.2151.96 Mobile/15E148 Safari/605.1.15",
+    ];
+    let brands = r#""Google Chrome";v="119.0.6045.169", "Chromium";v="119.0.6045.169", "Not?A_Brand";v="24.0.0.0""#;
+
+    let detector = DeviceDetector::new();
+    for ua in uas {
+        for client in [
+            detector
+                .parse(ua, None)?
+                .get_known_device()
+                .and_then(|x| x.client.clone())
+                .expect("client"),
+            client(&detector, ua, brands)?,
+        ] {
+            assert_eq!(client.engine.as_deref(), Some("WebKit"), "{}", ua);
+            assert_eq!(client.engine_version.as_deref(), Some("605.1.15"), "{}", ua);
+        }
+    }
+
+    let detector = DeviceDetector::new().without_quirk(Quirk::IosWebKit);
+    assert!(detector.parse(uas[0], None)?.get_known_device().is_some());
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use anyhow::Result;
+
+use std::sync::Arc;
+
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::{Detection, DeviceDetector};
+
+use crate::database::copy_database;
+
+const GOOGLEBOT: &str = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+
+fn bot_name(detector: &DeviceDetector) -> Result<String> {
+    match detector.parse(GOOGLEBOT, None)? {
+        Detection::Bot(bot) => Ok(bot.name),
+        x => panic!("expected a bot, got {:?}", x),
+    }
+}
+
+#[test]
+fn test_reload_from_dir() -> Result<()> {
+    let dir = copy_database("reload")?;
+    let detector = DeviceDetector::with_database_dir(&dir)?;
+    #[cfg(feature = "cache")]
+    let detector = detector.with_result_cache(100);
+    let clone = detector.clone();
+    assert_eq!(bot_name(&detector)?, "Googlebot");
+
+    let bots = dir.join("bots.yml");
+    let contents = std::fs::read_to_string(&bots)?;
+    std::fs::write(
+        &bots,
+        contents.replace("name: 'Googlebot'", "name: 'Renamed Googlebot'"),
+    )?;
+
+    let previous = detector.database();
+    detector.reload_from_dir(&dir)?;
+    assert!(!Arc::ptr_eq(&previous, &detector.database()));
+    // the cached detection was made with the previous database
+    assert_eq!(bot_name(&detector)?, "Renamed Googlebot");
+    assert_eq!(bot_name(&clone)?, "Renamed Googlebot");
+
+    // a detector given a database of its own no longer follows reloads
+    let other = clone.clone().without_result_cache().with_database(previous);
+    assert_eq!(bot_name(&other)?, "Googlebot");
+    assert_eq!(bot_name(&detector)?, "Renamed Googlebot");
+
+    std::fs::remove_file(dir.join("oss.yml"))?;
+    let err = detector.reload_from_dir(&dir).expect_err("missing oss.yml");
+    assert!(format!("{:#}", err).contains("oss.yml"), "{:#}", err);
+    assert_eq!(bot_name(&detector)?, "Renamed Googlebot");
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_reload_while_detecting() -> Result<()> {
+    let detector = DeviceDetector::new();
+    let db = Database::embedded();
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                scope.spawn(|| {
+                    for _ in 0..50 {
+                        assert_eq!(bot_name(&detector).unwrap(), "Googlebot");
+                    }
+                })
+            })
+            .collect();
+
+        for _ in 0..20 {
+            detector.reload(db.clone());
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use anyhow::{anyhow, Result};
+use futures::executor::block_on;
+
+use std::sync::Arc;
+
+use rust_device_detector::database::{bundle_dir, Database};
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::remote::{fetch_database, sha256_hex, verify_sha256};
+
+const URL: &str = "https://example.com/regexes.json";
+const GOOGLEBOT: &str = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+
+#[test]
+fn test_fetch_database() -> Result<()> {
+    let bundle = bundle_dir("data/regexes")?;
+    let sha256 = sha256_hex(&bundle);
+    assert_eq!(sha256.len(), 64);
+
+    let db = block_on(fetch_database(URL, &sha256.to_uppercase(), |url| {
+        assert_eq!(url, URL);
+        let bundle = bundle.clone();
+        async move { Ok(bundle) }
+    }))?;
+
+    let detector = DeviceDetector::new_with_database(Arc::new(db));
+    assert!(detector.parse(GOOGLEBOT, None)?.is_bot());
+
+    // a single changed byte fails the check
+    let mut tampered = bundle.clone();
+    let at = tampered.len() / 2;
+    tampered[at] ^= 1;
+    let err = block_on(fetch_database(
+        URL,
+        &sha256,
+        |_| async move { Ok(tampered) },
+    ))
+    .err()
+    .expect("tampered bundle");
+    assert!(err.to_string().contains("sha256"), "{}", err);
+
+    let err = block_on(fetch_database(URL, &sha256, |_| async move {
+        Err(anyhow!("connection reset"))
+    }))
+    .err()
+    .expect("failed download");
+    assert!(err.to_string().contains(URL), "{}", err);
+    assert!(err.to_string().contains("connection reset"), "{}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_database_from_bundle() -> Result<()> {
+    let bundle = bundle_dir("data/regexes")?;
+    verify_sha256(&bundle, &sha256_hex(&bundle))?;
+
+    let mut files: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&bundle)?;
+    files.remove("oss.yml");
+    let err = Database::from_bundle(&serde_json::to_vec(&files)?)
+        .err()
+        .expect("missing oss.yml");
+    assert!(format!("{:#}", err).contains("oss.yml"), "{:#}", err);
+
+    assert!(Database::from_bundle(b"not json").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use serde_yaml::Value;
+
+use crate::utils;
+use rust_device_detector::replay::{load_corpus, new_mismatches, replay};
+
+#[test]
+fn test_replay_fixtures() -> Result<()> {
+    for path in [
+        "tests/data/fixtures/camera.yml",
+        "tests/data/fixtures/bots.yml",
+    ] {
+        let cases = load_corpus(path)?;
+        let mismatches = replay(&utils::DD, &cases)?;
+        assert!(mismatches.is_empty(), "{}: {:#?}", path, mismatches);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_replay_reports_new_mismatches() -> Result<()> {
+    let mut cases = load_corpus("tests/data/fixtures/camera.yml")?;
+    cases.truncate(3);
+
+    cases[0]["device"]["model"] = Value::String("Not A Camera".to_owned());
+    let baseline = replay(&utils::DD, &cases)?;
+
+    assert_eq!(baseline.len(), 1, "{:#?}", baseline);
+    assert_eq!(baseline[0].field, "device.model");
+    assert_eq!(baseline[0].expected, "Not A Camera");
+
+    cases[1]["os"] = Value::Sequence(Vec::new());
+    cases[2]["client"]["name"] = Value::String("Not A Browser".to_owned());
+    let current = replay(&utils::DD, &cases)?;
+
+    let new = new_mismatches(&current, &baseline);
+    let fields = new.iter().map(|x| x.field.as_str()).collect::<Vec<_>>();
+    assert_eq!(fields, vec!["os", "client.name"]);
+
+    Ok(())
+}
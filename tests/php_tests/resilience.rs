@@ -0,0 +1,96 @@
+use anyhow::Result;
+
+use std::sync::Arc;
+
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::overlay::Overlay;
+use rust_device_detector::resilience::Stage;
+
+// an os regex which fails to compile, added before all the others so that
+// every user agent runs into it.
+const OVERLAY: &str = r#"
+oss.yml:
+  - regex: 'Brokenos('
+    name: 'Brokenos'
+    version: ''
+"#;
+
+const PHONE: &str = "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36";
+
+fn detector() -> Result<DeviceDetector> {
+    let overlay = Overlay::from_yaml(OVERLAY)?;
+    Ok(DeviceDetector::new_with_database(Arc::new(
+        Database::embedded_with_overlay(&overlay)?,
+    )))
+}
+
+#[test]
+fn test_stage_error_fails_parse() -> Result<()> {
+    assert!(detector()?.parse(PHONE, None).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_resilience() -> Result<()> {
+    let detector = detector()?.with_resilience(true);
+
+    let detection = detector.parse(PHONE, None)?;
+    let errors = detection.stage_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].stage, Stage::Os);
+    assert!(
+        errors[0].message.contains("Brokenos("),
+        "{}",
+        errors[0].message
+    );
+
+    let known = detection.get_known_device().unwrap();
+    assert!(known.os.is_none());
+    assert_eq!(known.client.as_ref().unwrap().name, "Chrome Mobile");
+    let device = known.device.as_ref().unwrap();
+    assert_eq!(device.brand.as_deref(), Some("Samsung"));
+    assert_eq!(device.model.as_deref(), Some("Galaxy S23 Ultra"));
+
+    let json = detection.clone().to_value();
+    assert_eq!(json["stage_errors"][0]["stage"], "os");
+
+    Ok(())
+}
+
+#[test]
+fn test_resilience_without_errors() -> Result<()> {
+    let detector = DeviceDetector::new().with_resilience(true);
+
+    let detection = detector.parse(PHONE, None)?;
+    assert!(detection.stage_errors().is_empty());
+    assert!(detection.clone().to_value().get("stage_errors").is_none());
+    assert_eq!(
+        detection
+            .get_known_device()
+            .unwrap()
+            .os
+            .as_ref()
+            .unwrap()
+            .name,
+        "Android"
+    );
+
+    let bot = detector.parse("Googlebot-News", None)?;
+    assert!(bot.is_bot());
+    assert!(bot.stage_errors().is_empty());
+
+    Ok(())
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_partial_detections_not_cached() -> Result<()> {
+    let detector = detector()?.with_resilience(true).with_result_cache(100);
+
+    detector.parse(PHONE, None)?;
+    assert!(!detector.parse(PHONE, None)?.stage_errors().is_empty());
+    assert_eq!(detector.memory_usage().cached_detections, 0);
+
+    Ok(())
+}
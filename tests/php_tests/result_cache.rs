@@ -0,0 +1,92 @@
+use anyhow::Result;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use rust_device_detector::device_detector::{Detection, DeviceDetector};
+use rust_device_detector::result_cache::{CacheKey, ResultCache};
+
+const REDUCED: &str = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+const FIREFOX: &str =
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0";
+
+fn hints(model: &str) -> Vec<(String, String)> {
+    vec![
+        ("Sec-CH-UA-Mobile".to_owned(), "?1".to_owned()),
+        ("Sec-CH-UA-Platform".to_owned(), r#""Android""#.to_owned()),
+        ("Sec-CH-UA-Model".to_owned(), format!("\"{}\"", model)),
+    ]
+}
+
+fn model(detection: &Detection) -> Option<&str> {
+    detection
+        .get_known_device()
+        .and_then(|x| x.device.as_ref())
+        .and_then(|x| x.model.as_deref())
+}
+
+#[test]
+fn test_result_cache_keys_on_hints() -> Result<()> {
+    let detector = DeviceDetector::new().with_custom_result_cache(CountingCache::default());
+
+    for _ in 0..2 {
+        let detection = detector.parse(REDUCED, Some(hints("SM-S918B")))?;
+        assert_eq!(model(&detection), Some("Galaxy S23 Ultra"));
+        let detection = detector.parse(REDUCED, Some(hints("Pixel 7")))?;
+        assert_eq!(model(&detection), Some("Pixel 7"));
+        detector.parse(REDUCED, None)?;
+    }
+    assert_eq!(detector.memory_usage().cached_detections, 3);
+
+    let detector = detector.without_result_cache();
+    detector.parse(FIREFOX, None)?;
+    assert_eq!(detector.memory_usage().cached_detections, 0);
+
+    Ok(())
+}
+
+#[derive(Clone, Default)]
+struct CountingCache {
+    entries: Arc<Mutex<HashMap<CacheKey, Detection>>>,
+    hits: Arc<AtomicUsize>,
+}
+
+impl ResultCache for CountingCache {
+    fn get(&self, key: &CacheKey) -> Option<Detection> {
+        let res = self.entries.lock().unwrap().get(key).cloned();
+        if res.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        res
+    }
+
+    fn insert(&self, key: CacheKey, detection: Detection) {
+        self.entries.lock().unwrap().insert(key, detection);
+    }
+
+    fn entry_count(&self) -> u64 {
+        self.entries.lock().unwrap().len() as u64
+    }
+}
+
+#[test]
+fn test_custom_result_cache() -> Result<()> {
+    let cache = CountingCache::default();
+    let detector = DeviceDetector::new().with_custom_result_cache(cache.clone());
+
+    for _ in 0..3 {
+        let detection = detector.parse(FIREFOX, None)?;
+        assert_eq!(
+            detection
+                .get_known_device()
+                .and_then(|x| x.client.as_ref())
+                .map(|x| x.name.as_str()),
+            Some("Firefox")
+        );
+    }
+    assert_eq!(cache.hits.load(Ordering::Relaxed), 2);
+    assert_eq!(cache.entry_count(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::rules::Rules;
+
+const RULES: &str = r#"
+- id: my-company-app
+  when:
+    - ua: 'MyCompanyApp/(?P<version>[\d.]+)'
+  then:
+    - rename: My Company App
+    - set: client.version
+      value: '${version}'
+    - unset: client.engine
+
+- id: samsung-phablets
+  when:
+    - field: device.brand
+      equals: Samsung
+    - field: device.model
+      matches: 'Ultra$'
+    - hint: sec-ch-ua-platform
+      matches: 'Android'
+  then:
+    - set: device.type
+      value: phablet
+
+- id: google-renders
+  when:
+    - field: bot.name
+      equals: Googlebot
+    - ua: 'Nexus 5X'
+  then:
+    - drop_bot: true
+"#;
+
+const UA: &str = "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36";
+
+fn hints() -> Vec<(String, String)> {
+    vec![("sec-ch-ua-platform".to_owned(), r#""Android""#.to_owned())]
+}
+
+#[test]
+fn test_rules() -> Result<()> {
+    let rules = Rules::from_yaml(RULES)?;
+    assert_eq!(
+        rules.ids().collect::<Vec<_>>(),
+        vec!["my-company-app", "samsung-phablets", "google-renders"]
+    );
+    let detector = DeviceDetector::new().with_rules(rules);
+
+    let detection = detector.parse(&format!("{} MyCompanyApp/2.1", UA), None)?;
+    let known = detection.get_known_device().expect("known device");
+    let client = known.client.as_ref().expect("client");
+    assert_eq!(client.name, "My Company App");
+    assert_eq!(client.version.as_deref(), Some("2.1"));
+    assert_eq!(client.engine, None);
+    // no hints, so not a phablet
+    assert!(known.is_smart_phone());
+
+    let detection = detector.parse(UA, Some(hints()))?;
+    let known = detection.get_known_device().expect("known device");
+    assert_eq!(
+        known.client.as_ref().map(|x| x.name.as_str()),
+        Some("Chrome Mobile")
+    );
+    assert!(known.is_phablet());
+
+    Ok(())
+}
+
+#[test]
+fn test_rules_hint_conditions() -> Result<()> {
+    let rules = r#"
+- id: brands
+  when:
+    - hint: sec-ch-ua
+  then:
+    - unset: client.version
+
+- id: android
+  when:
+    - hint: sec-ch-ua-platform
+      matches: '^Android$'
+  then:
+    - unset: client.engine
+"#;
+    let detector = DeviceDetector::new().with_rules(Rules::from_yaml(rules)?);
+    let client = |headers: Vec<(String, String)>| -> Result<_> {
+        let detection = detector.parse(UA, Some(headers))?;
+        let client = detection.get_known_device().and_then(|x| x.client.clone());
+        Ok(client.expect("client"))
+    };
+
+    // only the full version list was sent, so not sec-ch-ua
+    let full = r#""Chromium";v="120.0.6099.144", "Google Chrome";v="120.0.6099.144""#;
+    let res = client(vec![(
+        "sec-ch-ua-full-version-list".to_owned(),
+        full.to_owned(),
+    )])?;
+    assert!(res.version.is_some());
+
+    let brands = r#""Chromium";v="120", "Google Chrome";v="120""#;
+    let res = client(vec![("sec-ch-ua".to_owned(), brands.to_owned())])?;
+    assert_eq!(res.version, None);
+
+    // single values are matched without their quotes
+    let res = client(hints())?;
+    assert_eq!(res.engine, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_rules_drop_bot() -> Result<()> {
+    let detector = DeviceDetector::new().with_rules(Rules::from_yaml(RULES)?);
+
+    let ua = "Mozilla/5.0 (Linux; Android 6.0.1; Nexus 5X Build/MMB29P) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.71 Mobile Safari/537.36 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+    assert!(DeviceDetector::new().parse(ua, None)?.is_bot());
+
+    let detection = detector.parse(ua, None)?;
+    let known = detection.get_known_device().expect("not a bot");
+    assert_eq!(
+        known.client.as_ref().map(|x| x.name.as_str()),
+        Some("Chrome Mobile")
+    );
+    assert_eq!(
+        known.device.as_ref().and_then(|x| x.model.as_deref()),
+        Some("Nexus 5X")
+    );
+
+    assert!(detector
+        .parse(
+            "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+            None
+        )?
+        .is_bot());
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_rules() -> Result<()> {
+    for rules in [
+        "- id: a\n  then:\n    - unset: client.colour\n",
+        "- id: a\n  when:\n    - ua: 'x'\n      hint: sec-ch-ua\n  then:\n    - unset: client.version\n",
+        "- id: a\n  when:\n    - field: client.name\n  then:\n    - unset: client.version\n",
+        "- id: a\n  then:\n    - set: device.type\n      value: toaster\n",
+        "- id: a\n  then:\n    - rename: x\n      drop_bot: true\n",
+        "- id: a\n  when:\n    - ua: '('\n  then:\n    - unset: client.version\n",
+    ] {
+        assert!(Rules::from_yaml(rules).is_err(), "{}", rules);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use anyhow::Result;
+
+use std::time::{Duration, SystemTime};
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::scripting::ScriptRules;
+
+const UA: &str = "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36 MyCompanyApp/2.1";
+
+#[test]
+fn test_script_rules() -> Result<()> {
+    let rules = ScriptRules::new(
+        r#"
+        if ua.contains("MyCompanyApp/") {
+            detection.client = #{ type: "mobile app", name: "My Company App", version: "2.1" };
+        }
+        if detection.device != () && detection.device.brand == "Samsung" {
+            detection.device.type = "phablet";
+        }
+        "#,
+    )?;
+
+    let detection = DeviceDetector::new().parse(UA, None)?;
+    let res = rules.apply(UA, &detection)?;
+
+    let client = res.client.as_ref().expect("client");
+    assert_eq!(client.client_type, "mobile app");
+    assert_eq!(client.name, "My Company App");
+    assert_eq!(client.version.as_deref(), Some("2.1"));
+    assert_eq!(client.engine, None);
+
+    let device = res.device.as_ref().expect("device");
+    assert_eq!(device.device_type.as_deref(), Some("phablet"));
+    assert_eq!(device.model.as_deref(), Some("Galaxy S23 Ultra"));
+    assert_eq!(res.os, detection.to_v1().os);
+
+    Ok(())
+}
+
+#[test]
+fn test_script_rules_sandbox() -> Result<()> {
+    let detection = DeviceDetector::new().parse(UA, None)?;
+
+    let rules = ScriptRules::new("loop { }")?;
+    assert!(rules.apply(UA, &detection).is_err());
+
+    let rules = ScriptRules::new("detection.is_bot = \"maybe\";")?;
+    assert!(rules.apply(UA, &detection).is_err());
+
+    assert!(ScriptRules::new(r#"import "rules" as rules;"#)?
+        .apply(UA, &detection)
+        .is_err());
+    assert!(ScriptRules::new(r#"eval("1")"#).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_script_rules_reload() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("rdd-rules-{}.rhai", std::process::id()));
+    let write = |source: &str, age: u64| -> Result<()> {
+        std::fs::write(&path, source)?;
+        let modified = SystemTime::now() - Duration::from_secs(age);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(modified)?;
+        Ok(())
+    };
+
+    let detection = DeviceDetector::new().parse(UA, None)?;
+    let name = |rules: &ScriptRules| -> Result<Option<String>> {
+        Ok(rules.apply(UA, &detection)?.client.map(|x| x.name))
+    };
+
+    write(r#"detection.client.name = "first";"#, 60)?;
+    let rules = ScriptRules::from_file(&path)?.with_reload_interval(Duration::ZERO);
+    assert_eq!(name(&rules)?.as_deref(), Some("first"));
+    assert!(!rules.reload()?);
+
+    write(r#"detection.client.name = "second";"#, 30)?;
+    assert_eq!(name(&rules)?.as_deref(), Some("second"));
+
+    // a broken change keeps the previous rules
+    write(r#"detection.client.name = "#, 0)?;
+    assert_eq!(name(&rules)?.as_deref(), Some("second"));
+
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}
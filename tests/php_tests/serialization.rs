@@ -0,0 +1,116 @@
+use anyhow::Result;
+
+use rust_device_detector::client_hints::ClientHint;
+use rust_device_detector::device_detector::Detection;
+use rust_device_detector::normalize::normalize;
+
+use crate::utils;
+
+fn round_trip(ua: &str, headers: Option<Vec<(String, String)>>) -> Result<()> {
+    let client_hints = headers.clone().map(ClientHint::from_headers).transpose()?;
+    let detection = utils::DD.parse(ua, headers)?;
+
+    let json = serde_json::to_string(&detection)?;
+    let parsed: Detection = serde_json::from_str(&json)?;
+
+    assert_eq!(serde_json::to_string(&parsed)?, json, "{}", ua);
+    let confidence = |x: &Detection| {
+        x.get_known_device()
+            .and_then(|x| x.device.as_ref())
+            .map(|x| x.confidence)
+    };
+    assert_eq!(confidence(&parsed), confidence(&detection), "{}", ua);
+    assert_eq!(
+        parsed.clone().to_value(),
+        detection.clone().to_value(),
+        "{}",
+        ua
+    );
+    assert_eq!(
+        parsed.clone().to_test_case(ua),
+        detection.clone().to_test_case(ua)
+    );
+    assert_eq!(
+        normalize(&parsed, ua, client_hints.as_ref()),
+        normalize(&detection, ua, client_hints.as_ref()),
+        "{}",
+        ua
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_round_trip_known() -> Result<()> {
+    round_trip(
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        None,
+    )?;
+    round_trip(
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; Touch) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        None,
+    )?;
+    round_trip(
+        "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1",
+        None,
+    )?;
+    round_trip(
+        "Mozilla/5.0 (SMART-TV; LINUX; Tizen 6.0) AppleWebKit/537.36 (KHTML, like Gecko) 76.0.3809.146/6.0 TV Safari/537.36 HbbTV/1.5.1 (+DRM+PVR; Samsung; SmartTV2021; T-NT11-1010.1; T-NT11; ;) FVC/5.0",
+        None,
+    )?;
+    round_trip("curl/8.4.0", None)?;
+    round_trip("", None)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_round_trip_keeps_flags() -> Result<()> {
+    let detection = utils::DD.parse(
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; Touch) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        None,
+    )?;
+    let parsed: Detection = serde_json::from_str(&serde_json::to_string(&detection)?)?;
+
+    let known = parsed.get_known_device().unwrap();
+    assert!(known.is_touch_enabled());
+
+    Ok(())
+}
+
+#[test]
+fn test_round_trip_client_hints() -> Result<()> {
+    let headers = vec![
+        (
+            "sec-ch-ua".to_owned(),
+            r#""Chromium";v="120", "Google Chrome";v="120", "Not?A_Brand";v="99""#.to_owned(),
+        ),
+        ("sec-ch-ua-mobile".to_owned(), "?1".to_owned()),
+        ("sec-ch-ua-platform".to_owned(), r#""Android""#.to_owned()),
+        ("sec-ch-ua-model".to_owned(), r#""Pixel 7""#.to_owned()),
+    ];
+    round_trip(
+        "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+        Some(headers),
+    )
+}
+
+#[test]
+fn test_round_trip_bot() -> Result<()> {
+    round_trip("Googlebot-News", None)?;
+
+    let detection = utils::DD.parse("Googlebot-News", None)?;
+    let json = serde_json::to_value(&detection)?;
+    assert_eq!(json["bot"]["name"], "Googlebot News");
+    assert_eq!(json["bot"]["producer"]["name"], "Google Inc.");
+
+    Ok(())
+}
+
+#[test]
+fn test_unknown_tv_operator() {
+    let json = r#"{"known":{"client":null,"os":null,"device":{"type":"television","brand":"Samsung","model":null,"hbbtv":{"version":"1.5.1","capabilities":[],"vendor":null,"model":null,"software_version":null,"hardware_version":null,"family":null,"operator":{"name":"Nowhere TV","country":"XX"}}}}}"#;
+
+    let err = serde_json::from_str::<Detection>(json).unwrap_err();
+    assert!(err.to_string().contains("Nowhere TV"), "{}", err);
+}
@@ -0,0 +1,91 @@
+use anyhow::Result;
+
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::session::SessionDetector;
+
+use std::sync::Arc;
+
+const DESKTOP: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+const REDUCED: &str = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+
+fn model(model: &str) -> Option<Vec<(String, String)>> {
+    Some(vec![(
+        "Sec-CH-UA-Model".to_owned(),
+        format!("\"{}\"", model),
+    )])
+}
+
+#[test]
+fn test_session_reuses_detection() -> Result<()> {
+    let shared = DeviceDetector::new();
+    let sessions = SessionDetector::from(shared.clone());
+
+    for _ in 0..3 {
+        let detection = sessions.parse(1, DESKTOP, None)?;
+        assert_eq!(
+            format!("{:?}", detection),
+            format!("{:?}", shared.parse(DESKTOP, None)?)
+        );
+    }
+    assert_eq!(sessions.detections_reused(), 2);
+
+    // another session is detected on its own
+    sessions.parse(2, DESKTOP, None)?;
+    assert_eq!(sessions.detections_reused(), 2);
+    assert_eq!(sessions.sessions(), 2);
+
+    sessions.end_session(&2);
+    assert_eq!(sessions.sessions(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_session_detects_changes() -> Result<()> {
+    let shared = DeviceDetector::new();
+    let sessions = SessionDetector::from(shared.clone());
+
+    // a new user agent or other hints detect the session again
+    for (ua, hints) in [
+        (REDUCED, model("Pixel 7")),
+        (REDUCED, model("SM-S918B")),
+        (DESKTOP, None),
+        (REDUCED, model("SM-S918B")),
+    ] {
+        let detection = sessions.parse("connection", ua, hints.clone())?;
+        let expected = shared.parse(ua, hints)?;
+        assert_eq!(format!("{:?}", detection), format!("{:?}", expected));
+    }
+    assert_eq!(sessions.detections_reused(), 0);
+
+    sessions.parse("connection", REDUCED, model("SM-S918B"))?;
+    assert_eq!(sessions.detections_reused(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_session_capacity_and_reload() -> Result<()> {
+    let shared = DeviceDetector::new();
+    let sessions = SessionDetector::from(shared.clone()).with_capacity(2);
+
+    sessions.parse(1, DESKTOP, None)?;
+    sessions.parse(2, DESKTOP, None)?;
+    sessions.parse(1, DESKTOP, None)?;
+    assert_eq!(sessions.sessions(), 2);
+    // dropped once full
+    sessions.parse(3, DESKTOP, None)?;
+    assert_eq!(sessions.sessions(), 1);
+
+    // and once the database is reloaded
+    shared.reload(Arc::new(Database::from_dir(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/data/regexes"
+    ))?));
+    sessions.parse(3, DESKTOP, None)?;
+    assert_eq!(sessions.detections_reused(), 1);
+    assert_eq!(sessions.sessions(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use anyhow::Result;
+
+use std::sync::Arc;
+
+use rust_device_detector::database::{bundle_dir, Database};
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::signing::{BundleKey, BundleSigner};
+
+use crate::database::copy_database;
+
+const GOOGLEBOT: &str = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+
+#[test]
+fn test_signed_bundle() -> Result<()> {
+    let signer = BundleSigner::from_seed([7; 32]);
+    let key = BundleKey::from_hex(&signer.key().to_hex())?;
+    assert_eq!(key, signer.key());
+
+    let bundle = bundle_dir("data/regexes")?;
+    let signature = signer.sign(&bundle);
+    assert_eq!(signature.len(), 64);
+
+    let db = Database::from_signed_bundle(&bundle, &signature, &key)?;
+    let detector = DeviceDetector::new_with_database(Arc::new(db));
+    assert!(detector.parse(GOOGLEBOT, None)?.is_bot());
+
+    let mut tampered = bundle.clone();
+    let at = tampered.len() / 2;
+    tampered[at] ^= 1;
+    assert!(Database::from_signed_bundle(&tampered, &signature, &key).is_err());
+
+    let other = BundleSigner::from_seed([8; 32]).key();
+    assert!(Database::from_signed_bundle(&bundle, &signature, &other).is_err());
+    assert!(Database::from_signed_bundle(&bundle, &signature[..32], &key).is_err());
+
+    assert!(BundleKey::from_hex("not a key").is_err());
+    assert!(BundleKey::from_hex(&signer.key().to_hex()[..62]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_signed_dir() -> Result<()> {
+    let signer = BundleSigner::from_seed([7; 32]);
+    let signature = signer.sign(&bundle_dir("data/regexes")?);
+
+    Database::from_signed_dir("data/regexes", &signature, &signer.key())?;
+
+    let dir = copy_database("signed")?;
+    let bots = dir.join("bots.yml");
+    let contents = std::fs::read_to_string(&bots)?;
+    std::fs::write(
+        &bots,
+        contents.replace("name: 'Googlebot'", "name: 'Renamed Googlebot'"),
+    )?;
+
+    let err = Database::from_signed_dir(&dir, &signature, &signer.key())
+        .err()
+        .expect("changed bots.yml");
+    assert!(err.to_string().contains("signature"), "{}", err);
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[cfg(feature = "remote-database")]
+#[test]
+fn test_fetch_signed_database() -> Result<()> {
+    use anyhow::anyhow;
+    use futures::executor::block_on;
+    use rust_device_detector::remote::fetch_signed_database;
+
+    let signer = BundleSigner::from_seed([7; 32]);
+    let bundle = bundle_dir("data/regexes")?;
+    let signature = signer.sign(&bundle);
+
+    let fetch = |url: String| {
+        let res = match url.as_str() {
+            "https://example.com/regexes.json" => Ok(bundle.clone()),
+            "https://example.com/regexes.json.sig" => Ok(signature.clone()),
+            _ => Err(anyhow!("not found")),
+        };
+        async move { res }
+    };
+
+    let db = block_on(fetch_signed_database(
+        "https://example.com/regexes.json",
+        &signer.key(),
+        fetch,
+    ))?;
+    assert!(DeviceDetector::new_with_database(Arc::new(db))
+        .parse(GOOGLEBOT, None)?
+        .is_bot());
+
+    let other = BundleSigner::from_seed([8; 32]).key();
+    assert!(block_on(fetch_signed_database(
+        "https://example.com/regexes.json",
+        &other,
+        fetch
+    ))
+    .is_err());
+
+    Ok(())
+}
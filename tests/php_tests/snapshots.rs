@@ -0,0 +1,83 @@
+use anyhow::Result;
+
+use std::sync::Arc;
+
+use rust_device_detector::database::bundle_dir;
+use rust_device_detector::snapshots::Snapshots;
+
+use crate::database::copy_database;
+use crate::utils;
+
+const GOOGLEBOT: &str = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+const DESKTOP: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+#[test]
+fn test_snapshots() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("rdd-snapshots-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+
+    // an older snapshot as a bundle, and a newer one as a directory in which
+    // googlebot was renamed
+    let old = copy_database("snapshot-old")?;
+    std::fs::write(dir.join("2023-01-01.json"), bundle_dir(&old)?)?;
+    std::fs::remove_dir_all(old)?;
+
+    let new = copy_database("snapshot-new")?;
+    let bots = new.join("bots.yml");
+    let contents = std::fs::read_to_string(&bots)?;
+    std::fs::write(
+        &bots,
+        contents.replace("name: 'Googlebot'", "name: 'Renamed Googlebot'"),
+    )?;
+    std::fs::rename(new, dir.join("2024-01-01"))?;
+    std::fs::write(dir.join("README.txt"), "not a snapshot")?;
+
+    let snapshots = Snapshots::from_dir(&dir)?;
+    assert_eq!(
+        snapshots.ids().collect::<Vec<_>>(),
+        ["2023-01-01", "2024-01-01"]
+    );
+    assert_eq!(snapshots.select("2022-12-31"), None);
+    assert_eq!(snapshots.select("2023-06-15"), Some("2023-01-01"));
+    assert_eq!(snapshots.select("2024-01-01"), Some("2024-01-01"));
+    assert_eq!(snapshots.select("2025-03-01"), Some("2024-01-01"));
+    assert!(snapshots.detector_at("2022-12-31").is_err());
+    assert!(snapshots.detector("2023-02-01").is_err());
+
+    let detector = snapshots.detector_at("2023-06-15")?;
+    assert_eq!(detector.snapshot(), Some("2023-01-01"));
+    let detection = detector.parse(GOOGLEBOT, None)?;
+    assert_eq!(detection.get_bot().unwrap().name, "Googlebot");
+    assert_eq!(detection.snapshot(), Some("2023-01-01"));
+
+    let detector = snapshots.detector_at("2024-02-01")?;
+    let detection = detector.parse(GOOGLEBOT, None)?;
+    assert_eq!(detection.get_bot().unwrap().name, "Renamed Googlebot");
+    assert_eq!(detection.snapshot(), Some("2024-01-01"));
+    assert_eq!(detection.to_value()["bot"]["snapshot"], "2024-01-01");
+
+    let detection = detector.parse(DESKTOP, None)?;
+    assert_eq!(detection.snapshot(), Some("2024-01-01"));
+    assert_eq!(detection.clone().to_value()["snapshot"], "2024-01-01");
+    let parsed: rust_device_detector::device_detector::Detection =
+        serde_json::from_str(&serde_json::to_string(&detection)?)?;
+    assert_eq!(parsed.snapshot(), Some("2024-01-01"));
+
+    // loaded once
+    assert!(Arc::ptr_eq(
+        &snapshots.database("2024-01-01")?,
+        &snapshots.database("2024-01-01")?
+    ));
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_unpinned_detections() -> Result<()> {
+    let detection = utils::DD.parse(DESKTOP, None)?;
+    assert_eq!(detection.snapshot(), None);
+    assert!(detection.to_value().get("snapshot").is_none());
+
+    Ok(())
+}
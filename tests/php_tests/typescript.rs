@@ -0,0 +1,156 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rust_device_detector::catalog::DeviceSpec;
+use rust_device_detector::client_hints::ClientHint;
+use rust_device_detector::database::Database;
+use rust_device_detector::device_detector::{Detection, DeviceDetector};
+use rust_device_detector::overlay::Overlay;
+use rust_device_detector::typescript::{self, Declaration, TsType};
+
+use crate::utils;
+
+const FIXTURES: [&str; 5] = [
+    "bots.yml",
+    "clienthints-app.yml",
+    "smartphone.yml",
+    "tv.yml",
+    "unknown.yml",
+];
+
+/// Whether `value` is of type `ty`, as declared in `declarations`.
+fn matches(declarations: &HashMap<&str, Declaration>, ty: &TsType, value: &Value) -> bool {
+    match ty {
+        TsType::String => value.is_string(),
+        TsType::Number => value.is_number(),
+        TsType::Boolean => value.is_boolean(),
+        TsType::Null => value.is_null(),
+        TsType::Literal(x) => value.as_str() == Some(x.as_str()),
+        TsType::Array(x) => value
+            .as_array()
+            .is_some_and(|values| values.iter().all(|v| matches(declarations, x, v))),
+        TsType::Union(x) => x.iter().any(|ty| matches(declarations, ty, value)),
+        TsType::Named(name) => match &declarations[name] {
+            Declaration::Alias { ty, .. } => matches(declarations, ty, value),
+            Declaration::Interface { fields, .. } => {
+                let Some(object) = value.as_object() else {
+                    return false;
+                };
+                let known = object
+                    .keys()
+                    .all(|key| fields.iter().any(|field| field.name == key));
+                known
+                    && fields.iter().all(|field| match object.get(field.name) {
+                        Some(value) => matches(declarations, &field.ty, value),
+                        None => field.optional,
+                    })
+            }
+        },
+    }
+}
+
+fn check(detection: Detection) {
+    let declarations: HashMap<_, _> = typescript::declarations()
+        .into_iter()
+        .map(|x| (x.name(), x))
+        .collect();
+
+    let value = detection.to_value();
+    assert!(
+        matches(&declarations, &TsType::Named("Detection"), &value),
+        "{}",
+        value
+    );
+}
+
+#[test]
+fn test_definitions_up_to_date() -> Result<()> {
+    assert_eq!(
+        std::fs::read_to_string("includes/detection.d.ts")?,
+        typescript::definitions(),
+        "regenerate includes/detection.d.ts with `rust-device-detector typescript`"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_declarations_match_json() -> Result<()> {
+    for ua in [
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; Touch) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        "Mozilla/5.0 (SMART-TV; LINUX; Tizen 6.0) AppleWebKit/537.36 (KHTML, like Gecko) 76.0.3809.146/6.0 TV Safari/537.36 HbbTV/1.5.1 (+DRM+PVR; Samsung; SmartTV2021; T-NT11-1010.1; T-NT11; ;) FVC/5.0",
+        "Googlebot-News",
+        "curl/8.4.0",
+        "",
+    ] {
+        check(utils::DD.parse(ua, None)?);
+    }
+
+    let detector = DeviceDetector::new().with_bot_claims(true);
+    let detection = detector.parse("Mozilla/5.0 (Linux; Android 6.0.1; Nexus 5X Build/MMB29P) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.71 Mobile Safari/537.36 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)", None)?;
+    assert!(detection.clone().to_value()["bot"]["claimed"].is_object());
+    check(detection);
+
+    let s23_ultra = "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+    let mut catalog = HashMap::new();
+    catalog.insert(
+        ("Samsung".to_owned(), "Galaxy S23 Ultra".to_owned()),
+        DeviceSpec {
+            screen_size: Some(6.8),
+            ram_mb: None,
+            release_year: Some(2023),
+        },
+    );
+    let detector = DeviceDetector::new().with_device_catalog(catalog);
+    let detection = detector.parse(s23_ultra, None)?;
+    assert!(detection.clone().to_value()["device"]["spec"].is_object());
+    check(detection);
+
+    // an os regex which fails to compile, for a detection with stage errors
+    let overlay = Overlay::from_yaml("oss.yml:\n  - regex: 'Brokenos('\n    name: 'Brokenos'\n")?;
+    let detector =
+        DeviceDetector::new_with_database(Arc::new(Database::embedded_with_overlay(&overlay)?))
+            .with_resilience(true);
+    let detection = detector.parse(s23_ultra, None)?;
+    assert!(!detection.stage_errors().is_empty());
+    check(detection);
+
+    Ok(())
+}
+
+#[test]
+fn test_declarations_match_fixtures() -> Result<()> {
+    for file in FIXTURES {
+        let contents = std::fs::read_to_string(format!("tests/data/fixtures/{}", file))?;
+        let cases: Vec<serde_yaml::Value> = serde_yaml::from_str(&contents)?;
+
+        for case in cases {
+            let ua = case["user_agent"].as_str().unwrap();
+            let client_hints: Option<ClientHint> = case
+                .get("headers")
+                .and_then(|headers| headers.as_mapping())
+                .and_then(|headers| utils::client_hint_mock(headers).ok());
+
+            check(utils::DD.parse_client_hints(ua, client_hints)?);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_declarations_reject_extra_keys() {
+    let declarations: HashMap<_, _> = typescript::declarations()
+        .into_iter()
+        .map(|x| (x.name(), x))
+        .collect();
+
+    let value = serde_json::json!({
+        "bot": {"name": "Bot", "category": null, "url": null, "producer": null, "extra": 1}
+    });
+    assert!(!matches(&declarations, &TsType::Named("Detection"), &value));
+}
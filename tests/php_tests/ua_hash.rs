@@ -0,0 +1,55 @@
+use anyhow::Result;
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::ua_hash::UaHasher;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[test]
+fn test_ua_hash() -> Result<()> {
+    let ua = "curl/8.0.1";
+    let detection = DeviceDetector::new().parse(ua, None)?;
+
+    let hash = UaHasher::new("secret").hash(ua, &detection);
+    assert_eq!(
+        hash.hash,
+        "836352778194116cf4dfd93dd852cfc4bb76966d8962fefd7c8c9af284f0ff81"
+    );
+    assert_eq!(hash.period, 0);
+    assert!(!hash.is_bot);
+    assert_eq!(hash.name.as_deref(), Some("curl"));
+    assert_eq!(hash.os, None);
+
+    assert_ne!(UaHasher::new("other").hash(ua, &detection).hash, hash.hash);
+    assert_ne!(
+        UaHasher::new("secret").hash("curl/8.0.2", &detection).hash,
+        hash.hash
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_ua_hash_rotation() -> Result<()> {
+    let ua = "Mozilla/5.0 (Linux; Android 13; SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36";
+    let detection = DeviceDetector::new().parse(ua, None)?;
+    let hasher = UaHasher::new("secret").with_rotation(DAY);
+
+    let morning = UNIX_EPOCH + DAY * 19000 + Duration::from_secs(60);
+    let evening = morning + Duration::from_secs(12 * 60 * 60);
+    let next_day = morning + DAY;
+
+    let hash = hasher.hash_at(ua, &detection, morning);
+    assert_eq!(hash.period, 19000);
+    assert_eq!(hash.device_type.as_deref(), Some("smartphone"));
+    assert_eq!(hash.os.as_deref(), Some("Android"));
+    assert_eq!(hasher.hash_at(ua, &detection, evening), hash);
+
+    let tomorrow = hasher.hash_at(ua, &detection, next_day);
+    assert_eq!(tomorrow.period, 19001);
+    assert_ne!(tomorrow.hash, hash.hash);
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use anyhow::Result;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rust_device_detector::device_detector::DeviceDetector;
+
+#[test]
+fn test_on_unknown() -> Result<()> {
+    let reported = Arc::new(Mutex::new(Vec::new()));
+
+    let detector = {
+        let reported = reported.clone();
+        DeviceDetector::new().on_unknown(2, Duration::from_secs(3600), move |ua, hints, known| {
+            assert!(hints.is_none());
+            assert!(known.client.is_none());
+            reported.lock().unwrap().push(ua.to_owned());
+        })
+    };
+
+    // complete detections and bots aren't reported
+    detector.parse(
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        None,
+    )?;
+    detector.parse(
+        "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+        None,
+    )?;
+    assert!(reported.lock().unwrap().is_empty());
+    assert_eq!(detector.unknowns_dropped(), 0);
+
+    for ua in ["Unknown One", "Unknown Two", "Unknown Three"] {
+        detector.parse(ua, None)?;
+    }
+
+    assert_eq!(
+        *reported.lock().unwrap(),
+        vec!["Unknown One", "Unknown Two"]
+    );
+    assert_eq!(detector.unknowns_dropped(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_on_unknown_rate_limit_window() -> Result<()> {
+    let reported = Arc::new(Mutex::new(0));
+
+    let detector = {
+        let reported = reported.clone();
+        DeviceDetector::new().on_unknown(1, Duration::from_millis(50), move |_, _, _| {
+            *reported.lock().unwrap() += 1;
+        })
+    };
+
+    detector.parse("Unknown One", None)?;
+    detector.parse("Unknown Two", None)?;
+    assert_eq!(*reported.lock().unwrap(), 1);
+
+    std::thread::sleep(Duration::from_millis(120));
+
+    detector.parse("Unknown Three", None)?;
+    assert_eq!(*reported.lock().unwrap(), 2);
+
+    Ok(())
+}
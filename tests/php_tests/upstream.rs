@@ -0,0 +1,81 @@
+use anyhow::{anyhow, Result};
+
+use std::path::Path;
+
+use rust_device_detector::database::FILES;
+use rust_device_detector::upstream::{self, commit_url, file_url, tree_url};
+
+const COMMIT: &str = "0123456789abcdef0123456789abcdef01234567";
+
+/// Upstream as served by github, with the files of `data/regexes`, and
+/// `bots.yml` replaced by `bots` if given.
+fn fetch(url: &str, bots: Option<&str>) -> Result<String> {
+    if url == commit_url("master") {
+        return Ok(format!(r#"{{"sha": "{}"}}"#, COMMIT));
+    }
+    if url == tree_url(COMMIT) {
+        let mut paths: Vec<_> = FILES.iter().map(|x| format!("regexes/{}", x)).collect();
+        paths.push("regexes/device/drones.yml".to_owned());
+        paths.push("Tests/fixtures/bots.yml".to_owned());
+        let tree: Vec<_> = paths
+            .iter()
+            .map(|x| serde_json::json!({ "path": x }))
+            .collect();
+        return Ok(serde_json::json!({ "tree": tree }).to_string());
+    }
+    for file in FILES {
+        if url == file_url(COMMIT, file) {
+            return match (*file, bots) {
+                ("bots.yml", Some(bots)) => Ok(bots.to_owned()),
+                _ => Ok(std::fs::read_to_string(
+                    Path::new("data/regexes").join(file),
+                )?),
+            };
+        }
+    }
+    Err(anyhow!("404 {}", url))
+}
+
+#[test]
+fn test_sync() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("rdd-upstream-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let report = upstream::sync("master", &dir, true, |url| fetch(url, None))?;
+    assert!(report.is_compatible(), "{:#?}", report.errors);
+    assert_eq!(report.commit, COMMIT);
+    assert_eq!(report.new_files, vec!["device/drones.yml".to_owned()]);
+    assert_eq!(report.changed.len(), FILES.len());
+    assert!(report.written);
+    // upstream relies on a few templates without groups
+    assert!(!report.warnings.is_empty());
+    assert_eq!(
+        std::fs::read_to_string(dir.join("client/browsers.yml"))?,
+        std::fs::read_to_string("data/regexes/client/browsers.yml")?
+    );
+
+    let report = upstream::sync("master", &dir, true, |url| fetch(url, None))?;
+    assert!(report.changed.is_empty());
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_sync_incompatible() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("rdd-upstream-broken-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let bots = "- regex: 'Brokenbot('\n  name: 'Brokenbot'\n  shiny: true\n";
+    let report = upstream::sync("master", &dir, true, |url| fetch(url, Some(bots)))?;
+    assert!(!report.is_compatible());
+    assert_eq!(report.errors.keys().collect::<Vec<_>>(), vec![&"bots.yml"]);
+    assert_eq!(report.unknown_keys["bots.yml"][0].key, "shiny");
+    assert!(!report.written);
+    assert!(!dir.exists());
+
+    // a commit which can't be fetched fails the sync rather than the report
+    assert!(upstream::sync("missing", &dir, true, |url| fetch(url, None)).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use anyhow::Result;
+
+use rust_device_detector::v1;
+
+use crate::utils;
+
+// these pin the shape of the v1 schema, which must never change. If one of
+// them fails because a field was added, removed or renamed, that change
+// belongs in a new schema version instead.
+
+#[test]
+fn test_v1_known() -> Result<()> {
+    let detection = utils::DD.parse(
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        None,
+    )?;
+
+    let json = serde_json::to_string(&detection.to_v1())?;
+    assert_eq!(
+        json,
+        r#"{"schema_version":1,"is_bot":false,"bot":null,"client":{"type":"browser","name":"Chrome","version":"120.0.0.0","short_code":"CH","engine":"Blink","engine_version":"120.0.0.0"},"os":{"name":"Windows","version":"10","platform":"x64","family":"Windows"},"device":{"type":"desktop","brand":null,"model":null,"is_mobile":false,"is_touch_enabled":false}}"#
+    );
+
+    let parsed: v1::Detection = serde_json::from_str(&json)?;
+    assert_eq!(parsed, detection.to_v1());
+
+    Ok(())
+}
+
+#[test]
+fn test_v1_bot() -> Result<()> {
+    let detection = utils::DD.parse("Googlebot-News", None)?;
+
+    let json = serde_json::to_string(&v1::Detection::from(&detection))?;
+    assert_eq!(
+        json,
+        r#"{"schema_version":1,"is_bot":true,"bot":{"name":"Googlebot News","category":"Search bot","url":"https://developers.google.com/search/docs/crawling-indexing/overview-google-crawlers","producer_name":"Google Inc.","producer_url":"https://www.google.com/"},"client":null,"os":null,"device":null}"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_v1_unknown() -> Result<()> {
+    let detection = utils::DD.parse("", None)?;
+
+    let json = serde_json::to_string(&detection.to_v1())?;
+    assert_eq!(
+        json,
+        r#"{"schema_version":1,"is_bot":false,"bot":null,"client":null,"os":null,"device":{"type":null,"brand":null,"model":null,"is_mobile":false,"is_touch_enabled":false}}"#
+    );
+
+    Ok(())
+}
@@ -0,0 +1,287 @@
+use anyhow::Result;
+
+use rust_device_detector::database::FILES;
+use rust_device_detector::device_detector::DeviceDetector;
+use rust_device_detector::validation::{
+    unknown_keys, validate_regex_file, validate_regex_str, RegexFileKind, TemplateExpansion,
+    UnknownKey, ValidationErrorKind,
+};
+
+fn embedded_regex_files() -> Result<Vec<(String, RegexFileKind)>> {
+    let files = [
+        ("data/regexes/bots.yml", RegexFileKind::Bots),
+        ("data/regexes/oss.yml", RegexFileKind::OperatingSystems),
+        (
+            "data/regexes/vendorfragments.yml",
+            RegexFileKind::VendorFragments,
+        ),
+        ("data/regexes/client/browsers.yml", RegexFileKind::Browsers),
+        (
+            "data/regexes/client/browser_engine.yml",
+            RegexFileKind::BrowserEngines,
+        ),
+        (
+            "data/regexes/client/feed_readers.yml",
+            RegexFileKind::Clients,
+        ),
+        ("data/regexes/client/libraries.yml", RegexFileKind::Clients),
+        (
+            "data/regexes/client/mediaplayers.yml",
+            RegexFileKind::Clients,
+        ),
+        (
+            "data/regexes/client/mobile_apps.yml",
+            RegexFileKind::Clients,
+        ),
+        ("data/regexes/client/pim.yml", RegexFileKind::Clients),
+        ("data/regexes/client/hints/apps.yml", RegexFileKind::Hints),
+        (
+            "data/regexes/client/hints/browsers.yml",
+            RegexFileKind::Hints,
+        ),
+    ];
+
+    let devices = glob::glob("data/regexes/device/*.yml")?
+        .map(|x| x.expect("glob"))
+        .map(|x| (x.to_string_lossy().into_owned(), RegexFileKind::Devices));
+
+    let res = files
+        .into_iter()
+        .map(|(path, kind)| (path.to_owned(), kind))
+        .chain(devices)
+        .collect();
+
+    Ok(res)
+}
+
+#[test]
+fn test_validate_embedded_regexes() -> Result<()> {
+    for (path, kind) in embedded_regex_files()? {
+        // upstream relies on a few `version: '$1'` templates without any groups
+        // quietly expanding to nothing, so those are the only errors tolerated.
+        let errors = validate_regex_file(&path, kind)?
+            .into_iter()
+            .filter(|x| !matches!(x.kind, ValidationErrorKind::CaptureGroupOutOfRange { .. }))
+            .collect::<Vec<_>>();
+        assert!(errors.is_empty(), "{}: {:#?}", path, errors);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_embedded_out_of_range_templates() -> Result<()> {
+    let mut found = Vec::new();
+
+    for (path, kind) in embedded_regex_files()? {
+        for error in validate_regex_file(&path, kind)? {
+            if let ValidationErrorKind::CaptureGroupOutOfRange { template, .. } = error.kind {
+                found.push((path.clone(), error.line, template));
+            }
+        }
+    }
+
+    // if this changes after a regex update, the templates below must be
+    // checked to still be intentional before updating the list.
+    let expected = [
+        ("data/regexes/client/browsers.yml", 242, "$1"),
+        ("data/regexes/client/browsers.yml", 1134, "$1"),
+        ("data/regexes/client/browsers.yml", 1649, "$1"),
+        ("data/regexes/client/libraries.yml", 153, "$1"),
+        ("data/regexes/client/libraries.yml", 466, "$1"),
+        ("data/regexes/client/libraries.yml", 497, "$1"),
+        ("data/regexes/client/mobile_apps.yml", 194, "$1"),
+        ("data/regexes/client/mobile_apps.yml", 2103, "$1"),
+        ("data/regexes/client/mobile_apps.yml", 2315, "$1"),
+        ("data/regexes/device/mobiles.yml", 6433, "$1"),
+        ("data/regexes/device/mobiles.yml", 31515, "Blade A$1 (2020)"),
+    ]
+    .map(|(path, line, template)| (path.to_owned(), Some(line), template.to_owned()))
+    .to_vec();
+
+    assert_eq!(found, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_template_expansion_modes() -> Result<()> {
+    // Classilla's version template refers to a group its regex doesn't have.
+    let ua = "Mozilla/5.0 (X11; U; Linux ppc; rv:1.9.1) Gecko/20091204 Classilla/CFM";
+
+    for mode in [TemplateExpansion::Lenient, TemplateExpansion::Warn] {
+        let detector = DeviceDetector::new().with_template_expansion(mode);
+        let detection = detector.parse(ua, None)?;
+        let client = detection
+            .get_known_device()
+            .and_then(|x| x.client.as_ref())
+            .expect("client");
+
+        assert_eq!(client.name, "Classilla");
+        assert_eq!(client.version, None);
+    }
+
+    let detector = DeviceDetector::new().with_template_expansion(TemplateExpansion::Strict);
+    let err = detector
+        .parse(ua, None)
+        .expect_err("strict mode should fail");
+    assert!(err.to_string().contains("$1"), "{}", err);
+
+    // user agents that don't hit any of those templates are unaffected
+    let ua = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    assert!(detector.parse(ua, None).is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_reports_lines() -> Result<()> {
+    let contents = r#"
+# comment
+- regex: 'Good/(\d+)'
+  name: 'Good'
+  version: '$1'
+
+- regex: 'NoVersion'
+  name: 'No Version'
+
+- regex: 'Broken/(\d+'
+  name: 'Broken'
+  version: '$1'
+
+- regex: 'OutOfRange/(\d+)'
+  name: 'Out Of Range'
+  version: '$1.$2'
+"#;
+
+    let errors = validate_regex_str(contents, RegexFileKind::Clients);
+
+    assert_eq!(errors.len(), 3, "{:#?}", errors);
+
+    assert_eq!(errors[0].line, Some(7));
+    assert_eq!(errors[0].kind, ValidationErrorKind::MissingKey("version"));
+
+    assert_eq!(errors[1].line, Some(10));
+    assert!(matches!(
+        errors[1].kind,
+        ValidationErrorKind::InvalidRegex { .. }
+    ));
+
+    assert_eq!(errors[2].line, Some(14));
+    assert!(matches!(
+        errors[2].kind,
+        ValidationErrorKind::CaptureGroupOutOfRange {
+            group: 2,
+            available: 1,
+            ..
+        }
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_device_models() -> Result<()> {
+    let contents = r#"
+Brand:
+  regex: 'Brand ([a-z]+)'
+  device: 'smartphone'
+  models:
+    - regex: 'Brand X(\d)'
+      model: 'X$1'
+    - regex: 'Brand Y'
+      model: 'Y$1'
+    - model: 'Z$2'
+"#;
+
+    let errors = validate_regex_str(contents, RegexFileKind::Devices);
+
+    assert_eq!(errors.len(), 2, "{:#?}", errors);
+    assert_eq!(errors[0].line, Some(8));
+    assert_eq!(errors[1].line, Some(10));
+    assert!(matches!(
+        errors[1].kind,
+        ValidationErrorKind::CaptureGroupOutOfRange { group: 2, .. }
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_embedded_regexes_have_no_unknown_keys() -> Result<()> {
+    for (path, kind) in embedded_regex_files()? {
+        let unknown = unknown_keys(&std::fs::read_to_string(&path)?, kind);
+        assert!(unknown.is_empty(), "{}: {:#?}", path, unknown);
+    }
+
+    for file in FILES {
+        assert!(RegexFileKind::for_path(file).is_some(), "{}", file);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_unknown_keys() {
+    let bots = r#"
+- regex: 'FooBot'
+  name: 'FooBot'
+  reputation: 'good'
+  producer:
+    name: 'Foo'
+    country: 'NZ'
+
+- regex: 'BarBot'
+  name: 'BarBot'
+  reputation: 'bad'
+"#;
+
+    let unknown = unknown_keys(bots, RegexFileKind::Bots);
+    assert_eq!(
+        unknown,
+        vec![
+            UnknownKey {
+                key: "reputation".to_owned(),
+                count: 2,
+                line: Some(2),
+            },
+            UnknownKey {
+                key: "producer.country".to_owned(),
+                count: 1,
+                line: Some(2),
+            },
+        ]
+    );
+
+    let devices = r#"
+Foo:
+  regex: 'Foo'
+  device: 'smartphone'
+  released: 2024
+  models:
+    - regex: 'Foo 1'
+      model: '1'
+    - regex: 'Foo 2'
+      model: '2'
+      released: 2024
+"#;
+
+    let unknown = unknown_keys(devices, RegexFileKind::Devices);
+    assert_eq!(
+        unknown,
+        vec![
+            UnknownKey {
+                key: "released".to_owned(),
+                count: 1,
+                line: Some(2),
+            },
+            UnknownKey {
+                key: "models.released".to_owned(),
+                count: 1,
+                line: Some(9),
+            },
+        ]
+    );
+
+    assert!(unknown_keys("not: [valid", RegexFileKind::Bots).is_empty());
+}
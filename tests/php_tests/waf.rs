@@ -0,0 +1,97 @@
+use anyhow::Result;
+
+use fancy_regex::Regex;
+
+use rust_device_detector::database::Database;
+use rust_device_detector::replay::load_corpus;
+use rust_device_detector::waf::WafRules;
+
+use crate::utils;
+
+#[test]
+fn test_waf_tokens() -> Result<()> {
+    let db = Database::embedded();
+    let rules = WafRules::new(4).with_bots(&db, &["search bot"]);
+
+    let token = |name: &str| rules.tokens().iter().find(|x| x.token == name);
+
+    let news = token("Googlebot-News").expect("googlebot news");
+    assert!(news.boundary);
+    assert_eq!(news.name, "Googlebot News");
+    assert_eq!(news.category, "Search bot");
+
+    // small groups are expanded
+    assert!(token("AdsBot-Google").is_some());
+    assert!(token("Mediapartners-Google").is_some());
+
+    assert!(rules.tokens().iter().all(|x| x.category == "Search bot"));
+    assert!(rules.tokens().iter().all(|x| x.token.len() >= 4));
+
+    Ok(())
+}
+
+#[test]
+fn test_waf_rules_match_bot_fixtures() -> Result<()> {
+    let db = Database::embedded();
+    let rules = WafRules::new(3).with_bots(&db, &[]);
+    let regex = Regex::new(&format!("(?i){}", rules.regex()))?;
+
+    let corpus = load_corpus("tests/data/fixtures/bots.yml")?;
+    let mut checked = 0;
+    for case in corpus.iter() {
+        let ua = case["user_agent"].as_str().expect("user_agent");
+        let detection = utils::DD.parse(ua, None)?;
+        let bot = detection.get_bot().expect("bot");
+
+        if rules.incomplete().contains(&bot.name) {
+            continue;
+        }
+
+        assert!(regex.is_match(ua)?, "{} not matched: {}", bot.name, ua);
+        checked += 1;
+    }
+
+    assert!(
+        checked > corpus.len() * 9 / 10,
+        "{} of {}",
+        checked,
+        corpus.len()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_waf_formats() -> Result<()> {
+    let db = Database::embedded();
+    let rules = WafRules::new(4)
+        .with_bots(&db, &["Security Checker"])
+        .with_libraries(&db);
+
+    assert!(rules
+        .tokens()
+        .iter()
+        .any(|x| x.token == "curl" && x.category == "Library"));
+
+    let map = rules.nginx_map("$bot_category");
+    assert!(map.contains("map $http_user_agent $bot_category {"));
+    assert!(map.contains("    default \"\";"));
+    assert!(map.contains("curl\" \"Library\"; # curl"));
+
+    let block = rules.nginx_if("return 403;");
+    assert!(block.contains("if ($http_user_agent ~* \""));
+    assert!(block.contains("    return 403;"));
+
+    let modsecurity = rules.modsecurity(1000, "deny,status:403,log");
+    let lines = modsecurity
+        .lines()
+        .filter(|x| x.starts_with("SecRule"))
+        .collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(
+        lines[0].contains("id:1000,phase:1,t:none,deny,status:403,log,msg:'Library user agent'")
+    );
+    assert!(lines[1].contains("id:1001,"));
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use anyhow::Result;
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use rust_device_detector::device_detector::DeviceDetector;
+
+#[test]
+fn test_warm_up_progress() -> Result<()> {
+    let detector = DeviceDetector::new();
+
+    let reports = Mutex::new(Vec::new());
+    detector.warm_up_with_progress(|progress| {
+        reports.lock().unwrap().push(progress.clone());
+    })?;
+
+    let reports = reports.into_inner().unwrap();
+
+    // every list is reported as done exactly once, after its other reports
+    let mut done = BTreeMap::new();
+    for progress in reports.iter() {
+        assert!(progress.compiled <= progress.total);
+        assert!(!done.contains_key(progress.list), "{}", progress.list);
+        if progress.is_done() {
+            done.insert(progress.list, progress.total);
+        }
+    }
+
+    assert_eq!(done.len(), 18);
+    assert!(done["bots.yml"] > 100);
+    assert!(done["device/mobiles.yml"] > 10_000);
+
+    // large lists report along the way
+    assert!(reports
+        .iter()
+        .any(|x| x.list == "device/mobiles.yml" && !x.is_done()));
+
+    let detection = detector.parse(
+        "Mozilla/5.0 (Linux; Android 13; SM-S911B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+        None,
+    )?;
+    let device = detection.get_known_device().and_then(|x| x.device.as_ref());
+    assert_eq!(device.and_then(|x| x.brand.as_deref()), Some("Samsung"));
+
+    Ok(())
+}
+
+#[test]
+fn test_prime_from_file() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("rdd-prime-{}.txt", std::process::id()));
+    std::fs::write(
+        &path,
+        "# top user agents\n\
+         Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36\n\
+         \n\
+         Mozilla/5.0 (iPhone; CPU iPhone OS 17_1 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1\n\
+         Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)\n",
+    )?;
+
+    let detector = DeviceDetector::new();
+    let primed = detector.prime_from_file(&path);
+    std::fs::remove_file(&path)?;
+    assert_eq!(primed?, 3);
+
+    assert!(detector
+        .parse(
+            "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+            None
+        )?
+        .is_bot());
+
+    assert!(detector.prime_from_file(&path).is_err());
+
+    Ok(())
+}